@@ -0,0 +1,424 @@
+//! Token account and mint data, for substreams reading account updates rather than transactions
+//!
+//! Account-based modules receive the data of the accounts written by a block, not the
+//! instructions that wrote them. [TokenAccount] decodes token account data, for both token
+//! programs, and [diff_token_account] compares two versions of an account into the
+//! [TokenAccountChange]s an instruction-based module would have extracted. [mint_updates]
+//! reads the mints written by a block, tracking their supply, decimals and authorities over
+//! time from account data alone, to cross-check supplies reconstructed from instructions.
+
+use anyhow::anyhow;
+use {
+    crate::{
+        account_state::AccountState,
+        mint_class::{classify_mint, MintClass},
+        option::COption,
+        program_ids::is_token_program,
+        pubkey::Pubkey,
+        rent::MULTISIG_LEN,
+        token_instruction_2022::{ExtensionType, TokenInstruction},
+    },
+    substreams::errors::Error,
+    substreams_solana_core::pb::sf::solana::r#type::v1::AccountBlock,
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    crate::serialization::coption_fromstr,
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// Offset of the account type byte of Token 2022 accounts with extensions, mints being
+/// padded to the length of token accounts
+pub const ACCOUNT_TYPE_OFFSET: usize = 165;
+/// Account type byte of Token 2022 mints with extensions
+const MINT_ACCOUNT_TYPE: u8 = 1;
+
+/// Mint data, without its extensions
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Mint {
+    /// Optional authority used to mint new tokens. The mint authority may only be provided
+    /// during mint creation. If no mint authority is present then the mint has a fixed
+    /// supply and no further tokens may be minted.
+    #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+    pub mint_authority: COption<Pubkey>,
+    /// Total supply of tokens
+    pub supply: u64,
+    /// Number of base 10 digits to the right of the decimal place
+    pub decimals: u8,
+    /// Is `true` if this structure has been initialized
+    pub is_initialized: bool,
+    /// Optional authority to freeze token accounts
+    #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+    pub freeze_authority: COption<Pubkey>,
+}
+
+impl Mint {
+    /// Length of the mint data without extensions
+    pub const LEN: usize = 82;
+
+    /// Unpacks mint data, ignoring the extensions that may follow it
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::account_data::Mint;
+    /// let mut data = vec![0u8; 82];
+    /// data[36..44].copy_from_slice(&1_000_000u64.to_le_bytes());
+    /// data[44] = 6;
+    /// data[45] = 1;
+    /// let mint = Mint::unpack(&data).unwrap();
+    /// assert_eq!((mint.supply, mint.decimals), (1_000_000, 6));
+    /// assert!(mint.mint_authority.is_none());
+    /// ```
+    pub fn unpack(data: &[u8]) -> Result<Self, Error> {
+        if data.len() < Self::LEN {
+            return Err(anyhow!("Invalid mint - data too short: {}", data.len()));
+        }
+        let (mint_authority, rest) = unpack_coption_pubkey(data)?;
+        let (supply, rest) = TokenInstruction::unpack_u64(rest)?;
+        let (&decimals, rest) = rest.split_first().ok_or(anyhow!("Invalid mint - missing decimals"))?;
+        let (&is_initialized, rest) = rest.split_first().ok_or(anyhow!("Invalid mint - missing initialized flag"))?;
+        let (freeze_authority, _rest) = unpack_coption_pubkey(rest)?;
+        Ok(Self {
+            mint_authority,
+            supply,
+            decimals,
+            is_initialized: is_initialized != 0,
+            freeze_authority,
+        })
+    }
+
+    /// Signed change of the supply since `previous`, a former version of the mint
+    pub fn supply_delta(&self, previous: &Mint) -> i128 {
+        self.supply as i128 - previous.supply as i128
+    }
+
+    /// Origin of the mint at `address`, see [classify_mint]
+    pub fn classify(&self, address: &Pubkey) -> MintClass {
+        let authority = match &self.mint_authority {
+            COption::Some(authority) => Some(authority),
+            COption::None => None,
+        };
+        classify_mint(address, authority)
+    }
+}
+
+/// Returns `true` if token program account `data` holds a mint rather than a token account
+/// or a multisig: mints without extensions have their exact length, Token 2022 mints with
+/// extensions are tagged with their account type. Multisigs, which never have extensions,
+/// are told apart by their length, the byte at the account type offset being part of their
+/// signers.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{account_data::is_mint_data, rent::MULTISIG_LEN};
+/// let mut mint = vec![0; 170];
+/// mint[165] = 1;
+/// assert!(is_mint_data(&mint));
+///
+/// let mut multisig = vec![0; MULTISIG_LEN];
+/// multisig[165] = 1;
+/// assert!(!is_mint_data(&multisig));
+/// ```
+pub fn is_mint_data(data: &[u8]) -> bool {
+    if data.len() == MULTISIG_LEN {
+        return false;
+    }
+    data.len() == Mint::LEN || (data.len() > ACCOUNT_TYPE_OFFSET && data[ACCOUNT_TYPE_OFFSET] == MINT_ACCOUNT_TYPE)
+}
+
+/// A mint written by a block of an account stream
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MintUpdate {
+    /// Slot of the block writing the mint
+    pub slot: u64,
+    /// Address of the mint
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub address: Pubkey,
+    /// The mint as written by the block
+    pub mint: Mint,
+}
+
+/// Extracts the initialized mints written by `block`, in the block's account order. Deleted
+/// accounts and accounts of other programs are skipped.
+pub fn mint_updates(block: &AccountBlock) -> Vec<MintUpdate> {
+    block
+        .accounts
+        .iter()
+        .filter(|account| !account.deleted && is_token_program(&account.owner) && is_mint_data(&account.data))
+        .filter_map(|account| {
+            let address = Pubkey::try_from(account.address.as_slice()).ok()?;
+            let mint = Mint::unpack(&account.data).ok().filter(|mint| mint.is_initialized)?;
+            Some(MintUpdate {
+                slot: block.slot,
+                address,
+                mint,
+            })
+        })
+        .collect()
+}
+
+/// Token account data, without its extensions
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenAccount {
+    /// The mint associated with this account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub mint: Pubkey,
+    /// The owner of this account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub owner: Pubkey,
+    /// The amount of tokens this account holds
+    pub amount: u64,
+    /// If `delegate` is `Some` then `delegated_amount` represents the amount authorized by
+    /// the delegate
+    #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+    pub delegate: COption<Pubkey>,
+    /// The account's state
+    pub state: AccountState,
+    /// If is_native.is_some, this is a native token, and the value logs the rent-exempt
+    /// reserve
+    #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+    pub is_native: COption<u64>,
+    /// The amount delegated
+    pub delegated_amount: u64,
+    /// Optional authority to close the account
+    #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+    pub close_authority: COption<Pubkey>,
+}
+
+impl TokenAccount {
+    /// Length of the account data without extensions
+    pub const LEN: usize = 165;
+
+    /// Unpacks token account data, ignoring the extensions that may follow it
+    pub fn unpack(data: &[u8]) -> Result<Self, Error> {
+        if data.len() < Self::LEN {
+            return Err(anyhow!("Invalid token account - data too short: {}", data.len()));
+        }
+        let (mint, rest) = TokenInstruction::unpack_pubkey(data)?;
+        let (owner, rest) = TokenInstruction::unpack_pubkey(rest)?;
+        let (amount, rest) = TokenInstruction::unpack_u64(rest)?;
+        let (delegate, rest) = unpack_coption_pubkey(rest)?;
+        let (state, rest) = AccountState::unpack(rest)?;
+        let (is_native, rest) = unpack_coption_u64(rest)?;
+        let (delegated_amount, rest) = TokenInstruction::unpack_u64(rest)?;
+        let (close_authority, _rest) = unpack_coption_pubkey(rest)?;
+        Ok(Self {
+            mint,
+            owner,
+            amount,
+            delegate,
+            state,
+            is_native,
+            delegated_amount,
+            close_authority,
+        })
+    }
+}
+
+/// Types of the extensions held by Token 2022 account `data`, mint or token account, in
+/// storage order. Entries of extension types unknown to this crate are skipped.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{account_data::extension_types, token_instruction_2022::ExtensionType};
+/// let mut data = vec![0u8; 165];
+/// // Account type, then the ImmutableOwner entry: type 7, length 0
+/// data.extend_from_slice(&[2, 7, 0, 0, 0]);
+/// assert_eq!(extension_types(&data), vec![ExtensionType::ImmutableOwner]);
+/// assert!(extension_types(&data[..165]).is_empty());
+/// ```
+pub fn extension_types(data: &[u8]) -> Vec<ExtensionType> {
+    extension_entries(data)
+        .filter_map(|(extension_type, _value)| ExtensionType::try_from(extension_type).ok())
+        .collect()
+}
+
+/// Value of the `extension_type` extension held by Token 2022 account `data`, mint or token
+/// account, `None` if it does not hold one
+///
+/// ```
+/// # use substreams_solana_program_instructions::{account_data::extension_data, token_instruction_2022::ExtensionType};
+/// let mut data = vec![0u8; 165];
+/// // Account type, then the MemoTransfer entry: type 8, length 1, enabled
+/// data.extend_from_slice(&[2, 8, 0, 1, 0, 1]);
+/// assert_eq!(extension_data(&data, ExtensionType::MemoTransfer), Some(&[1][..]));
+/// assert_eq!(extension_data(&data, ExtensionType::CpiGuard), None);
+/// ```
+pub fn extension_data(data: &[u8], extension_type: ExtensionType) -> Option<&[u8]> {
+    let extension_type = u16::from(extension_type);
+    extension_entries(data).find_map(|(entry_type, value)| {
+        if entry_type == extension_type {
+            Some(value)
+        } else {
+            None
+        }
+    })
+}
+
+/// Reads the `OptionalNonZeroPubkey` at `offset` of an extension value, all zeros when unset
+pub(crate) fn optional_nonzero_pubkey(value: &[u8], offset: usize) -> Option<Pubkey> {
+    let bytes: [u8; 32] = value.get(offset..offset + 32)?.try_into().ok()?;
+    if bytes == [0; 32] {
+        None
+    } else {
+        Some(Pubkey::from(bytes))
+    }
+}
+
+/// Raw type and value of the extension entries of `data`, in storage order. The value of a
+/// truncated last entry is cut short.
+fn extension_entries(data: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    let mut rest = data.get(ACCOUNT_TYPE_OFFSET + 1..).unwrap_or(&[]);
+    std::iter::from_fn(move || {
+        // Each entry is a u16 type, a u16 length and the value
+        let (header, value) = rest.get(..4).zip(rest.get(4..))?;
+        let extension_type = u16::from_le_bytes([header[0], header[1]]);
+        let len = u16::from_le_bytes([header[2], header[3]]) as usize;
+        // Uninitialized entries pad the end of the data
+        if extension_type == u16::from(ExtensionType::Uninitialized) {
+            return None;
+        }
+        let entry = (extension_type, &value[..len.min(value.len())]);
+        rest = value.get(len..).unwrap_or(&[]);
+        Some(entry)
+    })
+}
+
+/// A change between two versions of a token account
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenAccountChange {
+    /// The amount held changed
+    BalanceChanged {
+        /// Signed change of the amount
+        delta: i128,
+        /// The new amount
+        amount: u64,
+    },
+    /// The owner changed
+    OwnerChanged {
+        /// The previous owner
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        previous: Pubkey,
+        /// The new owner
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        owner: Pubkey,
+    },
+    /// The delegate or the delegated amount changed
+    DelegateChanged {
+        /// The previous delegate
+        #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+        previous: COption<Pubkey>,
+        /// The new delegate
+        #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+        delegate: COption<Pubkey>,
+        /// The new delegated amount
+        delegated_amount: u64,
+    },
+    /// The account was frozen or thawed
+    StateChanged {
+        /// The previous state
+        previous: AccountState,
+        /// The new state
+        state: AccountState,
+    },
+    /// An extension was added, the account being reallocated
+    ExtensionAdded {
+        /// Type of the extension
+        extension_type: ExtensionType,
+    },
+}
+
+/// Compares two versions of a token account's data into the changes from `old` to `new`,
+/// in the order of the [TokenAccountChange] variants. Both versions must be initialized
+/// token accounts of the same mint: account creations and closings are not diffs.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{account_data::{diff_token_account, TokenAccountChange}, account_state::AccountState};
+/// let mut old = vec![0u8; 165];
+/// old[64..72].copy_from_slice(&500u64.to_le_bytes());
+/// old[108] = 1;
+/// let mut new = old.clone();
+/// new[64..72].copy_from_slice(&200u64.to_le_bytes());
+/// new[108] = 2;
+/// assert_eq!(
+///     diff_token_account(&old, &new).unwrap(),
+///     vec![
+///         TokenAccountChange::BalanceChanged { delta: -300, amount: 200 },
+///         TokenAccountChange::StateChanged { previous: AccountState::Initialized, state: AccountState::Frozen },
+///     ]
+/// );
+/// ```
+pub fn diff_token_account(old: &[u8], new: &[u8]) -> Result<Vec<TokenAccountChange>, Error> {
+    let (previous, account) = (TokenAccount::unpack(old)?, TokenAccount::unpack(new)?);
+    if previous.mint != account.mint {
+        return Err(anyhow!("Invalid token account diff - mint changed: {} -> {}", previous.mint, account.mint));
+    }
+
+    let mut changes = vec![];
+    if previous.amount != account.amount {
+        changes.push(TokenAccountChange::BalanceChanged {
+            delta: account.amount as i128 - previous.amount as i128,
+            amount: account.amount,
+        });
+    }
+    if previous.owner != account.owner {
+        changes.push(TokenAccountChange::OwnerChanged {
+            previous: previous.owner,
+            owner: account.owner,
+        });
+    }
+    if previous.delegate != account.delegate || previous.delegated_amount != account.delegated_amount {
+        changes.push(TokenAccountChange::DelegateChanged {
+            previous: previous.delegate,
+            delegate: account.delegate,
+            delegated_amount: account.delegated_amount,
+        });
+    }
+    if previous.state != account.state {
+        changes.push(TokenAccountChange::StateChanged {
+            previous: previous.state,
+            state: account.state,
+        });
+    }
+    let previous_extensions = extension_types(old);
+    changes.extend(
+        extension_types(new)
+            .into_iter()
+            .filter(|extension_type| !previous_extensions.contains(extension_type))
+            .map(|extension_type| TokenAccountChange::ExtensionAdded { extension_type }),
+    );
+    Ok(changes)
+}
+
+/// Unpacks a `COption<Pubkey>` as stored in account data: a `u32` tag, then the pubkey,
+/// zeroed when unset
+fn unpack_coption_pubkey(input: &[u8]) -> Result<(COption<Pubkey>, &[u8]), Error> {
+    let (tag, rest) = TokenInstruction::unpack_u32(input)?;
+    let (pubkey, rest) = TokenInstruction::unpack_pubkey(rest)?;
+    match tag {
+        0 => Ok((COption::None, rest)),
+        1 => Ok((COption::Some(pubkey), rest)),
+        _ => Err(anyhow!("Invalid account data - option tag: {}", tag)),
+    }
+}
+
+/// Unpacks a `COption<u64>` as stored in account data: a `u32` tag, then the value
+fn unpack_coption_u64(input: &[u8]) -> Result<(COption<u64>, &[u8]), Error> {
+    let (tag, rest) = TokenInstruction::unpack_u32(input)?;
+    let (value, rest) = TokenInstruction::unpack_u64(rest)?;
+    match tag {
+        0 => Ok((COption::None, rest)),
+        1 => Ok((COption::Some(value), rest)),
+        _ => Err(anyhow!("Invalid account data - option tag: {}", tag)),
+    }
+}
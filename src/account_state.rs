@@ -0,0 +1,82 @@
+//! Token account state, as stored in token accounts and set by the Default Account State
+//! extension
+
+use anyhow::anyhow;
+use {
+    num_enum::{IntoPrimitive, TryFromPrimitive},
+    std::convert::TryFrom,
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Offset of the state byte in token account data: mint, owner, amount and delegate come first
+pub const ACCOUNT_STATE_OFFSET: usize = 108;
+
+/// Account state
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, TryFromPrimitive, IntoPrimitive)]
+#[repr(u8)]
+pub enum AccountState {
+    /// Account is not yet initialized
+    Uninitialized,
+    /// Account is initialized; the account owner and/or delegate may perform permitted operations
+    /// on this account
+    Initialized,
+    /// Account has been frozen by the mint freeze authority. Neither the account owner nor
+    /// the delegate are able to perform operations on this account.
+    Frozen,
+}
+
+impl AccountState {
+    /// Unpacks a state byte, returning the state and the remaining bytes
+    pub fn unpack(input: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (&state, rest) = input.split_first().ok_or(anyhow!("Unable to unpack account state"))?;
+        let state = Self::try_from(state).map_err(|_| anyhow!("Invalid account state: {}", state))?;
+        Ok((state, rest))
+    }
+
+    /// Decodes the state of a token account from its data, for both token programs
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::account_state::AccountState;
+    /// let mut data = vec![0u8; 165];
+    /// data[108] = 2;
+    /// assert_eq!(AccountState::from_account_data(&data).unwrap(), AccountState::Frozen);
+    /// assert!(AccountState::from_account_data(&data[..108]).is_err());
+    /// ```
+    pub fn from_account_data(data: &[u8]) -> Result<Self, Error> {
+        let state = data
+            .get(ACCOUNT_STATE_OFFSET..)
+            .ok_or(anyhow!("Unable to unpack account state - account data too short: {}", data.len()))?;
+        Self::unpack(state).map(|(state, _rest)| state)
+    }
+
+    /// Returns `true` if the account has been frozen by the mint freeze authority
+    pub fn is_frozen(self) -> bool {
+        self == Self::Frozen
+    }
+}
+
+/// Default Account State extension data for mints: the state new token accounts of the mint
+/// are initialized with
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DefaultAccountState {
+    /// Default state of new token accounts
+    pub state: AccountState,
+}
+
+impl DefaultAccountState {
+    /// Serialized length of the extension data
+    pub const LEN: usize = 1;
+
+    /// Unpacks the extension data, as found in the mint account's TLV entry
+    pub fn unpack(input: &[u8]) -> Result<Self, Error> {
+        let (state, _rest) = AccountState::unpack(input)?;
+        Ok(Self { state })
+    }
+}
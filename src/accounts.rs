@@ -0,0 +1,268 @@
+//! Account lists of instructions, abstracting over firehose compiled instructions (account
+//! indices into the transaction's keys) and RPC account lists (already resolved pubkeys)
+
+use anyhow::anyhow;
+use {
+    crate::pubkey::Pubkey,
+    substreams::errors::Error,
+    substreams_solana_core::pb::sf::solana::r#type::v1::{ConfirmedTransaction, MessageHeader},
+};
+
+/// Resolved account keys of a transaction: the message's static keys followed by the
+/// writable then readonly addresses loaded from address lookup tables.
+#[derive(Clone, Copy, Debug)]
+pub struct TransactionKeys<'a> {
+    /// Static account keys of the message
+    pub account_keys: &'a [Vec<u8>],
+    /// Writable addresses loaded from address lookup tables
+    pub loaded_writable_addresses: &'a [Vec<u8>],
+    /// Readonly addresses loaded from address lookup tables
+    pub loaded_readonly_addresses: &'a [Vec<u8>],
+    /// Message header, used to compute signer and writable flags
+    pub header: Option<&'a MessageHeader>,
+}
+
+impl<'a> TransactionKeys<'a> {
+    /// Returns the keys of `trx`, `None` if the transaction has no message or no meta
+    pub fn from_transaction(trx: &'a ConfirmedTransaction) -> Option<Self> {
+        let message = trx.transaction.as_ref()?.message.as_ref()?;
+        let meta = trx.meta.as_ref()?;
+        Some(Self {
+            account_keys: &message.account_keys,
+            loaded_writable_addresses: &meta.loaded_writable_addresses,
+            loaded_readonly_addresses: &meta.loaded_readonly_addresses,
+            header: message.header.as_ref(),
+        })
+    }
+
+    /// Total number of keys
+    pub fn len(&self) -> usize {
+        self.account_keys.len() + self.loaded_writable_addresses.len() + self.loaded_readonly_addresses.len()
+    }
+
+    /// Returns `true` if there is no key
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the key at `index`
+    pub fn get(&self, index: usize) -> Option<&'a [u8]> {
+        let mut index = index;
+        if index < self.account_keys.len() {
+            return Some(&self.account_keys[index]);
+        }
+        index -= self.account_keys.len();
+        if index < self.loaded_writable_addresses.len() {
+            return Some(&self.loaded_writable_addresses[index]);
+        }
+        index -= self.loaded_writable_addresses.len();
+        self.loaded_readonly_addresses.get(index).map(|key| key.as_slice())
+    }
+
+    /// Returns whether the key at `index` signed the transaction, `None` if the index is out
+    /// of bounds or the message header is unknown. Addresses loaded from lookup tables never
+    /// sign.
+    pub fn is_signer(&self, index: usize) -> Option<bool> {
+        let header = self.header?;
+        if index >= self.len() {
+            return None;
+        }
+        Some(index < self.account_keys.len() && is_signer(index, header))
+    }
+
+    /// Returns whether the key at `index` is writable, `None` if the index is out of bounds
+    /// or the message header is unknown
+    pub fn is_writable(&self, index: usize) -> Option<bool> {
+        let header = self.header?;
+        let num_signers = header.num_required_signatures as usize;
+        let num_static = self.account_keys.len();
+        Some(if index < num_signers {
+            index < num_signers.saturating_sub(header.num_readonly_signed_accounts as usize)
+        } else if index < num_static {
+            index < num_static.saturating_sub(header.num_readonly_unsigned_accounts as usize)
+        } else if index < num_static + self.loaded_writable_addresses.len() {
+            true
+        } else if index < self.len() {
+            false
+        } else {
+            return None;
+        })
+    }
+}
+
+/// Returns `true` if the account at `account_index` of a message with `header` signed it.
+/// Signers are the first `num_required_signatures` static keys of the message, in legacy as
+/// in v0 transactions, the addresses loaded from lookup tables coming after the static keys.
+pub fn is_signer(account_index: usize, header: &MessageHeader) -> bool {
+    account_index < header.num_required_signatures as usize
+}
+
+/// Accounts that signed `trx`, in message order, the fee payer first. Empty if the
+/// transaction has no message, meta or header.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{accounts::signers, pubkey::Pubkey};
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::*;
+/// let trx = ConfirmedTransaction {
+///     transaction: Some(Transaction {
+///         message: Some(Message {
+///             header: Some(MessageHeader { num_required_signatures: 2, num_readonly_signed_accounts: 1, num_readonly_unsigned_accounts: 1 }),
+///             account_keys: vec![vec![1; 32], vec![2; 32], vec![3; 32]],
+///             ..Default::default()
+///         }),
+///         ..Default::default()
+///     }),
+///     meta: Some(TransactionStatusMeta { loaded_writable_addresses: vec![vec![4; 32]], ..Default::default() }),
+///     ..Default::default()
+/// };
+/// assert_eq!(signers(&trx), vec![Pubkey::from([1; 32]), Pubkey::from([2; 32])]);
+/// ```
+pub fn signers(trx: &ConfirmedTransaction) -> Vec<Pubkey> {
+    let keys = match TransactionKeys::from_transaction(trx) {
+        Some(keys) => keys,
+        None => return vec![],
+    };
+    (0..keys.account_keys.len())
+        .take_while(|&index| keys.is_signer(index) == Some(true))
+        .filter_map(|index| keys.get(index).and_then(|key| Pubkey::try_from(key).ok()))
+        .collect()
+}
+
+/// An account along with its signer and writable flags, as found in RPC account lists
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AccountMeta {
+    /// The account's address
+    pub pubkey: Pubkey,
+    /// Whether the account signed the transaction
+    pub is_signer: bool,
+    /// Whether the account is writable
+    pub is_writable: bool,
+}
+
+/// Concrete accounts an instruction may write or only read, as locked by the runtime
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccountAccess {
+    /// Accounts locked for writing, in order of first appearance
+    pub writable: Vec<Pubkey>,
+    /// Accounts locked for reading only, in order of first appearance
+    pub readonly: Vec<Pubkey>,
+}
+
+/// Accounts of an instruction, in the order expected by the instruction
+#[derive(Clone, Copy, Debug)]
+pub enum Accounts<'a> {
+    /// Accounts of a firehose instruction, `indices` pointing into the transaction's `keys`
+    Compiled {
+        /// Keys of the transaction holding the instruction
+        keys: TransactionKeys<'a>,
+        /// Account indices of the instruction
+        indices: &'a [u8],
+    },
+    /// Accounts of an RPC instruction along with their flags
+    Metas(&'a [AccountMeta]),
+    /// Accounts of an RPC instruction, without flags
+    Pubkeys(&'a [Pubkey]),
+}
+
+impl<'a> Accounts<'a> {
+    /// Accounts of a firehose instruction holding `indices`
+    pub fn from_compiled(keys: TransactionKeys<'a>, indices: &'a [u8]) -> Self {
+        Self::Compiled { keys, indices }
+    }
+
+    /// Indices of the accounts into the transaction's keys, `None` for RPC account lists
+    pub fn indices(&self) -> Option<&'a [u8]> {
+        match *self {
+            Self::Compiled { indices, .. } => Some(indices),
+            _ => None,
+        }
+    }
+
+    /// Number of accounts
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Compiled { indices, .. } => indices.len(),
+            Self::Metas(metas) => metas.len(),
+            Self::Pubkeys(pubkeys) => pubkeys.len(),
+        }
+    }
+
+    /// Returns `true` if there is no account
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the address bytes of the `i`-th account
+    pub fn get(&self, i: usize) -> Option<&'a [u8]> {
+        match *self {
+            Self::Compiled { keys, indices } => keys.get(*indices.get(i)? as usize),
+            Self::Metas(metas) => metas.get(i).map(|meta| meta.pubkey.as_ref()),
+            Self::Pubkeys(pubkeys) => pubkeys.get(i).map(|pubkey| pubkey.as_ref()),
+        }
+    }
+
+    /// Returns the address of the `i`-th account
+    pub fn get_pubkey(&self, i: usize) -> Option<Pubkey> {
+        self.get(i).and_then(|key| Pubkey::try_from(key).ok())
+    }
+
+    /// Returns the address of the `i`-th account, failing if the instruction has fewer accounts
+    pub fn pubkey_at(&self, i: usize) -> Result<Pubkey, Error> {
+        self.get_pubkey(i)
+            .ok_or(anyhow!("Missing account at index {} (instruction has {} accounts)", i, self.len()))
+    }
+
+    /// Returns whether the `i`-th account signed the transaction, `None` if unknown
+    pub fn is_signer(&self, i: usize) -> Option<bool> {
+        match *self {
+            Self::Compiled { keys, indices } => keys.is_signer(*indices.get(i)? as usize),
+            Self::Metas(metas) => metas.get(i).map(|meta| meta.is_signer),
+            Self::Pubkeys(_) => None,
+        }
+    }
+
+    /// Returns whether the `i`-th account is writable, `None` if unknown
+    pub fn is_writable(&self, i: usize) -> Option<bool> {
+        match *self {
+            Self::Compiled { keys, indices } => keys.is_writable(*indices.get(i)? as usize),
+            Self::Metas(metas) => metas.get(i).map(|meta| meta.is_writable),
+            Self::Pubkeys(_) => None,
+        }
+    }
+
+    /// Splits the accounts into the writable and readonly sets, from the message header and
+    /// the address lookup tables rather than from what the instruction does with them: an
+    /// account is writable for every instruction of the transaction once writable for one.
+    /// Duplicates are reported once. `None` if the flags are unknown.
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::accounts::{AccountMeta, Accounts};
+    /// # use substreams_solana_program_instructions::pubkey::Pubkey;
+    /// let meta = |byte, is_writable| AccountMeta { pubkey: Pubkey::from([byte; 32]), is_signer: false, is_writable };
+    /// let metas = [meta(1, true), meta(2, false), meta(1, true)];
+    /// let access = Accounts::Metas(&metas).access().unwrap();
+    /// assert_eq!(access.writable, vec![Pubkey::from([1; 32])]);
+    /// assert_eq!(access.readonly, vec![Pubkey::from([2; 32])]);
+    /// ```
+    pub fn access(&self) -> Option<AccountAccess> {
+        let mut access = AccountAccess::default();
+        for i in 0..self.len() {
+            let is_writable = self.is_writable(i)?;
+            let pubkey = match self.get_pubkey(i) {
+                Some(pubkey) => pubkey,
+                None => continue,
+            };
+            let set = if is_writable { &mut access.writable } else { &mut access.readonly };
+            if !set.contains(&pubkey) {
+                set.push(pubkey);
+            }
+        }
+        Some(access)
+    }
+
+    /// Iterates over the address bytes of the accounts, unresolvable indices are skipped
+    pub fn iter(&self) -> impl Iterator<Item = &'a [u8]> + 'a {
+        let accounts = *self;
+        (0..accounts.len()).filter_map(move |i| accounts.get(i))
+    }
+}
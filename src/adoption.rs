@@ -0,0 +1,151 @@
+//! Token 2022 extension adoption metrics
+//!
+//! Mint extensions are initialized by their own instruction, sent before the mint's
+//! `InitializeMint`, or after it for the token metadata and group extensions. Counting these
+//! initializations per [ExtensionType] over blocks tells which extensions new mints adopt.
+//! Account extensions, `ImmutableOwner`, `MemoTransfer` or `CpiGuard` among them, are
+//! initialized on token accounts and are not counted.
+
+use {
+    crate::{
+        confidential_mint_burn_instruction::ConfidentialMintBurnInstruction,
+        confidential_transfer_instruction::ConfidentialTransferInstruction,
+        default_account_state_instruction::DefaultAccountStateInstruction,
+        group_member_pointer_instruction::GroupMemberPointerInstruction,
+        instructions::block_instructions,
+        interest_bearing_mint::InterestBearingMintInstruction,
+        pausable_instruction::PausableInstruction,
+        program_ids::is_token_2022_program,
+        scaled_ui_amount_instruction::ScaledUiAmountInstruction,
+        token_group_instruction::TokenGroupInstruction,
+        token_instruction_2022::{ExtensionType, TokenInstruction},
+        token_metadata_instruction::TokenMetadataInstruction,
+        transfer_fee_instruction::TransferFeeInstruction,
+    },
+    std::collections::BTreeMap,
+    substreams_solana_core::pb::sf::solana::r#type::v1::Block,
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Sub-instruction tag of the `Initialize` instruction of extensions
+const INITIALIZE_SUB_TAG: u8 = 0;
+
+/// Token 2022 mints and mint extensions initialized in a block
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExtensionAdoption {
+    /// Number of mints initialized, with or without extensions
+    pub mints_initialized: u64,
+    /// Number of initializations of each extension, ordered by extension type, extensions
+    /// not initialized in the block being left out
+    pub extensions: Vec<(ExtensionType, u64)>,
+}
+
+impl ExtensionAdoption {
+    /// Number of initializations of `extension_type`
+    pub fn count(&self, extension_type: ExtensionType) -> u64 {
+        self.extensions
+            .iter()
+            .find(|(extension, _)| *extension == extension_type)
+            .map(|(_, count)| *count)
+            .unwrap_or(0)
+    }
+}
+
+/// Counts the Token 2022 mints and mint extensions initialized by the successful transactions
+/// of `block`
+pub fn extension_adoption(block: &Block) -> ExtensionAdoption {
+    let mut adoption = ExtensionAdoption::default();
+    let mut counts: BTreeMap<u16, u64> = BTreeMap::new();
+    for block_instruction in block_instructions(block) {
+        let instruction = block_instruction.instruction;
+        if !is_token_2022_program(instruction.program_id) {
+            continue;
+        }
+        let data = instruction.data;
+        match TokenInstruction::unpack(data) {
+            Ok(TokenInstruction::InitializeMint { .. }) | Ok(TokenInstruction::InitializeMint2 { .. }) => {
+                adoption.mints_initialized += 1;
+                continue;
+            }
+            _ => {}
+        }
+        if let Some(extension_type) = initialized_extension(data) {
+            *counts.entry(extension_type.into()).or_insert(0) += 1;
+        }
+    }
+
+    adoption.extensions = counts
+        .into_iter()
+        .filter_map(|(extension_type, count)| ExtensionType::try_from(extension_type).ok().map(|extension| (extension, count)))
+        .collect();
+    adoption
+}
+
+/// Mint extension initialized by a Token 2022 instruction, `None` for any other instruction.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{adoption::initialized_extension, token_instruction_2022::ExtensionType};
+/// // InitializeNonTransferableMint
+/// assert_eq!(initialized_extension(&[32]), Some(ExtensionType::NonTransferable));
+/// // TransferHookExtension, Initialize
+/// assert_eq!(initialized_extension(&[36, 0]), Some(ExtensionType::TransferHook));
+/// // TransferHookExtension, Update
+/// assert_eq!(initialized_extension(&[36, 1]), None);
+/// // InitializeImmutableOwner, an account extension
+/// assert_eq!(initialized_extension(&[22]), None);
+/// ```
+pub fn initialized_extension(data: &[u8]) -> Option<ExtensionType> {
+    // The interfaces have 8 bytes discriminators and are tried first
+    match TokenMetadataInstruction::unpack(data) {
+        Ok(TokenMetadataInstruction::Initialize { .. }) => return Some(ExtensionType::TokenMetadata),
+        Ok(_) => return None,
+        Err(_) => {}
+    }
+    match TokenGroupInstruction::unpack(data) {
+        Ok(TokenGroupInstruction::InitializeGroup { .. }) => return Some(ExtensionType::TokenGroup),
+        Ok(TokenGroupInstruction::InitializeMember) => return Some(ExtensionType::TokenGroupMember),
+        Ok(_) => return None,
+        Err(_) => {}
+    }
+
+    let is_initialize = data.get(1) == Some(&INITIALIZE_SUB_TAG);
+    match TokenInstruction::unpack(data).ok()? {
+        TokenInstruction::InitializeMintCloseAuthority { .. } => Some(ExtensionType::MintCloseAuthority),
+        TokenInstruction::InitializeNonTransferableMint => Some(ExtensionType::NonTransferable),
+        TokenInstruction::InitializePermanentDelegate { .. } => Some(ExtensionType::PermanentDelegate),
+        TokenInstruction::TransferFeeExtension(TransferFeeInstruction::InitializeTransferFeeConfig { .. }) => {
+            Some(ExtensionType::TransferFeeConfig)
+        }
+        TokenInstruction::ConfidentialTransferExtension(ConfidentialTransferInstruction::InitializeMint { .. }) => {
+            Some(ExtensionType::ConfidentialTransferMint)
+        }
+        TokenInstruction::DefaultAccountStateExtension(DefaultAccountStateInstruction::Initialize { .. }) => {
+            Some(ExtensionType::DefaultAccountState)
+        }
+        TokenInstruction::GroupMemberPointerExtension(GroupMemberPointerInstruction::Initialize { .. }) => {
+            Some(ExtensionType::GroupMemberPointer)
+        }
+        TokenInstruction::ConfidentialMintBurnExtension(ConfidentialMintBurnInstruction::InitializeMint { .. }) => {
+            Some(ExtensionType::ConfidentialMintBurn)
+        }
+        TokenInstruction::PausableExtension(PausableInstruction::Initialize { .. }) => Some(ExtensionType::Pausable),
+        TokenInstruction::ScaledUiAmountExtension(ScaledUiAmountInstruction::Initialize { .. }) => {
+            Some(ExtensionType::ScaledUiAmount)
+        }
+        TokenInstruction::InterestBearingMintExtension(InterestBearingMintInstruction::Initialize { .. }) => {
+            Some(ExtensionType::InterestBearingConfig)
+        }
+        // Extensions whose sub-instructions are not decoded, told apart by their sub-tag
+        TokenInstruction::TransferHookExtension if is_initialize => Some(ExtensionType::TransferHook),
+        TokenInstruction::ConfidentialTransferFeeExtension if is_initialize => {
+            Some(ExtensionType::ConfidentialTransferFeeConfig)
+        }
+        TokenInstruction::MetadataPointerExtension if is_initialize => Some(ExtensionType::MetadataPointer),
+        TokenInstruction::GroupPointerExtension if is_initialize => Some(ExtensionType::GroupPointer),
+        _ => None,
+    }
+}
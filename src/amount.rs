@@ -0,0 +1,449 @@
+//! Typed token amounts and decimals
+//!
+//! With the `typed-amounts` feature, decoded instructions carry their amounts as [TokenAmount]
+//! and their decimals as [Decimals] instead of bare `u64` and `u8`, so token amounts cannot be
+//! mixed up with lamports or other integers. Without the feature, [Amount] and [AmountDecimals]
+//! are plain `u64` and `u8`.
+
+use std::{fmt, iter::Sum, ops::Neg, str::FromStr};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// One in basis points
+pub const ONE_IN_BASIS_POINTS: u128 = 10_000;
+
+/// A raw amount of tokens, in the mint's smallest unit
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(transparent))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TokenAmount(pub u64);
+
+/// Number of base 10 digits to the right of the decimal place of a mint
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(transparent))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Decimals(pub u8);
+
+impl TokenAmount {
+    /// Converts the amount to a ui amount, as `f64`. Prefer [Self::to_ui_string] when the
+    /// exact value matters.
+    pub fn to_ui(self, decimals: Decimals) -> f64 {
+        self.0 as f64 / 10_f64.powi(decimals.0 as i32)
+    }
+
+    /// Converts the amount to an exact ui amount string, without any trailing zero, see
+    /// [amount_to_ui_amount_string_trimmed]
+    pub fn to_ui_string(self, decimals: Decimals) -> String {
+        amount_to_ui_amount_string_trimmed(self.0, decimals.0)
+    }
+
+    /// Formats the amount as a fixed-point string, see [format_amount]
+    pub fn format(self, decimals: Decimals, thousands_sep: Option<char>) -> String {
+        format_amount(self.0, decimals.0, thousands_sep)
+    }
+
+    /// Computes `self * basis_points / 10_000`, rounded down, `None` on overflow. Fees are
+    /// rounded up, see [mul_bps_ceil](crate::fee_math::mul_bps_ceil).
+    pub fn checked_mul_bps(self, basis_points: u16) -> Option<TokenAmount> {
+        let value = (self.0 as u128).checked_mul(basis_points as u128)? / ONE_IN_BASIS_POINTS;
+        u64::try_from(value).ok().map(TokenAmount)
+    }
+
+    /// Checked addition, `None` on overflow
+    pub fn checked_add(self, other: TokenAmount) -> Option<TokenAmount> {
+        self.0.checked_add(other.0).map(TokenAmount)
+    }
+
+    /// Checked subtraction, `None` on underflow
+    pub fn checked_sub(self, other: TokenAmount) -> Option<TokenAmount> {
+        self.0.checked_sub(other.0).map(TokenAmount)
+    }
+}
+
+impl From<u64> for TokenAmount {
+    fn from(value: u64) -> Self {
+        TokenAmount(value)
+    }
+}
+
+impl From<TokenAmount> for u64 {
+    fn from(value: TokenAmount) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl From<u8> for Decimals {
+    fn from(value: u8) -> Self {
+        Decimals(value)
+    }
+}
+
+impl From<Decimals> for u8 {
+    fn from(value: Decimals) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for Decimals {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// A sum of raw amounts, wide enough for the total of any number of `u64` amounts a chain
+/// will ever move. Serialized as a decimal string, which JSON consumers and stores read
+/// without losing precision.
+///
+/// ```
+/// # use substreams_solana_program_instructions::amount::U128Sum;
+/// let total: U128Sum = [u64::MAX, u64::MAX].into_iter().sum();
+/// assert_eq!(total.to_string(), "36893488147419103230");
+/// assert_eq!(U128Sum::new(u128::MAX).saturating_add(1u64), U128Sum::new(u128::MAX));
+/// ```
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(transparent))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct U128Sum(#[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))] u128);
+
+impl U128Sum {
+    /// The sum `value`
+    pub const fn new(value: u128) -> Self {
+        Self(value)
+    }
+
+    /// Value of the sum
+    pub const fn get(self) -> u128 {
+        self.0
+    }
+
+    /// Checked addition, `None` on overflow
+    pub fn checked_add(self, value: impl Into<u128>) -> Option<Self> {
+        self.0.checked_add(value.into()).map(Self)
+    }
+
+    /// Saturating addition
+    pub fn saturating_add(self, value: impl Into<u128>) -> Self {
+        Self(self.0.saturating_add(value.into()))
+    }
+
+    /// Checked subtraction, `None` on underflow
+    pub fn checked_sub(self, value: impl Into<u128>) -> Option<Self> {
+        self.0.checked_sub(value.into()).map(Self)
+    }
+
+    /// Saturating subtraction
+    pub fn saturating_sub(self, value: impl Into<u128>) -> Self {
+        Self(self.0.saturating_sub(value.into()))
+    }
+}
+
+impl From<u64> for U128Sum {
+    fn from(value: u64) -> Self {
+        Self(value as u128)
+    }
+}
+
+impl From<u128> for U128Sum {
+    fn from(value: u128) -> Self {
+        Self(value)
+    }
+}
+
+impl From<U128Sum> for u128 {
+    fn from(value: U128Sum) -> Self {
+        value.0
+    }
+}
+
+impl Sum<u64> for U128Sum {
+    /// Saturating sum
+    fn sum<I: Iterator<Item = u64>>(iter: I) -> Self {
+        iter.fold(Self::default(), |sum, value| sum.saturating_add(value))
+    }
+}
+
+impl Sum for U128Sum {
+    /// Saturating sum
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |sum, value| sum.saturating_add(value.0))
+    }
+}
+
+impl fmt::Display for U128Sum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for U128Sum {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+/// A signed change of raw amounts, wide enough for any sum of `u64` debits and credits.
+/// Serialized as a decimal string, like [U128Sum].
+///
+/// ```
+/// # use substreams_solana_program_instructions::amount::I128Delta;
+/// let delta = I128Delta::credit(500).saturating_add(I128Delta::debit(800));
+/// assert_eq!(delta, I128Delta::new(-300));
+/// assert_eq!((-delta).to_string(), "300");
+/// ```
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(transparent))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct I128Delta(#[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))] i128);
+
+impl I128Delta {
+    /// The delta `value`
+    pub const fn new(value: i128) -> Self {
+        Self(value)
+    }
+
+    /// A positive delta of `amount`
+    pub const fn credit(amount: u64) -> Self {
+        Self(amount as i128)
+    }
+
+    /// A negative delta of `amount`
+    pub const fn debit(amount: u64) -> Self {
+        Self(-(amount as i128))
+    }
+
+    /// Value of the delta
+    pub const fn get(self) -> i128 {
+        self.0
+    }
+
+    /// Returns `true` for a null delta
+    pub const fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Checked addition, `None` on overflow
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.0.checked_add(other.0).map(Self)
+    }
+
+    /// Saturating addition
+    pub fn saturating_add(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0))
+    }
+
+    /// Checked subtraction, `None` on overflow
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.0.checked_sub(other.0).map(Self)
+    }
+
+    /// Saturating subtraction
+    pub fn saturating_sub(self, other: Self) -> Self {
+        Self(self.0.saturating_sub(other.0))
+    }
+}
+
+impl Neg for I128Delta {
+    type Output = Self;
+
+    /// Opposite delta, saturating for `i128::MIN`
+    fn neg(self) -> Self {
+        Self(self.0.saturating_neg())
+    }
+}
+
+impl From<i128> for I128Delta {
+    fn from(value: i128) -> Self {
+        Self(value)
+    }
+}
+
+impl From<I128Delta> for i128 {
+    fn from(value: I128Delta) -> Self {
+        value.0
+    }
+}
+
+impl Sum for I128Delta {
+    /// Saturating sum
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Self::saturating_add)
+    }
+}
+
+impl fmt::Display for I128Delta {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for I128Delta {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+/// Formats a raw `amount` of a mint with `decimals` as a fixed-point string, keeping all the
+/// decimals, with the integer part's digits grouped by three with `thousands_sep`. Computed
+/// on the digits, the result is exact whatever the amount, unlike a formatted ui amount
+/// going through `f64`.
+///
+/// ```
+/// # use substreams_solana_program_instructions::amount::format_amount;
+/// assert_eq!(format_amount(1_234_567_890, 6, Some(',')), "1,234.567890");
+/// assert_eq!(format_amount(5, 9, None), "0.000000005");
+/// assert_eq!(format_amount(u64::MAX, 0, Some(' ')), "18 446 744 073 709 551 615");
+/// ```
+pub fn format_amount(amount: u64, decimals: u8, thousands_sep: Option<char>) -> String {
+    let decimals = decimals as usize;
+    let digits = format!("{:0>width$}", amount, width = decimals + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - decimals);
+
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+    for (i, digit) in integer.chars().enumerate() {
+        if let Some(sep) = thousands_sep {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                out.push(sep);
+            }
+        }
+        out.push(digit);
+    }
+    if !fraction.is_empty() {
+        out.push('.');
+        out.push_str(fraction);
+    }
+    out
+}
+
+/// Converts a raw `amount` of a mint with `decimals` to a ui amount string keeping all the
+/// decimals, as the token programs' `amount_to_ui_amount_string`: the integer part is left
+/// padded to a single `0`, and no decimal point is written without decimals.
+///
+/// ```
+/// # use substreams_solana_program_instructions::amount::amount_to_ui_amount_string;
+/// assert_eq!(amount_to_ui_amount_string(0, 0), "0");
+/// assert_eq!(amount_to_ui_amount_string(0, 2), "0.00");
+/// assert_eq!(amount_to_ui_amount_string(1, 9), "0.000000001");
+/// assert_eq!(amount_to_ui_amount_string(1_500_000, 6), "1.500000");
+/// assert_eq!(amount_to_ui_amount_string(u64::MAX, 20), "0.18446744073709551615");
+/// ```
+pub fn amount_to_ui_amount_string(amount: u64, decimals: u8) -> String {
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return amount.to_string();
+    }
+    // Left padded to at least an integer zero
+    let mut digits = format!("{:0>width$}", amount, width = decimals + 1);
+    digits.insert(digits.len() - decimals, '.');
+    digits
+}
+
+/// Converts a raw `amount` of a mint with `decimals` to a ui amount string without trailing
+/// zeros, nor a decimal point when the amount is integral, as the token programs'
+/// `amount_to_ui_amount_string_trimmed` and the `uiAmountString` of RPC token balances
+///
+/// ```
+/// # use substreams_solana_program_instructions::amount::amount_to_ui_amount_string_trimmed;
+/// // (amount, decimals, ui amount string), as computed by the token programs
+/// let vectors: &[(u64, u8, &str)] = &[
+///     (0, 0, "0"),
+///     (1, 0, "1"),
+///     (0, 1, "0"),
+///     (1, 1, "0.1"),
+///     (10, 1, "1"),
+///     (1, 10, "0.0000000001"),
+///     (10, 10, "0.000000001"),
+///     (1_000_000_000, 9, "1"),
+///     (1_234_567_890, 3, "1234567.89"),
+///     (1_000_000_000_000, 6, "1000000"),
+///     (100, 2, "1"),
+///     (101, 2, "1.01"),
+///     (110, 2, "1.1"),
+///     (u64::MAX, 0, "18446744073709551615"),
+///     (u64::MAX, 9, "18446744073.709551615"),
+///     (u64::MAX, 20, "0.18446744073709551615"),
+/// ];
+/// for &(amount, decimals, expected) in vectors {
+///     assert_eq!(amount_to_ui_amount_string_trimmed(amount, decimals), expected);
+/// }
+/// assert_eq!(amount_to_ui_amount_string_trimmed(u64::MAX, 255).len(), 2 + 235 + 20);
+/// ```
+pub fn amount_to_ui_amount_string_trimmed(amount: u64, decimals: u8) -> String {
+    let mut digits = amount_to_ui_amount_string(amount, decimals);
+    if decimals > 0 {
+        let trimmed = digits.trim_end_matches('0').trim_end_matches('.').len();
+        digits.truncate(trimmed);
+    }
+    digits
+}
+
+/// Type of the token amounts carried by decoded instructions
+#[cfg(feature = "typed-amounts")]
+pub type Amount = TokenAmount;
+/// Type of the token amounts carried by decoded instructions
+#[cfg(not(feature = "typed-amounts"))]
+pub type Amount = u64;
+
+/// Type of the decimals carried by decoded instructions
+#[cfg(feature = "typed-amounts")]
+pub type AmountDecimals = Decimals;
+/// Type of the decimals carried by decoded instructions
+#[cfg(not(feature = "typed-amounts"))]
+pub type AmountDecimals = u8;
+
+/// Wraps a raw unpacked amount into [Amount]
+#[cfg(feature = "typed-amounts")]
+pub(crate) fn amount(raw: u64) -> Amount {
+    TokenAmount(raw)
+}
+/// Wraps a raw unpacked amount into [Amount]
+#[cfg(not(feature = "typed-amounts"))]
+pub(crate) fn amount(raw: u64) -> Amount {
+    raw
+}
+
+/// Wraps raw unpacked decimals into [AmountDecimals]
+#[cfg(feature = "typed-amounts")]
+pub(crate) fn decimals(raw: u8) -> AmountDecimals {
+    Decimals(raw)
+}
+/// Wraps raw unpacked decimals into [AmountDecimals]
+#[cfg(not(feature = "typed-amounts"))]
+pub(crate) fn decimals(raw: u8) -> AmountDecimals {
+    raw
+}
+
+/// Returns the raw value of an [Amount]
+#[cfg(feature = "typed-amounts")]
+pub fn raw_amount(value: Amount) -> u64 {
+    value.0
+}
+/// Returns the raw value of an [Amount]
+#[cfg(not(feature = "typed-amounts"))]
+pub fn raw_amount(value: Amount) -> u64 {
+    value
+}
+
+/// Returns the raw value of an [AmountDecimals]
+#[cfg(feature = "typed-amounts")]
+pub fn raw_decimals(value: AmountDecimals) -> u8 {
+    value.0
+}
+/// Returns the raw value of an [AmountDecimals]
+#[cfg(not(feature = "typed-amounts"))]
+pub fn raw_decimals(value: AmountDecimals) -> u8 {
+    value
+}
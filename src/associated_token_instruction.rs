@@ -0,0 +1,193 @@
+//! Associated Token Account program instruction types and account creations
+//!
+//! `CreateIdempotent` succeeds whether or not the associated token account already exists,
+//! creating it only when missing. Wallets and DEX routers send it before most transfers, so
+//! counting these instructions overstates the accounts created: [ata_created_events] tells the
+//! creations apart from the no-ops.
+
+use anyhow::anyhow;
+use {
+    crate::{
+        events::EventContext,
+        instructions::{instructions, invoked_instructions, is_successful, InstructionRef},
+        program_ids::{is_token_program, ASSOCIATED_TOKEN_PROGRAM_ID},
+        pubkey::Pubkey,
+        token_instruction_2022::TokenInstruction,
+    },
+    std::collections::HashSet,
+    substreams::errors::Error,
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// Instructions supported by the Associated Token Account program, with a single byte tag.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssociatedTokenInstruction {
+    /// Creates an associated token account for the given wallet address and token mint,
+    /// failing if the account exists. Sent with empty data by older clients.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Funding account
+    ///   1. `[writable]` Associated token account address to be created
+    ///   2. `[]` Wallet address for the new associated token account
+    ///   3. `[]` The token mint for the new associated token account
+    ///   4. `[]` System program
+    ///   5. `[]` SPL Token program
+    Create,
+    /// Creates an associated token account for the given wallet address and token mint, if
+    /// it doesn't already exist.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Funding account
+    ///   1. `[writable]` Associated token account address to be created
+    ///   2. `[]` Wallet address for the new associated token account
+    ///   3. `[]` The token mint for the new associated token account
+    ///   4. `[]` System program
+    ///   5. `[]` SPL Token program
+    CreateIdempotent,
+    /// Transfers from and closes a nested associated token account: an associated token
+    /// account owned by an associated token account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Nested associated token account
+    ///   1. `[]` Token mint for the nested associated token account
+    ///   2. `[writable]` Wallet's associated token account
+    ///   3. `[]` Owner associated token account address
+    ///   4. `[]` Token mint for the owner associated token account
+    ///   5. `[writable, signer]` Wallet address for the owner associated token account
+    ///   6. `[]` SPL Token program
+    RecoverNested,
+}
+impl AssociatedTokenInstruction {
+    /// Unpacks a byte buffer into a [AssociatedTokenInstruction](enum.AssociatedTokenInstruction.html).
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::associated_token_instruction::AssociatedTokenInstruction;
+    /// assert_eq!(AssociatedTokenInstruction::unpack(&[]).unwrap(), AssociatedTokenInstruction::Create);
+    /// assert_eq!(AssociatedTokenInstruction::unpack(&[1]).unwrap(), AssociatedTokenInstruction::CreateIdempotent);
+    /// ```
+    pub fn unpack(input: &[u8]) -> Result<Self, Error> {
+        Ok(match input.first() {
+            None | Some(0) => Self::Create,
+            Some(1) => Self::CreateIdempotent,
+            Some(2) => Self::RecoverNested,
+            Some(tag) => return Err(anyhow!("Invalid Associated Token Instruction - unknown tag: {}", tag)),
+        })
+    }
+}
+
+/// An associated token account creation instruction, along with whether it created the
+/// account
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AtaCreated {
+    /// Position of the creation instruction
+    pub context: EventContext,
+    /// The associated token account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub account: Pubkey,
+    /// Owner of the associated token account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub wallet: Pubkey,
+    /// Mint of the associated token account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub mint: Pubkey,
+    /// `true` for `CreateIdempotent`, `false` for `Create`
+    pub idempotent: bool,
+    /// `false` when a `CreateIdempotent` found the account already created
+    pub created: bool,
+}
+
+impl AtaCreated {
+    /// Kind of the event, part of its [AtaCreated::event_id]
+    pub const KIND: &'static str = "ata_created";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+}
+
+/// Extracts the associated token account creation instructions of all successful
+/// transactions of `block`, in execution order
+pub fn block_ata_created_events(block: &Block) -> Vec<AtaCreated> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| ata_created_events(trx, index as u32))
+        .collect()
+}
+
+/// Extracts the associated token account creation instructions of a successful transaction,
+/// the `transaction_index`-th of its block, in execution order.
+///
+/// A creation initializes the token account through the token program, which an idempotent
+/// no-op does not invoke. Creations invoked as inner instructions of blocks predating the
+/// invocation stack height fall back to the token balances: the account was created if it
+/// had no token balance before the transaction and has one after it.
+pub fn ata_created_events(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<AtaCreated> {
+    let meta = match trx.meta.as_ref() {
+        Some(meta) if is_successful(trx) => meta,
+        _ => return vec![],
+    };
+    let all: Vec<InstructionRef> = instructions(trx).collect();
+    let pre_balances: HashSet<u32> = meta.pre_token_balances.iter().map(|balance| balance.account_index).collect();
+    let post_balances: HashSet<u32> = meta.post_token_balances.iter().map(|balance| balance.account_index).collect();
+
+    let mut events = vec![];
+    for (position, instruction) in all.iter().enumerate() {
+        if instruction.program_id != ASSOCIATED_TOKEN_PROGRAM_ID.as_ref() {
+            continue;
+        }
+        let idempotent = match AssociatedTokenInstruction::unpack(instruction.data) {
+            Ok(AssociatedTokenInstruction::Create) => false,
+            Ok(AssociatedTokenInstruction::CreateIdempotent) => true,
+            _ => continue,
+        };
+        let accounts = &instruction.accounts;
+        let (account, wallet, mint) = match (accounts.get_pubkey(1), accounts.get_pubkey(2), accounts.get_pubkey(3)) {
+            (Some(account), Some(wallet), Some(mint)) => (account, wallet, mint),
+            _ => continue,
+        };
+        let created = match invoked_instructions(&all, position) {
+            Some(invoked) => invoked.iter().any(is_account_initialization),
+            None => match accounts.indices().and_then(|indices| indices.get(1)) {
+                Some(&index) => !pre_balances.contains(&(index as u32)) && post_balances.contains(&(index as u32)),
+                None => continue,
+            },
+        };
+
+        events.push(AtaCreated {
+            context: EventContext::new(trx, transaction_index, instruction),
+            account,
+            wallet,
+            mint,
+            idempotent,
+            created,
+        });
+    }
+
+    events
+}
+
+fn is_account_initialization(instruction: &InstructionRef) -> bool {
+    is_token_program(instruction.program_id)
+        && matches!(
+            TokenInstruction::unpack(instruction.data),
+            Ok(TokenInstruction::InitializeAccount)
+                | Ok(TokenInstruction::InitializeAccount2 { .. })
+                | Ok(TokenInstruction::InitializeAccount3 { .. })
+        )
+}
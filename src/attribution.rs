@@ -0,0 +1,176 @@
+//! Attribution of token events to the programs invoking the token programs
+//!
+//! Token instructions are mostly executed on behalf of other programs, aggregators and AMMs
+//! invoking the token programs through CPI. The attribution of an event names the program
+//! of its top-level instruction and the program immediately invoking the token instruction,
+//! so events can be labeled "via Jupiter" or "via Raydium".
+
+use {
+    crate::{
+        events::{token_events, EventContext, TokenEvent},
+        instructions::instructions,
+        pubkey::Pubkey,
+    },
+    std::collections::HashMap,
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// Programs an instruction was executed on behalf of
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CallerAttribution {
+    /// Program of the top-level instruction, the instruction's own program for top-level
+    /// instructions
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub top_level_program: Pubkey,
+    /// Program of the instruction that invoked the instruction, its parent in the CPI tree.
+    /// `None` for top-level instructions, and for inner instructions of blocks predating
+    /// the recording of stack heights, whose parent is unknown.
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub caller_program: Option<Pubkey>,
+}
+
+impl CallerAttribution {
+    /// Returns `true` if the instruction was invoked through CPI by a program other than
+    /// its top-level one, a router invoking an AMM for instance
+    pub fn is_nested(&self) -> bool {
+        matches!(self.caller_program, Some(caller) if caller != self.top_level_program)
+    }
+}
+
+/// Attributions of the instructions of a transaction, computed once and looked up by the
+/// position of events
+///
+/// ```
+/// # use substreams_solana_program_instructions::{attribution::TransactionAttributions, events::EventContext, pubkey::Pubkey};
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::{CompiledInstruction, ConfirmedTransaction, InnerInstruction, InnerInstructions, Message, Transaction, TransactionStatusMeta};
+/// let (router, amm, token) = ([1; 32], [2; 32], [3; 32]);
+/// let trx = ConfirmedTransaction {
+///     transaction: Some(Transaction {
+///         message: Some(Message {
+///             account_keys: vec![router.to_vec(), amm.to_vec(), token.to_vec()],
+///             instructions: vec![CompiledInstruction { program_id_index: 0, ..Default::default() }],
+///             ..Default::default()
+///         }),
+///         ..Default::default()
+///     }),
+///     meta: Some(TransactionStatusMeta {
+///         inner_instructions: vec![InnerInstructions {
+///             index: 0,
+///             instructions: vec![
+///                 InnerInstruction { program_id_index: 1, stack_height: Some(2), ..Default::default() },
+///                 InnerInstruction { program_id_index: 2, stack_height: Some(3), ..Default::default() },
+///             ],
+///         }],
+///         ..Default::default()
+///     }),
+/// };
+///
+/// let attributions = TransactionAttributions::new(&trx);
+/// let context = EventContext { instruction_index: 0, inner_instruction_index: Some(1), ..Default::default() };
+/// let attribution = attributions.get(&context).unwrap();
+/// assert_eq!(attribution.top_level_program, Pubkey::from(router));
+/// assert_eq!(attribution.caller_program, Some(Pubkey::from(amm)));
+/// assert!(attribution.is_nested());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TransactionAttributions {
+    attributions: HashMap<(u32, Option<u32>), CallerAttribution>,
+}
+
+impl TransactionAttributions {
+    /// Computes the attributions of the instructions of `trx`
+    pub fn new(trx: &ConfirmedTransaction) -> Self {
+        let mut attributions = HashMap::new();
+        // Programs of the instructions on the invocation stack, the top-level one first
+        let mut stack: Vec<Option<Pubkey>> = vec![];
+        let mut known_heights = true;
+        for instruction in instructions(trx) {
+            let caller_program = if instruction.is_root() {
+                stack.clear();
+                known_heights = true;
+                None
+            } else {
+                match instruction.stack_height {
+                    // The caller is the last instruction one level up the stack
+                    Some(height) if known_heights && height >= 2 && stack.len() >= height as usize - 1 => {
+                        stack.truncate(height as usize - 1);
+                        stack.last().copied().flatten()
+                    }
+                    _ => {
+                        known_heights = false;
+                        None
+                    }
+                }
+            };
+            stack.push(Pubkey::try_from(instruction.program_id).ok());
+            if let Some(&Some(top_level_program)) = stack.first() {
+                attributions.insert(
+                    (
+                        instruction.instruction_index as u32,
+                        instruction.inner_instruction_index.map(|index| index as u32),
+                    ),
+                    CallerAttribution {
+                        top_level_program,
+                        caller_program,
+                    },
+                );
+            }
+        }
+        Self { attributions }
+    }
+
+    /// Attribution of the instruction that emitted the event at `context`, `None` if the
+    /// transaction has no such instruction
+    pub fn get(&self, context: &EventContext) -> Option<CallerAttribution> {
+        self.attributions
+            .get(&(context.instruction_index, context.inner_instruction_index))
+            .copied()
+    }
+}
+
+/// A token event along with the programs it was executed on behalf of
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttributedTokenEvent {
+    /// The event
+    pub event: TokenEvent,
+    /// Programs the event was executed on behalf of
+    pub attribution: CallerAttribution,
+}
+
+/// Extracts the token events of a successful transaction, the `transaction_index`-th of its
+/// block, along with their attribution, in execution order
+pub fn attributed_token_events(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<AttributedTokenEvent> {
+    let events = token_events(trx, transaction_index);
+    if events.is_empty() {
+        return vec![];
+    }
+    let attributions = TransactionAttributions::new(trx);
+    events
+        .into_iter()
+        .filter_map(|event| {
+            let attribution = attributions.get(event.context())?;
+            Some(AttributedTokenEvent { event, attribution })
+        })
+        .collect()
+}
+
+/// Extracts the token events of all successful transactions of `block` along with their
+/// attribution, in execution order
+pub fn block_attributed_token_events(block: &Block) -> Vec<AttributedTokenEvent> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| attributed_token_events(trx, index as u32))
+        .collect()
+}
@@ -0,0 +1,110 @@
+//! Clockwork thread instruction types and attribution of the instructions run by threads
+//!
+//! A Clockwork thread holds a list of instructions run on a trigger (a schedule, an account
+//! change, ...). Workers kick a thread off once its trigger fires, then execute its
+//! instructions one `thread_exec` at a time, each invoked by the thread program with the
+//! thread as signer. The instructions invoked under a `thread_exec` are therefore bot-driven
+//! rather than user-driven, whoever signed the transaction. Both Clockwork thread programs are
+//! Anchor programs, their instructions identified by an 8 bytes discriminator.
+
+use anyhow::anyhow;
+use {
+    crate::{
+        instructions::{instructions, invoked_instructions, InstructionRef},
+        program_ids::{CLOCKWORK_THREAD_PROGRAM_ID, CLOCKWORK_THREAD_V1_PROGRAM_ID},
+        pubkey::Pubkey,
+        token_instruction_2022::TokenInstruction,
+    },
+    substreams::errors::Error,
+    substreams_solana_core::pb::sf::solana::r#type::v1::ConfirmedTransaction,
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// `thread_exec` discriminator, hash of `global:thread_exec`
+pub const THREAD_EXEC_DISCRIMINATOR: [u8; 8] = [141, 79, 219, 140, 125, 246, 162, 160];
+/// `thread_kickoff` discriminator, hash of `global:thread_kickoff`
+pub const THREAD_KICKOFF_DISCRIMINATOR: [u8; 8] = [34, 80, 5, 128, 197, 187, 14, 20];
+
+/// Instructions of the Clockwork thread programs run by workers.
+///
+/// Thread management instructions, sent by thread authorities, are rejected as unknown
+/// discriminators.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockworkThreadInstruction {
+    /// Execute the next instruction of a thread
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Fee account of the worker
+    ///   1. `[]` Worker pool
+    ///   2. `[writable, signer]` Signatory of the worker
+    ///   3. `[writable]` Thread
+    ///   4. `[]` Worker
+    ///   5. ..5+N Accounts of the executed instruction
+    ThreadExec,
+    /// Start a thread whose trigger fired
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Signatory of the worker
+    ///   1. `[writable]` Thread
+    ///   2. `[]` Worker
+    ///   3. ..3+N Accounts of the trigger
+    ThreadKickoff,
+}
+impl ClockworkThreadInstruction {
+    /// Unpacks a byte buffer into a [ClockworkThreadInstruction](enum.ClockworkThreadInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, Error> {
+        let (discriminator, _rest) =
+            TokenInstruction::unpack_bytes(input, 8).map_err(|_| anyhow!("Invalid Clockwork Thread Instruction"))?;
+        Ok(match discriminator {
+            d if d == THREAD_EXEC_DISCRIMINATOR => Self::ThreadExec,
+            d if d == THREAD_KICKOFF_DISCRIMINATOR => Self::ThreadKickoff,
+            _ => return Err(anyhow!("Invalid Clockwork Thread Instruction - unpack didn't match any discriminator: {:?}", discriminator)),
+        })
+    }
+}
+
+/// Returns `true` if `program_id` is one of the Clockwork thread programs
+pub fn is_thread_program(program_id: &[u8]) -> bool {
+    program_id == CLOCKWORK_THREAD_PROGRAM_ID.as_ref() || program_id == CLOCKWORK_THREAD_V1_PROGRAM_ID.as_ref()
+}
+
+/// An instruction run by an automation thread
+#[derive(Clone, Copy, Debug)]
+pub struct AutomatedInstruction<'a> {
+    /// The thread running the instruction
+    pub thread: Pubkey,
+    /// The instruction, invoked by the thread program or by an instruction it invoked
+    pub instruction: InstructionRef<'a>,
+}
+
+/// Instructions of `trx` run by automation threads, in execution order: the instructions
+/// invoked, directly or not, by `thread_exec` instructions. Thread executions sent as inner
+/// instructions of blocks predating the invocation stack height are skipped.
+pub fn automated_instructions(trx: &ConfirmedTransaction) -> Vec<AutomatedInstruction<'_>> {
+    let all: Vec<InstructionRef> = instructions(trx).collect();
+    let mut automated = vec![];
+    for (position, instruction) in all.iter().enumerate() {
+        if !is_thread_program(instruction.program_id) {
+            continue;
+        }
+        if ClockworkThreadInstruction::unpack(instruction.data).ok() != Some(ClockworkThreadInstruction::ThreadExec) {
+            continue;
+        }
+        let thread = match instruction.accounts.get_pubkey(3) {
+            Some(thread) => thread,
+            None => continue,
+        };
+        let invoked = invoked_instructions(&all, position).unwrap_or_default();
+        automated.extend(invoked.iter().map(|instruction| AutomatedInstruction {
+            thread,
+            instruction: *instruction,
+        }));
+    }
+    automated
+}
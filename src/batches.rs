@@ -0,0 +1,168 @@
+//! Grouping of consecutive token transfers sharing an authority into batches, typical of
+//! airdrops and other transfer-many patterns
+
+use {
+    crate::{
+        amount::U128Sum,
+        events::{transfer_events, EventContext, TransferEvent},
+        instructions::instructions,
+        pubkey::Pubkey,
+    },
+    std::collections::HashMap,
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// Minimum number of transfers making a batch
+pub const MIN_BATCH_LEN: usize = 2;
+
+/// Consecutive token transfers of a transaction signed by the same authority
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchTransfer {
+    /// Position of the first transfer of the batch
+    pub context: EventContext,
+    /// The authority shared by the transfers
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub authority: Pubkey,
+    /// The mint shared by the transfers, `None` when they move several or unresolved mints
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub mint: Option<Pubkey>,
+    /// Program of the top-level instruction that invoked all the transfers through CPI, `None`
+    /// when some transfers are top-level instructions or belong to different top-level
+    /// instructions
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub invoking_program: Option<Pubkey>,
+    /// The transfers, in execution order
+    pub transfers: Vec<TransferEvent>,
+}
+
+impl BatchTransfer {
+    /// Kind of the event, part of its [BatchTransfer::event_id]
+    pub const KIND: &'static str = "batch_transfer";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+
+    /// Number of transfers in the batch
+    pub fn len(&self) -> usize {
+        self.transfers.len()
+    }
+
+    /// Returns `true` if the batch holds no transfer
+    pub fn is_empty(&self) -> bool {
+        self.transfers.is_empty()
+    }
+
+    /// Total amount debited from the sources
+    pub fn total_amount(&self) -> U128Sum {
+        self.transfers.iter().map(|transfer| transfer.amount).sum()
+    }
+
+    /// Number of distinct destination token accounts
+    pub fn recipient_count(&self) -> usize {
+        let mut destinations: Vec<&Pubkey> = self.transfers.iter().map(|transfer| &transfer.destination).collect();
+        destinations.sort_unstable();
+        destinations.dedup();
+        destinations.len()
+    }
+}
+
+/// Groups runs of at least [MIN_BATCH_LEN] consecutive `transfers` of the same transaction
+/// sharing their authority into batches. Transfers left out of any batch are dropped.
+///
+/// The batches' `invoking_program` is left to `None`, see [batch_transfer_events] to resolve it.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{batches::batch_transfers, events::{EventContext, TransferEvent}, pubkey::Pubkey};
+/// let transfer = |authority: u8, destination: u8| TransferEvent {
+///     context: EventContext::default(),
+///     mint: Some(Pubkey::from([9; 32])),
+///     source: Pubkey::from([authority; 32]),
+///     source_owner: None,
+///     destination: Pubkey::from([destination; 32]),
+///     destination_owner: None,
+///     authority: Pubkey::from([authority; 32]),
+///     amount: 10,
+///     fee: None,
+/// };
+/// let batches = batch_transfers(vec![transfer(1, 2), transfer(1, 3), transfer(1, 4), transfer(5, 6)]);
+/// assert_eq!(batches.len(), 1);
+/// assert_eq!(batches[0].authority, Pubkey::from([1; 32]));
+/// assert_eq!(batches[0].recipient_count(), 3);
+/// assert_eq!(batches[0].total_amount().get(), 30);
+/// ```
+pub fn batch_transfers(transfers: Vec<TransferEvent>) -> Vec<BatchTransfer> {
+    let mut batches = vec![];
+    let mut run: Vec<TransferEvent> = vec![];
+    for transfer in transfers {
+        let continues = run.last().map_or(true, |last| {
+            last.authority == transfer.authority && last.context.signature == transfer.context.signature
+        });
+        if !continues {
+            push_batch(&mut batches, std::mem::take(&mut run));
+        }
+        run.push(transfer);
+    }
+    push_batch(&mut batches, run);
+    batches
+}
+
+fn push_batch(batches: &mut Vec<BatchTransfer>, transfers: Vec<TransferEvent>) {
+    if transfers.len() < MIN_BATCH_LEN {
+        return;
+    }
+    let first = &transfers[0];
+    let mint = first.mint.filter(|mint| transfers.iter().all(|transfer| transfer.mint == Some(*mint)));
+    batches.push(BatchTransfer {
+        context: first.context.clone(),
+        authority: first.authority,
+        mint,
+        invoking_program: None,
+        transfers,
+    });
+}
+
+/// Extracts the transfer batches of all successful transactions of `block`, in execution order
+pub fn block_batch_transfer_events(block: &Block) -> Vec<BatchTransfer> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| batch_transfer_events(trx, index as u32))
+        .collect()
+}
+
+/// Extracts the transfer batches of a successful transaction, the `transaction_index`-th of
+/// its block, in execution order, resolving the program that invoked each batch
+pub fn batch_transfer_events(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<BatchTransfer> {
+    let mut batches = batch_transfers(transfer_events(trx, transaction_index));
+    if batches.is_empty() {
+        return batches;
+    }
+
+    let top_level_programs: HashMap<u32, &[u8]> = instructions(trx)
+        .filter(|instruction| instruction.inner_instruction_index.is_none())
+        .map(|instruction| (instruction.instruction_index as u32, instruction.program_id))
+        .collect();
+    for batch in batches.iter_mut() {
+        let instruction_index = batch.context.instruction_index;
+        let invoked = batch.transfers.iter().all(|transfer| {
+            transfer.context.inner_instruction_index.is_some() && transfer.context.instruction_index == instruction_index
+        });
+        if invoked {
+            batch.invoking_program = top_level_programs
+                .get(&instruction_index)
+                .and_then(|program_id| Pubkey::try_from(*program_id).ok());
+        }
+    }
+    batches
+}
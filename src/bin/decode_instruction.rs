@@ -0,0 +1,168 @@
+//! Decodes raw instruction data with every decoder of the crate, printing the results as JSON
+//!
+//! Built with the `cli` feature, for spot-checking production payloads without writing Rust:
+//!
+//! ```text
+//! cargo run --features cli --bin decode-instruction -- DGDjFgAAAAAABg==
+//! ```
+//!
+//! Each argument is the base64 encoded data of an instruction, read from the standard input,
+//! one per line, when no argument is given. The output maps each decoder to either the
+//! `decoded` instruction or the decoding `error`, along with the `guess` of the instruction
+//! encoding. Decoders of disabled program features are left out.
+
+use {
+    base64::{engine::general_purpose::STANDARD, Engine},
+    serde::Serialize,
+    serde_json::{json, Map, Value},
+    std::io::{self, BufRead},
+    substreams::errors::Error,
+    substreams_solana_program_instructions::{
+        sniff::sniff, token_group_instruction::TokenGroupInstruction, token_instruction_2022::TokenInstruction,
+        token_metadata_instruction::TokenMetadataInstruction,
+    },
+};
+
+/// Records the outcome of a decoder under `name`
+fn record<T: Serialize>(decodings: &mut Map<String, Value>, name: &str, result: Result<T, Error>) {
+    let value = match result.map(|decoded| serde_json::to_value(decoded)) {
+        Ok(Ok(decoded)) => json!({ "decoded": decoded }),
+        Ok(Err(e)) => json!({ "error": format!("unable to serialize: {}", e) }),
+        Err(e) => json!({ "error": e.to_string() }),
+    };
+    decodings.insert(name.to_string(), value);
+}
+
+/// Decodings of `data` by every enabled decoder
+fn decode(data: &[u8]) -> Value {
+    let mut decodings = Map::new();
+    record(&mut decodings, "TokenInstruction", TokenInstruction::unpack(data));
+    record(&mut decodings, "TokenMetadataInstruction", TokenMetadataInstruction::unpack(data));
+    record(&mut decodings, "TokenGroupInstruction", TokenGroupInstruction::unpack(data));
+    #[cfg(feature = "associated-token")]
+    {
+        use substreams_solana_program_instructions::associated_token_instruction::AssociatedTokenInstruction;
+        record(&mut decodings, "AssociatedTokenInstruction", AssociatedTokenInstruction::unpack(data));
+    }
+    #[cfg(feature = "automation")]
+    {
+        use substreams_solana_program_instructions::automation_instruction::ClockworkThreadInstruction;
+        record(&mut decodings, "ClockworkThreadInstruction", ClockworkThreadInstruction::unpack(data));
+    }
+    #[cfg(feature = "bpf-loader")]
+    {
+        use substreams_solana_program_instructions::bpf_loader_upgradeable_instruction::UpgradeableLoaderInstruction;
+        record(&mut decodings, "UpgradeableLoaderInstruction", UpgradeableLoaderInstruction::unpack(data));
+    }
+    #[cfg(feature = "lending")]
+    {
+        use substreams_solana_program_instructions::lending_instruction::{KaminoLendingInstruction, SolendInstruction};
+        record(&mut decodings, "SolendInstruction", SolendInstruction::unpack(data));
+        record(&mut decodings, "KaminoLendingInstruction", KaminoLendingInstruction::unpack(data));
+    }
+    #[cfg(feature = "liquid-staking")]
+    {
+        use substreams_solana_program_instructions::{
+            marinade_instruction::MarinadeInstruction, stake_pool_instruction::StakePoolInstruction,
+        };
+        record(&mut decodings, "MarinadeInstruction", MarinadeInstruction::unpack(data));
+        record(&mut decodings, "StakePoolInstruction", StakePoolInstruction::unpack(data));
+    }
+    #[cfg(feature = "meteora")]
+    {
+        use substreams_solana_program_instructions::meteora_dlmm::MeteoraDlmmInstruction;
+        record(&mut decodings, "MeteoraDlmmInstruction", MeteoraDlmmInstruction::unpack(data));
+    }
+    #[cfg(feature = "name-service")]
+    {
+        use substreams_solana_program_instructions::name_service_instruction::{
+            NameServiceInstruction, SnsRegistrarInstruction,
+        };
+        record(&mut decodings, "NameServiceInstruction", NameServiceInstruction::unpack(data));
+        record(&mut decodings, "SnsRegistrarInstruction", SnsRegistrarInstruction::unpack(data));
+    }
+    #[cfg(feature = "oracles")]
+    {
+        use substreams_solana_program_instructions::oracle_instruction::{PythInstruction, SwitchboardInstruction};
+        record(&mut decodings, "PythInstruction", PythInstruction::unpack(data));
+        record(&mut decodings, "SwitchboardInstruction", SwitchboardInstruction::unpack(data));
+    }
+    #[cfg(feature = "precompiles")]
+    {
+        use substreams_solana_program_instructions::sig_verify_instruction::{Ed25519Instruction, Secp256k1Instruction};
+        record(&mut decodings, "Ed25519Instruction", Ed25519Instruction::unpack(data));
+        record(&mut decodings, "Secp256k1Instruction", Secp256k1Instruction::unpack(data));
+    }
+    #[cfg(feature = "raydium")]
+    {
+        use substreams_solana_program_instructions::raydium_instruction::{
+            RaydiumClmmInstruction, RaydiumCpmmInstruction,
+        };
+        record(&mut decodings, "RaydiumClmmInstruction", RaydiumClmmInstruction::unpack(data));
+        record(&mut decodings, "RaydiumCpmmInstruction", RaydiumCpmmInstruction::unpack(data));
+    }
+    #[cfg(feature = "stake")]
+    {
+        use substreams_solana_program_instructions::stake_instruction::StakeInstruction;
+        record(&mut decodings, "StakeInstruction", StakeInstruction::unpack(data));
+    }
+    #[cfg(feature = "system")]
+    {
+        use substreams_solana_program_instructions::system_instruction::SystemInstruction;
+        record(&mut decodings, "SystemInstruction", SystemInstruction::unpack(data));
+    }
+    #[cfg(feature = "vesting")]
+    {
+        use substreams_solana_program_instructions::vesting_instruction::{
+            BonfidaVestingInstruction, StreamflowInstruction,
+        };
+        record(&mut decodings, "StreamflowInstruction", StreamflowInstruction::unpack(data));
+        record(&mut decodings, "BonfidaVestingInstruction", BonfidaVestingInstruction::unpack(data));
+    }
+    #[cfg(feature = "vote")]
+    {
+        use substreams_solana_program_instructions::vote_instruction::VoteInstruction;
+        record(&mut decodings, "VoteInstruction", VoteInstruction::unpack(data));
+    }
+    #[cfg(feature = "wormhole")]
+    {
+        use substreams_solana_program_instructions::wormhole::CoreBridgeInstruction;
+        record(&mut decodings, "CoreBridgeInstruction", CoreBridgeInstruction::unpack(data));
+    }
+
+    json!({
+        "guess": serde_json::to_value(sniff(data)).unwrap_or(Value::Null),
+        "decodings": decodings,
+    })
+}
+
+fn print(input: &str) {
+    let input = input.trim();
+    let output = match STANDARD.decode(input) {
+        Ok(data) => decode(&data),
+        Err(e) => json!({ "error": format!("invalid base64 input: {}", e) }),
+    };
+    let output = json!({ "input": input, "output": output });
+    match serde_json::to_string_pretty(&output) {
+        Ok(rendered) => println!("{}", rendered),
+        Err(e) => eprintln!("unable to render {}: {}", input, e),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        args.iter().for_each(|input| print(input));
+        return;
+    }
+    for line in io::stdin().lock().lines() {
+        match line {
+            Ok(line) if !line.trim().is_empty() => print(&line),
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("unable to read the standard input: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Revisions of the `sf.solana.type.v1` block model
+//!
+//! Fields were added to the firehose block model over time, and blocks of older revisions
+//! lack them: inner instructions only later recorded their invocation stack height, and the
+//! earliest transactions have no inner instructions recorded at all. Their logs still hold a
+//! `Program <id> invoke [<height>]` line per instruction executed, top-level and inner alike.
+//!
+//! [BlockRevision::of] infers the revision of the block holding a transaction from its meta
+//! and logs, and [instructions](crate::instructions::instructions) tags each instruction with
+//! it. For blocks predating stack heights, it reads them from the logs instead, see
+//! [logged_stack_heights]. For blocks predating inner instructions,
+//! [invoked_instructions](crate::instructions::invoked_instructions) reports the instructions
+//! invoked by an instruction as unknown rather than empty: DEX trades are then skipped rather
+//! than reported without amounts, and the creation of associated token accounts is read from
+//! the token balances.
+
+use {
+    crate::accounts::TransactionKeys,
+    substreams_solana_core::pb::sf::solana::r#type::v1::ConfirmedTransaction,
+};
+
+/// Revision of the block model a transaction was recorded with, ordered from the oldest
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BlockRevision {
+    /// Inner instructions are not recorded
+    NoInnerInstructions,
+    /// Inner instructions are recorded without their stack height
+    NoStackHeight,
+    /// Inner instructions are recorded along with their stack height
+    #[default]
+    StackHeight,
+}
+
+impl BlockRevision {
+    /// Infers the revision of the block holding `trx`. Transactions without inner
+    /// instructions are reported with the latest revision unless their logs show
+    /// instructions invoked through CPI.
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::block_version::BlockRevision;
+    /// # use substreams_solana_core::pb::sf::solana::r#type::v1::{ConfirmedTransaction, InnerInstruction, InnerInstructions, TransactionStatusMeta};
+    /// let with_meta = |meta: TransactionStatusMeta| ConfirmedTransaction { meta: Some(meta), ..Default::default() };
+    /// let logs = vec![
+    ///     "Program 11111111111111111111111111111111 invoke [1]".to_string(),
+    ///     "Program 11111111111111111111111111111111 invoke [2]".to_string(),
+    /// ];
+    /// let legacy = with_meta(TransactionStatusMeta { log_messages: logs, ..Default::default() });
+    /// assert_eq!(BlockRevision::of(&legacy), BlockRevision::NoInnerInstructions);
+    ///
+    /// let inner = |stack_height: Option<u32>| InnerInstructions {
+    ///     index: 0,
+    ///     instructions: vec![InnerInstruction { stack_height, ..Default::default() }],
+    /// };
+    /// let trx = with_meta(TransactionStatusMeta { inner_instructions: vec![inner(None)], ..Default::default() });
+    /// assert_eq!(BlockRevision::of(&trx), BlockRevision::NoStackHeight);
+    /// let trx = with_meta(TransactionStatusMeta { inner_instructions: vec![inner(Some(2))], ..Default::default() });
+    /// assert_eq!(BlockRevision::of(&trx), BlockRevision::StackHeight);
+    /// ```
+    pub fn of(trx: &ConfirmedTransaction) -> Self {
+        let meta = match trx.meta.as_ref() {
+            Some(meta) => meta,
+            None => return Self::default(),
+        };
+        let mut inner_instructions = meta
+            .inner_instructions
+            .iter()
+            .flat_map(|inner| inner.instructions.iter())
+            .peekable();
+        if inner_instructions.peek().is_none() {
+            let invokes_cpi = meta
+                .log_messages
+                .iter()
+                .filter_map(|log| parse_invoke(log))
+                .any(|(_, height)| height >= 2);
+            return if invokes_cpi {
+                Self::NoInnerInstructions
+            } else {
+                Self::StackHeight
+            };
+        }
+        if inner_instructions.any(|inner| inner.stack_height.is_none()) {
+            Self::NoStackHeight
+        } else {
+            Self::StackHeight
+        }
+    }
+
+    /// Returns `true` if inner instructions are recorded
+    pub fn has_inner_instructions(&self) -> bool {
+        *self >= Self::NoStackHeight
+    }
+
+    /// Returns `true` if inner instructions record their stack height
+    pub fn has_stack_height(&self) -> bool {
+        *self >= Self::StackHeight
+    }
+}
+
+/// Stack heights of the inner instructions of `trx` read from its `Program <id> invoke
+/// [<height>]` log lines, by top-level instruction then inner instruction. `None` if the logs
+/// do not match the recorded instructions, truncated logs for instance.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{block_version::logged_stack_heights, instructions::instructions};
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::{CompiledInstruction, ConfirmedTransaction, InnerInstruction, InnerInstructions, Message, Transaction, TransactionStatusMeta};
+/// let (router, amm, token) = ([1; 32], [2; 32], [3; 32]);
+/// let invoke = |program: [u8; 32], height: u32| format!("Program {} invoke [{}]", bs58::encode(program).into_string(), height);
+/// let mut trx = ConfirmedTransaction {
+///     transaction: Some(Transaction {
+///         message: Some(Message {
+///             account_keys: vec![router.to_vec(), amm.to_vec(), token.to_vec()],
+///             instructions: vec![CompiledInstruction { program_id_index: 0, ..Default::default() }],
+///             ..Default::default()
+///         }),
+///         ..Default::default()
+///     }),
+///     meta: Some(TransactionStatusMeta {
+///         inner_instructions: vec![InnerInstructions {
+///             index: 0,
+///             instructions: vec![
+///                 InnerInstruction { program_id_index: 1, ..Default::default() },
+///                 InnerInstruction { program_id_index: 2, ..Default::default() },
+///             ],
+///         }],
+///         log_messages: vec![invoke(router, 1), invoke(amm, 2), "Program log: swap".to_string(), invoke(token, 3)],
+///         ..Default::default()
+///     }),
+/// };
+/// assert_eq!(logged_stack_heights(&trx), Some(vec![vec![2, 3]]));
+/// let stack_heights: Vec<_> = instructions(&trx).map(|instruction| instruction.stack_height).collect();
+/// assert_eq!(stack_heights, vec![Some(1), Some(2), Some(3)]);
+///
+/// trx.meta.as_mut().unwrap().log_messages.truncate(2);
+/// assert_eq!(logged_stack_heights(&trx), None);
+/// ```
+pub fn logged_stack_heights(trx: &ConfirmedTransaction) -> Option<Vec<Vec<u32>>> {
+    let message = trx.transaction.as_ref()?.message.as_ref()?;
+    let meta = trx.meta.as_ref()?;
+    let keys = TransactionKeys::from_transaction(trx)?;
+    let mut invokes = meta.log_messages.iter().filter_map(|log| parse_invoke(log));
+    let mut invoke = |program_id_index: u32| {
+        let (program, height) = invokes.next()?;
+        let program_id = keys.get(program_id_index as usize)?;
+        (bs58::encode(program_id).into_string() == program).then_some(height)
+    };
+
+    let mut heights = Vec::with_capacity(message.instructions.len());
+    for (instruction_index, compiled) in message.instructions.iter().enumerate() {
+        if invoke(compiled.program_id_index)? != 1 {
+            return None;
+        }
+        let mut group = vec![];
+        for inner in meta
+            .inner_instructions
+            .iter()
+            .filter(|inner| inner.index as usize == instruction_index)
+            .flat_map(|inner| inner.instructions.iter())
+        {
+            match invoke(inner.program_id_index)? {
+                height if height >= 2 => group.push(height),
+                _ => return None,
+            }
+        }
+        heights.push(group);
+    }
+    invokes.next().is_none().then_some(heights)
+}
+
+/// Program and stack height of a `Program <id> invoke [<height>]` log line
+fn parse_invoke(log: &str) -> Option<(&str, u32)> {
+    let (program, height) = log.strip_prefix("Program ")?.split_once(" invoke [")?;
+    Some((program, height.strip_suffix(']')?.parse().ok()?))
+}
@@ -0,0 +1,150 @@
+//! Upgradeable BPF loader instruction types
+
+use anyhow::anyhow;
+use {
+    crate::token_instruction_2022::TokenInstruction,
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Instructions supported by the upgradeable BPF loader, bincode encoded with a `u32` tag.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum UpgradeableLoaderInstruction<'a> {
+    /// Initialize a Buffer account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` source account to initialize.
+    ///   1. `[]` Buffer authority, optional, if omitted then the buffer will be immutable.
+    InitializeBuffer,
+    /// Write program data into a Buffer account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Buffer account to write program data to.
+    ///   1. `[signer]` Buffer authority
+    Write {
+        /// Offset at which to write the given bytes.
+        offset: u32,
+        /// Serialized program data
+        bytes: &'a [u8],
+    },
+    /// Deploy an executable program.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` The payer account that will pay to create the ProgramData
+    ///      account.
+    ///   1. `[writable]` The uninitialized ProgramData account.
+    ///   2. `[writable]` The uninitialized Program account.
+    ///   3. `[writable]` The Buffer account where the program data has been written. The
+    ///      buffer account's authority must match the program's authority
+    ///   4. `[]` Rent sysvar.
+    ///   5. `[]` Clock sysvar.
+    ///   6. `[]` System program.
+    ///   7. `[signer]` The program's authority
+    DeployWithMaxDataLen {
+        /// Maximum length that the program can be upgraded to.
+        max_data_len: u64,
+    },
+    /// Upgrade a program.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The ProgramData account.
+    ///   1. `[writable]` The Program account.
+    ///   2. `[writable]` The Buffer account where the program data has been written. The
+    ///      buffer account's authority must match the program's authority
+    ///   3. `[writable]` The spill account.
+    ///   4. `[]` Rent sysvar.
+    ///   5. `[]` Clock sysvar.
+    ///   6. `[signer]` The program's authority.
+    Upgrade,
+    /// Set a new authority that is allowed to write the buffer or upgrade the program. To
+    /// permanently make the buffer immutable or disable program updates omit the new
+    /// authority.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Buffer or ProgramData account to change the authority of.
+    ///   1. `[signer]` The current authority.
+    ///   2. `[]` The new authority, optional, if omitted then the program will not be
+    ///      upgradeable.
+    SetAuthority,
+    /// Closes an account owned by the upgradeable loader of all lamports and withdraws all
+    /// the lamports
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The account to close, if closing a program must be the ProgramData
+    ///      account.
+    ///   1. `[writable]` The account to deposit the closed account's lamports.
+    ///   2. `[signer]` The account's authority, Optional, required for initialized accounts.
+    ///   3. `[writable]` The associated Program account if the account to close is a
+    ///      ProgramData account.
+    Close,
+    /// Extend a program's ProgramData account by the specified number of bytes.
+    /// Only upgradeable programs can be extended.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The ProgramData account.
+    ///   1. `[writable]` The ProgramData account's associated Program account.
+    ///   2. `[]` System program, optional, used to transfer lamports from the payer to the
+    ///      ProgramData account.
+    ///   3. `[writable, signer]` The payer account, optional, that will pay necessary rent
+    ///      exemption costs for the increased storage size.
+    ExtendProgram {
+        /// Number of bytes to extend the program data.
+        additional_bytes: u32,
+    },
+    /// Set a new authority that is allowed to write the buffer or upgrade the program.
+    ///
+    /// This instruction differs from SetAuthority in that the new authority is a
+    /// required signer.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The Buffer or ProgramData account to change the authority of.
+    ///   1. `[signer]` The current authority.
+    ///   2. `[signer]` The new authority.
+    SetAuthorityChecked,
+}
+
+impl<'a> UpgradeableLoaderInstruction<'a> {
+    /// Unpacks a byte buffer into a [UpgradeableLoaderInstruction](enum.UpgradeableLoaderInstruction.html).
+    pub fn unpack(input: &'a [u8]) -> Result<Self, Error> {
+        let (tag, rest) = TokenInstruction::unpack_u32(input)
+            .map_err(|_| anyhow!("Invalid Upgradeable Loader Instruction"))?;
+        Ok(match tag {
+            0 => Self::InitializeBuffer,
+            1 => {
+                let (offset, rest) = TokenInstruction::unpack_u32(rest)?;
+                let (len, rest) = TokenInstruction::unpack_u64(rest)?;
+                let len = usize::try_from(len).map_err(|_| anyhow!("Unable to unpack bytes of length {}", len))?;
+                let (bytes, _rest) = TokenInstruction::unpack_bytes(rest, len)?;
+                Self::Write { offset, bytes }
+            }
+            2 => {
+                let (max_data_len, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::DeployWithMaxDataLen { max_data_len }
+            }
+            3 => Self::Upgrade,
+            4 => Self::SetAuthority,
+            5 => Self::Close,
+            6 => {
+                let (additional_bytes, _rest) = TokenInstruction::unpack_u32(rest)?;
+                Self::ExtendProgram { additional_bytes }
+            }
+            7 => Self::SetAuthorityChecked,
+            _ => return Err(anyhow!("Invalid Upgradeable Loader Instruction - unpack didn't match any tag value: {}", tag)),
+        })
+    }
+}
@@ -0,0 +1,112 @@
+//! Canonical JSON serialization of decoded instructions and events
+//!
+//! Only available with the `canonical-json` feature. Hashing the JSON produced by a regular
+//! serializer gives different digests for the same event depending on field order,
+//! whitespace or number formatting. [to_canonical_json] produces a single representation
+//! per value, so digests computed by independent consumers of the same events match:
+//!
+//! * object keys are sorted by their UTF-8 bytes, at every level
+//! * no whitespace between tokens
+//! * integers are written as plain digits, floats with an integral value as integers
+//!   (`-0.0` as `0`), other floats in their shortest round-trip form
+//! * strings are escaped as `serde_json` does, non-ASCII characters written as UTF-8
+//!
+//! Amounts exceeding `u64`, like [crate::amount::U128Sum], are serialized as strings by this
+//! crate and are not affected by the limits of JSON numbers.
+
+use {
+    anyhow::anyhow,
+    serde::Serialize,
+    serde_json::{Number, Value},
+    substreams::errors::Error,
+};
+
+/// Largest integer an `f64` represents exactly along with all smaller ones, 2^53
+const MAX_SAFE_FLOAT_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// Serializes `value` to canonical JSON, see the [module documentation](self). Fails for
+/// values `serde_json` cannot represent, maps with non-string keys or integers beyond the
+/// 64 bits range for instance.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{canonical_json::to_canonical_json, events::EventContext};
+/// let context = EventContext {
+///     signature: "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW".to_string(),
+///     transaction_index: 3,
+///     instruction_index: 1,
+///     inner_instruction_index: None,
+///     log_index: None,
+/// };
+/// assert_eq!(
+///     to_canonical_json(&context).unwrap(),
+///     r#"{"innerInstructionIndex":null,"instructionIndex":1,"signature":"5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW","transactionIndex":3}"#
+/// );
+/// ```
+pub fn to_canonical_json<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    let value = serde_json::to_value(value).map_err(|e| anyhow!("unable to serialize value: {}", e))?;
+    let mut out = String::new();
+    write_value(&mut out, &value)?;
+    Ok(out)
+}
+
+/// Serializes `value` to canonical JSON bytes, the input of digests, see [to_canonical_json]
+pub fn to_canonical_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, Error> {
+    to_canonical_json(value).map(String::into_bytes)
+}
+
+fn write_value(out: &mut String, value: &Value) -> Result<(), Error> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(true) => out.push_str("true"),
+        Value::Bool(false) => out.push_str("false"),
+        Value::Number(number) => write_number(out, number),
+        Value::String(string) => write_string(out, string)?,
+        Value::Array(values) => {
+            out.push('[');
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_value(out, value)?;
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            // Sorted explicitly, the map keeps insertion order when `serde_json`'s
+            // `preserve_order` feature is enabled elsewhere in the dependency graph
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+            out.push('{');
+            for (index, (key, value)) in entries.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(',');
+                }
+                write_string(out, key)?;
+                out.push(':');
+                write_value(out, value)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+fn write_number(out: &mut String, number: &Number) {
+    if let Some(value) = number.as_u64() {
+        out.push_str(&value.to_string());
+    } else if let Some(value) = number.as_i64() {
+        out.push_str(&value.to_string());
+    } else if let Some(value) = number.as_f64() {
+        if value.fract() == 0.0 && value.abs() <= MAX_SAFE_FLOAT_INTEGER {
+            out.push_str(&(value as i64).to_string());
+        } else {
+            out.push_str(&number.to_string());
+        }
+    }
+}
+
+fn write_string(out: &mut String, string: &str) -> Result<(), Error> {
+    let escaped = serde_json::to_string(string).map_err(|e| anyhow!("unable to serialize string: {}", e))?;
+    out.push_str(&escaped);
+    Ok(())
+}
@@ -0,0 +1,190 @@
+//! Commission changes of vote accounts, for substreams alerting delegators
+//!
+//! A validator keeps its commission, a share of the inflation rewards of the stake delegated
+//! to its vote account, and may change it at any time, raising it in the first half of an
+//! epoch only. [CommissionChanged] events carry the commission replaced, when known, and the
+//! epoch of the change: the commission in force at the end of an epoch applies to the rewards
+//! of the whole epoch.
+
+use {
+    crate::{
+        epoch::{EpochContext, EpochSchedule},
+        events::EventContext,
+        instructions::{instructions, is_successful},
+        metrics,
+        program_ids::VOTE_PROGRAM_ID,
+        pubkey::Pubkey,
+        vote_instruction::VoteInstruction,
+    },
+    std::collections::HashMap,
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// The commission of a vote account was updated through `UpdateCommission`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommissionChanged {
+    /// Position of the `UpdateCommission` instruction
+    pub context: EventContext,
+    /// Epoch of the block of the instruction
+    pub epoch: EpochContext,
+    /// The vote account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub vote_account: Pubkey,
+    /// The commission replaced, in percent, `None` if unknown
+    pub old_commission: Option<u8>,
+    /// The new commission, in percent
+    pub new_commission: u8,
+    /// The withdraw authority
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub authority: Pubkey,
+}
+
+impl CommissionChanged {
+    /// Kind of the event, part of its [CommissionChanged::event_id]
+    pub const KIND: &'static str = "commission_changed";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+
+    /// Signed change of the commission, in percentage points, `None` if the replaced
+    /// commission is unknown
+    pub fn delta(&self) -> Option<i16> {
+        self.old_commission
+            .map(|old_commission| self.new_commission as i16 - old_commission as i16)
+    }
+
+    /// Returns `true` if the commission was raised, `false` if lowered, unchanged or unknown
+    pub fn is_increase(&self) -> bool {
+        self.delta().map_or(false, |delta| delta > 0)
+    }
+}
+
+/// Extracts the commission updates of all successful transactions of `block`, in execution
+/// order, see [commission_change_events]. The epoch of the block is derived from its slot
+/// with `schedule`. The commission replaced by an update is read from `prior`, or from an
+/// earlier instruction of the block initializing the account or updating its commission.
+pub fn block_commission_change_events<F>(block: &Block, schedule: &EpochSchedule, prior: F) -> Vec<CommissionChanged>
+where
+    F: Fn(&Pubkey) -> Option<u8>,
+{
+    let epoch = schedule.context(block.slot);
+    let mut commissions = HashMap::new();
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| commission_changes(trx, index as u32, epoch, &mut commissions, &prior))
+        .collect()
+}
+
+/// Extracts the commission updates of a successful transaction, the `transaction_index`-th
+/// of its block, in execution order. `epoch` is the epoch of the block and `prior` gives the
+/// commission of a vote account before the transaction, `None` if unknown: the commission
+/// replaced is read from it, or from an earlier instruction of the transaction initializing
+/// the account or updating its commission.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{commission::*, epoch::EpochSchedule, program_ids::VOTE_PROGRAM_ID, pubkey::Pubkey};
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::{CompiledInstruction, ConfirmedTransaction, Message, Transaction, TransactionStatusMeta};
+/// let (vote_account, authority) = (Pubkey::from([1; 32]), Pubkey::from([2; 32]));
+/// let update = |commission: u8| CompiledInstruction { program_id_index: 2, accounts: vec![0, 1], data: vec![5, 0, 0, 0, commission] };
+/// let trx = ConfirmedTransaction {
+///     transaction: Some(Transaction {
+///         message: Some(Message {
+///             account_keys: vec![vote_account.as_ref().to_vec(), authority.as_ref().to_vec(), VOTE_PROGRAM_ID.as_ref().to_vec()],
+///             instructions: vec![update(10), update(5)],
+///             ..Default::default()
+///         }),
+///         ..Default::default()
+///     }),
+///     meta: Some(TransactionStatusMeta::default()),
+/// };
+///
+/// let epoch = EpochSchedule::default().context(300_000_000);
+/// let events = commission_change_events(&trx, 0, epoch, |_| Some(7));
+/// assert_eq!((events[0].old_commission, events[0].new_commission), (Some(7), 10));
+/// assert!(events[0].is_increase());
+/// assert_eq!((events[1].delta(), events[1].epoch.epoch), (Some(-5), 694));
+/// assert_eq!(commission_change_events(&trx, 0, epoch, |_| None)[0].old_commission, None);
+/// ```
+pub fn commission_change_events<F>(
+    trx: &ConfirmedTransaction,
+    transaction_index: u32,
+    epoch: EpochContext,
+    prior: F,
+) -> Vec<CommissionChanged>
+where
+    F: Fn(&Pubkey) -> Option<u8>,
+{
+    commission_changes(trx, transaction_index, epoch, &mut HashMap::new(), &prior)
+}
+
+/// Extracts the commission updates of `trx`, `commissions` holding the current commission of
+/// the vote accounts whose commission earlier instructions set, updated as the transaction
+/// sets them
+fn commission_changes<F>(
+    trx: &ConfirmedTransaction,
+    transaction_index: u32,
+    epoch: EpochContext,
+    commissions: &mut HashMap<Pubkey, u8>,
+    prior: &F,
+) -> Vec<CommissionChanged>
+where
+    F: Fn(&Pubkey) -> Option<u8>,
+{
+    if !is_successful(trx) {
+        return vec![];
+    }
+
+    let mut events = vec![];
+    for instruction in instructions(trx) {
+        if instruction.program_id != VOTE_PROGRAM_ID.as_ref() {
+            continue;
+        }
+        let decoded = match VoteInstruction::unpack(instruction.data) {
+            Ok(decoded) => decoded,
+            Err(_) => continue,
+        };
+        let vote_account = match instruction.accounts.get_pubkey(0) {
+            Some(vote_account) => vote_account,
+            None => continue,
+        };
+        let new_commission = match decoded {
+            VoteInstruction::InitializeAccount { commission, .. } => {
+                commissions.insert(vote_account, commission);
+                continue;
+            }
+            VoteInstruction::UpdateCommission { commission } => commission,
+            _ => continue,
+        };
+        let authority = match instruction.accounts.get_pubkey(1) {
+            Some(authority) => authority,
+            None => continue,
+        };
+        let old_commission = commissions
+            .insert(vote_account, new_commission)
+            .or_else(|| prior(&vote_account));
+
+        events.push(CommissionChanged {
+            context: EventContext::new(trx, transaction_index, &instruction),
+            epoch,
+            vote_account,
+            old_commission,
+            new_commission,
+            authority,
+        });
+    }
+
+    metrics::events_emitted(CommissionChanged::KIND, events.len());
+    events
+}
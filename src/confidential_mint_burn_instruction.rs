@@ -0,0 +1,233 @@
+use anyhow::anyhow;
+use {
+    substreams::errors::Error,
+    crate::{token_instruction_2022::TokenInstruction},
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Serialized length of an ElGamal public key
+pub const ELGAMAL_PUBKEY_BYTES: usize = 32;
+/// Serialized length of an ElGamal ciphertext
+pub const ELGAMAL_CIPHERTEXT_BYTES: usize = 64;
+/// Serialized length of an authenticated encryption ciphertext
+pub const AE_CIPHERTEXT_BYTES: usize = 36;
+
+/// Confidential Mint-Burn extension instructions
+///
+/// The cryptographic material (ElGamal keys, ciphertexts) is exposed as raw bytes, the
+/// zero-knowledge proofs themselves live in separate instructions of the ZK ElGamal proof
+/// program, located relative to this instruction through the `*_proof_instruction_offset` fields.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConfidentialMintBurnInstruction<'a> {
+    /// Initializes confidential mints and burns for a mint.
+    ///
+    /// The `ConfidentialMintBurnInstruction::InitializeMint` instruction
+    /// requires no signers and MUST be included within the same Transaction
+    /// as `TokenInstruction::InitializeMint`. Otherwise another party can
+    /// initialize the configuration.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token mint.
+    InitializeMint {
+        /// The ElGamal pubkey used to encrypt the confidential supply
+        supply_elgamal_pubkey: &'a [u8],
+        /// The initial 0 supply encrypted with the supply aes key
+        decryptable_supply: &'a [u8],
+    },
+    /// Rotates the ElGamal pubkey used to encrypt confidential supply
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The SPL Token mint.
+    ///   1. `[]` Instructions sysvar if `CiphertextCiphertextEquality` is included in the same
+    ///      transaction or context state account if `CiphertextCiphertextEquality` is
+    ///      pre-verified into a context state account.
+    ///   2. `[signer]` Confidential mint authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The SPL Token mint.
+    ///   1. `[]` Instructions sysvar or context state account.
+    ///   2. `[]` The multisig authority account owner.
+    ///   3. ..3+M `[signer]` M signer accounts.
+    RotateSupplyElGamalPubkey {
+        /// The new ElGamal pubkey for supply encryption
+        new_supply_elgamal_pubkey: &'a [u8],
+        /// The location of the `ProofInstruction::VerifyCiphertextCiphertextEquality`
+        /// instruction relative to the `RotateSupplyElGamalPubkey` instruction in the transaction
+        proof_instruction_offset: i8,
+    },
+    /// Updates the decryptable supply of the mint
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The SPL Token mint.
+    ///   1. `[signer]` Confidential mint authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The SPL Token mint.
+    ///   1. `[]` The multisig authority account owner.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    UpdateDecryptableSupply {
+        /// The new decryptable supply
+        new_decryptable_supply: &'a [u8],
+    },
+    /// Mints tokens to confidential balance
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[writable]` The SPL Token mint.
+    ///   2. ..5. `[]` (Optional) Instructions sysvar or context state accounts of the
+    ///      equality, ciphertext validity and range proofs.
+    ///   6. `[signer]` The single account owner.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The SPL Token mint.
+    ///   1. `[]` The SPL Token account.
+    ///   2. ..5. `[]` (Optional) Instructions sysvar or context state accounts.
+    ///   6. `[]` The multisig account owner.
+    ///   7. ..7+M `[signer]` M signer accounts.
+    Mint {
+        /// The new decryptable supply if the mint succeeds
+        new_decryptable_supply: &'a [u8],
+        /// The transfer amount encrypted under the auditor ElGamal public key, low bits
+        mint_amount_auditor_ciphertext_lo: &'a [u8],
+        /// The transfer amount encrypted under the auditor ElGamal public key, high bits
+        mint_amount_auditor_ciphertext_hi: &'a [u8],
+        /// Relative location of the `ProofInstruction::VerifyCiphertextCommitmentEquality`
+        /// instruction to the `ConfidentialMint` instruction in the transaction. 0 if the
+        /// proof is in a pre-verified context account
+        equality_proof_instruction_offset: i8,
+        /// Relative location of the
+        /// `ProofInstruction::VerifyBatchedGroupedCiphertext3HandlesValidity` instruction
+        /// to the `ConfidentialMint` instruction in the transaction. 0 if the proof is in a
+        /// pre-verified context account
+        ciphertext_validity_proof_instruction_offset: i8,
+        /// Relative location of the `ProofInstruction::VerifyBatchedRangeProofU128`
+        /// instruction to the `ConfidentialMint` instruction in the transaction. 0 if the
+        /// proof is in a pre-verified context account
+        range_proof_instruction_offset: i8,
+    },
+    /// Burn tokens from confidential balance
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[writable]` The SPL Token mint.
+    ///   2. ..5. `[]` (Optional) Instructions sysvar or context state accounts of the
+    ///      equality, ciphertext validity and range proofs.
+    ///   6. `[signer]` The single account owner.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The SPL Token mint.
+    ///   1. `[]` The SPL Token account.
+    ///   2. ..5. `[]` (Optional) Instructions sysvar or context state accounts.
+    ///   6. `[]` The multisig account owner.
+    ///   7. ..7+M `[signer]` M signer accounts.
+    Burn {
+        /// The new decryptable balance of the burner if the burn succeeds
+        new_decryptable_available_balance: &'a [u8],
+        /// The transfer amount encrypted under the auditor ElGamal public key, low bits
+        burn_amount_auditor_ciphertext_lo: &'a [u8],
+        /// The transfer amount encrypted under the auditor ElGamal public key, high bits
+        burn_amount_auditor_ciphertext_hi: &'a [u8],
+        /// Relative location of the `ProofInstruction::VerifyCiphertextCommitmentEquality`
+        /// instruction to the `ConfidentialBurn` instruction in the transaction. 0 if the
+        /// proof is in a pre-verified context account
+        equality_proof_instruction_offset: i8,
+        /// Relative location of the
+        /// `ProofInstruction::VerifyBatchedGroupedCiphertext3HandlesValidity` instruction
+        /// to the `ConfidentialBurn` instruction in the transaction. 0 if the proof is in a
+        /// pre-verified context account
+        ciphertext_validity_proof_instruction_offset: i8,
+        /// Relative location of the `ProofInstruction::VerifyBatchedRangeProofU128`
+        /// instruction to the `ConfidentialBurn` instruction in the transaction. 0 if the
+        /// proof is in a pre-verified context account
+        range_proof_instruction_offset: i8,
+    },
+    /// Applies the pending burn amount to the confidential supply
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The SPL token mint.
+    ///   1. `[signer]` The mint's confidential mint authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The SPL token mint.
+    ///   1. `[]` The multisig account owner.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    ApplyPendingBurn,
+}
+impl<'a> ConfidentialMintBurnInstruction<'a> {
+    /// Unpacks a byte buffer into a ConfidentialMintBurnInstruction
+    pub fn unpack(input: &'a [u8]) -> Result<(Self, &'a [u8]), Error> {
+        let (&tag, rest) = input.split_first().ok_or(anyhow!("Invalid Confidential Mint Burn Instruction"))?;
+        Ok(match tag {
+            0 => {
+                let (supply_elgamal_pubkey, rest) = TokenInstruction::unpack_bytes(rest, ELGAMAL_PUBKEY_BYTES)?;
+                let (decryptable_supply, rest) = TokenInstruction::unpack_bytes(rest, AE_CIPHERTEXT_BYTES)?;
+                let instruction = Self::InitializeMint {
+                    supply_elgamal_pubkey,
+                    decryptable_supply,
+                };
+                (instruction, rest)
+            }
+            1 => {
+                let (new_supply_elgamal_pubkey, rest) = TokenInstruction::unpack_bytes(rest, ELGAMAL_PUBKEY_BYTES)?;
+                let (proof_instruction_offset, rest) = TokenInstruction::unpack_i8(rest)?;
+                let instruction = Self::RotateSupplyElGamalPubkey {
+                    new_supply_elgamal_pubkey,
+                    proof_instruction_offset,
+                };
+                (instruction, rest)
+            }
+            2 => {
+                let (new_decryptable_supply, rest) = TokenInstruction::unpack_bytes(rest, AE_CIPHERTEXT_BYTES)?;
+                (Self::UpdateDecryptableSupply { new_decryptable_supply }, rest)
+            }
+            3 | 4 => {
+                let (decryptable, rest) = TokenInstruction::unpack_bytes(rest, AE_CIPHERTEXT_BYTES)?;
+                let (ciphertext_lo, rest) = TokenInstruction::unpack_bytes(rest, ELGAMAL_CIPHERTEXT_BYTES)?;
+                let (ciphertext_hi, rest) = TokenInstruction::unpack_bytes(rest, ELGAMAL_CIPHERTEXT_BYTES)?;
+                let (equality_proof_instruction_offset, rest) = TokenInstruction::unpack_i8(rest)?;
+                let (ciphertext_validity_proof_instruction_offset, rest) = TokenInstruction::unpack_i8(rest)?;
+                let (range_proof_instruction_offset, rest) = TokenInstruction::unpack_i8(rest)?;
+                let instruction = match tag {
+                    3 => Self::Mint {
+                        new_decryptable_supply: decryptable,
+                        mint_amount_auditor_ciphertext_lo: ciphertext_lo,
+                        mint_amount_auditor_ciphertext_hi: ciphertext_hi,
+                        equality_proof_instruction_offset,
+                        ciphertext_validity_proof_instruction_offset,
+                        range_proof_instruction_offset,
+                    },
+                    4 => Self::Burn {
+                        new_decryptable_available_balance: decryptable,
+                        burn_amount_auditor_ciphertext_lo: ciphertext_lo,
+                        burn_amount_auditor_ciphertext_hi: ciphertext_hi,
+                        equality_proof_instruction_offset,
+                        ciphertext_validity_proof_instruction_offset,
+                        range_proof_instruction_offset,
+                    },
+                    _ => unreachable!(),
+                };
+                (instruction, rest)
+            }
+            5 => (Self::ApplyPendingBurn, rest),
+            _ => return Err(anyhow!("Invalid Confidential Mint Burn Instruction - unpack didn't match any tag value: {}", tag)),
+        })
+    }
+}
@@ -0,0 +1,437 @@
+use anyhow::anyhow;
+use {
+    substreams::errors::Error,
+    crate::{
+        amount::{amount, decimals, Amount, AmountDecimals},
+        confidential_mint_burn_instruction::{AE_CIPHERTEXT_BYTES, ELGAMAL_CIPHERTEXT_BYTES, ELGAMAL_PUBKEY_BYTES},
+        option::COption,
+        pubkey::Pubkey,
+        token_instruction_2022::TokenInstruction,
+    },
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    crate::serialization::coption_fromstr,
+    serde::{Deserialize, Serialize},
+};
+
+/// Confidential Transfer extension instructions
+///
+/// As for [ConfidentialMintBurnInstruction](crate::confidential_mint_burn_instruction::ConfidentialMintBurnInstruction),
+/// the cryptographic material is exposed as raw bytes and the zero-knowledge proofs live in
+/// separate instructions of the ZK ElGamal proof program, located relative to this instruction
+/// through the `*_proof_instruction_offset` fields.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConfidentialTransferInstruction<'a> {
+    /// Initializes confidential transfers for a mint.
+    ///
+    /// The `ConfidentialTransferInstruction::InitializeMint` instruction
+    /// requires no signers and MUST be included within the same Transaction
+    /// as `TokenInstruction::InitializeMint`. Otherwise another party can
+    /// initialize the configuration.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token mint.
+    InitializeMint {
+        /// Authority to modify the `ConfidentialTransferMint` configuration and to
+        /// approve new accounts.
+        #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+        authority: COption<Pubkey>,
+        /// Determines if newly configured accounts must be approved by the
+        /// `authority` before they may be used by the user.
+        auto_approve_new_accounts: bool,
+        /// New authority to decode any transfer amount in a confidential transfer,
+        /// empty if unset
+        auditor_elgamal_pubkey: &'a [u8],
+    },
+    /// Updates the confidential transfer mint configuration for a mint.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token mint.
+    ///   1. `[signer]` Confidential transfer mint authority.
+    UpdateMint {
+        /// Determines if newly configured accounts must be approved by the
+        /// `authority` before they may be used by the user.
+        auto_approve_new_accounts: bool,
+        /// New authority to decode any transfer amount in a confidential transfer,
+        /// empty if unset
+        auditor_elgamal_pubkey: &'a [u8],
+    },
+    /// Configures confidential transfers for a token account.
+    ///
+    /// The ElGamal public key of the account is not part of the instruction: it is the
+    /// context of the `ProofInstruction::VerifyPubkeyValidity` proof.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner/delegate
+    ///   0. `[writeable]` The SPL Token account.
+    ///   1. `[]` The corresponding SPL Token mint.
+    ///   2. `[]` Instructions sysvar if `VerifyPubkeyValidity` is included in the same
+    ///      transaction or context state account if `VerifyPubkeyValidity` is pre-verified
+    ///      into a context state account.
+    ///   3. `[]` (Optional) Record account if the accompanying proof is to be read from a
+    ///      record account.
+    ///   4. `[signer]` The single source account owner.
+    ///
+    ///   * Multisignature owner/delegate
+    ///   0. `[writeable]` The SPL Token account.
+    ///   1. `[]` The corresponding SPL Token mint.
+    ///   2. `[]` Instructions sysvar or context state account.
+    ///   3. `[]` (Optional) Record account.
+    ///   4. `[]` The multisig source account owner.
+    ///   5. ..5+M `[signer]` M signer accounts.
+    ConfigureAccount {
+        /// The decryptable balance (always 0) once the configure account succeeds
+        decryptable_zero_balance: &'a [u8],
+        /// The maximum number of deposits and transfers that an account can receive
+        /// before the `ApplyPendingBalance` is executed
+        maximum_pending_balance_credit_counter: u64,
+        /// Relative location of the `ProofInstruction::VerifyPubkeyValidity`
+        /// instruction to the `ConfigureAccount` instruction in the transaction. 0 if the
+        /// proof is in a pre-verified context account
+        proof_instruction_offset: i8,
+    },
+    /// Approves a token account for confidential transfers.
+    ///
+    /// Approval is only required when the
+    /// `ConfidentialTransferMint::approve_new_accounts` field is set in the
+    /// SPL Token mint.  This instruction must be executed after the account
+    /// owner configures their account for confidential transfers with
+    /// `ConfidentialTransferInstruction::ConfigureAccount`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token account to approve.
+    ///   1. `[]` The SPL Token mint.
+    ///   2. `[signer]` Confidential transfer mint authority.
+    ApproveAccount,
+    /// Empties the pending and available balances in a confidential token account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[]` Instructions sysvar or context state account of the zero balance proof.
+    ///   2. `[]` (Optional) Record account.
+    ///   3. `[signer]` The single account owner, or the multisig account owner followed
+    ///      by its signers.
+    EmptyAccount {
+        /// Relative location of the `ProofInstruction::VerifyZeroCiphertext`
+        /// instruction to the `EmptyAccount` instruction in the transaction. 0 if the
+        /// proof is in a pre-verified context account
+        proof_instruction_offset: i8,
+    },
+    /// Deposits SPL Tokens into the pending balance of a confidential token account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[]` The token mint.
+    ///   2. `[signer]` The single account owner or delegate, or the multisig account owner
+    ///      followed by its signers.
+    Deposit {
+        /// The amount of tokens to deposit.
+        amount: Amount,
+        /// Expected number of base 10 digits to the right of the decimal place.
+        decimals: AmountDecimals,
+    },
+    /// Withdraws SPL Tokens from the available balance of a confidential token account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[]` The token mint.
+    ///   2. ..3. `[]` (Optional) Instructions sysvar or context state accounts of the
+    ///      equality and range proofs.
+    ///   4. `[signer]` The single source account owner, or the multisig account owner
+    ///      followed by its signers.
+    Withdraw {
+        /// The amount of tokens to withdraw.
+        amount: Amount,
+        /// Expected number of base 10 digits to the right of the decimal place.
+        decimals: AmountDecimals,
+        /// The new decryptable balance if the withdrawal succeeds
+        new_decryptable_available_balance: &'a [u8],
+        /// Relative location of the `ProofInstruction::VerifyCiphertextCommitmentEquality`
+        /// instruction to the `Withdraw` instruction in the transaction
+        equality_proof_instruction_offset: i8,
+        /// Relative location of the `ProofInstruction::BatchedRangeProofU64` instruction
+        /// to the `Withdraw` instruction in the transaction
+        range_proof_instruction_offset: i8,
+    },
+    /// Transfers tokens confidentially.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[]` The token mint.
+    ///   2. `[writable]` The destination SPL Token account.
+    ///   3. ..5. `[]` (Optional) Instructions sysvar or context state accounts of the
+    ///      equality, ciphertext validity and range proofs.
+    ///   6. `[signer]` The single source account owner, or the multisig account owner
+    ///      followed by its signers.
+    Transfer {
+        /// The new source decryptable balance if the transfer succeeds
+        new_source_decryptable_available_balance: &'a [u8],
+        /// The transfer amount encrypted under the auditor ElGamal public key, low bits
+        transfer_amount_auditor_ciphertext_lo: &'a [u8],
+        /// The transfer amount encrypted under the auditor ElGamal public key, high bits
+        transfer_amount_auditor_ciphertext_hi: &'a [u8],
+        /// Relative location of the `ProofInstruction::VerifyCiphertextCommitmentEquality`
+        /// instruction to the `Transfer` instruction in the transaction
+        equality_proof_instruction_offset: i8,
+        /// Relative location of the
+        /// `ProofInstruction::VerifyBatchedGroupedCiphertext3HandlesValidity` instruction
+        /// to the `Transfer` instruction in the transaction
+        ciphertext_validity_proof_instruction_offset: i8,
+        /// Relative location of the `ProofInstruction::BatchedRangeProofU128Data`
+        /// instruction to the `Transfer` instruction in the transaction
+        range_proof_instruction_offset: i8,
+    },
+    /// Applies the pending balance to the available balance, based on the history of
+    /// `Deposit` and/or `Transfer` instructions.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[signer]` The single account owner, or the multisig account owner followed by
+    ///      its signers.
+    ApplyPendingBalance {
+        /// The expected number of pending balance credits since the last successful
+        /// `ApplyPendingBalance` instruction
+        expected_pending_balance_credit_counter: u64,
+        /// The new decryptable balance if the pending balance is applied successfully
+        new_decryptable_available_balance: &'a [u8],
+    },
+    /// Configures a confidential extension account to accept incoming confidential transfers.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[signer]` The single account owner, or the multisig account owner followed by
+    ///      its signers.
+    EnableConfidentialCredits,
+    /// Configures a confidential extension account to reject any incoming confidential
+    /// transfers.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[signer]` The single account owner, or the multisig account owner followed by
+    ///      its signers.
+    DisableConfidentialCredits,
+    /// Configures an account with the confidential extension to accept incoming
+    /// non-confidential transfers.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[signer]` The single account owner, or the multisig account owner followed by
+    ///      its signers.
+    EnableNonConfidentialCredits,
+    /// Configures an account with the confidential extension to reject any incoming
+    /// non-confidential transfers.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[signer]` The single account owner, or the multisig account owner followed by
+    ///      its signers.
+    DisableNonConfidentialCredits,
+    /// Transfers tokens confidentially with fee.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[]` The token mint.
+    ///   2. `[writable]` The destination SPL Token account.
+    ///   3. ..7. `[]` (Optional) Instructions sysvar or context state accounts of the
+    ///      proofs.
+    ///   8. `[signer]` The single source account owner, or the multisig account owner
+    ///      followed by its signers.
+    TransferWithFee {
+        /// The new source decryptable balance if the transfer succeeds
+        new_source_decryptable_available_balance: &'a [u8],
+        /// The transfer amount encrypted under the auditor ElGamal public key, low bits
+        transfer_amount_auditor_ciphertext_lo: &'a [u8],
+        /// The transfer amount encrypted under the auditor ElGamal public key, high bits
+        transfer_amount_auditor_ciphertext_hi: &'a [u8],
+        /// Relative location of the `ProofInstruction::VerifyCiphertextCommitmentEquality`
+        /// instruction to the `TransferWithFee` instruction in the transaction
+        equality_proof_instruction_offset: i8,
+        /// Relative location of the
+        /// `ProofInstruction::VerifyBatchedGroupedCiphertext3HandlesValidity` instruction
+        /// to the `TransferWithFee` instruction in the transaction
+        transfer_amount_ciphertext_validity_proof_instruction_offset: i8,
+        /// Relative location of the `ProofInstruction::VerifyPercentageWithFee` instruction
+        /// to the `TransferWithFee` instruction in the transaction
+        fee_sigma_proof_instruction_offset: i8,
+        /// Relative location of the
+        /// `ProofInstruction::VerifyBatchedGroupedCiphertext2HandlesValidity` instruction
+        /// to the `TransferWithFee` instruction in the transaction
+        fee_ciphertext_validity_proof_instruction_offset: i8,
+        /// Relative location of the `ProofInstruction::BatchedRangeProofU256Data`
+        /// instruction to the `TransferWithFee` instruction in the transaction
+        range_proof_instruction_offset: i8,
+    },
+    /// Configures confidential transfers for a token account, with the ElGamal public key
+    /// read from an ElGamal registry account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[]` The corresponding SPL Token mint.
+    ///   2. `[]` The ElGamal registry account.
+    ///   3. `[signer, writable]` (Optional) The payer account for a reallocation.
+    ///   4. `[]` (Optional) The system program.
+    ConfigureAccountWithRegistry,
+}
+impl<'a> ConfidentialTransferInstruction<'a> {
+    /// Unpacks a byte buffer into a ConfidentialTransferInstruction
+    pub fn unpack(input: &'a [u8]) -> Result<(Self, &'a [u8]), Error> {
+        let (&tag, rest) = input.split_first().ok_or(anyhow!("Invalid Confidential Transfer Instruction"))?;
+        Ok(match tag {
+            0 => {
+                let (authority, rest) = TokenInstruction::unpack_optional_nonzero_pubkey(rest)?;
+                let (auto_approve_new_accounts, rest) = unpack_bool(rest)?;
+                let (auditor_elgamal_pubkey, rest) = unpack_optional_elgamal_pubkey(rest)?;
+                let instruction = Self::InitializeMint {
+                    authority,
+                    auto_approve_new_accounts,
+                    auditor_elgamal_pubkey,
+                };
+                (instruction, rest)
+            }
+            1 => {
+                let (auto_approve_new_accounts, rest) = unpack_bool(rest)?;
+                let (auditor_elgamal_pubkey, rest) = unpack_optional_elgamal_pubkey(rest)?;
+                let instruction = Self::UpdateMint {
+                    auto_approve_new_accounts,
+                    auditor_elgamal_pubkey,
+                };
+                (instruction, rest)
+            }
+            2 => {
+                let (decryptable_zero_balance, rest) = TokenInstruction::unpack_bytes(rest, AE_CIPHERTEXT_BYTES)?;
+                let (maximum_pending_balance_credit_counter, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (proof_instruction_offset, rest) = TokenInstruction::unpack_i8(rest)?;
+                let instruction = Self::ConfigureAccount {
+                    decryptable_zero_balance,
+                    maximum_pending_balance_credit_counter,
+                    proof_instruction_offset,
+                };
+                (instruction, rest)
+            }
+            3 => (Self::ApproveAccount, rest),
+            4 => {
+                let (proof_instruction_offset, rest) = TokenInstruction::unpack_i8(rest)?;
+                (Self::EmptyAccount { proof_instruction_offset }, rest)
+            }
+            5 => {
+                let (raw_amount, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (&raw_decimals, rest) = rest.split_first().ok_or(anyhow!("Unable to unpack decimals"))?;
+                let instruction = Self::Deposit {
+                    amount: amount(raw_amount),
+                    decimals: decimals(raw_decimals),
+                };
+                (instruction, rest)
+            }
+            6 => {
+                let (raw_amount, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (&raw_decimals, rest) = rest.split_first().ok_or(anyhow!("Unable to unpack decimals"))?;
+                let (new_decryptable_available_balance, rest) =
+                    TokenInstruction::unpack_bytes(rest, AE_CIPHERTEXT_BYTES)?;
+                let (equality_proof_instruction_offset, rest) = TokenInstruction::unpack_i8(rest)?;
+                let (range_proof_instruction_offset, rest) = TokenInstruction::unpack_i8(rest)?;
+                let instruction = Self::Withdraw {
+                    amount: amount(raw_amount),
+                    decimals: decimals(raw_decimals),
+                    new_decryptable_available_balance,
+                    equality_proof_instruction_offset,
+                    range_proof_instruction_offset,
+                };
+                (instruction, rest)
+            }
+            7 | 13 => {
+                let (new_source_decryptable_available_balance, rest) =
+                    TokenInstruction::unpack_bytes(rest, AE_CIPHERTEXT_BYTES)?;
+                let (transfer_amount_auditor_ciphertext_lo, rest) =
+                    TokenInstruction::unpack_bytes(rest, ELGAMAL_CIPHERTEXT_BYTES)?;
+                let (transfer_amount_auditor_ciphertext_hi, rest) =
+                    TokenInstruction::unpack_bytes(rest, ELGAMAL_CIPHERTEXT_BYTES)?;
+                let (equality_proof_instruction_offset, rest) = TokenInstruction::unpack_i8(rest)?;
+                let (ciphertext_validity_proof_instruction_offset, rest) = TokenInstruction::unpack_i8(rest)?;
+                if tag == 7 {
+                    let (range_proof_instruction_offset, rest) = TokenInstruction::unpack_i8(rest)?;
+                    let instruction = Self::Transfer {
+                        new_source_decryptable_available_balance,
+                        transfer_amount_auditor_ciphertext_lo,
+                        transfer_amount_auditor_ciphertext_hi,
+                        equality_proof_instruction_offset,
+                        ciphertext_validity_proof_instruction_offset,
+                        range_proof_instruction_offset,
+                    };
+                    (instruction, rest)
+                } else {
+                    let (fee_sigma_proof_instruction_offset, rest) = TokenInstruction::unpack_i8(rest)?;
+                    let (fee_ciphertext_validity_proof_instruction_offset, rest) = TokenInstruction::unpack_i8(rest)?;
+                    let (range_proof_instruction_offset, rest) = TokenInstruction::unpack_i8(rest)?;
+                    let instruction = Self::TransferWithFee {
+                        new_source_decryptable_available_balance,
+                        transfer_amount_auditor_ciphertext_lo,
+                        transfer_amount_auditor_ciphertext_hi,
+                        equality_proof_instruction_offset,
+                        transfer_amount_ciphertext_validity_proof_instruction_offset:
+                            ciphertext_validity_proof_instruction_offset,
+                        fee_sigma_proof_instruction_offset,
+                        fee_ciphertext_validity_proof_instruction_offset,
+                        range_proof_instruction_offset,
+                    };
+                    (instruction, rest)
+                }
+            }
+            8 => {
+                let (expected_pending_balance_credit_counter, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (new_decryptable_available_balance, rest) =
+                    TokenInstruction::unpack_bytes(rest, AE_CIPHERTEXT_BYTES)?;
+                let instruction = Self::ApplyPendingBalance {
+                    expected_pending_balance_credit_counter,
+                    new_decryptable_available_balance,
+                };
+                (instruction, rest)
+            }
+            9 => (Self::EnableConfidentialCredits, rest),
+            10 => (Self::DisableConfidentialCredits, rest),
+            11 => (Self::EnableNonConfidentialCredits, rest),
+            12 => (Self::DisableNonConfidentialCredits, rest),
+            14 => (Self::ConfigureAccountWithRegistry, rest),
+            _ => return Err(anyhow!("Invalid Confidential Transfer Instruction - unpack didn't match any tag value: {}", tag)),
+        })
+    }
+}
+
+fn unpack_bool(input: &[u8]) -> Result<(bool, &[u8]), Error> {
+    match input.split_first() {
+        Some((0, rest)) => Ok((false, rest)),
+        Some((1, rest)) => Ok((true, rest)),
+        _ => Err(anyhow!("Unable to unpack bool")),
+    }
+}
+
+/// Unpacks an optional ElGamal pubkey, all zeroes when unset, returning an empty slice when unset
+fn unpack_optional_elgamal_pubkey(input: &[u8]) -> Result<(&[u8], &[u8]), Error> {
+    let (pubkey, rest) = TokenInstruction::unpack_bytes(input, ELGAMAL_PUBKEY_BYTES)?;
+    if pubkey.iter().all(|byte| *byte == 0) {
+        Ok((&[], rest))
+    } else {
+        Ok((pubkey, rest))
+    }
+}
@@ -0,0 +1,132 @@
+//! Reporting of token instructions whose tag this crate does not decode
+//!
+//! The token programs gain instructions over time: an instruction with a tag missing from
+//! [supported_tags] fails to decode and is usually dropped by extractors. The helpers of this
+//! module log such instructions as a single `key=value` line, so coverage gaps can be counted
+//! on dashboards built from the substreams logs:
+//!
+//! ```text
+//! unknown_token_instruction tag=45 program=TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb slot=300000000 transaction_index=12 instruction_index=3 inner_instruction_index=none
+//! ```
+
+use {
+    crate::{
+        instructions::block_instructions,
+        program_ids::is_token_program,
+        pubkey::Pubkey,
+        token_instruction_2022::{is_supported_tag, supported_tags, TokenInstruction},
+    },
+    substreams::errors::Error,
+    substreams_solana_core::pb::sf::solana::r#type::v1::Block,
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "serde-traits")]
+use serde_with::{As, DisplayFromStr};
+
+/// A token instruction whose tag is not decoded by this crate
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownTag {
+    /// The instruction tag
+    pub tag: u8,
+    /// The token program invoked
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub program_id: Pubkey,
+    /// Slot of the block holding the instruction
+    pub slot: u64,
+    /// Index of the transaction in the block
+    pub transaction_index: u32,
+    /// Index of the top-level instruction
+    pub instruction_index: usize,
+    /// Index of the inner instruction, `None` for top-level instructions
+    pub inner_instruction_index: Option<usize>,
+}
+
+impl UnknownTag {
+    /// Renders the telemetry line logged by [UnknownTag::log]
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::{coverage::UnknownTag, program_ids::TOKEN_2022_PROGRAM_ID};
+    /// let unknown = UnknownTag {
+    ///     tag: 45,
+    ///     program_id: TOKEN_2022_PROGRAM_ID,
+    ///     slot: 300_000_000,
+    ///     transaction_index: 12,
+    ///     instruction_index: 3,
+    ///     inner_instruction_index: None,
+    /// };
+    /// assert_eq!(
+    ///     unknown.to_log_line(),
+    ///     "unknown_token_instruction tag=45 program=TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb \
+    ///      slot=300000000 transaction_index=12 instruction_index=3 inner_instruction_index=none"
+    /// );
+    /// ```
+    pub fn to_log_line(&self) -> String {
+        let inner_instruction_index = match self.inner_instruction_index {
+            Some(index) => index.to_string(),
+            None => "none".to_string(),
+        };
+        format!(
+            "unknown_token_instruction tag={} program={} slot={} transaction_index={} instruction_index={} inner_instruction_index={}",
+            self.tag, self.program_id, self.slot, self.transaction_index, self.instruction_index, inner_instruction_index
+        )
+    }
+
+    /// Logs the instruction on the substreams logger
+    pub fn log(&self) {
+        substreams::log::println(self.to_log_line());
+    }
+}
+
+/// Unpacks the token instruction `data` of `program_id`, logging a telemetry line with `slot`
+/// when its tag is not in [supported_tags]. Fails as [TokenInstruction::unpack] does.
+pub fn unpack_or_report<'a>(data: &'a [u8], program_id: &[u8], slot: u64) -> Result<TokenInstruction<'a>, Error> {
+    let result = TokenInstruction::unpack(data);
+    if let (Err(_), Some(&tag)) = (&result, data.first()) {
+        if !is_supported_tag(tag) {
+            let program = Pubkey::try_from(program_id)
+                .map(|program_id| program_id.to_string())
+                .unwrap_or_else(|_| "unknown".to_string());
+            substreams::log::println(format!(
+                "unknown_token_instruction tag={} program={} slot={}",
+                tag, program, slot
+            ));
+        }
+    }
+    result
+}
+
+/// Token instructions of the successful transactions of `block` whose tag is not decoded by
+/// this crate
+pub fn unknown_tags(block: &Block) -> Vec<UnknownTag> {
+    block_instructions(block)
+        .filter(|item| is_token_program(item.instruction.program_id))
+        .filter_map(|item| {
+            let tag = *item.instruction.data.first()?;
+            if supported_tags().contains(&tag) {
+                return None;
+            }
+            Some(UnknownTag {
+                tag,
+                program_id: Pubkey::try_from(item.instruction.program_id).ok()?,
+                slot: block.slot,
+                transaction_index: item.transaction_index,
+                instruction_index: item.instruction.instruction_index,
+                inner_instruction_index: item.instruction.inner_instruction_index,
+            })
+        })
+        .collect()
+}
+
+/// Logs the token instructions of `block` whose tag is not decoded by this crate, returning
+/// them
+pub fn report_unknown_tags(block: &Block) -> Vec<UnknownTag> {
+    let unknown = unknown_tags(block);
+    for tag in &unknown {
+        tag.log();
+    }
+    unknown
+}
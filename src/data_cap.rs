@@ -0,0 +1,144 @@
+//! Caps on the instruction data examined, against oversized spam payloads
+//!
+//! Decoders read payloads of a few dozen bytes, yet nothing prevents a transaction from
+//! carrying instructions with huge data, which spam transactions use to slow down the
+//! modules copying, hashing or decoding it. A [DataCap] lets through the data up to its
+//! maximum length only: longer data is reported as [Capped::Truncated], with its tag and
+//! length, without being decoded, and counted in its [CapStats].
+
+use {crate::token_instruction_2022::TokenInstruction, substreams::errors::Error};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Outcome of capped decoding
+#[derive(Clone, Debug, PartialEq)]
+pub enum Capped<T> {
+    /// The data was within the cap and decoded
+    Complete(T),
+    /// The data exceeded the cap and was not decoded
+    Truncated {
+        /// First byte of the data, the instruction tag of most programs
+        tag: Option<u8>,
+        /// Length of the data
+        len: usize,
+    },
+}
+
+impl<T> Capped<T> {
+    /// The decoded value, `None` if the data was truncated
+    pub fn complete(self) -> Option<T> {
+        match self {
+            Self::Complete(value) => Some(value),
+            Self::Truncated { .. } => None,
+        }
+    }
+
+    /// Returns `true` if the data exceeded the cap
+    pub fn is_truncated(&self) -> bool {
+        matches!(self, Self::Truncated { .. })
+    }
+}
+
+/// Counters of the data examined by a [DataCap]
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CapStats {
+    /// Instruction data checked
+    pub examined: u64,
+    /// Instruction data exceeding the cap
+    pub truncated: u64,
+    /// Total number of bytes skipped, beyond the cap
+    pub skipped_bytes: u64,
+    /// Length of the longest data seen
+    pub largest_len: u64,
+}
+
+impl CapStats {
+    /// Adds the counters of `other`, to aggregate the statistics of several blocks
+    pub fn merge(&mut self, other: &CapStats) {
+        self.examined += other.examined;
+        self.truncated += other.truncated;
+        self.skipped_bytes += other.skipped_bytes;
+        self.largest_len = self.largest_len.max(other.largest_len);
+    }
+}
+
+/// Guard capping the instruction data decoded
+///
+/// ```
+/// # use substreams_solana_program_instructions::{data_cap::{Capped, DataCap}, token_instruction_2022::TokenInstruction};
+/// let mut cap = DataCap::new(64);
+/// assert_eq!(cap.unpack_token(&[9]).unwrap(), Capped::Complete(TokenInstruction::CloseAccount));
+///
+/// let spam = vec![3; 1 << 20];
+/// assert_eq!(cap.unpack_token(&spam).unwrap(), Capped::Truncated { tag: Some(3), len: 1 << 20 });
+/// assert_eq!(cap.stats().truncated, 1);
+/// assert_eq!(cap.stats().skipped_bytes, (1 << 20) - 64);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DataCap {
+    max_len: usize,
+    stats: CapStats,
+}
+
+impl DataCap {
+    /// Guard letting through the data up to `max_len` bytes. Top-level instruction data fits
+    /// in the 1232 bytes of a transaction, so caps at or above that only ever truncate inner
+    /// instructions: the cap is left to the caller, the longest legitimate data depending on
+    /// the programs decoded.
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            max_len,
+            stats: CapStats::default(),
+        }
+    }
+
+    /// Maximum data length let through
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    /// Counters of the data checked so far
+    pub fn stats(&self) -> &CapStats {
+        &self.stats
+    }
+
+    /// Returns the counters of the data checked so far and resets them, typically at the
+    /// end of a block
+    pub fn take_stats(&mut self) -> CapStats {
+        std::mem::take(&mut self.stats)
+    }
+
+    /// Checks `data` against the cap, returning it if it is within the cap
+    pub fn check<'a>(&mut self, data: &'a [u8]) -> Capped<&'a [u8]> {
+        self.stats.examined += 1;
+        self.stats.largest_len = self.stats.largest_len.max(data.len() as u64);
+        if data.len() <= self.max_len {
+            return Capped::Complete(data);
+        }
+        self.stats.truncated += 1;
+        self.stats.skipped_bytes += (data.len() - self.max_len) as u64;
+        Capped::Truncated {
+            tag: data.first().copied(),
+            len: data.len(),
+        }
+    }
+
+    /// Decodes `data` with `decode` if it is within the cap
+    pub fn decode<'a, T, F>(&mut self, data: &'a [u8], decode: F) -> Result<Capped<T>, Error>
+    where
+        F: FnOnce(&'a [u8]) -> Result<T, Error>,
+    {
+        match self.check(data) {
+            Capped::Complete(data) => decode(data).map(Capped::Complete),
+            Capped::Truncated { tag, len } => Ok(Capped::Truncated { tag, len }),
+        }
+    }
+
+    /// Decodes the token instruction `data` if it is within the cap
+    pub fn unpack_token<'a>(&mut self, data: &'a [u8]) -> Result<Capped<TokenInstruction<'a>>, Error> {
+        self.decode(data, TokenInstruction::unpack)
+    }
+}
@@ -0,0 +1,154 @@
+//! Mint decimals lookups, for reporting UI amounts of events lacking them
+//!
+//! `Transfer`, unlike `TransferChecked`, does not state the decimals of the mint, and the
+//! token balances of a transaction only mention some of its accounts. A [DecimalsProvider]
+//! supplies the decimals of the other mints, typically from a store of the decimals of the
+//! mints initialized so far ([StoreDecimals]) or from a static map of well-known mints, so
+//! [ui_transfer_events] can report every transfer with its UI amount.
+
+use {
+    crate::{
+        amount::amount_to_ui_amount_string_trimmed,
+        events::{transfer_events, TransferEvent},
+        pubkey::Pubkey,
+        store_keys::mint_key,
+    },
+    std::collections::{BTreeMap, HashMap},
+    substreams::store::StoreGet,
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Source of the decimals of mints
+///
+/// ```
+/// # use substreams_solana_program_instructions::{decimals::DecimalsProvider, pubkey::Pubkey};
+/// # use std::collections::HashMap;
+/// let usdc: Pubkey = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".parse().unwrap();
+/// let known = HashMap::from([(usdc, 6)]);
+/// assert_eq!(known.decimals(&usdc), Some(6));
+/// assert_eq!(known.decimals(&Pubkey::from([0; 32])), None);
+/// ```
+pub trait DecimalsProvider {
+    /// Decimals of `mint`, `None` if unknown
+    fn decimals(&self, mint: &Pubkey) -> Option<u8>;
+}
+
+impl<T: DecimalsProvider + ?Sized> DecimalsProvider for &T {
+    fn decimals(&self, mint: &Pubkey) -> Option<u8> {
+        (**self).decimals(mint)
+    }
+}
+
+impl DecimalsProvider for HashMap<Pubkey, u8> {
+    fn decimals(&self, mint: &Pubkey) -> Option<u8> {
+        self.get(mint).copied()
+    }
+}
+
+impl DecimalsProvider for BTreeMap<Pubkey, u8> {
+    fn decimals(&self, mint: &Pubkey) -> Option<u8> {
+        self.get(mint).copied()
+    }
+}
+
+/// Decimals read from a store holding the decimals of each mint under its
+/// [crate::store_keys::mint_key], a `StoreGetInt64` for instance
+pub struct StoreDecimals<'a, S> {
+    store: &'a S,
+}
+
+impl<'a, S: StoreGet<i64>> StoreDecimals<'a, S> {
+    /// Provider reading `store`
+    pub fn new(store: &'a S) -> Self {
+        Self { store }
+    }
+}
+
+impl<S: StoreGet<i64>> DecimalsProvider for StoreDecimals<'_, S> {
+    fn decimals(&self, mint: &Pubkey) -> Option<u8> {
+        self.store
+            .get_last(mint_key(mint))
+            .and_then(|decimals| u8::try_from(decimals).ok())
+    }
+}
+
+/// Decimals of the mints mentioned by the token balances of `trx`
+pub fn transaction_decimals(trx: &ConfirmedTransaction) -> HashMap<Pubkey, u8> {
+    let meta = match trx.meta.as_ref() {
+        Some(meta) => meta,
+        None => return HashMap::new(),
+    };
+    meta.pre_token_balances
+        .iter()
+        .chain(meta.post_token_balances.iter())
+        .filter_map(|balance| {
+            let mint = balance.mint.parse().ok()?;
+            let decimals = u8::try_from(balance.ui_token_amount.as_ref()?.decimals).ok()?;
+            Some((mint, decimals))
+        })
+        .collect()
+}
+
+/// A transfer along with the decimals of its mint
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct UiTransferEvent {
+    /// The transfer
+    pub event: TransferEvent,
+    /// Decimals of the mint, `None` if the mint or its decimals are unknown
+    pub decimals: Option<u8>,
+}
+
+impl UiTransferEvent {
+    /// Amount debited from the source, as an exact UI amount formatted like the
+    /// `uiAmountString` of RPC token balances, see [amount_to_ui_amount_string_trimmed]
+    pub fn ui_amount(&self) -> Option<String> {
+        self.decimals
+            .map(|decimals| amount_to_ui_amount_string_trimmed(self.event.amount, decimals))
+    }
+
+    /// Amount credited to the destination, as an exact UI amount
+    pub fn ui_received_amount(&self) -> Option<String> {
+        self.decimals
+            .map(|decimals| amount_to_ui_amount_string_trimmed(self.event.received_amount(), decimals))
+    }
+}
+
+/// Extracts the token transfers of a successful transaction, the `transaction_index`-th of
+/// its block, along with the decimals of their mint. The decimals come from the token
+/// balances of the transaction, or from `provider` for the mints they do not mention.
+pub fn ui_transfer_events<P: DecimalsProvider + ?Sized>(
+    trx: &ConfirmedTransaction,
+    transaction_index: u32,
+    provider: &P,
+) -> Vec<UiTransferEvent> {
+    let events = transfer_events(trx, transaction_index);
+    if events.is_empty() {
+        return vec![];
+    }
+    let known = transaction_decimals(trx);
+    events
+        .into_iter()
+        .map(|event| {
+            let decimals = event
+                .mint
+                .and_then(|mint| known.decimals(&mint).or_else(|| provider.decimals(&mint)));
+            UiTransferEvent { event, decimals }
+        })
+        .collect()
+}
+
+/// Extracts the token transfers of all successful transactions of `block` along with the
+/// decimals of their mint, see [ui_transfer_events]
+pub fn block_ui_transfer_events<P: DecimalsProvider + ?Sized>(block: &Block, provider: &P) -> Vec<UiTransferEvent> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| ui_transfer_events(trx, index as u32, provider))
+        .collect()
+}
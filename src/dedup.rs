@@ -0,0 +1,157 @@
+//! Duplicate transaction suppression across the blocks of a short window
+//!
+//! Micro-forks make a module see the same transaction in several blocks of close slots, the
+//! abandoned fork's and the canonical one's. Store modules keep a [SignatureWindow] of the
+//! signatures processed over the last slots and skip the transactions it already holds, see
+//! [SignatureWindow::fresh_transactions].
+
+use {
+    crate::events::signature,
+    std::collections::{BTreeMap, HashMap},
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Signatures seen over the last `slots` slots, at most `capacity` of them, the least
+/// recently seen being evicted first
+///
+/// ```
+/// # use substreams_solana_program_instructions::dedup::SignatureWindow;
+/// let mut window = SignatureWindow::new(2, 100);
+/// assert!(window.insert(100, "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW"));
+/// assert!(!window.insert(101, "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW"));
+/// // Seen last at slot 101, the signature leaves the window at slot 103
+/// assert!(window.insert(103, "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW"));
+///
+/// # #[cfg(feature = "canonical-json")]
+/// # {
+/// let json = serde_json::to_string(&window).unwrap();
+/// let mut restored: SignatureWindow = serde_json::from_str(&json).unwrap();
+/// assert_eq!(restored, window);
+/// assert!(!restored.insert(104, "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW"));
+/// # }
+/// ```
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(from = "SignatureWindowData"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignatureWindow {
+    slots: u64,
+    capacity: usize,
+    /// Signatures and the slot they were last seen at, keyed by recency, least recently seen
+    /// first
+    order: BTreeMap<u64, (String, u64)>,
+    /// Recency of each signature of `order`, rebuilt on deserialization
+    #[cfg_attr(feature = "serde-traits", serde(skip))]
+    index: HashMap<String, u64>,
+}
+
+/// Serialized fields of a [SignatureWindow]
+#[cfg(feature = "serde-traits")]
+#[derive(Deserialize)]
+struct SignatureWindowData {
+    slots: u64,
+    capacity: usize,
+    order: BTreeMap<u64, (String, u64)>,
+}
+
+#[cfg(feature = "serde-traits")]
+impl From<SignatureWindowData> for SignatureWindow {
+    fn from(data: SignatureWindowData) -> Self {
+        let index = data
+            .order
+            .iter()
+            .map(|(&recency, (signature, _))| (signature.clone(), recency))
+            .collect();
+        Self {
+            slots: data.slots,
+            capacity: data.capacity,
+            order: data.order,
+            index,
+        }
+    }
+}
+
+impl SignatureWindow {
+    /// Window over the last `slots` slots holding at most `capacity` signatures, both at
+    /// least one
+    pub fn new(slots: u64, capacity: usize) -> Self {
+        Self {
+            slots: slots.max(1),
+            capacity: capacity.max(1),
+            order: BTreeMap::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    /// Number of signatures held
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    /// Returns `true` if the window holds no signature
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    /// Returns `true` if the window holds `signature`
+    pub fn contains(&self, signature: &str) -> bool {
+        self.index.contains_key(signature)
+    }
+
+    /// Records `signature` as seen at `slot`, first evicting the signatures last seen before
+    /// the window ending at `slot`. Returns `true` if the window did not hold it.
+    pub fn insert(&mut self, slot: u64, signature: &str) -> bool {
+        self.evict_before(slot);
+        let recency = self.order.keys().next_back().map_or(0, |last| last + 1);
+        // Seen again: move it to the most recent end
+        let fresh = match self.index.insert(signature.to_string(), recency) {
+            Some(previous) => {
+                self.order.remove(&previous);
+                false
+            }
+            None => true,
+        };
+        self.order.insert(recency, (signature.to_string(), slot));
+        while self.order.len() > self.capacity {
+            self.evict_oldest();
+        }
+        fresh
+    }
+
+    /// Records the signature of `trx` as seen at `slot`, see [SignatureWindow::insert].
+    /// Transactions without signature are always fresh.
+    pub fn insert_transaction(&mut self, slot: u64, trx: &ConfirmedTransaction) -> bool {
+        let signature = signature(trx);
+        signature.is_empty() || self.insert(slot, &signature)
+    }
+
+    /// Transactions of `block` the window did not hold, along with their index in the block,
+    /// recording them as seen at the block's slot
+    pub fn fresh_transactions<'a>(&mut self, block: &'a Block) -> Vec<(u32, &'a ConfirmedTransaction)> {
+        block
+            .transactions
+            .iter()
+            .enumerate()
+            .filter(|(_, trx)| self.insert_transaction(block.slot, trx))
+            .map(|(index, trx)| (index as u32, trx))
+            .collect()
+    }
+
+    /// Evicts the signatures last seen before the window ending at `current_slot`. Slots are
+    /// expected to be inserted in increasing order, the least recently seen signatures then
+    /// being the oldest.
+    pub fn evict_before(&mut self, current_slot: u64) {
+        let oldest = current_slot.saturating_sub(self.slots - 1);
+        while matches!(self.order.first_key_value(), Some((_, (_, slot))) if *slot < oldest) {
+            self.evict_oldest();
+        }
+    }
+
+    fn evict_oldest(&mut self) {
+        if let Some((_, (signature, _))) = self.order.pop_first() {
+            self.index.remove(&signature);
+        }
+    }
+}
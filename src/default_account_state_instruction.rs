@@ -0,0 +1,76 @@
+use anyhow::anyhow;
+use {
+    substreams::errors::Error,
+    crate::account_state::AccountState,
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Default Account State extension instructions
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+pub enum DefaultAccountStateInstruction {
+    /// Initialize a new mint with the default state for new Accounts.
+    ///
+    /// Fails if the mint has already been initialized, so must be called before
+    /// `InitializeMint`.
+    ///
+    /// The mint must have exactly enough space allocated for the base mint (82
+    /// bytes), plus 83 bytes of padding, 1 byte reserved for the account type,
+    /// then space required for this extension, plus any others.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint to initialize.
+    Initialize {
+        /// The state new token accounts of the mint are initialized with
+        state: AccountState,
+    },
+    /// Update the default state for new Accounts. Only supported for mints that
+    /// include the `DefaultAccountState` extension.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[signer]` The mint freeze authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[]` The mint's multisignature freeze authority.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    Update {
+        /// The new default state of token accounts of the mint
+        state: AccountState,
+    },
+}
+impl DefaultAccountStateInstruction {
+    /// Unpacks a byte buffer into a DefaultAccountStateInstruction
+    pub fn unpack(input: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (&tag, rest) = input.split_first().ok_or(anyhow!("Invalid Default Account State Instruction"))?;
+        Ok(match tag {
+            0 => {
+                let (state, rest) = AccountState::unpack(rest)?;
+                (Self::Initialize { state }, rest)
+            }
+            1 => {
+                let (state, rest) = AccountState::unpack(rest)?;
+                (Self::Update { state }, rest)
+            }
+            _ => return Err(anyhow!("Invalid Default Account State Instruction - unpack didn't match any tag value: {}", tag)),
+        })
+    }
+
+    /// Returns the account state set by the instruction
+    pub fn state(&self) -> AccountState {
+        match *self {
+            Self::Initialize { state } | Self::Update { state } => state,
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Signed balance deltas for substreams stores with an `add` policy
+//!
+//! Stores with an `add` policy sum what modules add, and substreams reverts a block's additions
+//! on undo. Emitting signed deltas, rather than absolute balances, keeps every addition
+//! reversible: a delta and its [BalanceDelta::inverse] cancel out exactly, whatever the order
+//! in which blocks are applied and undone.
+
+use {
+    crate::{amount::I128Delta, events::TransferEvent, pubkey::Pubkey, store_keys::balance_key},
+    substreams::{scalar::BigInt, store::StoreAdd},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// A signed change of the balance of `owner` for `mint`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BalanceDelta {
+    /// Owner of the balance
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub owner: Pubkey,
+    /// Mint of the balance
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub mint: Pubkey,
+    /// Signed change, in the mint's smallest unit
+    pub delta: I128Delta,
+}
+
+impl BalanceDelta {
+    /// The delta cancelling this one
+    pub fn inverse(&self) -> Self {
+        Self {
+            delta: -self.delta,
+            ..*self
+        }
+    }
+
+    /// Store key of the balance, see [balance_key]
+    pub fn key(&self) -> String {
+        balance_key(self.owner, self.mint)
+    }
+
+    /// The delta as a [BigInt], as added to `StoreAddBigInt` stores
+    pub fn to_big_int(&self) -> BigInt {
+        BigInt::from_signed_bytes_le(&self.delta.get().to_le_bytes())
+    }
+}
+
+/// Computes the net balance deltas of `transfers`, one per owner and mint in order of first
+/// appearance, zero deltas dropped. Token accounts whose owner is unknown stand for their
+/// owner, transfers whose mint is unknown are skipped. Withheld fees are debited from the
+/// sender without being credited to the receiver.
+pub fn balance_deltas(transfers: &[TransferEvent]) -> Vec<BalanceDelta> {
+    let mut deltas: Vec<BalanceDelta> = vec![];
+    let mut add = |owner: Pubkey, mint: Pubkey, delta: I128Delta| {
+        match deltas.iter_mut().find(|d| d.owner == owner && d.mint == mint) {
+            Some(existing) => existing.delta = existing.delta.saturating_add(delta),
+            None => deltas.push(BalanceDelta { owner, mint, delta }),
+        }
+    };
+    for transfer in transfers {
+        let mint = match transfer.mint {
+            Some(mint) => mint,
+            None => continue,
+        };
+        add(transfer.source_owner.unwrap_or(transfer.source), mint, I128Delta::debit(transfer.amount));
+        add(
+            transfer.destination_owner.unwrap_or(transfer.destination),
+            mint,
+            I128Delta::credit(transfer.received_amount()),
+        );
+    }
+    deltas.retain(|delta| !delta.delta.is_zero());
+    deltas
+}
+
+/// Returns the deltas cancelling `deltas`
+pub fn inverse_deltas(deltas: &[BalanceDelta]) -> Vec<BalanceDelta> {
+    deltas.iter().map(BalanceDelta::inverse).collect()
+}
+
+/// Adds `deltas` to a `StoreAddBigInt` store at ordinal `ord`, keyed by [BalanceDelta::key]
+pub fn add_to_store<S: StoreAdd<BigInt>>(store: &S, ord: u64, deltas: &[BalanceDelta]) {
+    for delta in deltas {
+        store.add(ord, delta.key(), delta.to_big_int());
+    }
+}
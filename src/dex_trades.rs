@@ -0,0 +1,278 @@
+//! Normalization of the swaps of the supported DEX programs into a single [DexTrade] event
+//!
+//! Swap instructions only state the limits of the trade, an exact amount on one side and a
+//! slippage bound on the other. The amounts actually traded are read from the token transfers
+//! the swap invokes: the transfers out of the taker's input account and into its output
+//! account. Each venue has an adapter giving the position of the pool, taker and token
+//! accounts in the accounts of its swap instructions; venues whose feature is disabled are
+//! not extracted.
+
+use {
+    crate::{
+        events::{transfer_events, EventContext, TransferEvent},
+        instructions::{instructions, invoked_instructions, is_successful, InstructionRef},
+        pubkey::Pubkey,
+    },
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+#[cfg(feature = "meteora")]
+use crate::{
+    meteora_dlmm::{MeteoraDlmmInstruction, SwapEvent},
+    program_ids::METEORA_DLMM_PROGRAM_ID,
+};
+#[cfg(feature = "raydium")]
+use crate::{
+    program_ids::{RAYDIUM_CLMM_PROGRAM_ID, RAYDIUM_CPMM_PROGRAM_ID},
+    raydium_instruction::{RaydiumClmmInstruction, RaydiumCpmmInstruction},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// A DEX program supported by [dex_trades]
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Venue {
+    /// Raydium concentrated liquidity
+    RaydiumClmm,
+    /// Raydium constant product
+    RaydiumCpmm,
+    /// Meteora dynamic liquidity market maker
+    MeteoraDlmm,
+}
+
+impl Venue {
+    /// Short stable name of the venue, e.g. `raydium_clmm`
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::RaydiumClmm => "raydium_clmm",
+            Self::RaydiumCpmm => "raydium_cpmm",
+            Self::MeteoraDlmm => "meteora_dlmm",
+        }
+    }
+}
+
+/// A swap on a DEX, normalized across venues
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DexTrade {
+    /// Position of the swap instruction
+    pub context: EventContext,
+    /// The venue
+    pub venue: Venue,
+    /// The pool, or market, traded against
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub pool: Pubkey,
+    /// Side providing the liquidity, the pool itself for AMMs
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub maker: Pubkey,
+    /// Side initiating the swap, the signer of the swap instruction
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub taker: Pubkey,
+    /// Mint sold by the taker, `None` when neither the instruction nor the transfers state it
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub mint_in: Option<Pubkey>,
+    /// Mint bought by the taker, `None` when neither the instruction nor the transfers state it
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub mint_out: Option<Pubkey>,
+    /// Amount debited from the taker
+    pub amount_in: u64,
+    /// Amount credited to the taker
+    pub amount_out: u64,
+    /// Fee charged by the venue, in `mint_in`, `None` when the venue does not state it
+    pub fee: Option<u64>,
+}
+
+impl DexTrade {
+    /// Kind of the event, part of its [DexTrade::event_id]
+    pub const KIND: &'static str = "dex_trade";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+}
+
+/// Positions of the accounts of a swap instruction, as given by the venue adapters
+struct SwapLayout {
+    venue: Venue,
+    pool: usize,
+    taker: usize,
+    input_account: usize,
+    output_account: usize,
+    /// Positions of the input and output mints, for instructions listing them
+    mints: Option<(usize, usize)>,
+}
+
+/// Extracts the DEX trades of all successful transactions of `block`, in execution order
+pub fn block_dex_trades(block: &Block) -> Vec<DexTrade> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| dex_trades(trx, index as u32))
+        .collect()
+}
+
+/// Extracts the DEX trades of a successful transaction, the `transaction_index`-th of its
+/// block, in execution order.
+///
+/// Swaps invoked by other programs, routers for instance, are extracted as well. Their
+/// transfers are told apart with the invocation stack height, so inner swaps of blocks
+/// predating it are skipped.
+pub fn dex_trades(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<DexTrade> {
+    if !is_successful(trx) {
+        return vec![];
+    }
+    let all: Vec<InstructionRef> = instructions(trx).collect();
+    let mut transfers: Option<Vec<TransferEvent>> = None;
+
+    let mut trades = vec![];
+    for (position, instruction) in all.iter().enumerate() {
+        let layout = match swap_layout(instruction) {
+            Some(layout) => layout,
+            None => continue,
+        };
+        let accounts = &instruction.accounts;
+        let (pool, taker, input_account, output_account) = match (
+            accounts.get_pubkey(layout.pool),
+            accounts.get_pubkey(layout.taker),
+            accounts.get_pubkey(layout.input_account),
+            accounts.get_pubkey(layout.output_account),
+        ) {
+            (Some(pool), Some(taker), Some(input_account), Some(output_account)) => {
+                (pool, taker, input_account, output_account)
+            }
+            _ => continue,
+        };
+        let invoked = match invoked_instructions(&all, position) {
+            Some(invoked) => invoked,
+            None => continue,
+        };
+        let context = EventContext::new(trx, transaction_index, instruction);
+        let transfers = transfers.get_or_insert_with(|| transfer_events(trx, transaction_index));
+        let is_invoked = |event: &&TransferEvent| {
+            invoked.iter().any(|invoked| {
+                invoked.instruction_index as u32 == event.context.instruction_index
+                    && invoked.inner_instruction_index.map(|index| index as u32) == event.context.inner_instruction_index
+            })
+        };
+
+        let (mut amount_in, mut amount_out) = (0u64, 0u64);
+        let (mut transfer_mint_in, mut transfer_mint_out) = (None, None);
+        for transfer in transfers.iter().filter(is_invoked) {
+            if transfer.source == input_account {
+                amount_in = amount_in.saturating_add(transfer.amount);
+                transfer_mint_in = transfer_mint_in.or(transfer.mint);
+            }
+            if transfer.destination == output_account {
+                amount_out = amount_out.saturating_add(transfer.received_amount());
+                transfer_mint_out = transfer_mint_out.or(transfer.mint);
+            }
+        }
+        let (mint_in, mint_out) = match layout.mints {
+            Some((mint_in, mint_out)) => (accounts.get_pubkey(mint_in), accounts.get_pubkey(mint_out)),
+            None => (transfer_mint_in, transfer_mint_out),
+        };
+
+        trades.push(DexTrade {
+            fee: stated_fee(layout.venue, invoked),
+            context,
+            venue: layout.venue,
+            pool,
+            maker: pool,
+            taker,
+            mint_in,
+            mint_out,
+            amount_in,
+            amount_out,
+        });
+    }
+
+    trades
+}
+
+/// Adapter of the venue of `instruction`, `None` if it is not a swap of a supported venue
+fn swap_layout(instruction: &InstructionRef) -> Option<SwapLayout> {
+    #[cfg(feature = "raydium")]
+    if instruction.program_id == RAYDIUM_CLMM_PROGRAM_ID.as_ref() {
+        return match RaydiumClmmInstruction::unpack(instruction.data).ok()? {
+            RaydiumClmmInstruction::Swap { .. } => Some(SwapLayout {
+                venue: Venue::RaydiumClmm,
+                pool: 2,
+                taker: 0,
+                input_account: 3,
+                output_account: 4,
+                mints: None,
+            }),
+            RaydiumClmmInstruction::SwapV2 { .. } => Some(SwapLayout {
+                venue: Venue::RaydiumClmm,
+                pool: 2,
+                taker: 0,
+                input_account: 3,
+                output_account: 4,
+                mints: Some((11, 12)),
+            }),
+            _ => None,
+        };
+    }
+    #[cfg(feature = "raydium")]
+    if instruction.program_id == RAYDIUM_CPMM_PROGRAM_ID.as_ref() {
+        return match RaydiumCpmmInstruction::unpack(instruction.data).ok()? {
+            RaydiumCpmmInstruction::SwapBaseInput { .. } | RaydiumCpmmInstruction::SwapBaseOutput { .. } => {
+                Some(SwapLayout {
+                    venue: Venue::RaydiumCpmm,
+                    pool: 3,
+                    taker: 0,
+                    input_account: 4,
+                    output_account: 5,
+                    mints: Some((10, 11)),
+                })
+            }
+            _ => None,
+        };
+    }
+    #[cfg(feature = "meteora")]
+    if instruction.program_id == METEORA_DLMM_PROGRAM_ID.as_ref() {
+        // Mints are listed as X and Y, not as input and output
+        return match MeteoraDlmmInstruction::unpack(instruction.data).ok()? {
+            MeteoraDlmmInstruction::Swap { .. }
+            | MeteoraDlmmInstruction::SwapExactOut { .. }
+            | MeteoraDlmmInstruction::SwapWithPriceImpact { .. }
+            | MeteoraDlmmInstruction::Swap2 { .. }
+            | MeteoraDlmmInstruction::SwapExactOut2 { .. } => Some(SwapLayout {
+                venue: Venue::MeteoraDlmm,
+                pool: 0,
+                taker: 10,
+                input_account: 4,
+                output_account: 5,
+                mints: None,
+            }),
+            _ => None,
+        };
+    }
+    None
+}
+
+/// Fee of a swap of `venue`, from the event logged among the `invoked` instructions by the
+/// venues logging it
+fn stated_fee(venue: Venue, invoked: &[InstructionRef]) -> Option<u64> {
+    match venue {
+        #[cfg(feature = "meteora")]
+        Venue::MeteoraDlmm => invoked
+            .iter()
+            .filter(|instruction| instruction.program_id == METEORA_DLMM_PROGRAM_ID.as_ref())
+            .find_map(|instruction| SwapEvent::unpack(instruction.data).ok().flatten())
+            .map(|event| event.fee),
+        _ => {
+            let _ = invoked;
+            None
+        }
+    }
+}
@@ -0,0 +1,554 @@
+//! Drift v2 events, logged by the Drift program as Anchor events
+//!
+//! Drift records every order placement in an `OrderRecord`, and every action on an order
+//! (placement, cancellation, fill, trigger, expiry) in an `OrderActionRecord`, funding rate
+//! updates of perp markets in a `FundingRateRecord`. The records gain fields over program
+//! upgrades, always appended: only the fields listed here are decoded, trailing fields are
+//! ignored.
+
+use anyhow::anyhow;
+use {
+    crate::{
+        events::{signature, EventContext},
+        program_ids::DRIFT_PROGRAM_ID,
+        program_logs::program_data,
+        pubkey::Pubkey,
+        token_instruction_2022::TokenInstruction,
+    },
+    num_enum::TryFromPrimitive,
+    substreams::errors::Error,
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// `OrderRecord` discriminator, hash of `event:OrderRecord`
+pub const ORDER_RECORD_DISCRIMINATOR: [u8; 8] = [104, 19, 64, 56, 89, 21, 2, 90];
+/// `OrderActionRecord` discriminator, hash of `event:OrderActionRecord`
+pub const ORDER_ACTION_RECORD_DISCRIMINATOR: [u8; 8] = [224, 52, 67, 71, 194, 237, 109, 1];
+/// `FundingRateRecord` discriminator, hash of `event:FundingRateRecord`
+pub const FUNDING_RATE_RECORD_DISCRIMINATOR: [u8; 8] = [68, 3, 255, 26, 133, 91, 147, 254];
+
+/// Type of market
+#[repr(u8)]
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, TryFromPrimitive)]
+pub enum MarketType {
+    /// Spot market
+    Spot,
+    /// Perpetual futures market
+    Perp,
+}
+
+/// Side of an order or position
+#[repr(u8)]
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, TryFromPrimitive)]
+pub enum PositionDirection {
+    /// Buy, or long position
+    Long,
+    /// Sell, or short position
+    Short,
+}
+
+/// Type of order
+#[repr(u8)]
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, TryFromPrimitive)]
+pub enum OrderType {
+    /// Market order, filled through an auction
+    Market,
+    /// Limit order
+    Limit,
+    /// Market order placed once the trigger price is crossed
+    TriggerMarket,
+    /// Limit order placed once the trigger price is crossed
+    TriggerLimit,
+    /// Market order priced relative to the oracle
+    Oracle,
+}
+
+/// Status of an order
+#[repr(u8)]
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, TryFromPrimitive)]
+pub enum OrderStatus {
+    /// Order slot not in use
+    Init,
+    /// Order open
+    Open,
+    /// Order fully filled
+    Filled,
+    /// Order canceled
+    Canceled,
+}
+
+/// Action recorded by an [OrderActionRecord]
+#[repr(u8)]
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, TryFromPrimitive)]
+pub enum OrderAction {
+    /// Order placed
+    Place,
+    /// Order canceled
+    Cancel,
+    /// Order filled, partially or fully
+    Fill,
+    /// Trigger order triggered
+    Trigger,
+    /// Order expired
+    Expire,
+}
+
+/// An order, as recorded in an [OrderRecord]. Prices have 6 decimals, base asset amounts 9
+/// and quote asset amounts 6.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Order {
+    /// Slot the order was placed at
+    pub slot: u64,
+    /// Limit price, 0 for market orders
+    pub price: u64,
+    /// Size of the order
+    pub base_asset_amount: u64,
+    /// Size filled so far
+    pub base_asset_amount_filled: u64,
+    /// Quote amount filled so far
+    pub quote_asset_amount_filled: u64,
+    /// Trigger price of trigger orders
+    pub trigger_price: u64,
+    /// Price at the start of the auction
+    pub auction_start_price: i64,
+    /// Price at the end of the auction
+    pub auction_end_price: i64,
+    /// Unix timestamp after which the order expires, 0 for no expiry
+    pub max_ts: i64,
+    /// Offset to the oracle price of oracle limit orders
+    pub oracle_price_offset: i32,
+    /// Identifier of the order within its user account
+    pub order_id: u32,
+    /// Index of the market
+    pub market_index: u16,
+    /// Status of the order
+    pub status: OrderStatus,
+    /// Type of the order
+    pub order_type: OrderType,
+    /// Type of the market
+    pub market_type: MarketType,
+    /// Identifier set by the user
+    pub user_order_id: u8,
+    /// Direction of the user's position when the order was placed
+    pub existing_position_direction: PositionDirection,
+    /// Side of the order
+    pub direction: PositionDirection,
+    /// Whether the order can only reduce the position
+    pub reduce_only: bool,
+    /// Whether the order can only be a maker
+    pub post_only: bool,
+    /// Whether the unfilled part of the order is canceled right away
+    pub immediate_or_cancel: bool,
+}
+
+/// An order placement
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OrderRecord {
+    /// Unix timestamp of the placement
+    pub ts: i64,
+    /// User account placing the order
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub user: Pubkey,
+    /// The order
+    pub order: Order,
+}
+
+/// An action on an order. Fills hold both the taker and maker sides, maker fees are negative
+/// for rebates.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OrderActionRecord {
+    /// Unix timestamp of the action
+    pub ts: i64,
+    /// The action
+    pub action: OrderAction,
+    /// Reason of the action, as the index of Drift's `OrderActionExplanation`
+    pub action_explanation: u8,
+    /// Index of the market
+    pub market_index: u16,
+    /// Type of the market
+    pub market_type: MarketType,
+    /// Keeper that filled or triggered the order
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub filler: Option<Pubkey>,
+    /// Reward of the filler
+    pub filler_reward: Option<u64>,
+    /// Identifier of the fill within the market
+    pub fill_record_id: Option<u64>,
+    /// Size filled
+    pub base_asset_amount_filled: Option<u64>,
+    /// Quote amount filled
+    pub quote_asset_amount_filled: Option<u64>,
+    /// Fee paid by the taker
+    pub taker_fee: Option<u64>,
+    /// Fee paid by the maker, negative for rebates
+    pub maker_fee: Option<i64>,
+    /// Reward of the taker's referrer
+    pub referrer_reward: Option<u32>,
+    /// Quote amount surplus of the fill
+    pub quote_asset_amount_surplus: Option<i64>,
+    /// Fee of the external spot market filling the order
+    pub spot_fulfillment_method_fee: Option<u64>,
+    /// Taker user account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub taker: Option<Pubkey>,
+    /// Identifier of the taker order
+    pub taker_order_id: Option<u32>,
+    /// Side of the taker order
+    pub taker_order_direction: Option<PositionDirection>,
+    /// Size of the taker order
+    pub taker_order_base_asset_amount: Option<u64>,
+    /// Size of the taker order filled so far, this fill included
+    pub taker_order_cumulative_base_asset_amount_filled: Option<u64>,
+    /// Quote amount of the taker order filled so far, this fill included
+    pub taker_order_cumulative_quote_asset_amount_filled: Option<u64>,
+    /// Maker user account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub maker: Option<Pubkey>,
+    /// Identifier of the maker order
+    pub maker_order_id: Option<u32>,
+    /// Side of the maker order
+    pub maker_order_direction: Option<PositionDirection>,
+    /// Size of the maker order
+    pub maker_order_base_asset_amount: Option<u64>,
+    /// Size of the maker order filled so far, this fill included
+    pub maker_order_cumulative_base_asset_amount_filled: Option<u64>,
+    /// Quote amount of the maker order filled so far, this fill included
+    pub maker_order_cumulative_quote_asset_amount_filled: Option<u64>,
+    /// Oracle price at the time of the action
+    pub oracle_price: i64,
+}
+
+/// A funding rate update of a perp market. Funding rates have 9 decimals, prices 6.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FundingRateRecord {
+    /// Unix timestamp of the update
+    pub ts: i64,
+    /// Identifier of the update within the market
+    pub record_id: u64,
+    /// Index of the perp market
+    pub market_index: u16,
+    /// Funding rate, in quote per base
+    pub funding_rate: i64,
+    /// Funding rate paid by longs
+    pub funding_rate_long: i128,
+    /// Funding rate paid by shorts
+    pub funding_rate_short: i128,
+    /// Cumulative funding rate of longs
+    pub cumulative_funding_rate_long: i128,
+    /// Cumulative funding rate of shorts
+    pub cumulative_funding_rate_short: i128,
+    /// Time weighted average of the oracle price
+    pub oracle_price_twap: i64,
+    /// Time weighted average of the mark price
+    pub mark_price_twap: u64,
+    /// Revenue of the market over the funding period
+    pub period_revenue: i64,
+    /// Net base asset amount of the users against the AMM, the market's open interest
+    /// imbalance
+    pub base_asset_amount_with_amm: i128,
+    /// Base asset amount of the liquidity providers not yet settled
+    pub base_asset_amount_with_unsettled_lp: i128,
+}
+
+/// A Drift event
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DriftEvent {
+    /// An order placement
+    Order(OrderRecord),
+    /// An action on an order, boxed as by far the largest record
+    OrderAction(Box<OrderActionRecord>),
+    /// A funding rate update
+    FundingRate(FundingRateRecord),
+}
+
+impl DriftEvent {
+    /// Unpacks the data of an Anchor event logged by the Drift program. Returns `Ok(None)` for
+    /// the events not decoded by this module.
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::drift_events::{DriftEvent, FUNDING_RATE_RECORD_DISCRIMINATOR};
+    /// let mut data = FUNDING_RATE_RECORD_DISCRIMINATOR.to_vec();
+    /// data.extend_from_slice(&1_700_000_000i64.to_le_bytes()); // ts
+    /// data.extend_from_slice(&42u64.to_le_bytes()); // record id
+    /// data.extend_from_slice(&0u16.to_le_bytes()); // market index
+    /// data.extend_from_slice(&(-1_500i64).to_le_bytes()); // funding rate
+    /// data.extend_from_slice(&[0; 4 * 16 + 3 * 8 + 2 * 16]);
+    /// match DriftEvent::unpack(&data).unwrap() {
+    ///     Some(DriftEvent::FundingRate(record)) => {
+    ///         assert_eq!(record.record_id, 42);
+    ///         assert_eq!(record.funding_rate, -1_500);
+    ///     }
+    ///     _ => unreachable!(),
+    /// }
+    ///
+    /// // Other events of the program
+    /// assert_eq!(DriftEvent::unpack(&[0; 16]).unwrap(), None);
+    /// ```
+    pub fn unpack(data: &[u8]) -> Result<Option<Self>, Error> {
+        let (discriminator, rest) =
+            TokenInstruction::unpack_bytes(data, 8).map_err(|_| anyhow!("Invalid Drift Event"))?;
+        Ok(Some(match discriminator {
+            d if d == ORDER_RECORD_DISCRIMINATOR => Self::Order(unpack_order_record(rest)?),
+            d if d == ORDER_ACTION_RECORD_DISCRIMINATOR => Self::OrderAction(Box::new(unpack_order_action_record(rest)?)),
+            d if d == FUNDING_RATE_RECORD_DISCRIMINATOR => Self::FundingRate(unpack_funding_rate_record(rest)?),
+            _ => return Ok(None),
+        }))
+    }
+
+    /// Kind of the event, part of its [DriftEventRecord::event_id]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Order(_) => "drift_order",
+            Self::OrderAction(_) => "drift_order_action",
+            Self::FundingRate(_) => "drift_funding_rate",
+        }
+    }
+}
+
+/// A Drift event along with its position
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DriftEventRecord {
+    /// Position of the instruction that logged the event, and of the event among the data it
+    /// logged
+    pub context: EventContext,
+    /// The event
+    pub event: DriftEvent,
+}
+
+impl DriftEventRecord {
+    /// Deterministic identifier of the event, see [EventContext::event_id]. Events logged by
+    /// one instruction have distinct identifiers, their context holding their log index.
+    pub fn event_id(&self) -> String {
+        self.context.event_id(self.event.kind())
+    }
+}
+
+/// Extracts the Drift events of all successful transactions of `block`, in execution order
+pub fn block_drift_events(block: &Block) -> Vec<DriftEventRecord> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| drift_events(trx, index as u32))
+        .collect()
+}
+
+/// Extracts the Drift events of a successful transaction, the `transaction_index`-th of its
+/// block, in execution order. Events that fail to decode are skipped.
+pub fn drift_events(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<DriftEventRecord> {
+    program_data(trx)
+        .into_iter()
+        .filter(|logged| logged.program_id == DRIFT_PROGRAM_ID)
+        .filter_map(|logged| {
+            let event = DriftEvent::unpack(&logged.data).ok()??;
+            Some(DriftEventRecord {
+                context: EventContext {
+                    signature: signature(trx),
+                    transaction_index,
+                    instruction_index: logged.instruction_index as u32,
+                    inner_instruction_index: logged.inner_instruction_index.map(|index| index as u32),
+                    log_index: Some(logged.log_index as u32),
+                },
+                event,
+            })
+        })
+        .collect()
+}
+
+fn unpack_order_record(input: &[u8]) -> Result<OrderRecord, Error> {
+    let (ts, rest) = TokenInstruction::unpack_i64(input)?;
+    let (user, rest) = TokenInstruction::unpack_pubkey(rest)?;
+    let (slot, rest) = TokenInstruction::unpack_u64(rest)?;
+    let (price, rest) = TokenInstruction::unpack_u64(rest)?;
+    let (base_asset_amount, rest) = TokenInstruction::unpack_u64(rest)?;
+    let (base_asset_amount_filled, rest) = TokenInstruction::unpack_u64(rest)?;
+    let (quote_asset_amount_filled, rest) = TokenInstruction::unpack_u64(rest)?;
+    let (trigger_price, rest) = TokenInstruction::unpack_u64(rest)?;
+    let (auction_start_price, rest) = TokenInstruction::unpack_i64(rest)?;
+    let (auction_end_price, rest) = TokenInstruction::unpack_i64(rest)?;
+    let (max_ts, rest) = TokenInstruction::unpack_i64(rest)?;
+    let (oracle_price_offset, rest) = TokenInstruction::unpack_u32(rest)?;
+    let (order_id, rest) = TokenInstruction::unpack_u32(rest)?;
+    let (market_index, rest) = TokenInstruction::unpack_u16(rest)?;
+    let (status, rest) = unpack_enum(rest)?;
+    let (order_type, rest) = unpack_enum(rest)?;
+    let (market_type, rest) = unpack_enum(rest)?;
+    let (&user_order_id, rest) = rest.split_first().ok_or(anyhow!("Unable to unpack user order id"))?;
+    let (existing_position_direction, rest) = unpack_enum(rest)?;
+    let (direction, rest) = unpack_enum(rest)?;
+    let (flags, _rest) = TokenInstruction::unpack_bytes(rest, 3)?;
+    Ok(OrderRecord {
+        ts,
+        user,
+        order: Order {
+            slot,
+            price,
+            base_asset_amount,
+            base_asset_amount_filled,
+            quote_asset_amount_filled,
+            trigger_price,
+            auction_start_price,
+            auction_end_price,
+            max_ts,
+            oracle_price_offset: oracle_price_offset as i32,
+            order_id,
+            market_index,
+            status,
+            order_type,
+            market_type,
+            user_order_id,
+            existing_position_direction,
+            direction,
+            reduce_only: flags[0] != 0,
+            post_only: flags[1] != 0,
+            immediate_or_cancel: flags[2] != 0,
+        },
+    })
+}
+
+fn unpack_order_action_record(input: &[u8]) -> Result<OrderActionRecord, Error> {
+    let (ts, rest) = TokenInstruction::unpack_i64(input)?;
+    let (action, rest) = unpack_enum(rest)?;
+    let (&action_explanation, rest) = rest.split_first().ok_or(anyhow!("Unable to unpack action explanation"))?;
+    let (market_index, rest) = TokenInstruction::unpack_u16(rest)?;
+    let (market_type, rest) = unpack_enum(rest)?;
+    let (filler, rest) = unpack_option(rest, TokenInstruction::unpack_pubkey)?;
+    let (filler_reward, rest) = unpack_option(rest, TokenInstruction::unpack_u64)?;
+    let (fill_record_id, rest) = unpack_option(rest, TokenInstruction::unpack_u64)?;
+    let (base_asset_amount_filled, rest) = unpack_option(rest, TokenInstruction::unpack_u64)?;
+    let (quote_asset_amount_filled, rest) = unpack_option(rest, TokenInstruction::unpack_u64)?;
+    let (taker_fee, rest) = unpack_option(rest, TokenInstruction::unpack_u64)?;
+    let (maker_fee, rest) = unpack_option(rest, TokenInstruction::unpack_i64)?;
+    let (referrer_reward, rest) = unpack_option(rest, TokenInstruction::unpack_u32)?;
+    let (quote_asset_amount_surplus, rest) = unpack_option(rest, TokenInstruction::unpack_i64)?;
+    let (spot_fulfillment_method_fee, rest) = unpack_option(rest, TokenInstruction::unpack_u64)?;
+    let (taker, rest) = unpack_option(rest, TokenInstruction::unpack_pubkey)?;
+    let (taker_order_id, rest) = unpack_option(rest, TokenInstruction::unpack_u32)?;
+    let (taker_order_direction, rest) = unpack_option(rest, unpack_enum)?;
+    let (taker_order_base_asset_amount, rest) = unpack_option(rest, TokenInstruction::unpack_u64)?;
+    let (taker_order_cumulative_base_asset_amount_filled, rest) = unpack_option(rest, TokenInstruction::unpack_u64)?;
+    let (taker_order_cumulative_quote_asset_amount_filled, rest) = unpack_option(rest, TokenInstruction::unpack_u64)?;
+    let (maker, rest) = unpack_option(rest, TokenInstruction::unpack_pubkey)?;
+    let (maker_order_id, rest) = unpack_option(rest, TokenInstruction::unpack_u32)?;
+    let (maker_order_direction, rest) = unpack_option(rest, unpack_enum)?;
+    let (maker_order_base_asset_amount, rest) = unpack_option(rest, TokenInstruction::unpack_u64)?;
+    let (maker_order_cumulative_base_asset_amount_filled, rest) = unpack_option(rest, TokenInstruction::unpack_u64)?;
+    let (maker_order_cumulative_quote_asset_amount_filled, rest) = unpack_option(rest, TokenInstruction::unpack_u64)?;
+    let (oracle_price, _rest) = TokenInstruction::unpack_i64(rest)?;
+    Ok(OrderActionRecord {
+        ts,
+        action,
+        action_explanation,
+        market_index,
+        market_type,
+        filler,
+        filler_reward,
+        fill_record_id,
+        base_asset_amount_filled,
+        quote_asset_amount_filled,
+        taker_fee,
+        maker_fee,
+        referrer_reward,
+        quote_asset_amount_surplus,
+        spot_fulfillment_method_fee,
+        taker,
+        taker_order_id,
+        taker_order_direction,
+        taker_order_base_asset_amount,
+        taker_order_cumulative_base_asset_amount_filled,
+        taker_order_cumulative_quote_asset_amount_filled,
+        maker,
+        maker_order_id,
+        maker_order_direction,
+        maker_order_base_asset_amount,
+        maker_order_cumulative_base_asset_amount_filled,
+        maker_order_cumulative_quote_asset_amount_filled,
+        oracle_price,
+    })
+}
+
+fn unpack_funding_rate_record(input: &[u8]) -> Result<FundingRateRecord, Error> {
+    let (ts, rest) = TokenInstruction::unpack_i64(input)?;
+    let (record_id, rest) = TokenInstruction::unpack_u64(rest)?;
+    let (market_index, rest) = TokenInstruction::unpack_u16(rest)?;
+    let (funding_rate, rest) = TokenInstruction::unpack_i64(rest)?;
+    let (funding_rate_long, rest) = unpack_i128(rest)?;
+    let (funding_rate_short, rest) = unpack_i128(rest)?;
+    let (cumulative_funding_rate_long, rest) = unpack_i128(rest)?;
+    let (cumulative_funding_rate_short, rest) = unpack_i128(rest)?;
+    let (oracle_price_twap, rest) = TokenInstruction::unpack_i64(rest)?;
+    let (mark_price_twap, rest) = TokenInstruction::unpack_u64(rest)?;
+    let (period_revenue, rest) = TokenInstruction::unpack_i64(rest)?;
+    let (base_asset_amount_with_amm, rest) = unpack_i128(rest)?;
+    let (base_asset_amount_with_unsettled_lp, _rest) = unpack_i128(rest)?;
+    Ok(FundingRateRecord {
+        ts,
+        record_id,
+        market_index,
+        funding_rate,
+        funding_rate_long,
+        funding_rate_short,
+        cumulative_funding_rate_long,
+        cumulative_funding_rate_short,
+        oracle_price_twap,
+        mark_price_twap,
+        period_revenue,
+        base_asset_amount_with_amm,
+        base_asset_amount_with_unsettled_lp,
+    })
+}
+
+fn unpack_i128(input: &[u8]) -> Result<(i128, &[u8]), Error> {
+    let (bytes, rest) = TokenInstruction::unpack_bytes(input, 16)?;
+    let value = i128::from_le_bytes(bytes.try_into().map_err(|_| anyhow!("Unable to unpack i128"))?);
+    Ok((value, rest))
+}
+
+fn unpack_enum<T: TryFromPrimitive<Primitive = u8>>(input: &[u8]) -> Result<(T, &[u8]), Error> {
+    let (&value, rest) = input.split_first().ok_or(anyhow!("Unable to unpack enum"))?;
+    let value = T::try_from_primitive(value).map_err(|_| anyhow!("Invalid enum value: {}", value))?;
+    Ok((value, rest))
+}
+
+/// Unpacks a borsh `Option`, a flag byte followed by the value when set
+fn unpack_option<T, F>(input: &[u8], unpack: F) -> Result<(Option<T>, &[u8]), Error>
+where
+    F: Fn(&[u8]) -> Result<(T, &[u8]), Error>,
+{
+    match input.split_first() {
+        Some((0, rest)) => Ok((None, rest)),
+        Some((1, rest)) => unpack(rest).map(|(value, rest)| (Some(value), rest)),
+        _ => Err(anyhow!("Unable to unpack option")),
+    }
+}
@@ -0,0 +1,132 @@
+//! Ephemeral token accounts, initialized and closed within a single transaction
+//!
+//! Wrapping SOL for a swap, or borrowing through a flash loan, typically initializes a token
+//! account, moves tokens through it and closes it before the transaction ends. Such
+//! throwaway accounts never hold a balance between transactions: [ephemeral_accounts]
+//! finds them, and [flagged_transfer_events] flags the transfers they take part in, so
+//! holder counts and similar metrics can leave them out.
+
+use {
+    crate::{
+        events::{transfer_events, TransferEvent},
+        instructions::{instructions, is_successful},
+        program_ids::is_token_program,
+        pubkey::Pubkey,
+        token_instruction_2022::TokenInstruction,
+    },
+    std::collections::HashSet,
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Token accounts initialized then closed by `trx`, empty for failed transactions. Accounts
+/// closed without being initialized by the transaction, or initialized again after being
+/// closed, are not ephemeral.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{ephemeral::ephemeral_accounts, program_ids::TOKEN_PROGRAM_ID, pubkey::Pubkey};
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::{CompiledInstruction, ConfirmedTransaction, Message, Transaction, TransactionStatusMeta};
+/// let (wsol, payer) = ([1; 32], [2; 32]);
+/// let trx = ConfirmedTransaction {
+///     transaction: Some(Transaction {
+///         message: Some(Message {
+///             account_keys: vec![wsol.to_vec(), payer.to_vec(), TOKEN_PROGRAM_ID.as_ref().to_vec()],
+///             instructions: vec![
+///                 // InitializeAccount3 of wsol, owned by payer
+///                 CompiledInstruction { program_id_index: 2, accounts: vec![0, 0], data: [&[18][..], &payer].concat() },
+///                 // CloseAccount of wsol, to payer
+///                 CompiledInstruction { program_id_index: 2, accounts: vec![0, 1, 1], data: vec![9] },
+///             ],
+///             ..Default::default()
+///         }),
+///         ..Default::default()
+///     }),
+///     meta: Some(TransactionStatusMeta::default()),
+/// };
+/// assert!(ephemeral_accounts(&trx).contains(&Pubkey::from(wsol)));
+/// ```
+pub fn ephemeral_accounts(trx: &ConfirmedTransaction) -> HashSet<Pubkey> {
+    if !is_successful(trx) {
+        return HashSet::new();
+    }
+    let mut initialized = HashSet::new();
+    let mut ephemeral = HashSet::new();
+    for instruction in instructions(trx) {
+        if !is_token_program(instruction.program_id) {
+            continue;
+        }
+        let account = match instruction.accounts.get_pubkey(0) {
+            Some(account) => account,
+            None => continue,
+        };
+        match TokenInstruction::unpack(instruction.data) {
+            Ok(TokenInstruction::InitializeAccount)
+            | Ok(TokenInstruction::InitializeAccount2 { .. })
+            | Ok(TokenInstruction::InitializeAccount3 { .. }) => {
+                ephemeral.remove(&account);
+                initialized.insert(account);
+            }
+            Ok(TokenInstruction::CloseAccount) if initialized.remove(&account) => {
+                ephemeral.insert(account);
+            }
+            _ => {}
+        }
+    }
+    ephemeral
+}
+
+/// Token accounts initialized then closed by a single successful transaction of `block`
+pub fn block_ephemeral_accounts(block: &Block) -> HashSet<Pubkey> {
+    block.transactions.iter().flat_map(ephemeral_accounts).collect()
+}
+
+/// A transfer along with whether its token accounts are ephemeral
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlaggedTransferEvent {
+    /// The transfer
+    pub event: TransferEvent,
+    /// Whether the source account was initialized and closed by the transaction
+    pub ephemeral_source: bool,
+    /// Whether the destination account was initialized and closed by the transaction
+    pub ephemeral_destination: bool,
+}
+
+impl FlaggedTransferEvent {
+    /// Returns `true` if either account of the transfer is ephemeral
+    pub fn is_ephemeral(&self) -> bool {
+        self.ephemeral_source || self.ephemeral_destination
+    }
+}
+
+/// Extracts the token transfers of a successful transaction, the `transaction_index`-th of
+/// its block, flagging those from or to its ephemeral accounts, in execution order
+pub fn flagged_transfer_events(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<FlaggedTransferEvent> {
+    let events = transfer_events(trx, transaction_index);
+    if events.is_empty() {
+        return vec![];
+    }
+    let ephemeral = ephemeral_accounts(trx);
+    events
+        .into_iter()
+        .map(|event| FlaggedTransferEvent {
+            ephemeral_source: ephemeral.contains(&event.source),
+            ephemeral_destination: ephemeral.contains(&event.destination),
+            event,
+        })
+        .collect()
+}
+
+/// Extracts the token transfers of all successful transactions of `block`, flagging those
+/// from or to ephemeral accounts, in execution order
+pub fn block_flagged_transfer_events(block: &Block) -> Vec<FlaggedTransferEvent> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| flagged_transfer_events(trx, index as u32))
+        .collect()
+}
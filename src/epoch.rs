@@ -0,0 +1,88 @@
+//! Epochs of slots, for events tied to the epoch they occur in
+//!
+//! Firehose blocks carry their slot but not their epoch. [EpochSchedule] derives it, along
+//! with the position of the slot within the epoch, for clusters without warmup epochs:
+//! mainnet, whose epochs last 432,000 slots, and most test validators.
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Number of slots of a mainnet epoch
+pub const MAINNET_SLOTS_PER_EPOCH: u64 = 432_000;
+
+/// Epoch schedule of a cluster without warmup epochs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EpochSchedule {
+    /// Number of slots of each epoch
+    pub slots_per_epoch: u64,
+}
+
+impl Default for EpochSchedule {
+    fn default() -> Self {
+        Self {
+            slots_per_epoch: MAINNET_SLOTS_PER_EPOCH,
+        }
+    }
+}
+
+impl EpochSchedule {
+    /// Schedule of epochs lasting `slots_per_epoch` slots, at least one
+    pub fn new(slots_per_epoch: u64) -> Self {
+        Self {
+            slots_per_epoch: slots_per_epoch.max(1),
+        }
+    }
+
+    /// Epoch of `slot`
+    pub fn epoch(&self, slot: u64) -> u64 {
+        slot / self.slots_per_epoch
+    }
+
+    /// First slot of `epoch`
+    pub fn first_slot(&self, epoch: u64) -> u64 {
+        epoch.saturating_mul(self.slots_per_epoch)
+    }
+
+    /// Epoch of `slot` along with the position of the slot within it
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::epoch::EpochSchedule;
+    /// let context = EpochSchedule::default().context(300_000_000);
+    /// assert_eq!((context.epoch, context.slot_index), (694, 192_000));
+    /// assert!(context.is_first_half());
+    /// assert_eq!(context.slots_remaining(), 239_999);
+    /// ```
+    pub fn context(&self, slot: u64) -> EpochContext {
+        EpochContext {
+            epoch: self.epoch(slot),
+            slot_index: slot % self.slots_per_epoch,
+            slots_in_epoch: self.slots_per_epoch,
+        }
+    }
+}
+
+/// Epoch of a slot and the position of the slot within it
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EpochContext {
+    /// The epoch
+    pub epoch: u64,
+    /// Index of the slot within the epoch, from 0
+    pub slot_index: u64,
+    /// Number of slots of the epoch
+    pub slots_in_epoch: u64,
+}
+
+impl EpochContext {
+    /// Returns `true` if the slot is in the first half of its epoch, where the vote program
+    /// accepts commission increases
+    pub fn is_first_half(&self) -> bool {
+        self.slot_index < self.slots_in_epoch / 2
+    }
+
+    /// Number of slots of the epoch after the slot
+    pub fn slots_remaining(&self) -> u64 {
+        self.slots_in_epoch.saturating_sub(self.slot_index + 1)
+    }
+}
@@ -0,0 +1,1316 @@
+//! Structured events extracted from the token programs' instructions
+//!
+//! Every extractor emits its events in execution order: by transaction index, then
+//! instruction index, an inner instruction following the top-level instruction invoking it.
+//! [EventContext] orders events the same way, see [crate::ordering] to sort batches mixing
+//! blocks or extractors.
+
+use {
+    crate::{
+        amount::raw_amount,
+        confidential_mint_burn_instruction::ELGAMAL_PUBKEY_BYTES,
+        confidential_transfer_instruction::ConfidentialTransferInstruction,
+        instructions::{instructions, is_successful, InstructionRef},
+        metrics,
+        option::COption,
+        pausable_instruction::PausableInstruction,
+        program_ids::{is_token_2022_program, is_token_program, ZK_ELGAMAL_PROOF_PROGRAM_ID},
+        pubkey::Pubkey,
+        token_group_instruction::TokenGroupInstruction,
+        token_instruction_2022::{AuthorityType, TokenInstruction},
+        token_metadata_instruction::{Field, TokenMetadataInstruction},
+        transfer_fee_instruction::TransferFeeInstruction,
+    },
+    std::{cmp::Ordering, collections::HashMap},
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction, TokenBalance},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// Position of an event within its block, uniquely identifying it. Contexts are ordered by
+/// execution: transaction index, instruction index, inner instruction index, top-level
+/// instructions coming first, then log index.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EventContext {
+    /// Signature of the transaction, base58 encoded
+    pub signature: String,
+    /// Index of the transaction in its block, failed transactions included
+    pub transaction_index: u32,
+    /// Index of the top-level instruction that emitted the event, or that invoked the
+    /// inner instruction that emitted it
+    pub instruction_index: u32,
+    /// Index of the inner instruction that emitted the event, `None` for top-level instructions
+    pub inner_instruction_index: Option<u32>,
+    /// Position of the event among the data logged by its instruction, for events read from
+    /// the logs, several of which an instruction may log. `None` for other events.
+    #[cfg_attr(feature = "serde-traits", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub log_index: Option<u32>,
+}
+
+impl EventContext {
+    /// Context of an event emitted by `instruction` of `trx`, the `transaction_index`-th
+    /// transaction of its block
+    pub fn new(trx: &ConfirmedTransaction, transaction_index: u32, instruction: &InstructionRef) -> Self {
+        Self {
+            signature: signature(trx),
+            transaction_index,
+            instruction_index: instruction.instruction_index as u32,
+            inner_instruction_index: instruction.inner_instruction_index.map(|index| index as u32),
+            log_index: None,
+        }
+    }
+
+    /// Deterministic identifier of the event of kind `kind` at this position, the hex encoded
+    /// 128-bit FNV-1a hash of the signature, instruction index, inner instruction index, log
+    /// index when set, and kind. Stable across reprocessing and platforms, so sinks can
+    /// deduplicate on it.
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::events::EventContext;
+    /// let context = EventContext { instruction_index: 2, inner_instruction_index: Some(0), ..Default::default() };
+    /// assert_eq!(context.event_id("transfer"), context.clone().event_id("transfer"));
+    /// assert_ne!(context.event_id("transfer"), context.event_id("mint_paused"));
+    /// assert_eq!(context.event_id("transfer").len(), 32);
+    /// let logged = EventContext { log_index: Some(1), ..context.clone() };
+    /// assert_ne!(logged.event_id("transfer"), context.event_id("transfer"));
+    /// ```
+    pub fn event_id(&self, kind: &str) -> String {
+        let mut hash = FNV_OFFSET_BASIS;
+        let mut write = |bytes: &[u8]| {
+            for byte in bytes {
+                hash ^= *byte as u128;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        };
+        write(self.signature.as_bytes());
+        write(&self.instruction_index.to_le_bytes());
+        match self.inner_instruction_index {
+            Some(index) => {
+                write(&[1]);
+                write(&index.to_le_bytes());
+            }
+            None => write(&[0]),
+        }
+        if let Some(index) = self.log_index {
+            write(&[2]);
+            write(&index.to_le_bytes());
+        }
+        write(kind.as_bytes());
+        format!("{:032x}", hash)
+    }
+}
+
+impl PartialOrd for EventContext {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for EventContext {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.transaction_index, self.instruction_index, self.inner_instruction_index, self.log_index)
+            .cmp(&(other.transaction_index, other.instruction_index, other.inner_instruction_index, other.log_index))
+            .then_with(|| self.signature.cmp(&other.signature))
+    }
+}
+
+/// 128-bit FNV-1a offset basis
+const FNV_OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+/// 128-bit FNV-1a prime
+const FNV_PRIME: u128 = 0x0000000001000000000000000000013b;
+
+/// Returns the base58 encoded signature of `trx`, empty if the transaction has no signature
+pub fn signature(trx: &ConfirmedTransaction) -> String {
+    trx.transaction
+        .as_ref()
+        .and_then(|t| t.signatures.first())
+        .map(|signature| bs58::encode(signature).into_string())
+        .unwrap_or_default()
+}
+
+/// A mint added to a token group, through the token group interface's `InitializeMember`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupMemberAdded {
+    /// Position of the `InitializeMember` instruction
+    pub context: EventContext,
+    /// The group mint, holding the `TokenGroup` extension
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub group_mint: Pubkey,
+    /// The mint added to the group
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub member_mint: Pubkey,
+    /// The account holding the `TokenGroupMember` extension, the member mint itself for
+    /// token 2022 groups
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub member: Pubkey,
+    /// Member number assigned by the group, known only when the group was initialized in the
+    /// same transaction or when numbered through [GroupMemberAdded::number_from]
+    pub member_number: Option<u64>,
+}
+
+impl GroupMemberAdded {
+    /// Kind of the event, part of its [GroupMemberAdded::event_id]
+    pub const KIND: &'static str = "group_member_added";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+
+    /// Sets the member number from the group size prior to this member's addition, the token
+    /// program assigning `size + 1` to each new member
+    pub fn number_from(&mut self, previous_group_size: u64) {
+        self.member_number = Some(previous_group_size + 1);
+    }
+}
+
+/// Extracts the token group members added by all successful transactions of `block`, in
+/// execution order
+pub fn block_group_member_events(block: &Block) -> Vec<GroupMemberAdded> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| group_member_events(trx, index as u32))
+        .collect()
+}
+
+/// Extracts the token group members added by a successful transaction, the
+/// `transaction_index`-th of its block, in execution order
+pub fn group_member_events(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<GroupMemberAdded> {
+    if !is_successful(trx) {
+        return vec![];
+    }
+
+    let mut group_sizes: HashMap<Pubkey, u64> = HashMap::new();
+    let mut events = vec![];
+    for instruction in instructions(trx) {
+        if !is_token_2022_program(instruction.program_id) {
+            continue;
+        }
+        match TokenGroupInstruction::unpack(instruction.data) {
+            Ok(TokenGroupInstruction::InitializeGroup { .. }) => {
+                if let Some(group_mint) = instruction.accounts.get_pubkey(0) {
+                    group_sizes.insert(group_mint, 0);
+                }
+            }
+            Ok(TokenGroupInstruction::InitializeMember) => {
+                let accounts = instruction.accounts;
+                let (member, member_mint, group_mint) = match (
+                    accounts.get_pubkey(0),
+                    accounts.get_pubkey(1),
+                    accounts.get_pubkey(3),
+                ) {
+                    (Some(member), Some(member_mint), Some(group_mint)) => (member, member_mint, group_mint),
+                    _ => continue,
+                };
+                let member_number = group_sizes.get_mut(&group_mint).map(|size| {
+                    *size += 1;
+                    *size
+                });
+                events.push(GroupMemberAdded {
+                    context: EventContext::new(trx, transaction_index, &instruction),
+                    group_mint,
+                    member_mint,
+                    member,
+                    member_number,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    metrics::events_emitted(GroupMemberAdded::KIND, events.len());
+    events
+}
+
+/// A token 2022 mint paused through the pausable extension, making its tokens
+/// non-transferable until resumed
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MintPaused {
+    /// Position of the `Pause` instruction
+    pub context: EventContext,
+    /// The paused mint
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub mint: Pubkey,
+    /// The mint's pause authority, a multisig account when `signers` is not empty
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub authority: Pubkey,
+    /// Signers of the multisig pause authority, empty for a single authority
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Vec<DisplayFromStr>>"))]
+    pub signers: Vec<Pubkey>,
+}
+
+impl MintPaused {
+    /// Kind of the event, part of its [MintPaused::event_id]
+    pub const KIND: &'static str = "mint_paused";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+}
+
+/// A paused token 2022 mint resumed through the pausable extension
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MintResumed {
+    /// Position of the `Resume` instruction
+    pub context: EventContext,
+    /// The resumed mint
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub mint: Pubkey,
+    /// The mint's pause authority, a multisig account when `signers` is not empty
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub authority: Pubkey,
+    /// Signers of the multisig pause authority, empty for a single authority
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Vec<DisplayFromStr>>"))]
+    pub signers: Vec<Pubkey>,
+}
+
+impl MintResumed {
+    /// Kind of the event, part of its [MintResumed::event_id]
+    pub const KIND: &'static str = "mint_resumed";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+}
+
+/// Pause state change of a mint
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum PauseEvent {
+    /// The mint was paused
+    Paused(MintPaused),
+    /// The mint was resumed
+    Resumed(MintResumed),
+}
+
+/// Extracts the mint pauses and resumes of all successful transactions of `block`, in
+/// execution order
+pub fn block_pause_events(block: &Block) -> Vec<PauseEvent> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| pause_events(trx, index as u32))
+        .collect()
+}
+
+/// Extracts the mint pauses and resumes of a successful transaction, the
+/// `transaction_index`-th of its block, in execution order
+pub fn pause_events(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<PauseEvent> {
+    if !is_successful(trx) {
+        return vec![];
+    }
+
+    let mut events = vec![];
+    for instruction in instructions(trx) {
+        if !is_token_2022_program(instruction.program_id) {
+            continue;
+        }
+        let pausable = match TokenInstruction::unpack(instruction.data) {
+            Ok(TokenInstruction::PausableExtension(pausable)) => pausable,
+            _ => continue,
+        };
+        let accounts = instruction.accounts;
+        let (mint, authority) = match (accounts.get_pubkey(0), accounts.get_pubkey(1)) {
+            (Some(mint), Some(authority)) => (mint, authority),
+            _ => continue,
+        };
+        let signers = (2..accounts.len()).filter_map(|i| accounts.get_pubkey(i)).collect();
+        let context = EventContext::new(trx, transaction_index, &instruction);
+        match pausable {
+            PausableInstruction::Pause => events.push(PauseEvent::Paused(MintPaused {
+                context,
+                mint,
+                authority,
+                signers,
+            })),
+            PausableInstruction::Resume => events.push(PauseEvent::Resumed(MintResumed {
+                context,
+                mint,
+                authority,
+                signers,
+            })),
+            PausableInstruction::Initialize { .. } => {}
+        }
+    }
+
+    let paused = events.iter().filter(|event| matches!(event, PauseEvent::Paused(_))).count();
+    metrics::events_emitted(MintPaused::KIND, paused);
+    metrics::events_emitted(MintResumed::KIND, events.len() - paused);
+    events
+}
+
+/// A token account handed over to a new owner, through `SetAuthority` with the `AccountOwner`
+/// authority type
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountOwnerChanged {
+    /// Position of the `SetAuthority` instruction
+    pub context: EventContext,
+    /// The token account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub account: Pubkey,
+    /// The account's mint, from the transaction's token balances
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub mint: Option<Pubkey>,
+    /// The previous owner, from the transaction's pre token balances, `None` if they do not
+    /// mention the account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub previous_owner: Option<Pubkey>,
+    /// The new owner
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub new_owner: Pubkey,
+}
+
+impl AccountOwnerChanged {
+    /// Kind of the event, part of its [AccountOwnerChanged::event_id]
+    pub const KIND: &'static str = "account_owner_changed";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+}
+
+/// Extracts the token account owner changes of all successful transactions of `block`, in
+/// execution order
+pub fn block_account_owner_events(block: &Block) -> Vec<AccountOwnerChanged> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| account_owner_events(trx, index as u32))
+        .collect()
+}
+
+/// Extracts the token account owner changes of a successful transaction, the
+/// `transaction_index`-th of its block, in execution order. An account changing owner twice
+/// in the transaction reports the first new owner as the second change's previous owner.
+pub fn account_owner_events(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<AccountOwnerChanged> {
+    let meta = match trx.meta.as_ref() {
+        Some(meta) if is_successful(trx) => meta,
+        _ => return vec![],
+    };
+    let pre_balances: HashMap<u32, &TokenBalance> = meta
+        .pre_token_balances
+        .iter()
+        .map(|balance| (balance.account_index, balance))
+        .collect();
+
+    let mut owners: HashMap<Pubkey, Pubkey> = HashMap::new();
+    let mut events = vec![];
+    for instruction in instructions(trx) {
+        if !is_token_program(instruction.program_id) {
+            continue;
+        }
+        let new_owner = match TokenInstruction::unpack(instruction.data) {
+            Ok(TokenInstruction::SetAuthority {
+                authority_type: AuthorityType::AccountOwner,
+                new_authority: COption::Some(new_owner),
+            }) => new_owner,
+            _ => continue,
+        };
+        let account = match instruction.accounts.get_pubkey(0) {
+            Some(account) => account,
+            None => continue,
+        };
+        let balance = instruction
+            .accounts
+            .indices()
+            .and_then(|indices| indices.first())
+            .and_then(|index| pre_balances.get(&(*index as u32)));
+        let previous_owner = owners
+            .get(&account)
+            .copied()
+            .or_else(|| balance.and_then(|balance| balance.owner.parse().ok()));
+        owners.insert(account, new_owner);
+
+        events.push(AccountOwnerChanged {
+            context: EventContext::new(trx, transaction_index, &instruction),
+            account,
+            mint: balance.and_then(|balance| balance.mint.parse().ok()),
+            previous_owner,
+            new_owner,
+        });
+    }
+
+    metrics::events_emitted(AccountOwnerChanged::KIND, events.len());
+    events
+}
+
+/// A delegation of a token account set or cleared, through `Approve`, `ApproveChecked` or
+/// `Revoke`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DelegationChange {
+    /// Position of the delegation instruction
+    pub context: EventContext,
+    /// The token account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub account: Pubkey,
+    /// The account's mint, resolved through the transaction's token balances for `Approve`
+    /// and `Revoke`, `None` if they do not mention the account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub mint: Option<Pubkey>,
+    /// The account's owner, a multisig account when the instruction has more signers
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub owner: Pubkey,
+    /// The new delegate, `None` for `Revoke`
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub delegate: Option<Pubkey>,
+    /// Amount the delegate may transfer or burn, 0 for `Revoke`
+    pub amount: u64,
+}
+
+impl DelegationChange {
+    /// Kind of the event, part of its [DelegationChange::event_id]
+    pub const KIND: &'static str = "delegation_change";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+
+    /// Returns `true` if the delegation was cleared
+    pub fn is_revoke(&self) -> bool {
+        self.delegate.is_none()
+    }
+}
+
+/// Extracts the delegation changes of all successful transactions of `block`, in execution
+/// order
+pub fn block_delegation_events(block: &Block) -> Vec<DelegationChange> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| delegation_events(trx, index as u32))
+        .collect()
+}
+
+/// Extracts the delegation changes of a successful transaction, the `transaction_index`-th
+/// of its block, in execution order
+pub fn delegation_events(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<DelegationChange> {
+    let meta = match trx.meta.as_ref() {
+        Some(meta) if is_successful(trx) => meta,
+        _ => return vec![],
+    };
+    let balances: HashMap<u32, &TokenBalance> = meta
+        .pre_token_balances
+        .iter()
+        .chain(meta.post_token_balances.iter())
+        .map(|balance| (balance.account_index, balance))
+        .collect();
+
+    let mut events = vec![];
+    for instruction in instructions(trx) {
+        if !is_token_program(instruction.program_id) {
+            continue;
+        }
+        let ((mint, delegate, owner), amount) = match TokenInstruction::unpack(instruction.data) {
+            Ok(TokenInstruction::Approve { amount }) => ((None, Some(1), 2), raw_amount(amount)),
+            Ok(TokenInstruction::ApproveChecked { amount, .. }) => ((Some(1), Some(2), 3), raw_amount(amount)),
+            Ok(TokenInstruction::Revoke) => ((None, None, 1), 0),
+            _ => continue,
+        };
+        let accounts = instruction.accounts;
+        let (account, owner) = match (accounts.get_pubkey(0), accounts.get_pubkey(owner)) {
+            (Some(account), Some(owner)) => (account, owner),
+            _ => continue,
+        };
+        let delegate = match delegate {
+            Some(delegate) => match accounts.get_pubkey(delegate) {
+                Some(delegate) => Some(delegate),
+                None => continue,
+            },
+            None => None,
+        };
+        let mint = match mint {
+            Some(mint) => accounts.get_pubkey(mint),
+            None => accounts
+                .indices()
+                .and_then(|indices| indices.first())
+                .and_then(|index| balances.get(&(*index as u32)))
+                .and_then(|balance| balance.mint.parse().ok()),
+        };
+
+        events.push(DelegationChange {
+            context: EventContext::new(trx, transaction_index, &instruction),
+            account,
+            mint,
+            owner,
+            delegate,
+            amount,
+        });
+    }
+
+    metrics::events_emitted(DelegationChange::KIND, events.len());
+    events
+}
+
+/// A multisig account initialized, through `InitializeMultisig` or `InitializeMultisig2`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultisigInitialized {
+    /// Position of the `InitializeMultisig` instruction
+    pub context: EventContext,
+    /// The multisig account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub multisig: Pubkey,
+    /// Number of signers required, M
+    pub m: u8,
+    /// The signers, N of them, in instruction order
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Vec<DisplayFromStr>>"))]
+    pub signers: Vec<Pubkey>,
+}
+
+impl MultisigInitialized {
+    /// Kind of the event, part of its [MultisigInitialized::event_id]
+    pub const KIND: &'static str = "multisig_initialized";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+
+    /// Number of signers, N
+    pub fn n(&self) -> u8 {
+        self.signers.len() as u8
+    }
+}
+
+/// Extracts the multisig accounts initialized by all successful transactions of `block`, in
+/// execution order
+pub fn block_multisig_events(block: &Block) -> Vec<MultisigInitialized> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| multisig_events(trx, index as u32))
+        .collect()
+}
+
+/// Extracts the multisig accounts initialized by a successful transaction, the
+/// `transaction_index`-th of its block, in execution order
+pub fn multisig_events(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<MultisigInitialized> {
+    if !is_successful(trx) {
+        return vec![];
+    }
+
+    let mut events = vec![];
+    for instruction in instructions(trx) {
+        if !is_token_program(instruction.program_id) {
+            continue;
+        }
+        // `InitializeMultisig` takes the rent sysvar before the signers
+        let (m, first_signer) = match TokenInstruction::unpack(instruction.data) {
+            Ok(TokenInstruction::InitializeMultisig { m }) => (m, 2),
+            Ok(TokenInstruction::InitializeMultisig2 { m }) => (m, 1),
+            _ => continue,
+        };
+        let multisig = match instruction.accounts.get_pubkey(0) {
+            Some(multisig) => multisig,
+            None => continue,
+        };
+        let signers = (first_signer..instruction.accounts.len())
+            .filter_map(|index| instruction.accounts.get_pubkey(index))
+            .collect();
+
+        events.push(MultisigInitialized {
+            context: EventContext::new(trx, transaction_index, &instruction),
+            multisig,
+            m,
+            signers,
+        });
+    }
+
+    metrics::events_emitted(MultisigInitialized::KIND, events.len());
+    events
+}
+
+/// A mint or freeze authority set to a multisig account initialized in the same transaction
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultisigAuthority {
+    /// Position of the `InitializeMint` or `SetAuthority` instruction setting the authority
+    pub context: EventContext,
+    /// The mint
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub mint: Pubkey,
+    /// `MintTokens` or `FreezeAccount`
+    pub authority_type: AuthorityType,
+    /// The multisig account set as authority
+    pub multisig: MultisigInitialized,
+}
+
+/// Mint and freeze authorities set by a successful transaction, the `transaction_index`-th
+/// of its block, to multisig accounts it initializes, in execution order. Authorities set to
+/// multisig accounts initialized by earlier transactions are not reported.
+pub fn multisig_authorities(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<MultisigAuthority> {
+    let multisigs = multisig_events(trx, transaction_index);
+    if multisigs.is_empty() {
+        return vec![];
+    }
+
+    let mut authorities = vec![];
+    for instruction in instructions(trx) {
+        if !is_token_program(instruction.program_id) {
+            continue;
+        }
+        let set: Vec<(AuthorityType, Pubkey)> = match TokenInstruction::unpack(instruction.data) {
+            Ok(TokenInstruction::InitializeMint {
+                mint_authority,
+                freeze_authority,
+                ..
+            })
+            | Ok(TokenInstruction::InitializeMint2 {
+                mint_authority,
+                freeze_authority,
+                ..
+            }) => {
+                let mut set = vec![(AuthorityType::MintTokens, mint_authority)];
+                if let COption::Some(freeze_authority) = freeze_authority {
+                    set.push((AuthorityType::FreezeAccount, freeze_authority));
+                }
+                set
+            }
+            Ok(TokenInstruction::SetAuthority {
+                authority_type: authority_type @ (AuthorityType::MintTokens | AuthorityType::FreezeAccount),
+                new_authority: COption::Some(new_authority),
+            }) => vec![(authority_type, new_authority)],
+            _ => continue,
+        };
+        let mint = match instruction.accounts.get_pubkey(0) {
+            Some(mint) => mint,
+            None => continue,
+        };
+        for (authority_type, authority) in set {
+            if let Some(multisig) = multisigs.iter().find(|multisig| multisig.multisig == authority) {
+                authorities.push(MultisigAuthority {
+                    context: EventContext::new(trx, transaction_index, &instruction),
+                    mint,
+                    authority_type,
+                    multisig: multisig.clone(),
+                });
+            }
+        }
+    }
+
+    authorities
+}
+
+/// A token transfer between two token accounts, through `Transfer`, `TransferChecked` or
+/// `TransferCheckedWithFee`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct TransferEvent {
+    /// Position of the transfer instruction
+    pub context: EventContext,
+    /// The mint, resolved through the transaction's token balances for `Transfer`, `None`
+    /// if they do not mention the accounts
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub mint: Option<Pubkey>,
+    /// The source token account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub source: Pubkey,
+    /// Owner of the source token account, from the transaction's token balances
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub source_owner: Option<Pubkey>,
+    /// The destination token account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub destination: Pubkey,
+    /// Owner of the destination token account, from the transaction's token balances
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub destination_owner: Option<Pubkey>,
+    /// The source account's owner or delegate, a multisig account when the transfer has
+    /// more signers
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub authority: Pubkey,
+    /// Amount debited from the source
+    pub amount: u64,
+    /// Fee withheld from the amount credited to the destination, `None` when not stated by
+    /// the instruction
+    pub fee: Option<u64>,
+}
+
+impl TransferEvent {
+    /// Kind of the event, part of its [TransferEvent::event_id]
+    pub const KIND: &'static str = "transfer";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+
+    /// Amount credited to the destination, the amount minus the stated fee
+    pub fn received_amount(&self) -> u64 {
+        self.amount.saturating_sub(self.fee.unwrap_or(0))
+    }
+}
+
+/// Extracts the token transfers of all successful transactions of `block`, in execution order
+pub fn block_transfer_events(block: &Block) -> Vec<TransferEvent> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| transfer_events(trx, index as u32))
+        .collect()
+}
+
+/// Extracts the token transfers of a successful transaction, the `transaction_index`-th of
+/// its block, in execution order
+pub fn transfer_events(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<TransferEvent> {
+    let meta = match trx.meta.as_ref() {
+        Some(meta) if is_successful(trx) => meta,
+        _ => return vec![],
+    };
+    let balances: HashMap<u32, &TokenBalance> = meta
+        .pre_token_balances
+        .iter()
+        .chain(meta.post_token_balances.iter())
+        .map(|balance| (balance.account_index, balance))
+        .collect();
+    let balance_of = |indices: Option<&[u8]>, i: usize| {
+        indices
+            .and_then(|indices| indices.get(i))
+            .and_then(|index| balances.get(&(*index as u32)))
+    };
+
+    let mut events = vec![];
+    for instruction in instructions(trx) {
+        if !is_token_program(instruction.program_id) {
+            continue;
+        }
+        let ((source, mint, destination, authority), amount, fee) = match TokenInstruction::unpack(instruction.data) {
+            #[allow(deprecated)]
+            Ok(TokenInstruction::Transfer { amount }) => ((0, None, 1, 2), raw_amount(amount), None),
+            Ok(TokenInstruction::TransferChecked { amount, .. }) => ((0, Some(1), 2, 3), raw_amount(amount), None),
+            Ok(TokenInstruction::TransferFeeExtension(TransferFeeInstruction::TransferCheckedWithFee {
+                amount,
+                fee,
+                ..
+            })) => ((0, Some(1), 2, 3), raw_amount(amount), Some(raw_amount(fee))),
+            _ => continue,
+        };
+        let accounts = instruction.accounts;
+        let (source_key, destination_key, authority_key) = match (
+            accounts.get_pubkey(source),
+            accounts.get_pubkey(destination),
+            accounts.get_pubkey(authority),
+        ) {
+            (Some(source), Some(destination), Some(authority)) => (source, destination, authority),
+            _ => continue,
+        };
+        let indices = accounts.indices();
+        let source_balance = balance_of(indices, source);
+        let destination_balance = balance_of(indices, destination);
+        let mint = match mint {
+            Some(mint) => accounts.get_pubkey(mint),
+            None => source_balance
+                .or(destination_balance)
+                .and_then(|balance| balance.mint.parse().ok()),
+        };
+
+        events.push(TransferEvent {
+            context: EventContext::new(trx, transaction_index, &instruction),
+            mint,
+            source: source_key,
+            source_owner: source_balance.and_then(|balance| balance.owner.parse().ok()),
+            destination: destination_key,
+            destination_owner: destination_balance.and_then(|balance| balance.owner.parse().ok()),
+            authority: authority_key,
+            amount,
+            fee,
+        });
+    }
+
+    metrics::events_emitted(TransferEvent::KIND, events.len());
+    events
+}
+
+/// Field of a token metadata, owned counterpart of [Field]
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MetadataField {
+    /// The name field
+    Name,
+    /// The symbol field
+    Symbol,
+    /// The uri field
+    Uri,
+    /// A user field, whose key is given by the associated string
+    Key(String),
+}
+
+impl MetadataField {
+    /// Name of the field: `name`, `symbol`, `uri` or the key of a user field
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Name => "name",
+            Self::Symbol => "symbol",
+            Self::Uri => "uri",
+            Self::Key(key) => key,
+        }
+    }
+}
+
+impl From<Field<'_>> for MetadataField {
+    fn from(field: Field) -> Self {
+        match field {
+            Field::Name => Self::Name,
+            Field::Symbol => Self::Symbol,
+            Field::Uri => Self::Uri,
+            Field::Key(key) => Self::Key(key.to_string()),
+        }
+    }
+}
+
+/// A token metadata field of a token 2022 mint updated through the token metadata
+/// interface's `UpdateField`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetadataFieldUpdated {
+    /// Position of the `UpdateField` instruction
+    pub context: EventContext,
+    /// The account holding the metadata, the mint itself for token 2022 metadata
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub metadata: Pubkey,
+    /// The metadata's update authority
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub update_authority: Pubkey,
+    /// The updated field
+    pub field: MetadataField,
+    /// The field's new value
+    pub value: String,
+}
+
+impl MetadataFieldUpdated {
+    /// Kind of the event, part of its [MetadataFieldUpdated::event_id]
+    pub const KIND: &'static str = "metadata_field_updated";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+}
+
+/// Extracts the token metadata field updates of all successful transactions of `block`, in
+/// execution order
+pub fn block_metadata_field_events(block: &Block) -> Vec<MetadataFieldUpdated> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| metadata_field_events(trx, index as u32))
+        .collect()
+}
+
+/// Extracts the token metadata field updates of a successful transaction, the
+/// `transaction_index`-th of its block, in execution order
+pub fn metadata_field_events(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<MetadataFieldUpdated> {
+    if !is_successful(trx) {
+        return vec![];
+    }
+
+    let mut events = vec![];
+    for instruction in instructions(trx) {
+        if !is_token_2022_program(instruction.program_id) {
+            continue;
+        }
+        let (field, value) = match TokenMetadataInstruction::unpack(instruction.data) {
+            Ok(TokenMetadataInstruction::UpdateField { field, value }) => (field, value),
+            _ => continue,
+        };
+        let (metadata, update_authority) = match (instruction.accounts.get_pubkey(0), instruction.accounts.get_pubkey(1)) {
+            (Some(metadata), Some(update_authority)) => (metadata, update_authority),
+            _ => continue,
+        };
+        events.push(MetadataFieldUpdated {
+            context: EventContext::new(trx, transaction_index, &instruction),
+            metadata,
+            update_authority,
+            field: field.into(),
+            value: value.to_string(),
+        });
+    }
+
+    metrics::events_emitted(MetadataFieldUpdated::KIND, events.len());
+    events
+}
+
+/// `ProofInstruction::VerifyPubkeyValidity` tag of the ZK ElGamal proof program
+const VERIFY_PUBKEY_VALIDITY: u8 = 4;
+/// Length of a pubkey validity proof: ElGamal pubkey and sigma proof
+const PUBKEY_VALIDITY_PROOF_DATA_BYTES: usize = ELGAMAL_PUBKEY_BYTES + 64;
+
+/// A token account configured for confidential transfers through the confidential transfer
+/// extension
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfidentialAccountConfigured {
+    /// Position of the `ConfigureAccount` instruction
+    pub context: EventContext,
+    /// The configured token account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub account: Pubkey,
+    /// Mint of the token account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub mint: Pubkey,
+    /// ElGamal public key of the account, `None` when its validity proof was pre-verified
+    /// into a context state account or read from a record account
+    pub elgamal_pubkey: Option<[u8; ELGAMAL_PUBKEY_BYTES]>,
+    /// Maximum number of deposits and transfers the account can receive before applying its
+    /// pending balance
+    pub maximum_pending_balance_credit_counter: u64,
+}
+
+impl ConfidentialAccountConfigured {
+    /// Kind of the event, part of its [ConfidentialAccountConfigured::event_id]
+    pub const KIND: &'static str = "confidential_account_configured";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+}
+
+/// A token account approved for confidential transfers by the mint's confidential transfer
+/// authority, required when the mint does not auto-approve new accounts
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfidentialAccountApproved {
+    /// Position of the `ApproveAccount` instruction
+    pub context: EventContext,
+    /// The approved token account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub account: Pubkey,
+    /// Mint of the token account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub mint: Pubkey,
+    /// The mint's confidential transfer authority
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub authority: Pubkey,
+}
+
+impl ConfidentialAccountApproved {
+    /// Kind of the event, part of its [ConfidentialAccountApproved::event_id]
+    pub const KIND: &'static str = "confidential_account_approved";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+}
+
+/// Confidential transfer configuration change of a token account
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfidentialAccountEvent {
+    /// The account was configured
+    Configured(ConfidentialAccountConfigured),
+    /// The account was approved
+    Approved(ConfidentialAccountApproved),
+}
+
+/// Extracts the confidential transfer account configurations and approvals of all successful
+/// transactions of `block`, in execution order
+pub fn block_confidential_account_events(block: &Block) -> Vec<ConfidentialAccountEvent> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| confidential_account_events(trx, index as u32))
+        .collect()
+}
+
+/// Extracts the confidential transfer account configurations and approvals of a successful
+/// transaction, the `transaction_index`-th of its block, in execution order
+pub fn confidential_account_events(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<ConfidentialAccountEvent> {
+    if !is_successful(trx) {
+        return vec![];
+    }
+
+    let mut events = vec![];
+    for instruction in instructions(trx) {
+        if !is_token_2022_program(instruction.program_id) {
+            continue;
+        }
+        let confidential = match TokenInstruction::unpack(instruction.data) {
+            Ok(TokenInstruction::ConfidentialTransferExtension(confidential)) => confidential,
+            _ => continue,
+        };
+        let accounts = instruction.accounts;
+        let (account, mint) = match (accounts.get_pubkey(0), accounts.get_pubkey(1)) {
+            (Some(account), Some(mint)) => (account, mint),
+            _ => continue,
+        };
+        let context = EventContext::new(trx, transaction_index, &instruction);
+        match confidential {
+            ConfidentialTransferInstruction::ConfigureAccount {
+                maximum_pending_balance_credit_counter,
+                proof_instruction_offset,
+                ..
+            } => events.push(ConfidentialAccountEvent::Configured(ConfidentialAccountConfigured {
+                context,
+                account,
+                mint,
+                elgamal_pubkey: proof_elgamal_pubkey(trx, instruction.instruction_index, proof_instruction_offset),
+                maximum_pending_balance_credit_counter,
+            })),
+            ConfidentialTransferInstruction::ApproveAccount => {
+                if let Some(authority) = accounts.get_pubkey(2) {
+                    events.push(ConfidentialAccountEvent::Approved(ConfidentialAccountApproved {
+                        context,
+                        account,
+                        mint,
+                        authority,
+                    }));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let configured = events.iter().filter(|event| matches!(event, ConfidentialAccountEvent::Configured(_))).count();
+    metrics::events_emitted(ConfidentialAccountConfigured::KIND, configured);
+    metrics::events_emitted(ConfidentialAccountApproved::KIND, events.len() - configured);
+    events
+}
+
+/// ElGamal pubkey proven by the `VerifyPubkeyValidity` instruction at `offset` from the
+/// `instruction_index`-th top-level instruction of `trx`, `None` if the proof is not part of
+/// the transaction or its data is read from a record account
+fn proof_elgamal_pubkey(
+    trx: &ConfirmedTransaction,
+    instruction_index: usize,
+    offset: i8,
+) -> Option<[u8; ELGAMAL_PUBKEY_BYTES]> {
+    if offset == 0 {
+        return None;
+    }
+    let proof_index = instruction_index as i64 + offset as i64;
+    let proof = instructions(trx)
+        .filter(|instruction| instruction.is_root())
+        .nth(usize::try_from(proof_index).ok()?)?;
+    if proof.program_id != ZK_ELGAMAL_PROOF_PROGRAM_ID.as_ref() {
+        return None;
+    }
+    match proof.data.split_first() {
+        Some((&VERIFY_PUBKEY_VALIDITY, data)) if data.len() == PUBKEY_VALIDITY_PROOF_DATA_BYTES => {
+            data[..ELGAMAL_PUBKEY_BYTES].try_into().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Any event extracted by this module
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenEvent {
+    /// A token transfer
+    Transfer(TransferEvent),
+    /// A mint added to a token group
+    GroupMemberAdded(GroupMemberAdded),
+    /// A mint paused
+    MintPaused(MintPaused),
+    /// A mint resumed
+    MintResumed(MintResumed),
+    /// A token metadata field updated
+    MetadataFieldUpdated(MetadataFieldUpdated),
+    /// A token account handed over to a new owner
+    AccountOwnerChanged(AccountOwnerChanged),
+    /// A token account delegation set or cleared
+    DelegationChange(DelegationChange),
+    /// A multisig account initialized
+    MultisigInitialized(MultisigInitialized),
+    /// A token account configured for confidential transfers
+    ConfidentialAccountConfigured(ConfidentialAccountConfigured),
+    /// A token account approved for confidential transfers
+    ConfidentialAccountApproved(ConfidentialAccountApproved),
+}
+
+impl TokenEvent {
+    /// Position of the event within its block
+    pub fn context(&self) -> &EventContext {
+        match self {
+            Self::Transfer(event) => &event.context,
+            Self::GroupMemberAdded(event) => &event.context,
+            Self::MintPaused(event) => &event.context,
+            Self::MintResumed(event) => &event.context,
+            Self::MetadataFieldUpdated(event) => &event.context,
+            Self::AccountOwnerChanged(event) => &event.context,
+            Self::DelegationChange(event) => &event.context,
+            Self::MultisigInitialized(event) => &event.context,
+            Self::ConfidentialAccountConfigured(event) => &event.context,
+            Self::ConfidentialAccountApproved(event) => &event.context,
+        }
+    }
+
+    /// Kind of the event
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Transfer(_) => TransferEvent::KIND,
+            Self::GroupMemberAdded(_) => GroupMemberAdded::KIND,
+            Self::MintPaused(_) => MintPaused::KIND,
+            Self::MintResumed(_) => MintResumed::KIND,
+            Self::MetadataFieldUpdated(_) => MetadataFieldUpdated::KIND,
+            Self::AccountOwnerChanged(_) => AccountOwnerChanged::KIND,
+            Self::DelegationChange(_) => DelegationChange::KIND,
+            Self::MultisigInitialized(_) => MultisigInitialized::KIND,
+            Self::ConfidentialAccountConfigured(_) => ConfidentialAccountConfigured::KIND,
+            Self::ConfidentialAccountApproved(_) => ConfidentialAccountApproved::KIND,
+        }
+    }
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context().event_id(self.kind())
+    }
+}
+
+impl From<TransferEvent> for TokenEvent {
+    fn from(event: TransferEvent) -> Self {
+        Self::Transfer(event)
+    }
+}
+
+impl From<GroupMemberAdded> for TokenEvent {
+    fn from(event: GroupMemberAdded) -> Self {
+        Self::GroupMemberAdded(event)
+    }
+}
+
+impl From<MetadataFieldUpdated> for TokenEvent {
+    fn from(event: MetadataFieldUpdated) -> Self {
+        Self::MetadataFieldUpdated(event)
+    }
+}
+
+impl From<AccountOwnerChanged> for TokenEvent {
+    fn from(event: AccountOwnerChanged) -> Self {
+        Self::AccountOwnerChanged(event)
+    }
+}
+
+impl From<DelegationChange> for TokenEvent {
+    fn from(event: DelegationChange) -> Self {
+        Self::DelegationChange(event)
+    }
+}
+
+impl From<MultisigInitialized> for TokenEvent {
+    fn from(event: MultisigInitialized) -> Self {
+        Self::MultisigInitialized(event)
+    }
+}
+
+impl From<ConfidentialAccountEvent> for TokenEvent {
+    fn from(event: ConfidentialAccountEvent) -> Self {
+        match event {
+            ConfidentialAccountEvent::Configured(event) => Self::ConfidentialAccountConfigured(event),
+            ConfidentialAccountEvent::Approved(event) => Self::ConfidentialAccountApproved(event),
+        }
+    }
+}
+
+impl From<PauseEvent> for TokenEvent {
+    fn from(event: PauseEvent) -> Self {
+        match event {
+            PauseEvent::Paused(event) => Self::MintPaused(event),
+            PauseEvent::Resumed(event) => Self::MintResumed(event),
+        }
+    }
+}
+
+/// Extracts all the events of all successful transactions of `block`, in execution order
+///
+/// ```
+/// # use substreams_solana_program_instructions::{events::block_token_events, program_ids::TOKEN_PROGRAM_ID};
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::*;
+/// // Transfer of 1 from account 0 to account 1, signed by account 2
+/// let transfer = [&[3][..], &1u64.to_le_bytes()].concat();
+/// let keys = vec![vec![1; 32], vec![2; 32], vec![3; 32], TOKEN_PROGRAM_ID.as_ref().to_vec(), vec![4; 32]];
+/// let trx = |instructions: Vec<CompiledInstruction>, inner_instructions: Vec<InnerInstructions>| ConfirmedTransaction {
+///     transaction: Some(Transaction {
+///         message: Some(Message { account_keys: keys.clone(), instructions, ..Default::default() }),
+///         ..Default::default()
+///     }),
+///     meta: Some(TransactionStatusMeta { inner_instructions, ..Default::default() }),
+/// };
+/// let top_level = CompiledInstruction { program_id_index: 3, accounts: vec![0, 1, 2], data: transfer.clone() };
+/// let block = Block {
+///     transactions: vec![
+///         // A program invoking a transfer, followed by a top-level transfer
+///         trx(
+///             vec![CompiledInstruction { program_id_index: 4, ..Default::default() }, top_level.clone()],
+///             vec![InnerInstructions {
+///                 index: 0,
+///                 instructions: vec![InnerInstruction { program_id_index: 3, accounts: vec![0, 1, 2], data: transfer, stack_height: Some(2) }],
+///             }],
+///         ),
+///         trx(vec![top_level], vec![]),
+///     ],
+///     ..Default::default()
+/// };
+///
+/// let positions: Vec<_> = block_token_events(&block)
+///     .iter()
+///     .map(|event| (event.context().transaction_index, event.context().instruction_index, event.context().inner_instruction_index))
+///     .collect();
+/// assert_eq!(positions, vec![(0, 0, Some(0)), (0, 1, None), (1, 0, None)]);
+/// ```
+pub fn block_token_events(block: &Block) -> Vec<TokenEvent> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| token_events(trx, index as u32))
+        .collect()
+}
+
+/// Extracts all the events of a successful transaction, the `transaction_index`-th of its
+/// block, in execution order
+pub fn token_events(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<TokenEvent> {
+    let mut events: Vec<TokenEvent> = transfer_events(trx, transaction_index)
+        .into_iter()
+        .map(TokenEvent::from)
+        .chain(group_member_events(trx, transaction_index).into_iter().map(TokenEvent::from))
+        .chain(pause_events(trx, transaction_index).into_iter().map(TokenEvent::from))
+        .chain(metadata_field_events(trx, transaction_index).into_iter().map(TokenEvent::from))
+        .chain(account_owner_events(trx, transaction_index).into_iter().map(TokenEvent::from))
+        .chain(delegation_events(trx, transaction_index).into_iter().map(TokenEvent::from))
+        .chain(multisig_events(trx, transaction_index).into_iter().map(TokenEvent::from))
+        .chain(confidential_account_events(trx, transaction_index).into_iter().map(TokenEvent::from))
+        .collect();
+    events.sort_by(|a, b| a.context().cmp(b.context()));
+    events
+}
@@ -0,0 +1,49 @@
+//! Basis-points fee arithmetic, rounding exactly as the token 2022 program does
+//!
+//! Fees are rounded up on-chain, recomputing them with a plain division is off by one
+//! whenever the amount is not a multiple of `10_000 / basis_points`.
+
+use crate::amount::ONE_IN_BASIS_POINTS;
+
+/// Divides `dividend` by `divisor`, rounding up. `None` if `divisor` is zero or on overflow.
+///
+/// ```
+/// # use substreams_solana_program_instructions::fee_math::ceil_div;
+/// assert_eq!(ceil_div(10, 3), Some(4));
+/// assert_eq!(ceil_div(9, 3), Some(3));
+/// assert_eq!(ceil_div(1, 0), None);
+/// ```
+pub fn ceil_div(dividend: u128, divisor: u128) -> Option<u128> {
+    dividend.checked_add(divisor)?.checked_sub(1)?.checked_div(divisor)
+}
+
+/// Computes `amount * basis_points / 10_000`, rounded up as the token program rounds fees,
+/// unlike [TokenAmount::checked_mul_bps](crate::amount::TokenAmount::checked_mul_bps) which
+/// rounds down. `None` on overflow.
+///
+/// ```
+/// # use substreams_solana_program_instructions::fee_math::mul_bps_ceil;
+/// assert_eq!(mul_bps_ceil(1_001, 100), Some(11));
+/// assert_eq!(mul_bps_ceil(1_000, 100), Some(10));
+/// assert_eq!(mul_bps_ceil(0, 100), Some(0));
+/// ```
+pub fn mul_bps_ceil(amount: u64, basis_points: u16) -> Option<u64> {
+    let numerator = (amount as u128).checked_mul(basis_points as u128)?;
+    u64::try_from(ceil_div(numerator, ONE_IN_BASIS_POINTS)?).ok()
+}
+
+/// Computes the fee withheld on a transfer of `amount`, mirroring the program's
+/// `TransferFee::calculate_fee`: the basis points fee rounded up, capped at `maximum_fee`.
+/// `None` on overflow.
+///
+/// ```
+/// # use substreams_solana_program_instructions::fee_math::calculate_fee;
+/// assert_eq!(calculate_fee(1_001, 100, 5), Some(5));
+/// assert_eq!(calculate_fee(1_001, 100, 50), Some(11));
+/// ```
+pub fn calculate_fee(amount: u64, transfer_fee_basis_points: u16, maximum_fee: u64) -> Option<u64> {
+    if transfer_fee_basis_points == 0 || amount == 0 {
+        return Some(0);
+    }
+    Some(mul_bps_ceil(amount, transfer_fee_basis_points)?.min(maximum_fee))
+}
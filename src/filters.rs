@@ -0,0 +1,149 @@
+//! Filters selecting the instructions of a block touching given mints, owners or programs
+//!
+//! Filters are evaluated on the raw instructions, before decoding, so only the instructions
+//! passing them pay the decoding cost.
+
+use {
+    crate::{
+        instructions::{block_instructions, BlockInstructionRef, DecodedInstruction},
+        program_ids::is_token_program,
+        pubkey::Pubkey,
+        token_instruction_2022::TokenInstruction,
+    },
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, TokenBalance},
+};
+
+/// Instruction filter, built with [InstructionFilter::for_mints], [InstructionFilter::for_owners]
+/// and [InstructionFilter::for_programs]. All criteria set must match, an empty filter matches
+/// everything.
+///
+/// ```no_run
+/// # use substreams_solana_program_instructions::{filters::InstructionFilter, pubkey::Pubkey};
+/// # let block = substreams_solana_core::pb::sf::solana::r#type::v1::Block::default();
+/// # let usdc: Pubkey = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".parse().unwrap();
+/// let mints = [usdc];
+/// let filter = InstructionFilter::default().for_mints(&mints);
+/// for ix in filter.token_instructions(&block) {
+///     // Only instructions touching USDC are decoded
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct InstructionFilter<'f> {
+    mints: Option<KeySet<'f>>,
+    owners: Option<KeySet<'f>>,
+    programs: Option<&'f [Pubkey]>,
+}
+
+/// Keys to match, along with their base58 encoding to match token balances entries
+#[derive(Clone, Debug)]
+struct KeySet<'f> {
+    keys: &'f [Pubkey],
+    encoded: Vec<String>,
+}
+
+impl<'f> KeySet<'f> {
+    fn new(keys: &'f [Pubkey]) -> Self {
+        Self {
+            keys,
+            encoded: keys.iter().map(|key| key.to_string()).collect(),
+        }
+    }
+
+    fn contains(&self, key: &[u8]) -> bool {
+        self.keys.iter().any(|candidate| candidate.as_ref() == key)
+    }
+
+    fn contains_encoded(&self, key: &str) -> bool {
+        self.encoded.iter().any(|candidate| candidate == key)
+    }
+}
+
+impl<'f> InstructionFilter<'f> {
+    /// Keeps instructions referencing one of `mints`, either directly or through a token
+    /// account of one of them
+    pub fn for_mints(mut self, mints: &'f [Pubkey]) -> Self {
+        self.mints = Some(KeySet::new(mints));
+        self
+    }
+
+    /// Keeps instructions referencing one of `owners`, either directly (as authority) or
+    /// through a token account they own
+    pub fn for_owners(mut self, owners: &'f [Pubkey]) -> Self {
+        self.owners = Some(KeySet::new(owners));
+        self
+    }
+
+    /// Keeps instructions executed by one of `programs`
+    pub fn for_programs(mut self, programs: &'f [Pubkey]) -> Self {
+        self.programs = Some(programs);
+        self
+    }
+
+    /// Returns `true` if the raw instruction passes the filter. Token accounts are resolved to
+    /// their mint and owner through the transaction's pre and post token balances.
+    pub fn matches(&self, item: &BlockInstructionRef) -> bool {
+        let instruction = &item.instruction;
+        if let Some(programs) = self.programs {
+            if !programs.iter().any(|program| program.as_ref() == instruction.program_id) {
+                return false;
+            }
+        }
+
+        if self.mints.is_none() && self.owners.is_none() {
+            return true;
+        }
+
+        let balances: Vec<&TokenBalance> = match item.transaction.meta.as_ref() {
+            Some(meta) => {
+                let indices = instruction.accounts.indices().unwrap_or(&[]);
+                meta.pre_token_balances
+                    .iter()
+                    .chain(meta.post_token_balances.iter())
+                    .filter(|balance| indices.contains(&(balance.account_index as u8)))
+                    .collect()
+            }
+            None => vec![],
+        };
+
+        if let Some(mints) = &self.mints {
+            let touches = instruction.accounts.iter().any(|key| mints.contains(key))
+                || balances.iter().any(|balance| mints.contains_encoded(&balance.mint));
+            if !touches {
+                return false;
+            }
+        }
+
+        if let Some(owners) = &self.owners {
+            let touches = instruction.accounts.iter().any(|key| owners.contains(key))
+                || balances.iter().any(|balance| owners.contains_encoded(&balance.owner));
+            if !touches {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Iterates lazily over the instructions of `block` passing the filter
+    pub fn block_instructions<'a>(&'a self, block: &'a Block) -> impl Iterator<Item = BlockInstructionRef<'a>> + 'a {
+        block_instructions(block).filter(move |item| self.matches(item))
+    }
+
+    /// Iterates lazily over the token program instructions of `block` passing the filter,
+    /// decoding only those that pass it
+    pub fn token_instructions<'a>(&'a self, block: &'a Block) -> impl Iterator<Item = DecodedInstruction<'a>> + 'a {
+        block_instructions(block)
+            .filter(|item| is_token_program(item.instruction.program_id))
+            .filter(move |item| self.matches(item))
+            .filter_map(|item| {
+                TokenInstruction::unpack(item.instruction.data)
+                    .ok()
+                    .map(|decoded| DecodedInstruction {
+                        transaction_index: item.transaction_index,
+                        transaction: item.transaction,
+                        instruction: item.instruction,
+                        decoded,
+                    })
+            })
+    }
+}
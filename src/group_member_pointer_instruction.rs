@@ -0,0 +1,80 @@
+use anyhow::anyhow;
+use {
+    substreams::errors::Error,
+    crate::{token_instruction_2022::TokenInstruction},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    crate::serialization::coption_fromstr,
+    serde::{Deserialize, Serialize},
+};
+use crate::option::COption;
+use crate::pubkey::Pubkey;
+
+/// Group member pointer extension instructions
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+pub enum GroupMemberPointerInstruction {
+    /// Initialize a new mint with a group member pointer
+    ///
+    /// Fails if the mint has already been initialized, so must be called before
+    /// `InitializeMint`.
+    ///
+    /// The mint must have exactly enough space allocated for the base mint (82
+    /// bytes), plus 83 bytes of padding, 1 byte reserved for the account type,
+    /// then space required for this extension, plus any others.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint to initialize.
+    Initialize {
+        /// The public key for the account that can update the group address
+        #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+        authority: COption<Pubkey>,
+        /// The account address that holds the member
+        #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+        member_address: COption<Pubkey>,
+    },
+    /// Update the group member pointer address. Only supported for mints that
+    /// include the `GroupMemberPointer` extension.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[signer]` The group member pointer authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[]` The mint's group member pointer authority.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    Update {
+        /// The new account address that holds the member
+        #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+        member_address: COption<Pubkey>,
+    },
+}
+impl GroupMemberPointerInstruction {
+    /// Unpacks a byte buffer into a GroupMemberPointerInstruction
+    pub fn unpack(input: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (&tag, rest) = input.split_first().ok_or(anyhow!("Invalid Group Member Pointer Instruction"))?;
+        Ok(match tag {
+            0 => {
+                let (authority, rest) = TokenInstruction::unpack_optional_nonzero_pubkey(rest)?;
+                let (member_address, rest) = TokenInstruction::unpack_optional_nonzero_pubkey(rest)?;
+                (Self::Initialize { authority, member_address }, rest)
+            }
+            1 => {
+                let (member_address, rest) = TokenInstruction::unpack_optional_nonzero_pubkey(rest)?;
+                (Self::Update { member_address }, rest)
+            }
+            _ => return Err(anyhow!("Invalid Group Member Pointer Instruction - unpack didn't match any tag value: {}", tag)),
+        })
+    }
+}
@@ -0,0 +1,196 @@
+//! Validation of the order in which the extensions of a Token 2022 mint are initialized
+//!
+//! Mint extensions have to be initialized on the uninitialized mint account, before its
+//! `InitializeMint`, while the token metadata and group extensions need an initialized mint
+//! and come after it. Transactions breaking these rules fail on chain; auditing token launch
+//! transactions, failed ones included, tells which instruction broke them.
+
+use {
+    crate::{
+        adoption::initialized_extension,
+        events::EventContext,
+        instructions::{instructions, InstructionRef},
+        program_ids::is_token_2022_program,
+        pubkey::Pubkey,
+        token_instruction_2022::{ExtensionType, TokenInstruction},
+    },
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// An initialization ordering rule
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OrderingRule {
+    /// Mint extensions are initialized before the mint's `InitializeMint`
+    ExtensionBeforeMint,
+    /// The token metadata and group extensions are initialized after the mint's
+    /// `InitializeMint`
+    InterfaceAfterMint,
+    /// An extension is initialized once
+    InitializedOnce,
+}
+
+/// An extension initialization breaking an [OrderingRule]
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OrderingViolation {
+    /// Position of the offending extension initialization
+    pub context: EventContext,
+    /// The mint
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub mint: Pubkey,
+    /// The extension initialized
+    pub extension: ExtensionType,
+    /// The rule broken
+    pub rule: OrderingRule,
+}
+
+/// An initialization step of a mint
+enum Step {
+    InitializeMint,
+    Extension(ExtensionType),
+}
+
+/// Mint initialized by `instruction`, or whose extension it initializes, along with the step
+fn initialization_step(instruction: &InstructionRef) -> Option<(Pubkey, Step)> {
+    if !is_token_2022_program(instruction.program_id) {
+        return None;
+    }
+    match TokenInstruction::unpack(instruction.data) {
+        Ok(TokenInstruction::InitializeMint { .. }) | Ok(TokenInstruction::InitializeMint2 { .. }) => {
+            return Some((instruction.accounts.get_pubkey(0)?, Step::InitializeMint));
+        }
+        _ => {}
+    }
+    let extension = initialized_extension(instruction.data)?;
+    let mint_index = match extension {
+        ExtensionType::TokenMetadata => 2,
+        ExtensionType::TokenGroup | ExtensionType::TokenGroupMember => 1,
+        _ => 0,
+    };
+    Some((instruction.accounts.get_pubkey(mint_index)?, Step::Extension(extension)))
+}
+
+/// Returns `true` for the extensions initialized after the mint's `InitializeMint`
+fn follows_mint(extension: ExtensionType) -> bool {
+    matches!(
+        extension,
+        ExtensionType::TokenMetadata | ExtensionType::TokenGroup | ExtensionType::TokenGroupMember
+    )
+}
+
+/// Checks the order of the initializations of `mint` in `trx`, the `transaction_index`-th
+/// transaction of its block, returning the violations in instruction order.
+///
+/// Failed transactions are checked too, a violation making its transaction fail. Mints
+/// initialized by earlier transactions have no `InitializeMint` in `trx`: their interface
+/// extensions are not reported, and only the extensions initialized twice in `trx` are.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{init_order::*, program_ids::TOKEN_2022_PROGRAM_ID, token_instruction_2022::ExtensionType};
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::{CompiledInstruction, ConfirmedTransaction, Message, Transaction, TransactionStatusMeta};
+/// let mint = [1; 32];
+/// let message = Message {
+///     account_keys: vec![mint.to_vec(), TOKEN_2022_PROGRAM_ID.as_ref().to_vec()],
+///     instructions: vec![
+///         // InitializeMint2, decimals 6, mint authority [0; 32], no freeze authority
+///         CompiledInstruction { program_id_index: 1, accounts: vec![0], data: [&[20, 6][..], &[0; 33]].concat() },
+///         // InitializeNonTransferableMint, after the mint
+///         CompiledInstruction { program_id_index: 1, accounts: vec![0], data: vec![32] },
+///     ],
+///     ..Default::default()
+/// };
+/// let trx = ConfirmedTransaction {
+///     transaction: Some(Transaction { message: Some(message), ..Default::default() }),
+///     meta: Some(TransactionStatusMeta::default()),
+/// };
+///
+/// let violations = check_initialization_order(&trx, 0, &mint.into());
+/// assert_eq!(violations.len(), 1);
+/// assert_eq!(violations[0].extension, ExtensionType::NonTransferable);
+/// assert_eq!(violations[0].rule, OrderingRule::ExtensionBeforeMint);
+/// assert_eq!(violations[0].context.instruction_index, 1);
+/// ```
+pub fn check_initialization_order(
+    trx: &ConfirmedTransaction,
+    transaction_index: u32,
+    mint: &Pubkey,
+) -> Vec<OrderingViolation> {
+    let steps: Vec<(InstructionRef, Step)> = instructions(trx)
+        .filter_map(|instruction| match initialization_step(&instruction) {
+            Some((target, step)) if target == *mint => Some((instruction, step)),
+            _ => None,
+        })
+        .collect();
+    let mint_position = steps.iter().position(|(_, step)| matches!(step, Step::InitializeMint));
+
+    let mut initialized: Vec<ExtensionType> = vec![];
+    let mut violations = vec![];
+    for (position, (instruction, step)) in steps.iter().enumerate() {
+        let extension = match step {
+            Step::Extension(extension) => *extension,
+            Step::InitializeMint => continue,
+        };
+        let rule = if initialized.contains(&extension) {
+            Some(OrderingRule::InitializedOnce)
+        } else {
+            match mint_position {
+                Some(mint_position) if follows_mint(extension) && position < mint_position => {
+                    Some(OrderingRule::InterfaceAfterMint)
+                }
+                Some(mint_position) if !follows_mint(extension) && position > mint_position => {
+                    Some(OrderingRule::ExtensionBeforeMint)
+                }
+                _ => None,
+            }
+        };
+        initialized.push(extension);
+        if let Some(rule) = rule {
+            violations.push(OrderingViolation {
+                context: EventContext::new(trx, transaction_index, instruction),
+                mint: *mint,
+                extension,
+                rule,
+            });
+        }
+    }
+    violations
+}
+
+/// Checks the order of the initializations of every mint initialized or extended in `trx`,
+/// see [check_initialization_order], returning the violations in instruction order
+pub fn initialization_order_violations(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<OrderingViolation> {
+    let mut mints: Vec<Pubkey> = vec![];
+    for instruction in instructions(trx) {
+        if let Some((mint, _)) = initialization_step(&instruction) {
+            if !mints.contains(&mint) {
+                mints.push(mint);
+            }
+        }
+    }
+    let mut violations: Vec<OrderingViolation> = mints
+        .iter()
+        .flat_map(|mint| check_initialization_order(trx, transaction_index, mint))
+        .collect();
+    violations.sort_by(|a, b| a.context.cmp(&b.context));
+    violations
+}
+
+/// Initialization order violations of the transactions of `block`, failed ones included, in
+/// transaction order
+pub fn block_initialization_order_violations(block: &Block) -> Vec<OrderingViolation> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| initialization_order_violations(trx, index as u32))
+        .collect()
+}
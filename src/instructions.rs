@@ -0,0 +1,271 @@
+//! Iteration over the instructions of a transaction, top-level and inner instructions alike
+
+use {
+    crate::{
+        accounts::{AccountAccess, Accounts, TransactionKeys},
+        block_version::{logged_stack_heights, BlockRevision},
+        metrics,
+        program_ids::is_token_program,
+        token_instruction_2022::TokenInstruction,
+    },
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction, InnerInstructions},
+};
+
+/// An instruction of a transaction, with its program id and accounts resolved
+#[derive(Clone, Copy, Debug)]
+pub struct InstructionRef<'a> {
+    /// Index of the top-level instruction, or of the top-level instruction holding this inner instruction
+    pub instruction_index: usize,
+    /// Index of the inner instruction within its top-level instruction, `None` for top-level instructions
+    pub inner_instruction_index: Option<usize>,
+    /// Invocation stack height, 1 for top-level instructions, `None` if unknown. Read from the
+    /// logs for blocks predating its recording, see [logged_stack_heights].
+    pub stack_height: Option<u32>,
+    /// Resolved program id, empty if the program id index is out of bounds
+    pub program_id: &'a [u8],
+    /// Accounts of the instruction
+    pub accounts: Accounts<'a>,
+    /// Instruction data
+    pub data: &'a [u8],
+    /// Revision of the block model the transaction was recorded with
+    pub revision: BlockRevision,
+}
+
+impl InstructionRef<'_> {
+    /// Returns `true` for top-level instructions
+    pub fn is_root(&self) -> bool {
+        self.inner_instruction_index.is_none()
+    }
+
+    /// Writable and readonly accounts of the instruction, see [Accounts::access]
+    pub fn account_access(&self) -> Option<AccountAccess> {
+        self.accounts.access()
+    }
+}
+
+/// Returns `true` if `trx` executed successfully
+pub fn is_successful(trx: &ConfirmedTransaction) -> bool {
+    matches!(trx.meta.as_ref(), Some(meta) if meta.err.is_none())
+}
+
+/// Iterates over all instructions of `trx` in execution order: each top-level instruction
+/// followed by its inner instructions. Yields nothing if the transaction has no message or meta.
+pub fn instructions(trx: &ConfirmedTransaction) -> impl Iterator<Item = InstructionRef<'_>> {
+    let keys = TransactionKeys::from_transaction(trx);
+    let revision = BlockRevision::of(trx);
+    let logged = match revision {
+        BlockRevision::NoStackHeight => logged_stack_heights(trx),
+        _ => None,
+    };
+    let parts = trx
+        .transaction
+        .as_ref()
+        .and_then(|t| t.message.as_ref())
+        .zip(trx.meta.as_ref())
+        .zip(keys);
+
+    parts.into_iter().zip(std::iter::once(logged)).flat_map(move |(((message, meta), keys), logged)| {
+        message.instructions.iter().enumerate().flat_map(move |(instruction_index, compiled)| {
+            let logged = logged.as_ref().and_then(|logged| logged.get(instruction_index)).cloned();
+            let top_level = InstructionRef {
+                instruction_index,
+                inner_instruction_index: None,
+                stack_height: Some(1),
+                program_id: keys.get(compiled.program_id_index as usize).unwrap_or(&[]),
+                accounts: Accounts::from_compiled(keys, &compiled.accounts),
+                data: &compiled.data,
+                revision,
+            };
+
+            let inner = meta
+                .inner_instructions
+                .iter()
+                .filter(move |inner: &&InnerInstructions| inner.index as usize == instruction_index)
+                .flat_map(|inner| inner.instructions.iter().enumerate())
+                .enumerate()
+                .map(move |(position, (inner_instruction_index, inner))| InstructionRef {
+                    instruction_index,
+                    inner_instruction_index: Some(inner_instruction_index),
+                    stack_height: inner
+                        .stack_height
+                        .or_else(|| logged.as_ref().and_then(|logged| logged.get(position)).copied()),
+                    program_id: keys.get(inner.program_id_index as usize).unwrap_or(&[]),
+                    accounts: Accounts::from_compiled(keys, &inner.accounts),
+                    data: &inner.data,
+                    revision,
+                });
+
+            std::iter::once(top_level).chain(inner)
+        })
+    })
+}
+
+/// Instructions invoked by the instruction at `position` of `all`, directly or not, `all`
+/// being the instructions of a transaction as yielded by [instructions]. `None` when they are
+/// unknown, in blocks predating the recording of inner instructions, or of the stack height
+/// delimiting those of an inner instruction, see [BlockRevision], and when `position` is out
+/// of bounds.
+///
+/// ```
+/// # use substreams_solana_program_instructions::instructions::{instructions, invoked_instructions};
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::{CompiledInstruction, ConfirmedTransaction, Message, Transaction, TransactionStatusMeta};
+/// let program = bs58::encode([1; 32]).into_string();
+/// let transaction = |log_messages: Vec<String>| ConfirmedTransaction {
+///     transaction: Some(Transaction {
+///         message: Some(Message {
+///             account_keys: vec![vec![1; 32]],
+///             instructions: vec![CompiledInstruction::default()],
+///             ..Default::default()
+///         }),
+///         ..Default::default()
+///     }),
+///     meta: Some(TransactionStatusMeta { log_messages, ..Default::default() }),
+/// };
+///
+/// let trx = transaction(vec![format!("Program {} invoke [1]", program)]);
+/// let all: Vec<_> = instructions(&trx).collect();
+/// assert_eq!(invoked_instructions(&all, 0).map(|invoked| invoked.len()), Some(0));
+/// // A CPI logged but no inner instructions recorded
+/// let legacy = transaction(vec![format!("Program {} invoke [1]", program), format!("Program {} invoke [2]", program)]);
+/// let all: Vec<_> = instructions(&legacy).collect();
+/// assert!(invoked_instructions(&all, 0).is_none());
+/// assert!(invoked_instructions(&all, 1).is_none());
+/// ```
+pub fn invoked_instructions<'a, 'b>(all: &'b [InstructionRef<'a>], position: usize) -> Option<&'b [InstructionRef<'a>]> {
+    let instruction = all.get(position)?;
+    let delimited = if instruction.is_root() {
+        instruction.revision.has_inner_instructions()
+    } else {
+        instruction.revision.has_stack_height()
+    };
+    if !delimited {
+        return None;
+    }
+    let following = &all[position + 1..];
+    let mut len = 0;
+    for next in following {
+        if next.instruction_index != instruction.instruction_index || next.is_root() {
+            break;
+        }
+        // Top-level instructions invoke all the inner instructions that follow them
+        if !instruction.is_root() && next.stack_height? <= instruction.stack_height? {
+            break;
+        }
+        len += 1;
+    }
+    Some(&following[..len])
+}
+
+/// An instruction of a block, along with the transaction holding it
+#[derive(Clone, Copy, Debug)]
+pub struct BlockInstructionRef<'a> {
+    /// Index of the transaction in the block, failed transactions included
+    pub transaction_index: u32,
+    /// The transaction holding the instruction
+    pub transaction: &'a ConfirmedTransaction,
+    /// The instruction
+    pub instruction: InstructionRef<'a>,
+}
+
+/// A token program instruction of a block, decoded
+#[derive(Clone, Debug)]
+pub struct DecodedInstruction<'a> {
+    /// Index of the transaction in the block, failed transactions included
+    pub transaction_index: u32,
+    /// The transaction holding the instruction
+    pub transaction: &'a ConfirmedTransaction,
+    /// The raw instruction
+    pub instruction: InstructionRef<'a>,
+    /// The decoded instruction
+    pub decoded: TokenInstruction<'a>,
+}
+
+impl<'a> DecodedInstruction<'a> {
+    /// Program id of the instruction, SPL Token or SPL Token 2022
+    pub fn program_id(&self) -> &'a [u8] {
+        self.instruction.program_id
+    }
+
+    /// Indices of the instruction's accounts into the transaction's keys, in the order the
+    /// instruction expects them
+    pub fn account_indices(&self) -> &'a [u8] {
+        self.instruction.accounts.indices().unwrap_or(&[])
+    }
+
+    /// Accounts of the instruction, resolving each index to its address and flags
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::{instructions::token_instructions, program_ids::TOKEN_PROGRAM_ID, token_instruction_2022::TokenInstruction};
+    /// # use substreams_solana_core::pb::sf::solana::r#type::v1::*;
+    /// let block = Block {
+    ///     transactions: vec![ConfirmedTransaction {
+    ///         transaction: Some(Transaction {
+    ///             message: Some(Message {
+    ///                 account_keys: vec![vec![1; 32], vec![2; 32], TOKEN_PROGRAM_ID.as_ref().to_vec()],
+    ///                 // CloseAccount of account 1, to and by account 0
+    ///                 instructions: vec![CompiledInstruction { program_id_index: 2, accounts: vec![1, 0, 0], data: vec![9] }],
+    ///                 ..Default::default()
+    ///             }),
+    ///             ..Default::default()
+    ///         }),
+    ///         meta: Some(TransactionStatusMeta::default()),
+    ///     }],
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let ix = token_instructions(&block).next().unwrap();
+    /// assert_eq!(ix.decoded, TokenInstruction::CloseAccount);
+    /// assert_eq!(ix.program_id(), TOKEN_PROGRAM_ID.as_ref());
+    /// assert_eq!(ix.account_indices(), &[1, 0, 0]);
+    /// assert_eq!(ix.accounts().get(0), Some(&[2; 32][..]));
+    /// ```
+    pub fn accounts(&self) -> Accounts<'a> {
+        self.instruction.accounts
+    }
+}
+
+/// Iterates lazily over all instructions of the successful transactions of `block`, in
+/// execution order
+pub fn block_instructions(block: &Block) -> impl Iterator<Item = BlockInstructionRef<'_>> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .filter(|(_, trx)| is_successful(trx))
+        .flat_map(|(transaction_index, trx)| {
+            instructions(trx).map(move |instruction| BlockInstructionRef {
+                transaction_index: transaction_index as u32,
+                transaction: trx,
+                instruction,
+            })
+        })
+}
+
+/// Iterates lazily over the token program instructions (SPL Token and SPL Token 2022) of the
+/// successful transactions of `block`, decoding each one only when reached. Instructions that
+/// fail to decode are skipped.
+///
+/// Nothing is collected upfront, so filter-style modules can stop at the first match:
+///
+/// ```no_run
+/// # use substreams_solana_program_instructions::{instructions::token_instructions, token_instruction_2022::TokenInstruction};
+/// # let block = substreams_solana_core::pb::sf::solana::r#type::v1::Block::default();
+/// let has_mint = token_instructions(&block)
+///     .any(|ix| matches!(ix.decoded, TokenInstruction::MintTo { .. } | TokenInstruction::MintToChecked { .. }));
+/// ```
+pub fn token_instructions(block: &Block) -> impl Iterator<Item = DecodedInstruction<'_>> {
+    block_instructions(block)
+        .filter(|item| is_token_program(item.instruction.program_id))
+        .filter_map(|item| {
+            let decoded = TokenInstruction::unpack(item.instruction.data);
+            metrics::decoded(item.instruction.data, &decoded);
+            decoded
+                .ok()
+                .map(|decoded| DecodedInstruction {
+                    transaction_index: item.transaction_index,
+                    transaction: item.transaction,
+                    instruction: item.instruction,
+                    decoded,
+                })
+        })
+}
@@ -0,0 +1,349 @@
+//! Interest-bearing mint extension instructions, state and interest accrual
+//!
+//! [rate_change_events] reports rate updates along with the rate they replace, so alerts can
+//! act on the change rather than the new rate alone.
+
+use anyhow::anyhow;
+use {
+    crate::{
+        events::EventContext,
+        instructions::{instructions, is_successful},
+        metrics,
+        option::COption,
+        program_ids::is_token_program,
+        pubkey::Pubkey,
+        token_instruction_2022::TokenInstruction,
+    },
+    std::collections::HashMap,
+    substreams::errors::Error,
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    crate::serialization::coption_fromstr,
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// Annual interest rate, expressed as basis points
+pub type BasisPoints = i16;
+const ONE_IN_BASIS_POINTS: f64 = 10_000.;
+const SECONDS_PER_YEAR: f64 = 60. * 60. * 24. * 365.24;
+
+/// UnixTimestamp expressed with an alignment-independent type
+pub type UnixTimestamp = i64;
+
+/// Interest-bearing extension data for mints
+///
+/// Tokens accrue interest at an annual rate expressed by `current_rate`,
+/// compounded continuously, so APY will be higher than the published interest
+/// rate.
+///
+/// To support changing the rate, the config also maintains state for the previous
+/// rate.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InterestBearingConfig {
+    /// Authority that can set the interest rate and authority
+    #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+    pub rate_authority: COption<Pubkey>,
+    /// Timestamp of initialization, from which to base interest calculations
+    pub initialization_timestamp: UnixTimestamp,
+    /// Average rate from initialization until the last time it was updated
+    pub pre_update_average_rate: BasisPoints,
+    /// Timestamp of the last update, used to calculate the total amount accrued
+    pub last_update_timestamp: UnixTimestamp,
+    /// Current rate, since the last update
+    pub current_rate: BasisPoints,
+}
+
+impl InterestBearingConfig {
+    /// Serialized length of the extension data
+    pub const LEN: usize = 52;
+
+    /// Unpacks the extension data, as found in the mint account's TLV entry
+    pub fn unpack(input: &[u8]) -> Result<Self, Error> {
+        let (rate_authority, rest) = TokenInstruction::unpack_optional_nonzero_pubkey(input)?;
+        let (initialization_timestamp, rest) = TokenInstruction::unpack_i64(rest)?;
+        let (pre_update_average_rate, rest) = TokenInstruction::unpack_i16(rest)?;
+        let (last_update_timestamp, rest) = TokenInstruction::unpack_i64(rest)?;
+        let (current_rate, _rest) = TokenInstruction::unpack_i16(rest)?;
+        Ok(Self {
+            rate_authority,
+            initialization_timestamp,
+            pre_update_average_rate,
+            last_update_timestamp,
+            current_rate,
+        })
+    }
+
+    fn pre_update_timespan(&self) -> Option<i64> {
+        self.last_update_timestamp.checked_sub(self.initialization_timestamp)
+    }
+
+    fn pre_update_exp(&self) -> Option<f64> {
+        let numerator = (self.pre_update_average_rate as i128)
+            .checked_mul(self.pre_update_timespan()? as i128)? as f64;
+        let exponent = numerator / SECONDS_PER_YEAR / ONE_IN_BASIS_POINTS;
+        Some(exponent.exp())
+    }
+
+    fn post_update_timespan(&self, unix_timestamp: UnixTimestamp) -> Option<i64> {
+        unix_timestamp.checked_sub(self.last_update_timestamp)
+    }
+
+    fn post_update_exp(&self, unix_timestamp: UnixTimestamp) -> Option<f64> {
+        let numerator = (self.current_rate as i128)
+            .checked_mul(self.post_update_timespan(unix_timestamp)? as i128)? as f64;
+        let exponent = numerator / SECONDS_PER_YEAR / ONE_IN_BASIS_POINTS;
+        Some(exponent.exp())
+    }
+
+    /// Multiplier applied to raw amounts at `unix_timestamp`, see [accrued_multiplier]
+    pub fn accrued_multiplier(&self, unix_timestamp: UnixTimestamp) -> Option<f64> {
+        Some(self.pre_update_exp()? * self.post_update_exp(unix_timestamp)?)
+    }
+
+    /// Converts a raw amount to its ui amount with interest accrued up to `unix_timestamp`,
+    /// mirroring the token program's `amount_to_ui_amount`
+    pub fn amount_to_ui_amount(&self, amount: u64, decimals: u8, unix_timestamp: UnixTimestamp) -> Option<f64> {
+        let scale = self.accrued_multiplier(unix_timestamp)? / 10_f64.powi(decimals as i32);
+        Some(amount as f64 * scale)
+    }
+}
+
+/// Computes the continuously compounded interest multiplier of `config` at `unix_timestamp`,
+/// exactly as the token program does: the pre-update average rate applied from initialization
+/// to the last update, times the current rate applied from the last update to `unix_timestamp`.
+///
+/// Returns `None` if the timespans overflow.
+pub fn accrued_multiplier(config: &InterestBearingConfig, unix_timestamp: UnixTimestamp) -> Option<f64> {
+    config.accrued_multiplier(unix_timestamp)
+}
+
+/// Interest-bearing mint extension instructions
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+pub enum InterestBearingMintInstruction {
+    /// Initialize a new mint with interest accrual.
+    ///
+    /// Fails if the mint has already been initialized, so must be called before
+    /// `InitializeMint`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint to initialize.
+    Initialize {
+        /// The public key for the account that can update the rate
+        #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+        rate_authority: COption<Pubkey>,
+        /// The initial interest rate
+        rate: BasisPoints,
+    },
+    /// Update the interest rate. Only supported for mints that include the
+    /// `InterestBearingConfig` extension.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[signer]` The mint rate authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[]` The mint's multisignature rate authority.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    UpdateRate {
+        /// The new interest rate
+        rate: BasisPoints,
+    },
+}
+impl InterestBearingMintInstruction {
+    /// Unpacks a byte buffer into an InterestBearingMintInstruction
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::{interest_bearing_mint::InterestBearingMintInstruction, token_instruction_2022::TokenInstruction};
+    /// let data = [&[33, 1][..], &250i16.to_le_bytes()].concat();
+    /// assert_eq!(
+    ///     TokenInstruction::unpack(&data).unwrap(),
+    ///     TokenInstruction::InterestBearingMintExtension(InterestBearingMintInstruction::UpdateRate { rate: 250 })
+    /// );
+    /// ```
+    pub fn unpack(input: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (&tag, rest) = input
+            .split_first()
+            .ok_or(anyhow!("Invalid Interest Bearing Mint Instruction"))?;
+        Ok(match tag {
+            0 => {
+                let (rate_authority, rest) = TokenInstruction::unpack_optional_nonzero_pubkey(rest)?;
+                let (rate, rest) = TokenInstruction::unpack_i16(rest)?;
+                (Self::Initialize { rate_authority, rate }, rest)
+            }
+            1 => {
+                let (rate, rest) = TokenInstruction::unpack_i16(rest)?;
+                (Self::UpdateRate { rate }, rest)
+            }
+            _ => {
+                return Err(anyhow!(
+                    "Invalid Interest Bearing Mint Instruction - unpack didn't match any tag value: {}",
+                    tag
+                ))
+            }
+        })
+    }
+}
+
+/// An interest rate updated, through `UpdateRate`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateChanged {
+    /// Position of the `UpdateRate` instruction
+    pub context: EventContext,
+    /// The mint
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub mint: Pubkey,
+    /// The rate replaced, `None` if unknown
+    pub old_rate: Option<BasisPoints>,
+    /// The new rate
+    pub new_rate: BasisPoints,
+    /// The rate authority, a multisig account when the update has more signers
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub authority: Pubkey,
+}
+
+impl RateChanged {
+    /// Kind of the event, part of its [RateChanged::event_id]
+    pub const KIND: &'static str = "rate_changed";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+
+    /// Signed change of the rate, `None` if the replaced rate is unknown
+    pub fn delta(&self) -> Option<i32> {
+        self.old_rate.map(|old_rate| self.new_rate as i32 - old_rate as i32)
+    }
+}
+
+/// Extracts the rate updates of all successful transactions of `block`, in execution order,
+/// see [rate_change_events]. The rate replaced by an update is read from `prior`, or from an
+/// earlier instruction of the block initializing or updating the rate.
+pub fn block_rate_change_events<F>(block: &Block, prior: F) -> Vec<RateChanged>
+where
+    F: Fn(&Pubkey) -> Option<InterestBearingConfig>,
+{
+    let mut rates = HashMap::new();
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| rate_changes(trx, index as u32, &mut rates, &prior))
+        .collect()
+}
+
+/// Extracts the rate updates of a successful transaction, the `transaction_index`-th of its
+/// block, in execution order. `prior` gives the extension state of a mint before the
+/// transaction, `None` if unknown: the rate replaced is read from it, or from an earlier
+/// instruction of the transaction initializing or updating the rate.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{interest_bearing_mint::*, option::COption, program_ids::TOKEN_2022_PROGRAM_ID, pubkey::Pubkey};
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::{CompiledInstruction, ConfirmedTransaction, Message, Transaction, TransactionStatusMeta};
+/// let (mint, authority) = (Pubkey::from([1; 32]), Pubkey::from([2; 32]));
+/// let update = |rate: i16| CompiledInstruction { program_id_index: 2, accounts: vec![0, 1], data: [&[33, 1][..], &rate.to_le_bytes()].concat() };
+/// let trx = ConfirmedTransaction {
+///     transaction: Some(Transaction {
+///         message: Some(Message {
+///             account_keys: vec![mint.as_ref().to_vec(), authority.as_ref().to_vec(), TOKEN_2022_PROGRAM_ID.as_ref().to_vec()],
+///             instructions: vec![update(300), update(-50)],
+///             ..Default::default()
+///         }),
+///         ..Default::default()
+///     }),
+///     meta: Some(TransactionStatusMeta::default()),
+/// };
+/// let config = InterestBearingConfig {
+///     rate_authority: COption::Some(authority),
+///     initialization_timestamp: 0,
+///     pre_update_average_rate: 100,
+///     last_update_timestamp: 0,
+///     current_rate: 100,
+/// };
+///
+/// let events = rate_change_events(&trx, 0, |_| Some(config));
+/// assert_eq!((events[0].old_rate, events[0].new_rate, events[0].delta()), (Some(100), 300, Some(200)));
+/// assert_eq!((events[1].old_rate, events[1].new_rate), (Some(300), -50));
+/// assert_eq!(events[1].authority, authority);
+/// assert_eq!(rate_change_events(&trx, 0, |_| None)[0].old_rate, None);
+/// ```
+pub fn rate_change_events<F>(trx: &ConfirmedTransaction, transaction_index: u32, prior: F) -> Vec<RateChanged>
+where
+    F: Fn(&Pubkey) -> Option<InterestBearingConfig>,
+{
+    rate_changes(trx, transaction_index, &mut HashMap::new(), &prior)
+}
+
+/// Extracts the rate updates of `trx`, `rates` holding the current rate of the mints whose
+/// rate earlier instructions set, updated as the transaction sets them
+fn rate_changes<F>(
+    trx: &ConfirmedTransaction,
+    transaction_index: u32,
+    rates: &mut HashMap<Pubkey, BasisPoints>,
+    prior: &F,
+) -> Vec<RateChanged>
+where
+    F: Fn(&Pubkey) -> Option<InterestBearingConfig>,
+{
+    if !is_successful(trx) {
+        return vec![];
+    }
+
+    let mut events = vec![];
+    for instruction in instructions(trx) {
+        if !is_token_program(instruction.program_id) {
+            continue;
+        }
+        let decoded = match TokenInstruction::unpack(instruction.data) {
+            Ok(TokenInstruction::InterestBearingMintExtension(decoded)) => decoded,
+            _ => continue,
+        };
+        let mint = match instruction.accounts.get_pubkey(0) {
+            Some(mint) => mint,
+            None => continue,
+        };
+        let new_rate = match decoded {
+            InterestBearingMintInstruction::Initialize { rate, .. } => {
+                rates.insert(mint, rate);
+                continue;
+            }
+            InterestBearingMintInstruction::UpdateRate { rate } => rate,
+        };
+        let authority = match instruction.accounts.get_pubkey(1) {
+            Some(authority) => authority,
+            None => continue,
+        };
+        let old_rate = rates
+            .insert(mint, new_rate)
+            .or_else(|| prior(&mint).map(|config| config.current_rate));
+
+        events.push(RateChanged {
+            context: EventContext::new(trx, transaction_index, &instruction),
+            mint,
+            old_rate,
+            new_rate,
+            authority,
+        });
+    }
+
+    metrics::events_emitted(RateChanged::KIND, events.len());
+    events
+}
@@ -0,0 +1,224 @@
+//! Human labels of well-known programs and mints, so dashboards need no join table to name
+//! the addresses found in extracted events
+
+use crate::{
+    events::TransferEvent,
+    program_ids::{
+        BPF_LOADER_UPGRADEABLE_PROGRAM_ID, COMPUTE_BUDGET_PROGRAM_ID, MEMO_PROGRAM_ID, MEMO_V1_PROGRAM_ID,
+        TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID,
+    },
+    pubkey::Pubkey,
+};
+
+/// Kind of labeled address
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LabelKind {
+    /// An on-chain program
+    Program,
+    /// A token mint
+    Mint,
+}
+
+/// A labeled address
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Label {
+    /// The address
+    pub address: Pubkey,
+    /// Human label of the address
+    pub name: &'static str,
+    /// Kind of address
+    pub kind: LabelKind,
+}
+
+/// All the labeled addresses
+pub static LABELS: &[Label] = &[
+    Label {
+        address: TOKEN_PROGRAM_ID,
+        name: "SPL Token",
+        kind: LabelKind::Program,
+    },
+    Label {
+        address: TOKEN_2022_PROGRAM_ID,
+        name: "SPL Token 2022",
+        kind: LabelKind::Program,
+    },
+    Label {
+        address: MEMO_PROGRAM_ID,
+        name: "SPL Memo",
+        kind: LabelKind::Program,
+    },
+    Label {
+        address: MEMO_V1_PROGRAM_ID,
+        name: "SPL Memo v1",
+        kind: LabelKind::Program,
+    },
+    Label {
+        address: COMPUTE_BUDGET_PROGRAM_ID,
+        name: "Compute Budget",
+        kind: LabelKind::Program,
+    },
+    Label {
+        address: BPF_LOADER_UPGRADEABLE_PROGRAM_ID,
+        name: "BPF Upgradeable Loader",
+        kind: LabelKind::Program,
+    },
+    // 11111111111111111111111111111111
+    Label {
+        address: Pubkey([
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]),
+        name: "System Program",
+        kind: LabelKind::Program,
+    },
+    // ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL
+    Label {
+        address: Pubkey([
+            140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142, 13, 131,
+            11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216, 219, 233, 248, 89,
+        ]),
+        name: "Associated Token Account",
+        kind: LabelKind::Program,
+    },
+    // JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4
+    Label {
+        address: Pubkey([
+            4, 121, 213, 91, 242, 49, 192, 110, 238, 116, 197, 110, 206, 104, 21, 7,
+            253, 177, 178, 222, 163, 244, 142, 81, 2, 177, 205, 162, 86, 188, 19, 143,
+        ]),
+        name: "Jupiter Aggregator v6",
+        kind: LabelKind::Program,
+    },
+    // 675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8
+    Label {
+        address: Pubkey([
+            75, 217, 73, 196, 54, 2, 195, 63, 32, 119, 144, 237, 22, 163, 82, 76,
+            161, 185, 151, 92, 241, 33, 162, 169, 12, 255, 236, 125, 248, 182, 138, 205,
+        ]),
+        name: "Raydium AMM v4",
+        kind: LabelKind::Program,
+    },
+    // CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK
+    Label {
+        address: Pubkey([
+            165, 213, 202, 158, 4, 207, 93, 181, 144, 183, 20, 186, 47, 227, 44, 177,
+            89, 19, 63, 193, 193, 146, 183, 34, 87, 253, 7, 211, 156, 176, 64, 30,
+        ]),
+        name: "Raydium CLMM",
+        kind: LabelKind::Program,
+    },
+    // whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc
+    Label {
+        address: Pubkey([
+            14, 3, 104, 95, 142, 144, 144, 83, 228, 88, 18, 28, 102, 245, 167, 106,
+            237, 199, 112, 106, 161, 28, 130, 248, 170, 149, 42, 143, 43, 120, 121, 169,
+        ]),
+        name: "Orca Whirlpool",
+        kind: LabelKind::Program,
+    },
+    // LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo
+    Label {
+        address: Pubkey([
+            4, 233, 225, 47, 188, 132, 232, 38, 201, 50, 204, 233, 226, 100, 12, 206,
+            21, 89, 12, 28, 98, 115, 176, 146, 87, 8, 186, 59, 133, 32, 176, 188,
+        ]),
+        name: "Meteora DLMM",
+        kind: LabelKind::Program,
+    },
+    // PhoeNiXZ8ByJGLkxNfZRnkUfjvmuYqLR89jjFHGqdXY
+    Label {
+        address: Pubkey([
+            5, 208, 234, 79, 51, 115, 112, 19, 165, 99, 224, 147, 72, 237, 182, 244,
+            89, 61, 145, 252, 118, 65, 249, 36, 124, 36, 65, 168, 66, 161, 187, 235,
+        ]),
+        name: "Phoenix",
+        kind: LabelKind::Program,
+    },
+    // opnb2LAfJYbRMAHHvqjCwQxanZn7ReEHp1k81EohpZb
+    Label {
+        address: Pubkey([
+            11, 254, 191, 189, 251, 171, 250, 208, 180, 101, 113, 191, 149, 140, 30, 184,
+            36, 120, 123, 176, 153, 75, 177, 132, 107, 41, 120, 7, 30, 23, 153, 190,
+        ]),
+        name: "OpenBook v2",
+        kind: LabelKind::Program,
+    },
+    // 6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P
+    Label {
+        address: Pubkey([
+            1, 86, 224, 246, 147, 102, 90, 207, 68, 219, 21, 104, 191, 23, 91, 170,
+            81, 137, 203, 151, 245, 210, 255, 59, 101, 93, 43, 182, 253, 109, 24, 176,
+        ]),
+        name: "Pump.fun",
+        kind: LabelKind::Program,
+    },
+    // EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v
+    Label {
+        address: Pubkey([
+            198, 250, 122, 243, 190, 219, 173, 58, 61, 101, 243, 106, 171, 201, 116, 49,
+            177, 187, 228, 194, 210, 246, 224, 228, 124, 166, 2, 3, 69, 47, 93, 97,
+        ]),
+        name: "USDC",
+        kind: LabelKind::Mint,
+    },
+    // Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB
+    Label {
+        address: Pubkey([
+            206, 1, 14, 96, 175, 237, 178, 39, 23, 189, 99, 25, 47, 84, 20, 90,
+            63, 150, 90, 51, 187, 130, 210, 199, 2, 158, 178, 206, 30, 32, 130, 100,
+        ]),
+        name: "USDT",
+        kind: LabelKind::Mint,
+    },
+    // So11111111111111111111111111111111111111112
+    Label {
+        address: Pubkey([
+            6, 155, 136, 87, 254, 171, 129, 132, 251, 104, 127, 99, 70, 24, 192, 53,
+            218, 196, 57, 220, 26, 235, 59, 85, 152, 160, 240, 0, 0, 0, 0, 1,
+        ]),
+        name: "Wrapped SOL",
+        kind: LabelKind::Mint,
+    },
+    // 2b1kV6DkPAnxd5ixfnxCpjxmKwqjjaYmCZfHsFu24GXo
+    Label {
+        address: Pubkey([
+            23, 146, 72, 59, 108, 138, 42, 135, 183, 71, 29, 129, 79, 149, 145, 249,
+            57, 92, 132, 10, 156, 227, 217, 244, 213, 186, 125, 58, 75, 138, 116, 158,
+        ]),
+        name: "PYUSD",
+        kind: LabelKind::Mint,
+    },
+];
+
+/// Returns the label of `address`, `None` if it is not a well-known address
+///
+/// ```
+/// # use substreams_solana_program_instructions::{labels::{label, LabelKind}, program_ids::TOKEN_PROGRAM_ID};
+/// let usdc = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".parse().unwrap();
+/// assert_eq!(label(&usdc).map(|label| label.name), Some("USDC"));
+/// assert_eq!(label(&usdc).map(|label| label.kind), Some(LabelKind::Mint));
+/// assert_eq!(label(&TOKEN_PROGRAM_ID).map(|label| label.name), Some("SPL Token"));
+/// ```
+pub fn label(address: &Pubkey) -> Option<&'static Label> {
+    LABELS.iter().find(|label| label.address == *address)
+}
+
+/// Returns the label of `program_id` if it is a well-known program
+pub fn program_label(program_id: &[u8]) -> Option<&'static str> {
+    LABELS
+        .iter()
+        .find(|label| label.kind == LabelKind::Program && label.address.as_ref() == program_id)
+        .map(|label| label.name)
+}
+
+/// Returns the label of `mint` if it is a well-known mint
+pub fn mint_label(mint: &Pubkey) -> Option<&'static str> {
+    label(mint).filter(|label| label.kind == LabelKind::Mint).map(|label| label.name)
+}
+
+impl TransferEvent {
+    /// Label of the transferred mint, `None` if unresolved or not a well-known mint
+    pub fn mint_label(&self) -> Option<&'static str> {
+        self.mint.as_ref().and_then(mint_label)
+    }
+}
@@ -0,0 +1,216 @@
+//! Native SOL movements of transactions: System program transfers, top-level and inner alike,
+//! reconciled with the accounts' lamport balance changes and the fee paid by the fee payer
+
+use {
+    crate::{
+        accounts::TransactionKeys,
+        events::{signature, EventContext},
+        instructions::{instructions, is_successful},
+        program_ids::SYSTEM_PROGRAM_ID,
+        pubkey::Pubkey,
+        system_instruction::SystemInstruction,
+    },
+    std::collections::HashMap,
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// Lamports moved by a System program instruction: `Transfer`, `TransferWithSeed`,
+/// `CreateAccount`, `CreateAccountWithSeed` or `WithdrawNonceAccount`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct LamportTransfer {
+    /// Position of the System program instruction
+    pub context: EventContext,
+    /// The funding account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub from: Pubkey,
+    /// The recipient account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub to: Pubkey,
+    /// Lamports moved
+    pub lamports: u64,
+}
+
+impl LamportTransfer {
+    /// Kind of the event, part of its [LamportTransfer::event_id]
+    pub const KIND: &'static str = "lamport_transfer";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+}
+
+/// Transaction fee debited from the fee payer, failed transactions included
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeePaid {
+    /// Position of the transaction, its first top-level instruction
+    pub context: EventContext,
+    /// The fee payer, first account of the transaction
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub payer: Pubkey,
+    /// Fee in lamports, prioritization fee included
+    pub fee: u64,
+}
+
+impl FeePaid {
+    /// Kind of the event, part of its [FeePaid::event_id]
+    pub const KIND: &'static str = "fee_paid";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+}
+
+/// Part of an account's lamport balance change not explained by System program transfers
+/// and fees: lamports moved directly by the programs owning the accounts, rent refunded by
+/// closed accounts, ...
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct UnattributedLamports {
+    /// Position of the transaction, its first top-level instruction
+    pub context: EventContext,
+    /// The account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub account: Pubkey,
+    /// Unexplained balance change, negative for debits
+    pub lamports: i128,
+}
+
+impl UnattributedLamports {
+    /// Kind of the event, part of its [UnattributedLamports::event_id]
+    pub const KIND: &'static str = "unattributed_lamports";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]. The account is
+    /// hashed as well, the accounts of a transaction sharing its context.
+    pub fn event_id(&self) -> String {
+        self.context.event_id(&format!("{}:{}", Self::KIND, self.account))
+    }
+}
+
+/// A native SOL movement
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum LamportEvent {
+    /// Fee paid by the fee payer
+    Fee(FeePaid),
+    /// A System program transfer
+    Transfer(LamportTransfer),
+    /// A balance change not explained by transfers and fees
+    Unattributed(UnattributedLamports),
+}
+
+impl LamportEvent {
+    /// Position of the event within its block
+    pub fn context(&self) -> &EventContext {
+        match self {
+            Self::Fee(event) => &event.context,
+            Self::Transfer(event) => &event.context,
+            Self::Unattributed(event) => &event.context,
+        }
+    }
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        match self {
+            Self::Fee(event) => event.event_id(),
+            Self::Transfer(event) => event.event_id(),
+            Self::Unattributed(event) => event.event_id(),
+        }
+    }
+}
+
+/// Extracts the native SOL movements of all transactions of `block`
+pub fn block_lamport_events(block: &Block) -> Vec<LamportEvent> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| lamport_events(trx, index as u32))
+        .collect()
+}
+
+/// Extracts the native SOL movements of `trx`, the `transaction_index`-th of its block: the
+/// fee first, then the System program transfers in execution order, then the unexplained
+/// balance changes by account index. Failed transactions only yield their fee.
+pub fn lamport_events(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<LamportEvent> {
+    let (meta, keys) = match (trx.meta.as_ref(), TransactionKeys::from_transaction(trx)) {
+        (Some(meta), Some(keys)) => (meta, keys),
+        _ => return vec![],
+    };
+    let key = |index: usize| keys.get(index).and_then(|key| Pubkey::try_from(key).ok());
+    let context = EventContext {
+        signature: signature(trx),
+        transaction_index,
+        ..Default::default()
+    };
+
+    let mut events = vec![];
+    let mut expected: HashMap<usize, i128> = HashMap::new();
+    if let Some(payer) = key(0) {
+        *expected.entry(0).or_default() -= meta.fee as i128;
+        events.push(LamportEvent::Fee(FeePaid {
+            context: context.clone(),
+            payer,
+            fee: meta.fee,
+        }));
+    }
+    if !is_successful(trx) {
+        return events;
+    }
+
+    for instruction in instructions(trx) {
+        if instruction.program_id != SYSTEM_PROGRAM_ID.as_ref() {
+            continue;
+        }
+        let (lamports, from, to) = match SystemInstruction::unpack(instruction.data)
+            .ok()
+            .and_then(|decoded| decoded.lamport_transfer())
+        {
+            Some(transfer) => transfer,
+            None => continue,
+        };
+        let indices = instruction.accounts.indices().unwrap_or(&[]);
+        if let (Some(&from_index), Some(&to_index)) = (indices.get(from), indices.get(to)) {
+            *expected.entry(from_index as usize).or_default() -= lamports as i128;
+            *expected.entry(to_index as usize).or_default() += lamports as i128;
+        }
+        if let (Some(from), Some(to)) = (instruction.accounts.get_pubkey(from), instruction.accounts.get_pubkey(to)) {
+            events.push(LamportEvent::Transfer(LamportTransfer {
+                context: EventContext::new(trx, transaction_index, &instruction),
+                from,
+                to,
+                lamports,
+            }));
+        }
+    }
+
+    for (index, (pre, post)) in meta.pre_balances.iter().zip(meta.post_balances.iter()).enumerate() {
+        let actual = *post as i128 - *pre as i128;
+        let unexplained = actual - expected.get(&index).copied().unwrap_or_default();
+        if unexplained == 0 {
+            continue;
+        }
+        if let Some(account) = key(index) {
+            events.push(LamportEvent::Unattributed(UnattributedLamports {
+                context: context.clone(),
+                account,
+                lamports: unexplained,
+            }));
+        }
+    }
+
+    events
+}
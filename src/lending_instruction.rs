@@ -0,0 +1,458 @@
+//! Solend and Kamino lending instruction types
+//!
+//! Both programs follow the SPL token-lending model: lenders deposit liquidity in a reserve
+//! for collateral tokens, borrowers post collateral tokens in an obligation to borrow the
+//! liquidity of other reserves, and liquidators repay unhealthy obligations in exchange for
+//! their collateral. Solend keeps the native single byte tags of SPL token-lending, Kamino
+//! is an Anchor program identified by 8 bytes discriminators.
+
+use anyhow::anyhow;
+use {
+    crate::token_instruction_2022::TokenInstruction,
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// `deposit_reserve_liquidity` discriminator, hash of `global:deposit_reserve_liquidity`
+pub const KAMINO_DEPOSIT_RESERVE_LIQUIDITY_DISCRIMINATOR: [u8; 8] = [169, 201, 30, 126, 6, 205, 102, 68];
+/// `redeem_reserve_collateral` discriminator, hash of `global:redeem_reserve_collateral`
+pub const KAMINO_REDEEM_RESERVE_COLLATERAL_DISCRIMINATOR: [u8; 8] = [234, 117, 181, 125, 185, 142, 220, 29];
+/// `deposit_obligation_collateral` discriminator, hash of `global:deposit_obligation_collateral`
+pub const KAMINO_DEPOSIT_OBLIGATION_COLLATERAL_DISCRIMINATOR: [u8; 8] = [108, 209, 4, 72, 21, 22, 118, 133];
+/// `withdraw_obligation_collateral` discriminator, hash of `global:withdraw_obligation_collateral`
+pub const KAMINO_WITHDRAW_OBLIGATION_COLLATERAL_DISCRIMINATOR: [u8; 8] = [37, 116, 205, 103, 243, 192, 92, 198];
+/// `borrow_obligation_liquidity` discriminator, hash of `global:borrow_obligation_liquidity`
+pub const KAMINO_BORROW_OBLIGATION_LIQUIDITY_DISCRIMINATOR: [u8; 8] = [121, 127, 18, 204, 73, 245, 225, 65];
+/// `repay_obligation_liquidity` discriminator, hash of `global:repay_obligation_liquidity`
+pub const KAMINO_REPAY_OBLIGATION_LIQUIDITY_DISCRIMINATOR: [u8; 8] = [145, 178, 13, 225, 76, 240, 147, 72];
+/// `liquidate_obligation_and_redeem_reserve_collateral` discriminator, hash of
+/// `global:liquidate_obligation_and_redeem_reserve_collateral`
+pub const KAMINO_LIQUIDATE_OBLIGATION_DISCRIMINATOR: [u8; 8] = [177, 71, 154, 188, 226, 133, 74, 55];
+/// `deposit_reserve_liquidity_and_obligation_collateral` discriminator, hash of
+/// `global:deposit_reserve_liquidity_and_obligation_collateral`
+pub const KAMINO_DEPOSIT_AND_COLLATERALIZE_DISCRIMINATOR: [u8; 8] = [129, 199, 4, 2, 222, 39, 26, 46];
+/// `withdraw_obligation_collateral_and_redeem_reserve_collateral` discriminator, hash of
+/// `global:withdraw_obligation_collateral_and_redeem_reserve_collateral`
+pub const KAMINO_WITHDRAW_AND_REDEEM_DISCRIMINATOR: [u8; 8] = [75, 93, 93, 220, 34, 150, 218, 196];
+/// `flash_borrow_reserve_liquidity` discriminator, hash of `global:flash_borrow_reserve_liquidity`
+pub const KAMINO_FLASH_BORROW_RESERVE_LIQUIDITY_DISCRIMINATOR: [u8; 8] = [135, 231, 52, 167, 7, 52, 212, 193];
+/// `flash_repay_reserve_liquidity` discriminator, hash of `global:flash_repay_reserve_liquidity`
+pub const KAMINO_FLASH_REPAY_RESERVE_LIQUIDITY_DISCRIMINATOR: [u8; 8] = [185, 117, 0, 203, 96, 245, 180, 186];
+
+/// Instructions of the Solend program.
+///
+/// Only the instructions moving liquidity or collateral are decoded, market and reserve
+/// administration instructions are rejected as unknown tags.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolendInstruction {
+    /// Deposit liquidity into a reserve in exchange for collateral tokens
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Source liquidity token account
+    ///   1. `[writable]` Destination collateral token account
+    ///   2. `[writable]` Reserve
+    ///   3. `[writable]` Reserve liquidity supply
+    ///   4. `[writable]` Reserve collateral mint
+    ///   5. `[]` Lending market
+    ///   6. `[]` Lending market authority
+    ///   7. `[signer]` User transfer authority
+    ///   8. `[]` Token program
+    DepositReserveLiquidity {
+        /// Amount of liquidity to deposit
+        liquidity_amount: u64,
+    },
+    /// Redeem collateral tokens for the reserve liquidity they represent
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Source collateral token account
+    ///   1. `[writable]` Destination liquidity token account
+    ///   2. `[writable]` Reserve
+    ///   3. `[writable]` Reserve collateral mint
+    ///   4. `[writable]` Reserve liquidity supply
+    ///   5. `[]` Lending market
+    ///   6. `[]` Lending market authority
+    ///   7. `[signer]` User transfer authority
+    ///   8. `[]` Token program
+    RedeemReserveCollateral {
+        /// Amount of collateral tokens to redeem
+        collateral_amount: u64,
+    },
+    /// Accrue interest and refresh the market price of an obligation's reserves, required
+    /// before any borrow, withdrawal or liquidation
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Obligation
+    ///   1. ..1+N `[]` Reserves of the obligation's deposits then borrows
+    RefreshObligation,
+    /// Deposit collateral tokens in an obligation
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Source collateral token account
+    ///   1. `[writable]` Destination deposit reserve collateral supply
+    ///   2. `[]` Deposit reserve
+    ///   3. `[writable]` Obligation
+    ///   4. `[]` Lending market
+    ///   5. `[signer]` Obligation owner
+    ///   6. `[signer]` User transfer authority
+    ///   7. `[]` Token program
+    DepositObligationCollateral {
+        /// Amount of collateral tokens to deposit
+        collateral_amount: u64,
+    },
+    /// Withdraw collateral tokens from an obligation
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Source withdraw reserve collateral supply
+    ///   1. `[writable]` Destination collateral token account
+    ///   2. `[]` Withdraw reserve
+    ///   3. `[writable]` Obligation
+    ///   4. `[]` Lending market
+    ///   5. `[]` Lending market authority
+    ///   6. `[signer]` Obligation owner
+    ///   7. `[]` Token program
+    WithdrawObligationCollateral {
+        /// Amount of collateral tokens to withdraw, `u64::MAX` for as much as possible
+        collateral_amount: u64,
+    },
+    /// Borrow liquidity from a reserve against an obligation's collateral
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Source borrow reserve liquidity supply
+    ///   1. `[writable]` Destination liquidity token account
+    ///   2. `[writable]` Borrow reserve
+    ///   3. `[writable]` Borrow reserve liquidity fee receiver
+    ///   4. `[writable]` Obligation
+    ///   5. `[]` Lending market
+    ///   6. `[]` Lending market authority
+    ///   7. `[signer]` Obligation owner
+    ///   8. `[]` Token program
+    ///   9. `[writable]` (Optional) Host fee receiver
+    BorrowObligationLiquidity {
+        /// Amount of liquidity to borrow, `u64::MAX` for as much as possible
+        liquidity_amount: u64,
+    },
+    /// Repay borrowed liquidity of an obligation
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Source liquidity token account
+    ///   1. `[writable]` Destination repay reserve liquidity supply
+    ///   2. `[writable]` Repay reserve
+    ///   3. `[writable]` Obligation
+    ///   4. `[]` Lending market
+    ///   5. `[signer]` User transfer authority
+    ///   6. `[]` Token program
+    RepayObligationLiquidity {
+        /// Amount of liquidity to repay, `u64::MAX` for the whole borrow
+        liquidity_amount: u64,
+    },
+    /// Repay the borrowed liquidity of an unhealthy obligation, receiving its collateral
+    /// tokens at a discount
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Source liquidity token account
+    ///   1. `[writable]` Destination collateral token account
+    ///   2. `[writable]` Repay reserve
+    ///   3. `[writable]` Repay reserve liquidity supply
+    ///   4. `[]` Withdraw reserve
+    ///   5. `[writable]` Withdraw reserve collateral supply
+    ///   6. `[writable]` Obligation
+    ///   7. `[]` Lending market
+    ///   8. `[]` Lending market authority
+    ///   9. `[signer]` User transfer authority
+    ///   10. `[]` Token program
+    LiquidateObligation {
+        /// Amount of liquidity to repay, `u64::MAX` for up to the close factor
+        liquidity_amount: u64,
+    },
+    /// Combines `DepositReserveLiquidity` and `DepositObligationCollateral`
+    DepositReserveLiquidityAndObligationCollateral {
+        /// Amount of liquidity to deposit
+        liquidity_amount: u64,
+    },
+    /// Combines `WithdrawObligationCollateral` and `RedeemReserveCollateral`
+    WithdrawObligationCollateralAndRedeemReserveCollateral {
+        /// Amount of collateral tokens to withdraw
+        collateral_amount: u64,
+    },
+    /// Combines `LiquidateObligation` and `RedeemReserveCollateral`, the liquidator receiving
+    /// liquidity instead of collateral tokens
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Source liquidity token account
+    ///   1. `[writable]` Destination collateral token account
+    ///   2. `[writable]` Destination liquidity token account
+    ///   3. `[writable]` Repay reserve
+    ///   4. `[writable]` Repay reserve liquidity supply
+    ///   5. `[writable]` Withdraw reserve
+    ///   6. `[writable]` Withdraw reserve collateral mint
+    ///   7. `[writable]` Withdraw reserve collateral supply
+    ///   8. `[writable]` Withdraw reserve liquidity supply
+    ///   9. `[writable]` Withdraw reserve liquidity fee receiver
+    ///   10. `[writable]` Obligation
+    ///   11. `[]` Lending market
+    ///   12. `[]` Lending market authority
+    ///   13. `[signer]` User transfer authority
+    ///   14. `[]` Token program
+    LiquidateObligationAndRedeemReserveCollateral {
+        /// Amount of liquidity to repay, `u64::MAX` for up to the close factor
+        liquidity_amount: u64,
+    },
+    /// Borrow liquidity from a reserve, to be repaid by a `FlashRepayReserveLiquidity`
+    /// instruction of the same transaction
+    FlashBorrowReserveLiquidity {
+        /// Amount of liquidity to borrow
+        liquidity_amount: u64,
+    },
+    /// Repay liquidity flash borrowed in the same transaction, fees included
+    FlashRepayReserveLiquidity {
+        /// Amount of liquidity borrowed, fees excluded
+        liquidity_amount: u64,
+        /// Index of the `FlashBorrowReserveLiquidity` instruction in the transaction
+        borrow_instruction_index: u8,
+    },
+}
+impl SolendInstruction {
+    /// Unpacks a byte buffer into a [SolendInstruction](enum.SolendInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, Error> {
+        let (&tag, rest) = input.split_first().ok_or(anyhow!("Invalid Solend Instruction"))?;
+        if tag == 7 {
+            return Ok(Self::RefreshObligation);
+        }
+        let (amount, rest) = match tag {
+            4 | 5 | 8..=12 | 14 | 15 | 17 | 19 | 20 => TokenInstruction::unpack_u64(rest)?,
+            _ => return Err(anyhow!("Invalid Solend Instruction - unpack didn't match any tag value: {}", tag)),
+        };
+        Ok(match tag {
+            4 => Self::DepositReserveLiquidity { liquidity_amount: amount },
+            5 => Self::RedeemReserveCollateral { collateral_amount: amount },
+            8 => Self::DepositObligationCollateral { collateral_amount: amount },
+            9 => Self::WithdrawObligationCollateral { collateral_amount: amount },
+            10 => Self::BorrowObligationLiquidity { liquidity_amount: amount },
+            11 => Self::RepayObligationLiquidity { liquidity_amount: amount },
+            12 => Self::LiquidateObligation { liquidity_amount: amount },
+            14 => Self::DepositReserveLiquidityAndObligationCollateral { liquidity_amount: amount },
+            15 => Self::WithdrawObligationCollateralAndRedeemReserveCollateral { collateral_amount: amount },
+            17 => Self::LiquidateObligationAndRedeemReserveCollateral { liquidity_amount: amount },
+            19 => Self::FlashBorrowReserveLiquidity { liquidity_amount: amount },
+            20 => {
+                let (&borrow_instruction_index, _rest) =
+                    rest.split_first().ok_or(anyhow!("Invalid Solend Instruction - 20"))?;
+                Self::FlashRepayReserveLiquidity {
+                    liquidity_amount: amount,
+                    borrow_instruction_index,
+                }
+            }
+            _ => unreachable!(),
+        })
+    }
+}
+
+/// Instructions of the Kamino lending program.
+///
+/// Only the instructions moving liquidity or collateral are decoded, other instructions
+/// are rejected as unknown discriminators.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KaminoLendingInstruction {
+    /// Deposit liquidity into a reserve in exchange for collateral tokens
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` Owner
+    ///   1. `[writable]` Reserve
+    ///   2. `[]` Lending market
+    ///   3. `[]` Lending market authority
+    ///   4. `[]` Reserve liquidity mint
+    ///   5. `[writable]` Reserve liquidity supply
+    ///   6. `[writable]` Reserve collateral mint
+    ///   7. `[writable]` User source liquidity token account
+    ///   8. `[writable]` User destination collateral token account
+    ///   9. `[]` Collateral token program
+    ///   10. `[]` Liquidity token program
+    ///   11. `[]` Instructions sysvar
+    DepositReserveLiquidity {
+        /// Amount of liquidity to deposit
+        liquidity_amount: u64,
+    },
+    /// Redeem collateral tokens for the reserve liquidity they represent
+    RedeemReserveCollateral {
+        /// Amount of collateral tokens to redeem
+        collateral_amount: u64,
+    },
+    /// Deposit collateral tokens in an obligation
+    DepositObligationCollateral {
+        /// Amount of collateral tokens to deposit
+        collateral_amount: u64,
+    },
+    /// Withdraw collateral tokens from an obligation
+    WithdrawObligationCollateral {
+        /// Amount of collateral tokens to withdraw, `u64::MAX` for as much as possible
+        collateral_amount: u64,
+    },
+    /// Borrow liquidity from a reserve against an obligation's collateral
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` Owner
+    ///   1. `[writable]` Obligation
+    ///   2. `[]` Lending market
+    ///   3. `[]` Lending market authority
+    ///   4. `[writable]` Borrow reserve
+    ///   5. `[]` Borrow reserve liquidity mint
+    ///   6. `[writable]` Reserve source liquidity supply
+    ///   7. `[writable]` Borrow reserve liquidity fee receiver
+    ///   8. `[writable]` User destination liquidity token account
+    ///   9. `[writable]` (Optional) Referrer token state
+    ///   10. `[]` Token program
+    ///   11. `[]` Instructions sysvar
+    BorrowObligationLiquidity {
+        /// Amount of liquidity to borrow, `u64::MAX` for as much as possible
+        liquidity_amount: u64,
+    },
+    /// Repay borrowed liquidity of an obligation
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` Owner
+    ///   1. `[writable]` Obligation
+    ///   2. `[]` Lending market
+    ///   3. `[writable]` Repay reserve
+    ///   4. `[]` Reserve liquidity mint
+    ///   5. `[writable]` Reserve destination liquidity supply
+    ///   6. `[writable]` User source liquidity token account
+    ///   7. `[]` Token program
+    ///   8. `[]` Instructions sysvar
+    RepayObligationLiquidity {
+        /// Amount of liquidity to repay, `u64::MAX` for the whole borrow
+        liquidity_amount: u64,
+    },
+    /// Repay the borrowed liquidity of an unhealthy obligation, receiving its collateral at
+    /// a discount, redeemed for liquidity
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` Liquidator
+    ///   1. `[writable]` Obligation
+    ///   2. `[]` Lending market
+    ///   3. `[]` Lending market authority
+    ///   4. `[writable]` Repay reserve
+    ///   5. `[]` Repay reserve liquidity mint
+    ///   6. `[writable]` Repay reserve liquidity supply
+    ///   7. `[writable]` Withdraw reserve
+    ///   8. `[]` Withdraw reserve liquidity mint
+    ///   9. `[writable]` Withdraw reserve collateral mint
+    ///   10. `[writable]` Withdraw reserve collateral supply
+    ///   11. `[writable]` Withdraw reserve liquidity supply
+    ///   12. `[writable]` Withdraw reserve liquidity fee receiver
+    ///   13. `[writable]` User source liquidity token account
+    ///   14. `[writable]` User destination collateral token account
+    ///   15. `[writable]` User destination liquidity token account
+    ///   16. `[]` Collateral token program
+    ///   17. `[]` Repay liquidity token program
+    ///   18. `[]` Withdraw liquidity token program
+    ///   19. `[]` Instructions sysvar
+    LiquidateObligationAndRedeemReserveCollateral {
+        /// Amount of liquidity to repay, `u64::MAX` for up to the close factor
+        liquidity_amount: u64,
+        /// Minimum liquidity to receive for the seized collateral
+        min_acceptable_received_liquidity_amount: u64,
+        /// Maximum loan-to-value allowed for the liquidation, in percent
+        max_allowed_ltv_override_percent: u64,
+    },
+    /// Combines `DepositReserveLiquidity` and `DepositObligationCollateral`
+    DepositReserveLiquidityAndObligationCollateral {
+        /// Amount of liquidity to deposit
+        liquidity_amount: u64,
+    },
+    /// Combines `WithdrawObligationCollateral` and `RedeemReserveCollateral`
+    WithdrawObligationCollateralAndRedeemReserveCollateral {
+        /// Amount of collateral tokens to withdraw
+        collateral_amount: u64,
+    },
+    /// Borrow liquidity from a reserve, to be repaid by a `FlashRepayReserveLiquidity`
+    /// instruction of the same transaction
+    FlashBorrowReserveLiquidity {
+        /// Amount of liquidity to borrow
+        liquidity_amount: u64,
+    },
+    /// Repay liquidity flash borrowed in the same transaction, fees included
+    FlashRepayReserveLiquidity {
+        /// Amount of liquidity borrowed, fees excluded
+        liquidity_amount: u64,
+        /// Index of the `FlashBorrowReserveLiquidity` instruction in the transaction
+        borrow_instruction_index: u8,
+    },
+}
+impl KaminoLendingInstruction {
+    /// Unpacks a byte buffer into a [KaminoLendingInstruction](enum.KaminoLendingInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, Error> {
+        let (discriminator, rest) =
+            TokenInstruction::unpack_bytes(input, 8).map_err(|_| anyhow!("Invalid Kamino Lending Instruction"))?;
+        let discriminator: [u8; 8] = discriminator.try_into().map_err(|_| anyhow!("Invalid Kamino Lending Instruction"))?;
+        // All decoded instructions start with an amount, unpacked once the discriminator matched
+        let amount = TokenInstruction::unpack_u64(rest);
+        Ok(match discriminator {
+            KAMINO_DEPOSIT_RESERVE_LIQUIDITY_DISCRIMINATOR => Self::DepositReserveLiquidity { liquidity_amount: amount?.0 },
+            KAMINO_REDEEM_RESERVE_COLLATERAL_DISCRIMINATOR => Self::RedeemReserveCollateral { collateral_amount: amount?.0 },
+            KAMINO_DEPOSIT_OBLIGATION_COLLATERAL_DISCRIMINATOR => {
+                Self::DepositObligationCollateral { collateral_amount: amount?.0 }
+            }
+            KAMINO_WITHDRAW_OBLIGATION_COLLATERAL_DISCRIMINATOR => {
+                Self::WithdrawObligationCollateral { collateral_amount: amount?.0 }
+            }
+            KAMINO_BORROW_OBLIGATION_LIQUIDITY_DISCRIMINATOR => {
+                Self::BorrowObligationLiquidity { liquidity_amount: amount?.0 }
+            }
+            KAMINO_REPAY_OBLIGATION_LIQUIDITY_DISCRIMINATOR => Self::RepayObligationLiquidity { liquidity_amount: amount?.0 },
+            KAMINO_LIQUIDATE_OBLIGATION_DISCRIMINATOR => {
+                let (liquidity_amount, rest) = amount?;
+                let (min_acceptable_received_liquidity_amount, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (max_allowed_ltv_override_percent, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::LiquidateObligationAndRedeemReserveCollateral {
+                    liquidity_amount,
+                    min_acceptable_received_liquidity_amount,
+                    max_allowed_ltv_override_percent,
+                }
+            }
+            KAMINO_DEPOSIT_AND_COLLATERALIZE_DISCRIMINATOR => {
+                Self::DepositReserveLiquidityAndObligationCollateral { liquidity_amount: amount?.0 }
+            }
+            KAMINO_WITHDRAW_AND_REDEEM_DISCRIMINATOR => {
+                Self::WithdrawObligationCollateralAndRedeemReserveCollateral { collateral_amount: amount?.0 }
+            }
+            KAMINO_FLASH_BORROW_RESERVE_LIQUIDITY_DISCRIMINATOR => {
+                Self::FlashBorrowReserveLiquidity { liquidity_amount: amount?.0 }
+            }
+            KAMINO_FLASH_REPAY_RESERVE_LIQUIDITY_DISCRIMINATOR => {
+                let (liquidity_amount, rest) = amount?;
+                let (&borrow_instruction_index, _rest) =
+                    rest.split_first().ok_or(anyhow!("Unable to unpack borrow instruction index"))?;
+                Self::FlashRepayReserveLiquidity {
+                    liquidity_amount,
+                    borrow_instruction_index,
+                }
+            }
+            _ => return Err(anyhow!("Invalid Kamino Lending Instruction - unpack didn't match any discriminator: {:?}", discriminator)),
+        })
+    }
+}
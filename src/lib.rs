@@ -1,4 +1,116 @@
+pub mod account_data;
+pub mod accounts;
+pub mod adoption;
+pub mod account_state;
+pub mod amount;
+#[cfg(feature = "associated-token")]
+pub mod associated_token_instruction;
+pub mod attribution;
+pub mod batches;
+pub mod block_version;
+#[cfg(feature = "canonical-json")]
+pub mod canonical_json;
 pub mod token_instruction_2022;
 pub mod option;
+#[cfg(feature = "oracles")]
+pub mod oracle_instruction;
 pub mod pubkey;
 pub mod transfer_fee_instruction;
+#[cfg(feature = "automation")]
+pub mod automation_instruction;
+#[cfg(feature = "bpf-loader")]
+pub mod bpf_loader_upgradeable_instruction;
+#[cfg(feature = "vote")]
+pub mod commission;
+pub mod confidential_mint_burn_instruction;
+pub mod confidential_transfer_instruction;
+pub mod coverage;
+pub mod data_cap;
+pub mod decimals;
+pub mod dedup;
+pub mod default_account_state_instruction;
+pub mod deltas;
+#[cfg(any(feature = "raydium", feature = "meteora"))]
+pub mod dex_trades;
+#[cfg(feature = "drift")]
+pub mod drift_events;
+pub mod ephemeral;
+pub mod epoch;
+pub mod events;
+pub mod fee_math;
+pub mod filters;
+pub mod group_member_pointer_instruction;
+pub mod init_order;
+pub mod instructions;
+pub mod interest_bearing_mint;
+#[cfg(feature = "labels")]
+pub mod labels;
+#[cfg(feature = "lending")]
+pub mod lending_instruction;
+#[cfg(feature = "system")]
+pub mod lamports;
+pub mod lint;
+#[cfg(feature = "liquid-staking")]
+pub mod marinade_instruction;
+#[cfg(feature = "meteora")]
+pub mod meteora_dlmm;
+pub mod metrics;
+pub mod mint_class;
+pub mod mint_risk;
+#[cfg(feature = "name-service")]
+pub mod name_service_instruction;
+#[cfg(feature = "system")]
+pub mod nonce;
+pub mod normalize;
+pub mod ordering;
+#[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
+pub mod parallel;
+pub mod pass_through;
+pub mod pausable_instruction;
+pub mod pointers;
+pub mod pretty;
+pub mod program_ids;
+pub mod program_logs;
+#[cfg(feature = "raydium")]
+pub mod raydium_instruction;
+pub mod records;
+pub mod rent;
+pub mod replay;
+pub mod return_data;
+pub mod routes;
+pub mod sampling;
+pub mod scaled_ui_amount_instruction;
+pub mod security;
+#[cfg(feature = "precompiles")]
+pub mod sig_verify_instruction;
+pub mod simulation;
+pub mod snapshot;
+pub mod sniff;
+#[cfg(feature = "stats")]
+pub mod stats;
+#[cfg(feature = "stake")]
+pub mod stake_instruction;
+#[cfg(feature = "stake")]
+pub mod stake_lifecycle;
+#[cfg(feature = "liquid-staking")]
+pub mod stake_pool_instruction;
+pub mod store_keys;
+#[cfg(feature = "system")]
+pub mod system_instruction;
+pub mod token_group_instruction;
+pub mod token_metadata_instruction;
+pub mod trailing_bytes;
+#[cfg(feature = "tx-summary")]
+pub mod tx_summary;
+pub mod unified;
+#[cfg(feature = "vesting")]
+pub mod vesting_instruction;
+pub mod visitor;
+#[cfg(feature = "vote")]
+pub mod vote_instruction;
+pub mod window;
+pub mod wire_format;
+#[cfg(feature = "wormhole")]
+pub mod wormhole;
+#[cfg(feature = "serde-traits")]
+pub mod serialization;
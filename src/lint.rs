@@ -0,0 +1,386 @@
+//! Consistency checks flagging suspicious token patterns in successful transactions
+//!
+//! Findings are not errors, the transactions executed successfully: they point at token
+//! movements that do not add up and deserve a closer look, typically by fraud monitoring.
+
+use {
+    crate::{
+        accounts::TransactionKeys,
+        amount::raw_amount,
+        events::{signature, EventContext},
+        fee_math::calculate_fee,
+        instructions::{instructions, is_successful, InstructionRef},
+        program_ids::{is_token_2022_program, is_token_program},
+        pubkey::Pubkey,
+        token_instruction_2022::TokenInstruction,
+        transfer_fee_instruction::TransferFeeInstruction,
+    },
+    std::collections::{HashMap, HashSet},
+    substreams_solana_core::pb::sf::solana::r#type::v1::{ConfirmedTransaction, TokenBalance},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// Wrapped SOL mint, whose balances also move through lamport transfers and `SyncNative`
+const NATIVE_MINT: &str = "So11111111111111111111111111111111111111112";
+
+/// A suspicious token pattern
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Finding {
+    /// The balance of a token account changed differently than its transfers, mints and burns
+    /// account for
+    BalanceMismatch {
+        /// Position of the last token instruction of the account, the first top-level
+        /// instruction if no token instruction has the account
+        context: EventContext,
+        /// The token account
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        account: Pubkey,
+        /// Balance change implied by the decoded instructions
+        expected_delta: i128,
+        /// Balance change between the pre and post token balances
+        actual_delta: i128,
+    },
+    /// A `TransferCheckedWithFee` whose fee differs from the one computed from the mint's
+    /// transfer fee configuration
+    FeeMismatch {
+        /// Position of the `TransferCheckedWithFee` instruction
+        context: EventContext,
+        /// The mint
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        mint: Pubkey,
+        /// Amount transferred
+        amount: u64,
+        /// Fee stated by the instruction
+        fee: u64,
+        /// Fee computed from the mint's configuration
+        expected_fee: u64,
+    },
+    /// A token account initialized at an address that already held a token account before
+    /// the transaction, or earlier in the transaction
+    AccountReinitialized {
+        /// Position of the `InitializeAccount` instruction
+        context: EventContext,
+        /// The token account
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        account: Pubkey,
+    },
+}
+
+impl Finding {
+    /// Position of the finding within its block
+    pub fn context(&self) -> &EventContext {
+        match self {
+            Self::BalanceMismatch { context, .. } => context,
+            Self::FeeMismatch { context, .. } => context,
+            Self::AccountReinitialized { context, .. } => context,
+        }
+    }
+
+    /// Kind of the finding
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::BalanceMismatch { .. } => "balance_mismatch",
+            Self::FeeMismatch { .. } => "fee_mismatch",
+            Self::AccountReinitialized { .. } => "account_reinitialized",
+        }
+    }
+
+    /// Deterministic identifier of the finding, see [EventContext::event_id]. Balance
+    /// mismatches also hash their account, as the accounts of one instruction may all mismatch.
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::{events::EventContext, lint::Finding, pubkey::Pubkey};
+    /// let mismatch = |account: [u8; 32]| Finding::BalanceMismatch {
+    ///     context: EventContext::default(),
+    ///     account: Pubkey::from(account),
+    ///     expected_delta: 10,
+    ///     actual_delta: 20,
+    /// };
+    /// assert_ne!(mismatch([1; 32]).event_id(), mismatch([2; 32]).event_id());
+    /// ```
+    pub fn event_id(&self) -> String {
+        match self {
+            Self::BalanceMismatch { context, account, .. } => context.event_id(&format!("{}:{}", self.kind(), account)),
+            _ => self.context().event_id(self.kind()),
+        }
+    }
+}
+
+/// Transfer fee parameters of a mint
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct TransferFee {
+    basis_points: u16,
+    maximum_fee: u64,
+}
+
+/// Expected balance change of a token account
+#[derive(Clone, Copy, Debug, Default)]
+struct Delta {
+    /// Exact change implied by the instructions
+    exact: i128,
+    /// Amount that may have been withheld as transfer fees on top of `exact`
+    withheld_allowance: i128,
+}
+
+/// Runs the checks of this module over transactions. Transfer fee configurations are learned
+/// from `InitializeTransferFeeConfig` instructions, those of mints initialized earlier must be
+/// provided through [Linter::with_transfer_fee] for their fees to be checked.
+#[derive(Clone, Debug, Default)]
+pub struct Linter {
+    transfer_fees: HashMap<Pubkey, TransferFee>,
+}
+
+impl Linter {
+    /// Registers the transfer fee in effect for `mint`
+    pub fn with_transfer_fee(mut self, mint: Pubkey, basis_points: u16, maximum_fee: u64) -> Self {
+        self.transfer_fees.insert(mint, TransferFee { basis_points, maximum_fee });
+        self
+    }
+
+    /// Checks a successful transaction, the `transaction_index`-th of its block. Failed
+    /// transactions yield no finding.
+    pub fn lint(&self, trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<Finding> {
+        let (meta, keys) = match (trx.meta.as_ref(), TransactionKeys::from_transaction(trx)) {
+            (Some(meta), Some(keys)) if is_successful(trx) => (meta, keys),
+            _ => return vec![],
+        };
+        let pre_balances = balances_by_index(&meta.pre_token_balances);
+        let post_balances = balances_by_index(&meta.post_token_balances);
+
+        let mut findings = vec![];
+        let mut transfer_fees = self.transfer_fees.clone();
+        let mut deltas: HashMap<u32, Delta> = HashMap::new();
+        let mut uncertain: HashSet<u32> = HashSet::new();
+        let mut initialized: HashSet<u32> = HashSet::new();
+        let mut used: HashSet<u32> = pre_balances.keys().copied().collect();
+        let mut last_instructions: HashMap<u32, InstructionRef> = HashMap::new();
+
+        for instruction in instructions(trx) {
+            if !is_token_program(instruction.program_id) {
+                continue;
+            }
+            let indices = match instruction.accounts.indices() {
+                Some(indices) => indices,
+                None => continue,
+            };
+            for &index in indices {
+                last_instructions.insert(index as u32, instruction);
+            }
+            let index = |i: usize| indices.get(i).map(|&index| index as u32);
+            let decoded = match TokenInstruction::unpack(instruction.data) {
+                Ok(decoded) => decoded,
+                Err(_) => {
+                    uncertain.extend(indices.iter().map(|&index| index as u32));
+                    continue;
+                }
+            };
+            // Plain transfers of token 2022 may withhold a fee from the destination
+            let allowance = |amount: u64| {
+                if is_token_2022_program(instruction.program_id) {
+                    amount
+                } else {
+                    0
+                }
+            };
+
+            match decoded {
+                #[allow(deprecated)]
+                TokenInstruction::Transfer { amount } => {
+                    let amount = raw_amount(amount);
+                    debit(&mut deltas, index(0), amount);
+                    credit(&mut deltas, index(1), amount, allowance(amount));
+                }
+                TokenInstruction::TransferChecked { amount, .. } => {
+                    let amount = raw_amount(amount);
+                    debit(&mut deltas, index(0), amount);
+                    credit(&mut deltas, index(2), amount, allowance(amount));
+                }
+                TokenInstruction::MintTo { amount } | TokenInstruction::MintToChecked { amount, .. } => {
+                    credit(&mut deltas, index(1), raw_amount(amount), 0);
+                }
+                TokenInstruction::Burn { amount } | TokenInstruction::BurnChecked { amount, .. } => {
+                    debit(&mut deltas, index(0), raw_amount(amount));
+                }
+                TokenInstruction::TransferFeeExtension(TransferFeeInstruction::TransferCheckedWithFee {
+                    amount,
+                    fee,
+                    ..
+                }) => {
+                    let (amount, fee) = (raw_amount(amount), raw_amount(fee));
+                    debit(&mut deltas, index(0), amount);
+                    credit(&mut deltas, index(2), amount.saturating_sub(fee), 0);
+                    let mint = match instruction.accounts.get_pubkey(1) {
+                        Some(mint) => mint,
+                        None => continue,
+                    };
+                    if let Some(config) = transfer_fees.get(&mint) {
+                        let expected_fee = calculate_fee(amount, config.basis_points, config.maximum_fee);
+                        if expected_fee != Some(fee) {
+                            findings.push(Finding::FeeMismatch {
+                                context: EventContext::new(trx, transaction_index, &instruction),
+                                mint,
+                                amount,
+                                fee,
+                                expected_fee: expected_fee.unwrap_or(u64::MAX),
+                            });
+                        }
+                    }
+                }
+                TokenInstruction::TransferFeeExtension(TransferFeeInstruction::InitializeTransferFeeConfig {
+                    transfer_fee_basis_points,
+                    maximum_fee,
+                    ..
+                }) => {
+                    if let Some(mint) = instruction.accounts.get_pubkey(0) {
+                        let fee = TransferFee {
+                            basis_points: transfer_fee_basis_points,
+                            maximum_fee: raw_amount(maximum_fee),
+                        };
+                        transfer_fees.insert(mint, fee);
+                    }
+                }
+                // Moves withheld, wrapped SOL or confidential amounts in and out of balances
+                TokenInstruction::TransferFeeExtension(_)
+                | TokenInstruction::SyncNative
+                | TokenInstruction::ConfidentialTransferExtension(_)
+                | TokenInstruction::ConfidentialMintBurnExtension(_) => {
+                    uncertain.extend(indices.iter().map(|&index| index as u32));
+                }
+                TokenInstruction::InitializeAccount
+                | TokenInstruction::InitializeAccount2 { .. }
+                | TokenInstruction::InitializeAccount3 { .. } => {
+                    let account_index = match index(0) {
+                        Some(account_index) => account_index,
+                        None => continue,
+                    };
+                    if !used.insert(account_index) {
+                        if let Some(account) = instruction.accounts.get_pubkey(0) {
+                            findings.push(Finding::AccountReinitialized {
+                                context: EventContext::new(trx, transaction_index, &instruction),
+                                account,
+                            });
+                        }
+                    }
+                    initialized.insert(account_index);
+                }
+                _ => {}
+            }
+        }
+
+        let mut indices: Vec<u32> = deltas.keys().chain(post_balances.keys()).copied().collect();
+        indices.sort_unstable();
+        indices.dedup();
+        for index in indices {
+            if uncertain.contains(&index) {
+                continue;
+            }
+            // Closed accounts drop out of post balances, wrapped SOL also moves through lamports
+            let post = match post_balances.get(&index) {
+                Some(post) if post.mint != NATIVE_MINT => post,
+                _ => continue,
+            };
+            let pre_amount = match pre_balances.get(&index) {
+                Some(pre) => amount_of(pre),
+                None if initialized.contains(&index) => Some(0),
+                None => None,
+            };
+            let (pre_amount, post_amount) = match (pre_amount, amount_of(post)) {
+                (Some(pre_amount), Some(post_amount)) => (pre_amount, post_amount),
+                _ => continue,
+            };
+            let actual_delta = post_amount as i128 - pre_amount as i128;
+            let delta = deltas.get(&index).copied().unwrap_or_default();
+            if actual_delta <= delta.exact && actual_delta >= delta.exact - delta.withheld_allowance {
+                continue;
+            }
+            if let Some(account) = keys.get(index as usize).and_then(|key| Pubkey::try_from(key).ok()) {
+                let context = match last_instructions.get(&index) {
+                    Some(instruction) => EventContext::new(trx, transaction_index, instruction),
+                    None => EventContext {
+                        signature: signature(trx),
+                        transaction_index,
+                        ..Default::default()
+                    },
+                };
+                findings.push(Finding::BalanceMismatch {
+                    context,
+                    account,
+                    expected_delta: delta.exact,
+                    actual_delta,
+                });
+            }
+        }
+
+        findings
+    }
+}
+
+/// Checks a successful transaction, the `transaction_index`-th of its block, with a default
+/// [Linter]
+///
+/// ```
+/// # use substreams_solana_program_instructions::{lint::{lint, Finding}, program_ids::TOKEN_PROGRAM_ID};
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::{CompiledInstruction, ConfirmedTransaction, Message, TokenBalance, Transaction, TransactionStatusMeta, UiTokenAmount};
+/// let balance = |account_index: u32, amount: &str| TokenBalance {
+///     account_index,
+///     mint: "mint".to_string(),
+///     ui_token_amount: Some(UiTokenAmount { amount: amount.to_string(), ..Default::default() }),
+///     ..Default::default()
+/// };
+/// let trx = ConfirmedTransaction {
+///     transaction: Some(Transaction {
+///         message: Some(Message {
+///             // Source, destination, owner, token program
+///             account_keys: vec![vec![1; 32], vec![2; 32], vec![3; 32], TOKEN_PROGRAM_ID.as_ref().to_vec()],
+///             // Transfer of 10
+///             instructions: vec![CompiledInstruction { program_id_index: 3, accounts: vec![0, 1, 2], data: vec![3, 10, 0, 0, 0, 0, 0, 0, 0] }],
+///             ..Default::default()
+///         }),
+///         ..Default::default()
+///     }),
+///     meta: Some(TransactionStatusMeta {
+///         pre_token_balances: vec![balance(0, "10"), balance(1, "0")],
+///         post_token_balances: vec![balance(0, "0"), balance(1, "20")],
+///         ..Default::default()
+///     }),
+/// };
+///
+/// let findings = lint(&trx, 0);
+/// assert!(matches!(findings[..], [Finding::BalanceMismatch { expected_delta: 10, actual_delta: 20, .. }]));
+/// assert_eq!((findings[0].context().instruction_index, findings[0].kind()), (0, "balance_mismatch"));
+/// ```
+pub fn lint(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<Finding> {
+    Linter::default().lint(trx, transaction_index)
+}
+
+fn credit(deltas: &mut HashMap<u32, Delta>, index: Option<u32>, amount: u64, withheld_allowance: u64) {
+    if let Some(index) = index {
+        let delta = deltas.entry(index).or_default();
+        delta.exact += amount as i128;
+        delta.withheld_allowance += withheld_allowance as i128;
+    }
+}
+
+fn debit(deltas: &mut HashMap<u32, Delta>, index: Option<u32>, amount: u64) {
+    if let Some(index) = index {
+        deltas.entry(index).or_default().exact -= amount as i128;
+    }
+}
+
+fn balances_by_index(balances: &[TokenBalance]) -> HashMap<u32, &TokenBalance> {
+    balances.iter().map(|balance| (balance.account_index, balance)).collect()
+}
+
+fn amount_of(balance: &TokenBalance) -> Option<u64> {
+    balance.ui_token_amount.as_ref()?.amount.parse().ok()
+}
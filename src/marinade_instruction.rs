@@ -0,0 +1,205 @@
+//! Marinade liquid staking instruction types
+//!
+//! Marinade stakes the deposited SOL across validators and mints mSOL in exchange. mSOL is
+//! turned back into SOL either immediately through the protocol's liquidity pool, for a fee
+//! (`LiquidUnstake`), or at the end of the epoch through a ticket (`OrderUnstake`, then
+//! `Claim`).
+
+use anyhow::anyhow;
+use {
+    crate::token_instruction_2022::TokenInstruction,
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+use crate::pubkey::Pubkey;
+
+/// mSOL mint, `mSoLzYCxHdYgdzU16g5QSh3i5K3z3KZK7ytfqcJm7So`
+pub const MSOL_MINT: Pubkey = Pubkey([
+    11, 98, 186, 7, 79, 114, 44, 157, 65, 20, 242, 216, 247, 10, 0, 198,
+    96, 2, 51, 123, 155, 249, 12, 135, 54, 87, 166, 210, 1, 219, 76, 128,
+]);
+
+/// `deposit` discriminator, hash of `global:deposit`
+pub const DEPOSIT_DISCRIMINATOR: [u8; 8] = [242, 35, 198, 137, 82, 225, 242, 182];
+/// `deposit_stake_account` discriminator, hash of `global:deposit_stake_account`
+pub const DEPOSIT_STAKE_ACCOUNT_DISCRIMINATOR: [u8; 8] = [110, 130, 115, 41, 164, 102, 2, 59];
+/// `liquid_unstake` discriminator, hash of `global:liquid_unstake`
+pub const LIQUID_UNSTAKE_DISCRIMINATOR: [u8; 8] = [30, 30, 119, 240, 191, 227, 12, 16];
+/// `order_unstake` discriminator, hash of `global:order_unstake`
+pub const ORDER_UNSTAKE_DISCRIMINATOR: [u8; 8] = [97, 167, 144, 107, 117, 190, 128, 36];
+/// `claim` discriminator, hash of `global:claim`
+pub const CLAIM_DISCRIMINATOR: [u8; 8] = [62, 198, 214, 193, 213, 159, 108, 210];
+/// `add_liquidity` discriminator, hash of `global:add_liquidity`
+pub const ADD_LIQUIDITY_DISCRIMINATOR: [u8; 8] = [181, 157, 89, 67, 143, 182, 52, 72];
+/// `remove_liquidity` discriminator, hash of `global:remove_liquidity`
+pub const REMOVE_LIQUIDITY_DISCRIMINATOR: [u8; 8] = [80, 85, 209, 72, 24, 206, 177, 108];
+
+/// Instructions of the Marinade liquid staking program.
+///
+/// Only the instructions moving SOL or mSOL of users are decoded, administrative and
+/// validator management instructions are rejected as unknown discriminators.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarinadeInstruction {
+    /// Deposit SOL, minting mSOL. Part of the deposit may be swapped against the mSOL of the
+    /// liquidity pool instead of being minted.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Marinade state
+    ///   1. `[writable]` mSOL mint
+    ///   2. `[writable]` Liquidity pool SOL leg
+    ///   3. `[writable]` Liquidity pool mSOL leg
+    ///   4. `[]` Liquidity pool mSOL leg authority
+    ///   5. `[writable]` Reserve
+    ///   6. `[writable, signer]` Depositor, transferring the SOL
+    ///   7. `[writable]` mSOL token account receiving the mSOL
+    ///   8. `[]` mSOL mint authority
+    ///   9. `[]` System program
+    ///   10. `[]` Token program
+    Deposit {
+        /// Lamports deposited
+        lamports: u64,
+    },
+    /// Deposit an activated stake account, minting mSOL
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Marinade state
+    ///   1. `[writable]` Validator list
+    ///   2. `[writable]` Stake list
+    ///   3. `[writable]` Stake account deposited
+    ///   4. `[signer]` Stake account authority
+    ///   5. `[writable]` Duplication flag
+    ///   6. `[writable, signer]` Rent payer
+    ///   7. `[writable]` mSOL mint
+    ///   8. `[writable]` mSOL token account receiving the mSOL
+    ///   9. `[]` mSOL mint authority
+    DepositStakeAccount {
+        /// Index of the stake account's validator in the validator list
+        validator_index: u32,
+    },
+    /// Swap mSOL for SOL immediately against the liquidity pool, for a fee
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Marinade state
+    ///   1. `[writable]` mSOL mint
+    ///   2. `[writable]` Liquidity pool SOL leg
+    ///   3. `[writable]` Liquidity pool mSOL leg
+    ///   4. `[writable]` Treasury mSOL account
+    ///   5. `[writable]` mSOL token account sending the mSOL
+    ///   6. `[signer]` mSOL token account authority
+    ///   7. `[writable]` Account receiving the SOL
+    ///   8. `[]` System program
+    ///   9. `[]` Token program
+    LiquidUnstake {
+        /// mSOL amount unstaked
+        msol_amount: u64,
+    },
+    /// Burn mSOL for a ticket claimable for SOL once the stake is deactivated, at the end of
+    /// the epoch
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Marinade state
+    ///   1. `[writable]` mSOL mint
+    ///   2. `[writable]` mSOL token account burning the mSOL
+    ///   3. `[signer]` mSOL token account authority
+    ///   4. `[writable]` New ticket account
+    ///   5. `[]` Clock sysvar
+    ///   6. `[]` Rent sysvar
+    ///   7. `[]` Token program
+    OrderUnstake {
+        /// mSOL amount unstaked
+        msol_amount: u64,
+    },
+    /// Claim the SOL of a ticket whose unstake completed
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Marinade state
+    ///   1. `[writable]` Reserve
+    ///   2. `[writable]` Ticket account
+    ///   3. `[writable]` Account receiving the SOL
+    ///   4. `[]` Clock sysvar
+    ///   5. `[]` System program
+    Claim,
+    /// Add SOL to the liquidity pool, minting LP tokens
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Marinade state
+    ///   1. `[writable]` LP mint
+    ///   2. `[]` LP mint authority
+    ///   3. `[]` Liquidity pool mSOL leg
+    ///   4. `[writable]` Liquidity pool SOL leg
+    ///   5. `[writable, signer]` Account sending the SOL
+    ///   6. `[writable]` LP token account receiving the LP tokens
+    ///   7. `[]` System program
+    ///   8. `[]` Token program
+    AddLiquidity {
+        /// Lamports added
+        lamports: u64,
+    },
+    /// Burn LP tokens, withdrawing their share of SOL and mSOL from the liquidity pool
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Marinade state
+    ///   1. `[writable]` LP mint
+    ///   2. `[writable]` LP token account burning the LP tokens
+    ///   3. `[signer]` LP token account authority
+    ///   4. `[writable]` Account receiving the SOL
+    ///   5. `[writable]` mSOL token account receiving the mSOL
+    ///   6. `[writable]` Liquidity pool SOL leg
+    ///   7. `[writable]` Liquidity pool mSOL leg
+    ///   8. `[]` Liquidity pool mSOL leg authority
+    ///   9. `[]` System program
+    ///   10. `[]` Token program
+    RemoveLiquidity {
+        /// LP tokens burnt
+        tokens: u64,
+    },
+}
+impl MarinadeInstruction {
+    /// Unpacks a byte buffer into a [MarinadeInstruction](enum.MarinadeInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, Error> {
+        let (discriminator, rest) =
+            TokenInstruction::unpack_bytes(input, 8).map_err(|_| anyhow!("Invalid Marinade Instruction"))?;
+        Ok(match discriminator {
+            d if d == DEPOSIT_DISCRIMINATOR => {
+                let (lamports, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::Deposit { lamports }
+            }
+            d if d == DEPOSIT_STAKE_ACCOUNT_DISCRIMINATOR => {
+                let (validator_index, _rest) = TokenInstruction::unpack_u32(rest)?;
+                Self::DepositStakeAccount { validator_index }
+            }
+            d if d == LIQUID_UNSTAKE_DISCRIMINATOR => {
+                let (msol_amount, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::LiquidUnstake { msol_amount }
+            }
+            d if d == ORDER_UNSTAKE_DISCRIMINATOR => {
+                let (msol_amount, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::OrderUnstake { msol_amount }
+            }
+            d if d == CLAIM_DISCRIMINATOR => Self::Claim,
+            d if d == ADD_LIQUIDITY_DISCRIMINATOR => {
+                let (lamports, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::AddLiquidity { lamports }
+            }
+            d if d == REMOVE_LIQUIDITY_DISCRIMINATOR => {
+                let (tokens, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::RemoveLiquidity { tokens }
+            }
+            _ => return Err(anyhow!("Invalid Marinade Instruction - unpack didn't match any discriminator: {:?}", discriminator)),
+        })
+    }
+}
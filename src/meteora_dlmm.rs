@@ -0,0 +1,772 @@
+//! Meteora DLMM (dynamic liquidity market maker) instruction types
+//!
+//! A DLMM pair splits its liquidity in bins, each a constant sum pool at a fixed price: bin
+//! `id` prices token X at `(1 + bin_step / 10000) ^ id` of token Y. Swaps consume the active bin
+//! and move to the next one once it is emptied, and liquidity is added or removed per bin,
+//! so the liquidity of a position cannot be derived from the balances of its owner alone.
+//! The program is an Anchor program, its instructions identified by an 8 bytes
+//! discriminator. The `2` instructions add Token 2022 support and end with a description of
+//! their remaining accounts, not decoded.
+
+use anyhow::anyhow;
+use {
+    crate::{pubkey::Pubkey, token_instruction_2022::TokenInstruction},
+    num_enum::TryFromPrimitive,
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// `swap` discriminator, hash of `global:swap`
+pub const SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+/// `swap_exact_out` discriminator, hash of `global:swap_exact_out`
+pub const SWAP_EXACT_OUT_DISCRIMINATOR: [u8; 8] = [250, 73, 101, 33, 38, 207, 75, 184];
+/// `swap_with_price_impact` discriminator, hash of `global:swap_with_price_impact`
+pub const SWAP_WITH_PRICE_IMPACT_DISCRIMINATOR: [u8; 8] = [56, 173, 230, 208, 173, 228, 156, 205];
+/// `swap2` discriminator, hash of `global:swap2`
+pub const SWAP2_DISCRIMINATOR: [u8; 8] = [65, 75, 63, 76, 235, 91, 91, 136];
+/// `swap_exact_out2` discriminator, hash of `global:swap_exact_out2`
+pub const SWAP_EXACT_OUT2_DISCRIMINATOR: [u8; 8] = [43, 215, 247, 132, 137, 60, 243, 81];
+/// `add_liquidity` discriminator, hash of `global:add_liquidity`
+pub const ADD_LIQUIDITY_DISCRIMINATOR: [u8; 8] = [181, 157, 89, 67, 143, 182, 52, 72];
+/// `add_liquidity_by_weight` discriminator, hash of `global:add_liquidity_by_weight`
+pub const ADD_LIQUIDITY_BY_WEIGHT_DISCRIMINATOR: [u8; 8] = [28, 140, 238, 99, 231, 162, 21, 149];
+/// `add_liquidity_by_strategy` discriminator, hash of `global:add_liquidity_by_strategy`
+pub const ADD_LIQUIDITY_BY_STRATEGY_DISCRIMINATOR: [u8; 8] = [7, 3, 150, 127, 148, 40, 61, 200];
+/// `add_liquidity_by_strategy2` discriminator, hash of `global:add_liquidity_by_strategy2`
+pub const ADD_LIQUIDITY_BY_STRATEGY2_DISCRIMINATOR: [u8; 8] = [3, 221, 149, 218, 111, 141, 118, 213];
+/// `add_liquidity_one_side` discriminator, hash of `global:add_liquidity_one_side`
+pub const ADD_LIQUIDITY_ONE_SIDE_DISCRIMINATOR: [u8; 8] = [94, 155, 103, 151, 70, 95, 220, 165];
+/// `remove_liquidity` discriminator, hash of `global:remove_liquidity`
+pub const REMOVE_LIQUIDITY_DISCRIMINATOR: [u8; 8] = [80, 85, 209, 72, 24, 206, 177, 108];
+/// `remove_all_liquidity` discriminator, hash of `global:remove_all_liquidity`
+pub const REMOVE_ALL_LIQUIDITY_DISCRIMINATOR: [u8; 8] = [10, 51, 61, 35, 112, 105, 24, 85];
+/// `remove_liquidity_by_range` discriminator, hash of `global:remove_liquidity_by_range`
+pub const REMOVE_LIQUIDITY_BY_RANGE_DISCRIMINATOR: [u8; 8] = [26, 82, 102, 152, 240, 74, 105, 26];
+/// `remove_liquidity_by_range2` discriminator, hash of `global:remove_liquidity_by_range2`
+pub const REMOVE_LIQUIDITY_BY_RANGE2_DISCRIMINATOR: [u8; 8] = [204, 2, 195, 145, 53, 145, 145, 205];
+/// `initialize_lb_pair` discriminator, hash of `global:initialize_lb_pair`
+pub const INITIALIZE_LB_PAIR_DISCRIMINATOR: [u8; 8] = [45, 154, 237, 210, 221, 15, 166, 92];
+/// `update_fee_parameters` discriminator, hash of `global:update_fee_parameters`
+pub const UPDATE_FEE_PARAMETERS_DISCRIMINATOR: [u8; 8] = [128, 128, 208, 91, 246, 53, 31, 176];
+
+/// Prefix of the self-invoked instructions carrying Anchor events, the little-endian
+/// encoding of the hash of `anchor:event`
+pub const EVENT_INSTRUCTION_TAG: [u8; 8] = [228, 69, 165, 46, 81, 203, 154, 29];
+/// `Swap` event discriminator, hash of `event:Swap`
+pub const SWAP_EVENT_DISCRIMINATOR: [u8; 8] = [81, 108, 227, 190, 205, 208, 10, 196];
+
+/// Precision of the fee rates, `1_000_000_000` being 100%
+pub const FEE_PRECISION: u64 = 1_000_000_000;
+
+/// Liquidity added to a bin, as shares of the amounts added
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BinLiquidityDistribution {
+    /// The bin
+    pub bin_id: i32,
+    /// Share of the amount of token X, in basis points
+    pub distribution_x: u16,
+    /// Share of the amount of token Y, in basis points
+    pub distribution_y: u16,
+}
+
+/// Liquidity added to a bin, as a weight relative to the other bins
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BinLiquidityWeight {
+    /// The bin
+    pub bin_id: i32,
+    /// Weight of the bin
+    pub weight: u16,
+}
+
+/// Liquidity removed from a bin
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BinLiquidityReduction {
+    /// The bin
+    pub bin_id: i32,
+    /// Share of the position's liquidity in the bin to remove, in basis points
+    pub bps_to_remove: u16,
+}
+
+/// Shape of the liquidity spread over a range of bins
+#[repr(u8)]
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, TryFromPrimitive)]
+pub enum StrategyType {
+    /// Uniform, one token only
+    SpotOneSide,
+    /// Concentrated around the active bin, one token only
+    CurveOneSide,
+    /// Increasing away from the active bin, one token only
+    BidAskOneSide,
+    /// Uniform, both tokens in balanced amounts
+    SpotBalanced,
+    /// Concentrated around the active bin, both tokens in balanced amounts
+    CurveBalanced,
+    /// Increasing away from the active bin, both tokens in balanced amounts
+    BidAskBalanced,
+    /// Uniform, both tokens in any amounts
+    SpotImBalanced,
+    /// Concentrated around the active bin, both tokens in any amounts
+    CurveImBalanced,
+    /// Increasing away from the active bin, both tokens in any amounts
+    BidAskImBalanced,
+}
+
+/// Fee parameters of a pair, set by its admin
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeParameter {
+    /// Share of the swap fees kept by the protocol, in basis points
+    pub protocol_share: u16,
+    /// Base fee factor, scaled by the bin step
+    pub base_factor: u16,
+    /// Power of ten scaling the base fee, 0 for pairs set before its introduction
+    pub base_fee_power_factor: u8,
+}
+
+impl FeeParameter {
+    /// Base fee rate of the swaps of a pair of step `bin_step`, over [FEE_PRECISION], `None`
+    /// on overflow. The variable fee, growing with volatility, comes on top of it.
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::meteora_dlmm::{FeeParameter, FEE_PRECISION};
+    /// let parameters = FeeParameter { protocol_share: 500, base_factor: 10_000, base_fee_power_factor: 0 };
+    /// // 0.25% base fee with a bin step of 25 basis points
+    /// assert_eq!(parameters.base_fee_rate(25), Some(FEE_PRECISION / 400));
+    /// ```
+    pub fn base_fee_rate(&self, bin_step: u16) -> Option<u64> {
+        (self.base_factor as u64)
+            .checked_mul(bin_step as u64)?
+            .checked_mul(10)?
+            .checked_mul(10u64.checked_pow(self.base_fee_power_factor as u32)?)
+    }
+}
+
+/// Outcome of a swap, emitted by the DLMM program as an inner instruction to itself
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapEvent {
+    /// The pair
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub lb_pair: Pubkey,
+    /// The user swapping
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub from: Pubkey,
+    /// Active bin before the swap
+    pub start_bin_id: i32,
+    /// Active bin after the swap
+    pub end_bin_id: i32,
+    /// Amount in, fees included
+    pub amount_in: u64,
+    /// Amount out
+    pub amount_out: u64,
+    /// Whether token X was swapped for token Y
+    pub swap_for_y: bool,
+    /// Fee charged, in the input token
+    pub fee: u64,
+    /// Part of the fee kept by the protocol
+    pub protocol_fee: u64,
+    /// Fee rate applied, over [FEE_PRECISION]
+    pub fee_bps: u128,
+    /// Part of the protocol fee paid to the host
+    pub host_fee: u64,
+}
+
+impl SwapEvent {
+    /// Unpacks the data of an event instruction into a [SwapEvent], `Ok(None)` for other
+    /// instructions and events
+    pub fn unpack(input: &[u8]) -> Result<Option<Self>, Error> {
+        let rest = match (input.get(..8), input.get(8..16)) {
+            (Some(tag), Some(discriminator)) if tag == EVENT_INSTRUCTION_TAG && discriminator == SWAP_EVENT_DISCRIMINATOR => {
+                &input[16..]
+            }
+            _ => return Ok(None),
+        };
+        let (lb_pair, rest) = TokenInstruction::unpack_pubkey(rest)?;
+        let (from, rest) = TokenInstruction::unpack_pubkey(rest)?;
+        let (start_bin_id, rest) = unpack_i32(rest)?;
+        let (end_bin_id, rest) = unpack_i32(rest)?;
+        let (amount_in, rest) = TokenInstruction::unpack_u64(rest)?;
+        let (amount_out, rest) = TokenInstruction::unpack_u64(rest)?;
+        let (&swap_for_y, rest) = rest.split_first().ok_or(anyhow!("Unable to unpack swap direction"))?;
+        let (fee, rest) = TokenInstruction::unpack_u64(rest)?;
+        let (protocol_fee, rest) = TokenInstruction::unpack_u64(rest)?;
+        let (fee_bps, rest) = TokenInstruction::unpack_bytes(rest, 16)?;
+        let (host_fee, _rest) = TokenInstruction::unpack_u64(rest)?;
+        Ok(Some(Self {
+            lb_pair,
+            from,
+            start_bin_id,
+            end_bin_id,
+            amount_in,
+            amount_out,
+            swap_for_y: swap_for_y != 0,
+            fee,
+            protocol_fee,
+            fee_bps: u128::from_le_bytes(fee_bps.try_into().map_err(|_| anyhow!("Unable to unpack fee rate"))?),
+            host_fee,
+        }))
+    }
+}
+
+/// Instructions of the Meteora DLMM program.
+///
+/// Only the instructions swapping, moving liquidity and setting fees are decoded, position
+/// and reward management instructions are rejected as unknown discriminators.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MeteoraDlmmInstruction {
+    /// Swap an exact amount in
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Pair
+    ///   1. `[]` Bin array bitmap extension, or the program id if none
+    ///   2. `[writable]` Reserve X
+    ///   3. `[writable]` Reserve Y
+    ///   4. `[writable]` Input token account
+    ///   5. `[writable]` Output token account
+    ///   6. `[]` Token X mint
+    ///   7. `[]` Token Y mint
+    ///   8. `[writable]` Oracle
+    ///   9. `[writable]` Host fee token account, or the program id if none
+    ///   10. `[signer]` User
+    ///   11. `[]` Token X program
+    ///   12. `[]` Token Y program
+    ///   13. `[]` Event authority
+    ///   14. `[]` DLMM program
+    ///   15. ..15+N `[writable]` Bin arrays crossed by the swap
+    Swap {
+        /// Amount in
+        amount_in: u64,
+        /// Minimum amount out
+        min_amount_out: u64,
+    },
+    /// Swap for an exact amount out
+    ///
+    /// Accounts expected by this instruction: same as [MeteoraDlmmInstruction::Swap]
+    SwapExactOut {
+        /// Maximum amount in
+        max_in_amount: u64,
+        /// Amount out
+        out_amount: u64,
+    },
+    /// Swap an exact amount in, failing if the price moves too far from the given bin
+    ///
+    /// Accounts expected by this instruction: same as [MeteoraDlmmInstruction::Swap]
+    SwapWithPriceImpact {
+        /// Amount in
+        amount_in: u64,
+        /// Bin of the expected price, the active bin of the pair if `None`
+        active_id: Option<i32>,
+        /// Maximum price impact, in basis points
+        max_price_impact_bps: u16,
+    },
+    /// Same as [MeteoraDlmmInstruction::Swap], supporting Token 2022 mints
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. ..12. Same as [MeteoraDlmmInstruction::Swap]
+    ///   13. `[]` Memo program
+    ///   14. `[]` Event authority
+    ///   15. `[]` DLMM program
+    ///   16. ..16+N Remaining accounts: transfer hook accounts and bin arrays
+    Swap2 {
+        /// Amount in
+        amount_in: u64,
+        /// Minimum amount out
+        min_amount_out: u64,
+    },
+    /// Same as [MeteoraDlmmInstruction::SwapExactOut], supporting Token 2022 mints
+    ///
+    /// Accounts expected by this instruction: same as [MeteoraDlmmInstruction::Swap2]
+    SwapExactOut2 {
+        /// Maximum amount in
+        max_in_amount: u64,
+        /// Amount out
+        out_amount: u64,
+    },
+    /// Add liquidity to a position, spread over bins by explicit shares
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Position
+    ///   1. `[writable]` Pair
+    ///   2. `[writable]` Bin array bitmap extension, or the program id if none
+    ///   3. `[writable]` Token X account of the sender
+    ///   4. `[writable]` Token Y account of the sender
+    ///   5. `[writable]` Reserve X
+    ///   6. `[writable]` Reserve Y
+    ///   7. `[]` Token X mint
+    ///   8. `[]` Token Y mint
+    ///   9. `[writable]` Lower bin array
+    ///   10. `[writable]` Upper bin array
+    ///   11. `[signer]` Sender
+    ///   12. `[]` Token X program
+    ///   13. `[]` Token Y program
+    ///   14. `[]` Event authority
+    ///   15. `[]` DLMM program
+    AddLiquidity {
+        /// Amount of token X to add
+        amount_x: u64,
+        /// Amount of token Y to add
+        amount_y: u64,
+        /// Shares of the amounts added to each bin
+        bin_liquidity_dist: Vec<BinLiquidityDistribution>,
+    },
+    /// Add liquidity to a position, spread over bins by weights
+    ///
+    /// Accounts expected by this instruction: same as [MeteoraDlmmInstruction::AddLiquidity]
+    AddLiquidityByWeight {
+        /// Amount of token X to add
+        amount_x: u64,
+        /// Amount of token Y to add
+        amount_y: u64,
+        /// Active bin expected by the sender
+        active_id: i32,
+        /// Maximum number of bins the active bin may have moved from `active_id`
+        max_active_bin_slippage: i32,
+        /// Weights of the bins
+        bin_liquidity_dist: Vec<BinLiquidityWeight>,
+    },
+    /// Add liquidity to a position, spread over a range of bins following a strategy
+    ///
+    /// Accounts expected by this instruction: same as [MeteoraDlmmInstruction::AddLiquidity]
+    AddLiquidityByStrategy {
+        /// Amount of token X to add
+        amount_x: u64,
+        /// Amount of token Y to add
+        amount_y: u64,
+        /// Active bin expected by the sender
+        active_id: i32,
+        /// Maximum number of bins the active bin may have moved from `active_id`
+        max_active_bin_slippage: i32,
+        /// Lowest bin of the range
+        min_bin_id: i32,
+        /// Highest bin of the range
+        max_bin_id: i32,
+        /// Shape of the liquidity over the range
+        strategy_type: StrategyType,
+    },
+    /// Same as [MeteoraDlmmInstruction::AddLiquidityByStrategy], supporting Token 2022 mints
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Position
+    ///   1. `[writable]` Pair
+    ///   2. `[writable]` Bin array bitmap extension, or the program id if none
+    ///   3. `[writable]` Token X account of the sender
+    ///   4. `[writable]` Token Y account of the sender
+    ///   5. `[writable]` Reserve X
+    ///   6. `[writable]` Reserve Y
+    ///   7. `[]` Token X mint
+    ///   8. `[]` Token Y mint
+    ///   9. `[signer]` Sender
+    ///   10. `[]` Token X program
+    ///   11. `[]` Token Y program
+    ///   12. `[]` Event authority
+    ///   13. `[]` DLMM program
+    ///   14. ..14+N Remaining accounts: transfer hook accounts and bin arrays
+    AddLiquidityByStrategy2 {
+        /// Amount of token X to add
+        amount_x: u64,
+        /// Amount of token Y to add
+        amount_y: u64,
+        /// Active bin expected by the sender
+        active_id: i32,
+        /// Maximum number of bins the active bin may have moved from `active_id`
+        max_active_bin_slippage: i32,
+        /// Lowest bin of the range
+        min_bin_id: i32,
+        /// Highest bin of the range
+        max_bin_id: i32,
+        /// Shape of the liquidity over the range
+        strategy_type: StrategyType,
+    },
+    /// Add liquidity of a single token to a position, spread over bins by weights
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Position
+    ///   1. `[writable]` Pair
+    ///   2. `[writable]` Bin array bitmap extension, or the program id if none
+    ///   3. `[writable]` Token account of the sender
+    ///   4. `[writable]` Reserve of the token
+    ///   5. `[]` Token mint
+    ///   6. `[writable]` Lower bin array
+    ///   7. `[writable]` Upper bin array
+    ///   8. `[signer]` Sender
+    ///   9. `[]` Token program
+    ///   10. `[]` Event authority
+    ///   11. `[]` DLMM program
+    AddLiquidityOneSide {
+        /// Amount of the token to add
+        amount: u64,
+        /// Active bin expected by the sender
+        active_id: i32,
+        /// Maximum number of bins the active bin may have moved from `active_id`
+        max_active_bin_slippage: i32,
+        /// Weights of the bins
+        bin_liquidity_dist: Vec<BinLiquidityWeight>,
+    },
+    /// Remove liquidity of a position from the given bins
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Position
+    ///   1. `[writable]` Pair
+    ///   2. `[writable]` Bin array bitmap extension, or the program id if none
+    ///   3. `[writable]` Token X account of the sender
+    ///   4. `[writable]` Token Y account of the sender
+    ///   5. `[writable]` Reserve X
+    ///   6. `[writable]` Reserve Y
+    ///   7. `[]` Token X mint
+    ///   8. `[]` Token Y mint
+    ///   9. `[writable]` Lower bin array
+    ///   10. `[writable]` Upper bin array
+    ///   11. `[signer]` Sender
+    ///   12. `[]` Token X program
+    ///   13. `[]` Token Y program
+    ///   14. `[]` Event authority
+    ///   15. `[]` DLMM program
+    RemoveLiquidity {
+        /// Share of the liquidity to remove from each bin
+        bin_liquidity_removal: Vec<BinLiquidityReduction>,
+    },
+    /// Remove all the liquidity of a position
+    ///
+    /// Accounts expected by this instruction: same as [MeteoraDlmmInstruction::RemoveLiquidity]
+    RemoveAllLiquidity,
+    /// Remove the same share of the liquidity of a position from a range of bins
+    ///
+    /// Accounts expected by this instruction: same as [MeteoraDlmmInstruction::RemoveLiquidity]
+    RemoveLiquidityByRange {
+        /// Lowest bin of the range
+        from_bin_id: i32,
+        /// Highest bin of the range
+        to_bin_id: i32,
+        /// Share of the liquidity to remove from each bin, in basis points
+        bps_to_remove: u16,
+    },
+    /// Same as [MeteoraDlmmInstruction::RemoveLiquidityByRange], supporting Token 2022 mints
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. ..8. Same as [MeteoraDlmmInstruction::RemoveLiquidity]
+    ///   9. `[signer]` Sender
+    ///   10. `[]` Token X program
+    ///   11. `[]` Token Y program
+    ///   12. `[]` Memo program
+    ///   13. `[]` Event authority
+    ///   14. `[]` DLMM program
+    ///   15. ..15+N Remaining accounts: transfer hook accounts and bin arrays
+    RemoveLiquidityByRange2 {
+        /// Lowest bin of the range
+        from_bin_id: i32,
+        /// Highest bin of the range
+        to_bin_id: i32,
+        /// Share of the liquidity to remove from each bin, in basis points
+        bps_to_remove: u16,
+    },
+    /// Create a pair
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Pair
+    ///   1. `[writable]` Bin array bitmap extension, or the program id if none
+    ///   2. `[]` Token X mint
+    ///   3. `[]` Token Y mint
+    ///   4. `[writable]` Reserve X
+    ///   5. `[writable]` Reserve Y
+    ///   6. `[writable]` Oracle
+    ///   7. `[]` Preset parameters
+    ///   8. `[writable, signer]` Funder
+    ///   9. `[]` Token program
+    ///   10. `[]` System program
+    ///   11. `[]` Rent sysvar
+    ///   12. `[]` Event authority
+    ///   13. `[]` DLMM program
+    InitializeLbPair {
+        /// Initial active bin
+        active_id: i32,
+        /// Price step between two bins, in basis points
+        bin_step: u16,
+    },
+    /// Update the fee parameters of a pair
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Pair
+    ///   1. `[signer]` Admin
+    ///   2. `[]` Event authority
+    ///   3. `[]` DLMM program
+    UpdateFeeParameters {
+        /// The new parameters
+        fee_parameter: FeeParameter,
+    },
+}
+impl MeteoraDlmmInstruction {
+    /// Unpacks a byte buffer into a [MeteoraDlmmInstruction](enum.MeteoraDlmmInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, Error> {
+        let (discriminator, rest) =
+            TokenInstruction::unpack_bytes(input, 8).map_err(|_| anyhow!("Invalid Meteora DLMM Instruction"))?;
+        let discriminator: [u8; 8] = discriminator.try_into().map_err(|_| anyhow!("Invalid Meteora DLMM Instruction"))?;
+        Ok(match discriminator {
+            SWAP_DISCRIMINATOR | SWAP2_DISCRIMINATOR => {
+                let (amount_in, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (min_amount_out, _rest) = TokenInstruction::unpack_u64(rest)?;
+                if discriminator == SWAP_DISCRIMINATOR {
+                    Self::Swap {
+                        amount_in,
+                        min_amount_out,
+                    }
+                } else {
+                    Self::Swap2 {
+                        amount_in,
+                        min_amount_out,
+                    }
+                }
+            }
+            SWAP_EXACT_OUT_DISCRIMINATOR | SWAP_EXACT_OUT2_DISCRIMINATOR => {
+                let (max_in_amount, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (out_amount, _rest) = TokenInstruction::unpack_u64(rest)?;
+                if discriminator == SWAP_EXACT_OUT_DISCRIMINATOR {
+                    Self::SwapExactOut {
+                        max_in_amount,
+                        out_amount,
+                    }
+                } else {
+                    Self::SwapExactOut2 {
+                        max_in_amount,
+                        out_amount,
+                    }
+                }
+            }
+            SWAP_WITH_PRICE_IMPACT_DISCRIMINATOR => {
+                let (amount_in, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (active_id, rest) = match rest.split_first() {
+                    Some((0, rest)) => (None, rest),
+                    Some((1, rest)) => {
+                        let (active_id, rest) = unpack_i32(rest)?;
+                        (Some(active_id), rest)
+                    }
+                    _ => return Err(anyhow!("Unable to unpack optional active id")),
+                };
+                let (max_price_impact_bps, _rest) = TokenInstruction::unpack_u16(rest)?;
+                Self::SwapWithPriceImpact {
+                    amount_in,
+                    active_id,
+                    max_price_impact_bps,
+                }
+            }
+            ADD_LIQUIDITY_DISCRIMINATOR => {
+                let (amount_x, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (amount_y, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (bin_liquidity_dist, _rest) = unpack_vec(rest, 8, |bin| {
+                    let (bin_id, bin) = unpack_i32(bin)?;
+                    let (distribution_x, bin) = TokenInstruction::unpack_u16(bin)?;
+                    let (distribution_y, _bin) = TokenInstruction::unpack_u16(bin)?;
+                    Ok(BinLiquidityDistribution {
+                        bin_id,
+                        distribution_x,
+                        distribution_y,
+                    })
+                })?;
+                Self::AddLiquidity {
+                    amount_x,
+                    amount_y,
+                    bin_liquidity_dist,
+                }
+            }
+            ADD_LIQUIDITY_BY_WEIGHT_DISCRIMINATOR => {
+                let (amount_x, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (amount_y, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (active_id, rest) = unpack_i32(rest)?;
+                let (max_active_bin_slippage, rest) = unpack_i32(rest)?;
+                let (bin_liquidity_dist, _rest) = unpack_bin_weights(rest)?;
+                Self::AddLiquidityByWeight {
+                    amount_x,
+                    amount_y,
+                    active_id,
+                    max_active_bin_slippage,
+                    bin_liquidity_dist,
+                }
+            }
+            ADD_LIQUIDITY_BY_STRATEGY_DISCRIMINATOR | ADD_LIQUIDITY_BY_STRATEGY2_DISCRIMINATOR => {
+                let (amount_x, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (amount_y, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (active_id, rest) = unpack_i32(rest)?;
+                let (max_active_bin_slippage, rest) = unpack_i32(rest)?;
+                let (min_bin_id, rest) = unpack_i32(rest)?;
+                let (max_bin_id, rest) = unpack_i32(rest)?;
+                // Followed by 64 bytes of strategy parameters, unused by the program
+                let (&strategy_type, _rest) = rest.split_first().ok_or(anyhow!("Unable to unpack strategy type"))?;
+                let strategy_type = StrategyType::try_from(strategy_type)
+                    .map_err(|_| anyhow!("Invalid strategy type: {}", strategy_type))?;
+                if discriminator == ADD_LIQUIDITY_BY_STRATEGY_DISCRIMINATOR {
+                    Self::AddLiquidityByStrategy {
+                        amount_x,
+                        amount_y,
+                        active_id,
+                        max_active_bin_slippage,
+                        min_bin_id,
+                        max_bin_id,
+                        strategy_type,
+                    }
+                } else {
+                    Self::AddLiquidityByStrategy2 {
+                        amount_x,
+                        amount_y,
+                        active_id,
+                        max_active_bin_slippage,
+                        min_bin_id,
+                        max_bin_id,
+                        strategy_type,
+                    }
+                }
+            }
+            ADD_LIQUIDITY_ONE_SIDE_DISCRIMINATOR => {
+                let (amount, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (active_id, rest) = unpack_i32(rest)?;
+                let (max_active_bin_slippage, rest) = unpack_i32(rest)?;
+                let (bin_liquidity_dist, _rest) = unpack_bin_weights(rest)?;
+                Self::AddLiquidityOneSide {
+                    amount,
+                    active_id,
+                    max_active_bin_slippage,
+                    bin_liquidity_dist,
+                }
+            }
+            REMOVE_LIQUIDITY_DISCRIMINATOR => {
+                let (bin_liquidity_removal, _rest) = unpack_vec(rest, 6, |bin| {
+                    let (bin_id, bin) = unpack_i32(bin)?;
+                    let (bps_to_remove, _bin) = TokenInstruction::unpack_u16(bin)?;
+                    Ok(BinLiquidityReduction { bin_id, bps_to_remove })
+                })?;
+                Self::RemoveLiquidity { bin_liquidity_removal }
+            }
+            REMOVE_ALL_LIQUIDITY_DISCRIMINATOR => Self::RemoveAllLiquidity,
+            REMOVE_LIQUIDITY_BY_RANGE_DISCRIMINATOR | REMOVE_LIQUIDITY_BY_RANGE2_DISCRIMINATOR => {
+                let (from_bin_id, rest) = unpack_i32(rest)?;
+                let (to_bin_id, rest) = unpack_i32(rest)?;
+                let (bps_to_remove, _rest) = TokenInstruction::unpack_u16(rest)?;
+                if discriminator == REMOVE_LIQUIDITY_BY_RANGE_DISCRIMINATOR {
+                    Self::RemoveLiquidityByRange {
+                        from_bin_id,
+                        to_bin_id,
+                        bps_to_remove,
+                    }
+                } else {
+                    Self::RemoveLiquidityByRange2 {
+                        from_bin_id,
+                        to_bin_id,
+                        bps_to_remove,
+                    }
+                }
+            }
+            INITIALIZE_LB_PAIR_DISCRIMINATOR => {
+                let (active_id, rest) = unpack_i32(rest)?;
+                let (bin_step, _rest) = TokenInstruction::unpack_u16(rest)?;
+                Self::InitializeLbPair { active_id, bin_step }
+            }
+            UPDATE_FEE_PARAMETERS_DISCRIMINATOR => {
+                let (protocol_share, rest) = TokenInstruction::unpack_u16(rest)?;
+                let (base_factor, rest) = TokenInstruction::unpack_u16(rest)?;
+                // Appended by a later version of the program
+                let base_fee_power_factor = rest.first().copied().unwrap_or(0);
+                Self::UpdateFeeParameters {
+                    fee_parameter: FeeParameter {
+                        protocol_share,
+                        base_factor,
+                        base_fee_power_factor,
+                    },
+                }
+            }
+            _ => return Err(anyhow!("Invalid Meteora DLMM Instruction - unpack didn't match any discriminator: {:?}", discriminator)),
+        })
+    }
+
+    /// Lowest and highest bins whose liquidity the instruction changes, `None` for
+    /// instructions not moving liquidity, [MeteoraDlmmInstruction::RemoveAllLiquidity]
+    /// included as its bins are those of the position, and for empty distributions
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::meteora_dlmm::{MeteoraDlmmInstruction, REMOVE_LIQUIDITY_BY_RANGE_DISCRIMINATOR};
+    /// let mut data = REMOVE_LIQUIDITY_BY_RANGE_DISCRIMINATOR.to_vec();
+    /// data.extend_from_slice(&(-12i32).to_le_bytes());
+    /// data.extend_from_slice(&8i32.to_le_bytes());
+    /// data.extend_from_slice(&10_000u16.to_le_bytes());
+    /// let instruction = MeteoraDlmmInstruction::unpack(&data).unwrap();
+    /// assert_eq!(instruction.bin_range(), Some((-12, 8)));
+    /// ```
+    pub fn bin_range(&self) -> Option<(i32, i32)> {
+        let bins: Vec<i32> = match self {
+            Self::AddLiquidity { bin_liquidity_dist, .. } => bin_liquidity_dist.iter().map(|bin| bin.bin_id).collect(),
+            Self::AddLiquidityByWeight { bin_liquidity_dist, .. }
+            | Self::AddLiquidityOneSide { bin_liquidity_dist, .. } => {
+                bin_liquidity_dist.iter().map(|bin| bin.bin_id).collect()
+            }
+            Self::RemoveLiquidity { bin_liquidity_removal } => {
+                bin_liquidity_removal.iter().map(|bin| bin.bin_id).collect()
+            }
+            Self::AddLiquidityByStrategy {
+                min_bin_id, max_bin_id, ..
+            }
+            | Self::AddLiquidityByStrategy2 {
+                min_bin_id, max_bin_id, ..
+            } => vec![*min_bin_id, *max_bin_id],
+            Self::RemoveLiquidityByRange {
+                from_bin_id, to_bin_id, ..
+            }
+            | Self::RemoveLiquidityByRange2 {
+                from_bin_id, to_bin_id, ..
+            } => vec![*from_bin_id, *to_bin_id],
+            _ => return None,
+        };
+        Some((*bins.iter().min()?, *bins.iter().max()?))
+    }
+}
+
+fn unpack_i32(input: &[u8]) -> Result<(i32, &[u8]), Error> {
+    TokenInstruction::unpack_u32(input).map(|(value, rest)| (value as i32, rest))
+}
+
+fn unpack_bin_weights(input: &[u8]) -> Result<(Vec<BinLiquidityWeight>, &[u8]), Error> {
+    unpack_vec(input, 6, |bin| {
+        let (bin_id, bin) = unpack_i32(bin)?;
+        let (weight, _bin) = TokenInstruction::unpack_u16(bin)?;
+        Ok(BinLiquidityWeight { bin_id, weight })
+    })
+}
+
+/// Unpacks a borsh `Vec` of fixed size items, a `u32` length followed by the items
+fn unpack_vec<T, F>(input: &[u8], item_len: usize, unpack: F) -> Result<(Vec<T>, &[u8]), Error>
+where
+    F: Fn(&[u8]) -> Result<T, Error>,
+{
+    let (len, rest) = TokenInstruction::unpack_u32(input)?;
+    let bytes_len = (len as usize)
+        .checked_mul(item_len)
+        .ok_or(anyhow!("Unable to unpack vec, invalid length: {}", len))?;
+    let (items, rest) = TokenInstruction::unpack_bytes(rest, bytes_len)?;
+    let items = items.chunks_exact(item_len).map(unpack).collect::<Result<_, _>>()?;
+    Ok((items, rest))
+}
@@ -0,0 +1,88 @@
+//! Hooks reporting decoding and extraction metrics
+//!
+//! Operators tracking decode failures or event volumes implement [Metrics] and install it
+//! with [set_metrics], wiring the callbacks to substreams metrics, logs or counters of their
+//! own. Until then, reporting is a no-op.
+//!
+//! Decodes are reported by the helpers decoding each token instruction once,
+//! [crate::instructions::token_instructions] and [crate::visitor::walk_instruction], along
+//! with `parallel::par_token_instructions` from the rayon workers running it, and
+//! emitted events by the transaction-level extractors of [crate::events], by kind.
+
+use std::sync::{Arc, RwLock};
+
+/// Callbacks receiving metrics, each defaulting to doing nothing. Hooks are called from
+/// whichever thread decodes or extracts, rayon workers included, hence `Send + Sync`.
+#[allow(unused_variables)]
+pub trait Metrics: Send + Sync {
+    /// A token instruction with `tag` was decoded
+    fn decoded_ok(&self, tag: u8) {}
+
+    /// A token instruction with `tag` failed to decode
+    fn decode_err(&self, tag: u8) {}
+
+    /// An extractor emitted `n` events of `kind`, see the `KIND` of each event
+    fn events_emitted(&self, kind: &'static str, n: usize) {}
+}
+
+/// Hooks installed through [set_metrics]
+static METRICS: RwLock<Option<Arc<dyn Metrics>>> = RwLock::new(None);
+
+/// Installs `metrics`, replacing the hooks installed before. The hooks are shared by all
+/// threads of the process: install them once, at the start of the module.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{instructions::token_instructions, metrics::{clear_metrics, set_metrics, Metrics}};
+/// # use std::sync::{atomic::{AtomicU64, Ordering}, Arc};
+/// # let block = substreams_solana_core::pb::sf::solana::r#type::v1::Block::default();
+/// /// Counts decode failures
+/// struct Failures(Arc<AtomicU64>);
+///
+/// impl Metrics for Failures {
+///     fn decode_err(&self, _tag: u8) {
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let failures = Arc::new(AtomicU64::new(0));
+/// set_metrics(Failures(failures.clone()));
+/// token_instructions(&block).for_each(drop);
+/// clear_metrics();
+/// assert_eq!(failures.load(Ordering::Relaxed), 0);
+/// ```
+pub fn set_metrics<M: Metrics + 'static>(metrics: M) {
+    *METRICS.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::new(metrics));
+}
+
+/// Uninstalls the hooks installed through [set_metrics]
+pub fn clear_metrics() {
+    *METRICS.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+/// Calls `report` with the installed hooks, if any. The lock is released before the call,
+/// so hooks may themselves decode or extract.
+fn report<F: FnOnce(&dyn Metrics)>(report: F) {
+    let metrics = METRICS.read().unwrap_or_else(|poisoned| poisoned.into_inner()).clone();
+    if let Some(metrics) = metrics {
+        report(metrics.as_ref());
+    }
+}
+
+/// Reports the decoding of the instruction `data`, ignored if it is empty
+pub(crate) fn decoded<T, E>(data: &[u8], result: &Result<T, E>) {
+    let tag = match data.first() {
+        Some(&tag) => tag,
+        None => return,
+    };
+    match result {
+        Ok(_) => report(|metrics| metrics.decoded_ok(tag)),
+        Err(_) => report(|metrics| metrics.decode_err(tag)),
+    }
+}
+
+/// Reports `n` events of `kind`, ignored if there is none
+pub(crate) fn events_emitted(kind: &'static str, n: usize) {
+    if n > 0 {
+        report(|metrics| metrics.events_emitted(kind, n));
+    }
+}
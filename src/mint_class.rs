@@ -0,0 +1,74 @@
+//! Classification of mints by origin, for asset taxonomies
+//!
+//! Bridges mint the tokens they wrap from program derived addresses: the Wormhole (Portal)
+//! token bridge derives every wrapped mint from `["wrapped", chain, token_address]` and signs
+//! its mints with a single PDA, so the mint authority alone identifies the wrapped assets.
+//! [classify_mint] applies these rules, and tells native SOL wrappers apart.
+
+use crate::pubkey::Pubkey;
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Mint authority of the mints wrapped by the Wormhole token bridge, its `["mint_signer"]`
+/// PDA, `BCD75RNBHrJJpW4dXVagL5mPjzRLnVZq4YirJdjEYMV7`
+pub const WORMHOLE_TOKEN_BRIDGE_MINT_AUTHORITY: Pubkey = Pubkey([
+    151, 115, 241, 239, 142, 235, 152, 149, 116, 202, 113, 4, 91, 19, 172, 13,
+    12, 17, 70, 162, 78, 16, 121, 25, 126, 83, 23, 85, 25, 77, 208, 150,
+]);
+
+/// Wrapped SOL mint of the SPL Token program, `So11111111111111111111111111111111111111112`
+pub const NATIVE_MINT: Pubkey = Pubkey([
+    6, 155, 136, 87, 254, 171, 129, 132, 251, 104, 127, 99, 70, 24, 192, 53,
+    218, 196, 57, 220, 26, 235, 59, 85, 152, 160, 240, 0, 0, 0, 0, 1,
+]);
+
+/// Wrapped SOL mint of the SPL Token 2022 program, `9pan9bMn5HatX4EJdBwg9VgCa7Uz5HL8N1m5D3NdXejP`
+pub const NATIVE_MINT_2022: Pubkey = Pubkey([
+    131, 13, 252, 159, 222, 95, 230, 184, 170, 124, 4, 164, 118, 233, 30, 138,
+    198, 187, 38, 74, 173, 144, 250, 25, 201, 223, 73, 216, 92, 62, 91, 94,
+]);
+
+/// Origin of a mint
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MintClass {
+    /// Wrapped SOL, of either token program
+    NativeSol,
+    /// Asset of another chain wrapped by the Wormhole (Portal) token bridge
+    WormholeWrapped,
+    /// Any other mint, native to Solana as far as these rules tell
+    Unclassified,
+}
+
+impl MintClass {
+    /// Returns `true` for assets bridged from another chain
+    pub fn is_bridged(self) -> bool {
+        matches!(self, Self::WormholeWrapped)
+    }
+}
+
+/// Classifies `mint` from its address and its mint `authority`, `None` for mints without
+/// one. Wrapped SOL mints have no authority, bridged mints keep the bridge's.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{mint_class::*, pubkey::Pubkey};
+/// // Wrapped Ether, bridged from Ethereum
+/// let weth: Pubkey = "7vfCXTUXx5WJV5JADk17DUJ4ksgau7utNKj4b963voxs".parse().unwrap();
+/// assert_eq!(
+///     classify_mint(&weth, Some(&WORMHOLE_TOKEN_BRIDGE_MINT_AUTHORITY)),
+///     MintClass::WormholeWrapped
+/// );
+/// assert_eq!(classify_mint(&NATIVE_MINT, None), MintClass::NativeSol);
+/// assert_eq!(classify_mint(&weth, None), MintClass::Unclassified);
+/// ```
+pub fn classify_mint(mint: &Pubkey, authority: Option<&Pubkey>) -> MintClass {
+    if *mint == NATIVE_MINT || *mint == NATIVE_MINT_2022 {
+        return MintClass::NativeSol;
+    }
+    match authority {
+        Some(authority) if *authority == WORMHOLE_TOKEN_BRIDGE_MINT_AUTHORITY => MintClass::WormholeWrapped,
+        _ => MintClass::Unclassified,
+    }
+}
@@ -0,0 +1,263 @@
+//! Mint "rug risk" reports, from mint state and authority changes
+//!
+//! Token safety checks look at what the authorities of a mint can still do to its holders:
+//! mint more tokens, freeze their accounts, move their tokens through a permanent delegate,
+//! run a program on every transfer, or raise the transfer fee. [MintRiskReport] reads them
+//! from the mint's account data, [MintRiskReport::apply] keeps them up to date with the
+//! [MintAuthorityChanged] events extracted by [mint_authority_events], and
+//! [MintRiskReport::factors] and [MintRiskReport::score] summarize them.
+
+use {
+    crate::{
+        account_data::{extension_data, optional_nonzero_pubkey, Mint},
+        events::EventContext,
+        instructions::{instructions, is_successful},
+        metrics,
+        option::COption,
+        program_ids::is_token_program,
+        pubkey::Pubkey,
+        token_instruction_2022::{AuthorityType, ExtensionType, TokenInstruction},
+    },
+    substreams::errors::Error,
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// A mint authority set or revoked, through `SetAuthority` with any authority type but
+/// `AccountOwner` and `CloseAccount`, which apply to token accounts
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct MintAuthorityChanged {
+    /// Position of the `SetAuthority` instruction
+    pub context: EventContext,
+    /// The mint
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub mint: Pubkey,
+    /// The authority changed
+    pub authority_type: AuthorityType,
+    /// The new authority, `None` when revoked
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub new_authority: Option<Pubkey>,
+}
+
+impl MintAuthorityChanged {
+    /// Kind of the event, part of its [MintAuthorityChanged::event_id]
+    pub const KIND: &'static str = "mint_authority_changed";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+
+    /// Returns `true` if the authority was revoked
+    pub fn is_revoked(&self) -> bool {
+        self.new_authority.is_none()
+    }
+}
+
+/// Extracts the mint authority changes of all successful transactions of `block`, in
+/// execution order
+pub fn block_mint_authority_events(block: &Block) -> Vec<MintAuthorityChanged> {
+    block
+        .transactions
+        .iter()
+        .enumerate()
+        .flat_map(|(index, trx)| mint_authority_events(trx, index as u32))
+        .collect()
+}
+
+/// Extracts the mint authority changes of a successful transaction, the
+/// `transaction_index`-th of its block, in execution order
+pub fn mint_authority_events(trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<MintAuthorityChanged> {
+    if !is_successful(trx) {
+        return vec![];
+    }
+
+    let mut events = vec![];
+    for instruction in instructions(trx) {
+        if !is_token_program(instruction.program_id) {
+            continue;
+        }
+        let (authority_type, new_authority) = match TokenInstruction::unpack(instruction.data) {
+            Ok(TokenInstruction::SetAuthority {
+                authority_type: AuthorityType::AccountOwner | AuthorityType::CloseAccount,
+                ..
+            }) => continue,
+            Ok(TokenInstruction::SetAuthority {
+                authority_type,
+                new_authority,
+            }) => (authority_type, new_authority),
+            _ => continue,
+        };
+        let mint = match instruction.accounts.get_pubkey(0) {
+            Some(mint) => mint,
+            None => continue,
+        };
+        events.push(MintAuthorityChanged {
+            context: EventContext::new(trx, transaction_index, &instruction),
+            mint,
+            authority_type,
+            new_authority: coption(new_authority),
+        });
+    }
+
+    metrics::events_emitted(MintAuthorityChanged::KIND, events.len());
+    events
+}
+
+/// A capability the authorities of a mint keep over its holders
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RiskFactor {
+    /// The mint authority can mint new tokens, diluting holders
+    MintAuthorityLive,
+    /// The freeze authority can freeze the token accounts of holders
+    FreezeAuthorityLive,
+    /// A permanent delegate can transfer or burn the tokens of any holder
+    PermanentDelegate,
+    /// A transfer hook program runs on every transfer, and may reject them
+    TransferHook,
+    /// The transfer fee authority can raise the transfer fee
+    MutableTransferFee,
+}
+
+impl RiskFactor {
+    /// Contribution of the factor to [MintRiskReport::score], the weights of all factors
+    /// adding up to 100
+    pub fn weight(self) -> u32 {
+        match self {
+            Self::MintAuthorityLive => 25,
+            Self::FreezeAuthorityLive => 25,
+            Self::PermanentDelegate => 30,
+            Self::TransferHook => 10,
+            Self::MutableTransferFee => 10,
+        }
+    }
+}
+
+/// Authorities and extension state of a mint bearing on the safety of its holders
+///
+/// ```
+/// # use substreams_solana_program_instructions::{events::EventContext, mint_risk::*, token_instruction_2022::AuthorityType};
+/// # use substreams_solana_program_instructions::pubkey::Pubkey;
+/// let mint = Pubkey::from([1; 32]);
+/// // Mint and freeze authorities set, no extension
+/// let mut data = vec![0u8; 82];
+/// data[..4].copy_from_slice(&1u32.to_le_bytes());
+/// data[4..36].copy_from_slice(&[2; 32]);
+/// data[45] = 1;
+/// data[46..50].copy_from_slice(&1u32.to_le_bytes());
+/// data[50..82].copy_from_slice(&[2; 32]);
+///
+/// let mut report = MintRiskReport::from_mint_data(mint, &data).unwrap();
+/// assert_eq!(report.factors(), vec![RiskFactor::MintAuthorityLive, RiskFactor::FreezeAuthorityLive]);
+/// assert_eq!(report.score(), 50);
+///
+/// report.apply(&MintAuthorityChanged {
+///     context: EventContext::default(),
+///     mint,
+///     authority_type: AuthorityType::FreezeAccount,
+///     new_authority: None,
+/// });
+/// assert_eq!(report.factors(), vec![RiskFactor::MintAuthorityLive]);
+/// assert_eq!(report.score(), 25);
+/// ```
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MintRiskReport {
+    /// The mint
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub mint: Pubkey,
+    /// The mint authority, `None` once revoked
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub mint_authority: Option<Pubkey>,
+    /// The freeze authority, `None` once revoked
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub freeze_authority: Option<Pubkey>,
+    /// The permanent delegate, `None` without the extension or once revoked
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub permanent_delegate: Option<Pubkey>,
+    /// The transfer hook program, `None` without the extension or program
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub transfer_hook_program: Option<Pubkey>,
+    /// The authority able to change the transfer hook program, `None` once revoked
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub transfer_hook_authority: Option<Pubkey>,
+    /// The transfer fee config authority, `None` without the extension or once revoked
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub transfer_fee_authority: Option<Pubkey>,
+}
+
+impl MintRiskReport {
+    /// Reads the report of the mint at `mint` from its account data
+    pub fn from_mint_data(mint: Pubkey, data: &[u8]) -> Result<Self, Error> {
+        let state = Mint::unpack(data)?;
+        let transfer_hook = extension_data(data, ExtensionType::TransferHook);
+        Ok(Self {
+            mint,
+            mint_authority: coption(state.mint_authority),
+            freeze_authority: coption(state.freeze_authority),
+            permanent_delegate: extension_data(data, ExtensionType::PermanentDelegate)
+                .and_then(|value| optional_nonzero_pubkey(value, 0)),
+            transfer_hook_program: transfer_hook.and_then(|value| optional_nonzero_pubkey(value, 32)),
+            transfer_hook_authority: transfer_hook.and_then(|value| optional_nonzero_pubkey(value, 0)),
+            transfer_fee_authority: extension_data(data, ExtensionType::TransferFeeConfig)
+                .and_then(|value| optional_nonzero_pubkey(value, 0)),
+        })
+    }
+
+    /// Applies an authority change, ignored if it is for another mint. Only the authorities
+    /// the report tracks are updated: a new transfer hook program, set through the transfer
+    /// hook extension, is only seen by reading the mint's data again.
+    pub fn apply(&mut self, event: &MintAuthorityChanged) {
+        if event.mint != self.mint {
+            return;
+        }
+        let authority = match event.authority_type {
+            AuthorityType::MintTokens => &mut self.mint_authority,
+            AuthorityType::FreezeAccount => &mut self.freeze_authority,
+            AuthorityType::PermanentDelegate => &mut self.permanent_delegate,
+            AuthorityType::TransferHookProgramId => &mut self.transfer_hook_authority,
+            AuthorityType::TransferFeeConfig => &mut self.transfer_fee_authority,
+            _ => return,
+        };
+        *authority = event.new_authority;
+    }
+
+    /// Capabilities the authorities of the mint keep over its holders, in the order of the
+    /// [RiskFactor] variants
+    pub fn factors(&self) -> Vec<RiskFactor> {
+        [
+            (self.mint_authority.is_some(), RiskFactor::MintAuthorityLive),
+            (self.freeze_authority.is_some(), RiskFactor::FreezeAuthorityLive),
+            (self.permanent_delegate.is_some(), RiskFactor::PermanentDelegate),
+            (self.transfer_hook_program.is_some(), RiskFactor::TransferHook),
+            (self.transfer_fee_authority.is_some(), RiskFactor::MutableTransferFee),
+        ]
+        .into_iter()
+        .filter_map(|(present, factor)| if present { Some(factor) } else { None })
+        .collect()
+    }
+
+    /// Risk score of the mint, from 0 (every authority revoked, no risky extension) to 100,
+    /// the sum of the weights of its [MintRiskReport::factors]
+    pub fn score(&self) -> u32 {
+        self.factors().into_iter().map(RiskFactor::weight).sum()
+    }
+}
+
+/// Converts an authority as stored by the token program
+fn coption(value: COption<Pubkey>) -> Option<Pubkey> {
+    match value {
+        COption::Some(value) => Some(value),
+        COption::None => None,
+    }
+}
@@ -0,0 +1,240 @@
+//! SPL Name Service and Solana Name Service registrar instruction types
+
+use anyhow::anyhow;
+use {
+    crate::token_instruction_2022::TokenInstruction,
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+use crate::pubkey::Pubkey;
+
+/// Instructions supported by the SPL Name Service program.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum NameServiceInstruction<'a> {
+    /// Create an empty name record
+    ///
+    /// The address of the name record (account #2) is a program-derived address with the
+    /// following seeds to ensure uniqueness:
+    ///     * SHA256(HASH_PREFIX, `Create::name`)
+    ///     * Account class (account #4)
+    ///     * Parent name record address (account #5)
+    ///
+    /// If this is a child record, the parent record's owner must approve by signing (account #6)
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` System program
+    ///   1. `[writeable, signer]` Funding account (must be a system account)
+    ///   2. `[writeable]` Name record to be created (program-derived address)
+    ///   3. `[]` Account owner (written into `NameRecordHeader::owner`)
+    ///   4. `[signer]` Account class (written into `NameRecordHeader::class`).
+    ///      If `Pubkey::default()` then the `signer` bit is not required
+    ///   5. `[]` Parent name record (written into `NameRecordHeader::parent_name). `Pubkey::default()` is equivalent to no parent.
+    ///   6. `[signer]` Owner of the parent name record. Optional but needed if parent name different than default.
+    Create {
+        /// SHA256 of the (HASH_PREFIX + Name) of the record to create, hashing is done off-chain
+        hashed_name: &'a [u8],
+        /// Number of lamports to fund the name record with
+        lamports: u64,
+        /// Number of bytes of memory to allocate in addition to the `NameRecordHeader`
+        space: u32,
+    },
+    /// Update the data in a name record
+    ///
+    /// Accounts expected by this instruction:
+    ///   * If account class is `Pubkey::default()`:
+    ///   0. `[writeable]` Name record to be updated
+    ///   1. `[signer]` Account owner
+    ///
+    ///   * If account class is not `Pubkey::default()`:
+    ///   0. `[writeable]` Name record to be updated
+    ///   1. `[signer]` Account class
+    ///
+    ///   * If the signer is the parent name account owner
+    ///   0. `[writeable]` Name record to be updated
+    ///   1. `[signer]` Parent name account owner
+    ///   2. `[]` Parent name record
+    Update {
+        /// Offset in the name record data at which `data` is written
+        offset: u32,
+        /// Data to write
+        data: &'a [u8],
+    },
+    /// Transfer ownership of a name record
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * If account class is `Pubkey::default()`:
+    ///   0. `[writeable]` Name record to be transferred
+    ///   1. `[signer]` Account owner
+    ///
+    ///   * If account class is not `Pubkey::default()`:
+    ///   0. `[writeable]` Name record to be transferred
+    ///   1. `[signer]` Account owner
+    ///   2. `[signer]` Account class
+    ///
+    ///    * If the signer is the parent name account owner
+    ///   0. `[writeable]` Name record to be transferred
+    ///   1. `[signer]` Account owner
+    ///   2. `[signer]` Account class
+    ///   3. `[]` Parent name record
+    Transfer {
+        /// The new owner of the name record
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        new_owner: Pubkey,
+    },
+    /// Delete a name record.
+    ///
+    /// Any lamports remaining in the name record will be transferred to the refund account (#2)
+    ///
+    /// Accounts expected by this instruction:
+    ///   0. `[writeable]` Name record to be deleted
+    ///   1. `[signer]` Account owner
+    ///   2. `[writeable]` Refund account
+    Delete,
+    /// Realloc the data of a name record.
+    ///
+    /// The space change cannot be more than `MAX_PERMITTED_DATA_INCREASE` for 1 instruction.
+    ///
+    /// Accounts expected by this instruction:
+    ///   0. `[]` System program
+    ///   1. `[writeable, signer]` Payer account (will be refunded if new `space` is less than current `space`)
+    ///   2. `[writeable]` Name record to be reallocated
+    ///   3. `[signer]` Account owner
+    Realloc {
+        /// New total number of bytes in addition to the `NameRecordHeader`.
+        space: u32,
+    },
+}
+impl<'a> NameServiceInstruction<'a> {
+    /// Unpacks a byte buffer into a [NameServiceInstruction](enum.NameServiceInstruction.html).
+    pub fn unpack(input: &'a [u8]) -> Result<Self, Error> {
+        let (&tag, rest) = input.split_first().ok_or(anyhow!("Invalid Name Service Instruction"))?;
+        Ok(match tag {
+            0 => {
+                let (hashed_name, rest) = unpack_bytes(rest)?;
+                let (lamports, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (space, _rest) = TokenInstruction::unpack_u32(rest)?;
+                Self::Create {
+                    hashed_name,
+                    lamports,
+                    space,
+                }
+            }
+            1 => {
+                let (offset, rest) = TokenInstruction::unpack_u32(rest)?;
+                let (data, _rest) = unpack_bytes(rest)?;
+                Self::Update { offset, data }
+            }
+            2 => {
+                let (new_owner, _rest) = TokenInstruction::unpack_pubkey(rest)?;
+                Self::Transfer { new_owner }
+            }
+            3 => Self::Delete,
+            4 => {
+                let (space, _rest) = TokenInstruction::unpack_u32(rest)?;
+                Self::Realloc { space }
+            }
+            _ => return Err(anyhow!("Invalid Name Service Instruction - unpack didn't match any tag value: {}", tag)),
+        })
+    }
+}
+
+/// Instructions of the Solana Name Service registrar program that registers `.sol` domains.
+///
+/// Only the instructions creating, splitting or burning domains are decoded, administrative
+/// instructions are rejected as unknown tags.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum SnsRegistrarInstruction<'a> {
+    /// Create the reverse lookup name record of a domain
+    CreateReverse {
+        /// The domain name, without the `.sol` suffix
+        name: &'a str,
+    },
+    /// Register a new `.sol` domain, paid in any of the accepted tokens
+    Create {
+        /// The domain name, without the `.sol` suffix
+        name: &'a str,
+        /// Number of bytes of data allocated in the name record
+        space: u32,
+        /// Index of the referrer in the registrar's referrer list, if any
+        referrer_idx_opt: Option<u16>,
+    },
+    /// Register a new `.sol` domain, with the name record owner different than the fee payer
+    CreateSplit {
+        /// The domain name, without the `.sol` suffix
+        name: &'a str,
+        /// Number of bytes of data allocated in the name record
+        space: u32,
+        /// Index of the referrer in the registrar's referrer list, if any
+        referrer_idx_opt: Option<u16>,
+    },
+    /// Burn a `.sol` domain, deleting its name record and its reverse lookup
+    Burn,
+}
+impl<'a> SnsRegistrarInstruction<'a> {
+    /// Unpacks a byte buffer into a [SnsRegistrarInstruction](enum.SnsRegistrarInstruction.html).
+    pub fn unpack(input: &'a [u8]) -> Result<Self, Error> {
+        let (&tag, rest) = input.split_first().ok_or(anyhow!("Invalid SNS Registrar Instruction"))?;
+        Ok(match tag {
+            12 => {
+                let (name, _rest) = unpack_str(rest)?;
+                Self::CreateReverse { name }
+            }
+            13 | 14 => {
+                let (name, rest) = unpack_str(rest)?;
+                let (space, rest) = TokenInstruction::unpack_u32(rest)?;
+                let referrer_idx_opt = match rest.split_first() {
+                    Option::Some((&0, _rest)) => None,
+                    Option::Some((&1, rest)) => Some(TokenInstruction::unpack_u16(rest)?.0),
+                    _ => return Err(anyhow!("Invalid SNS Registrar Instruction - {} - referrer index", tag)),
+                };
+                match tag {
+                    13 => Self::Create {
+                        name,
+                        space,
+                        referrer_idx_opt,
+                    },
+                    14 => Self::CreateSplit {
+                        name,
+                        space,
+                        referrer_idx_opt,
+                    },
+                    _ => unreachable!(),
+                }
+            }
+            16 => Self::Burn,
+            _ => return Err(anyhow!("Invalid SNS Registrar Instruction - unpack didn't match any tag value: {}", tag)),
+        })
+    }
+}
+
+/// Unpacks a borsh `Vec<u8>`, a little-endian `u32` length followed by the bytes
+fn unpack_bytes(input: &[u8]) -> Result<(&[u8], &[u8]), Error> {
+    let (len, rest) = TokenInstruction::unpack_u32(input)?;
+    let len = len as usize;
+    let bytes = rest.get(..len).ok_or(anyhow!("Unable to unpack bytes of length {}", len))?;
+    Ok((bytes, &rest[len..]))
+}
+
+/// Unpacks a borsh `String`, a little-endian `u32` length followed by the UTF-8 bytes
+fn unpack_str(input: &[u8]) -> Result<(&str, &[u8]), Error> {
+    let (bytes, rest) = unpack_bytes(input)?;
+    let value = std::str::from_utf8(bytes).map_err(|_| anyhow!("Unable to unpack string, invalid UTF-8"))?;
+    Ok((value, rest))
+}
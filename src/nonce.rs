@@ -0,0 +1,131 @@
+//! Durable nonce transactions, signed offline ahead of their submission
+//!
+//! A transaction normally expires some 150 blocks after the blockhash it references. A
+//! durable nonce transaction references instead the nonce stored in a nonce account, and
+//! stays valid until the nonce is advanced: its first instruction must be the System
+//! program's `AdvanceNonceAccount`, consuming the nonce. Payment processors track these
+//! transactions apart, for the delay between their signature and their execution.
+//!
+//! The nonce is advanced whether the rest of the transaction succeeds or not, so failed
+//! transactions are reported as well.
+
+use {
+    crate::{
+        accounts::TransactionKeys,
+        events::{signature, EventContext},
+        instructions::is_successful,
+        metrics,
+        program_ids::SYSTEM_PROGRAM_ID,
+        pubkey::Pubkey,
+        system_instruction::SystemInstruction,
+    },
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// A transaction using a durable nonce
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DurableNonceTransaction {
+    /// Position of the `AdvanceNonceAccount` instruction, the first top-level instruction
+    pub context: EventContext,
+    /// The nonce account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub nonce_account: Pubkey,
+    /// The nonce authority, which signed the transaction
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub nonce_authority: Pubkey,
+    /// The nonce consumed, base58 encoded: the message's recent blockhash
+    pub nonce: String,
+    /// Whether the transaction succeeded, the nonce being advanced either way
+    pub successful: bool,
+}
+
+impl DurableNonceTransaction {
+    /// Kind of the event, part of its [DurableNonceTransaction::event_id]
+    pub const KIND: &'static str = "durable_nonce_transaction";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+}
+
+/// Extracts the durable nonce transactions of `block`, failed transactions included
+pub fn block_durable_nonce_transactions(block: &Block) -> Vec<DurableNonceTransaction> {
+    let transactions: Vec<_> = block
+        .transactions
+        .iter()
+        .enumerate()
+        .filter_map(|(index, trx)| durable_nonce_transaction(trx, index as u32))
+        .collect();
+    metrics::events_emitted(DurableNonceTransaction::KIND, transactions.len());
+    transactions
+}
+
+/// Returns the durable nonce use of `trx`, the `transaction_index`-th of its block, `None`
+/// if its first instruction is not `AdvanceNonceAccount`
+///
+/// ```
+/// # use substreams_solana_program_instructions::{nonce::durable_nonce_transaction, pubkey::Pubkey};
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::{CompiledInstruction, ConfirmedTransaction, Message, Transaction, TransactionStatusMeta};
+/// let key = |byte: u8| Pubkey::from([byte; 32]).as_ref().to_vec();
+/// let trx = ConfirmedTransaction {
+///     transaction: Some(Transaction {
+///         message: Some(Message {
+///             // Nonce authority, nonce account, recent blockhashes sysvar, System program
+///             account_keys: vec![key(1), key(2), key(3), vec![0; 32]],
+///             recent_blockhash: vec![9; 32],
+///             instructions: vec![CompiledInstruction { program_id_index: 3, accounts: vec![1, 2, 0], data: vec![4, 0, 0, 0] }],
+///             ..Default::default()
+///         }),
+///         ..Default::default()
+///     }),
+///     meta: Some(TransactionStatusMeta::default()),
+/// };
+///
+/// let durable = durable_nonce_transaction(&trx, 0).unwrap();
+/// assert_eq!((durable.nonce_account, durable.nonce_authority), (Pubkey::from([2; 32]), Pubkey::from([1; 32])));
+/// assert_eq!(durable.nonce, Pubkey::from([9; 32]).to_string());
+/// assert_eq!(durable.context.instruction_index, 0);
+/// ```
+pub fn durable_nonce_transaction(trx: &ConfirmedTransaction, transaction_index: u32) -> Option<DurableNonceTransaction> {
+    let message = trx.transaction.as_ref()?.message.as_ref()?;
+    let keys = TransactionKeys::from_transaction(trx)?;
+    let instruction = message.instructions.first()?;
+    if keys.get(instruction.program_id_index as usize)? != SYSTEM_PROGRAM_ID.as_ref() {
+        return None;
+    }
+    if SystemInstruction::unpack(&instruction.data).ok()? != SystemInstruction::AdvanceNonceAccount {
+        return None;
+    }
+    let account = |position: usize| {
+        let index = *instruction.accounts.get(position)? as usize;
+        keys.get(index).and_then(|key| Pubkey::try_from(key).ok())
+    };
+
+    Some(DurableNonceTransaction {
+        context: EventContext {
+            signature: signature(trx),
+            transaction_index,
+            instruction_index: 0,
+            inner_instruction_index: None,
+            log_index: None,
+        },
+        nonce_account: account(0)?,
+        nonce_authority: account(2)?,
+        nonce: bs58::encode(&message.recent_blockhash).into_string(),
+        successful: is_successful(trx),
+    })
+}
+
+/// Returns `true` if `trx` uses a durable nonce, see [durable_nonce_transaction]
+pub fn is_durable_nonce_transaction(trx: &ConfirmedTransaction) -> bool {
+    durable_nonce_transaction(trx, 0).is_some()
+}
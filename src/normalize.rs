@@ -0,0 +1,55 @@
+//! Normalization of firehose blocks before extraction
+//!
+//! Blocks of some older firehose versions hold anomalies that extractors would otherwise
+//! turn into wrong events. The helpers of this module fix them in place, to be run on the
+//! block received by a map module before any extraction.
+
+use substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction, InnerInstructions};
+
+/// Removes the inner instruction entries of `trx` repeating an earlier entry of the same
+/// top-level instruction, returning the number of entries removed.
+///
+/// The runtime records a single entry per top-level instruction, but some older block
+/// versions repeat it, making every inner instruction of the entry appear twice. Only
+/// entries identical to an earlier one, same top-level index and same instructions, are
+/// removed: identical inner instructions within an entry are legitimate, two identical
+/// transfers in a row for instance, and are kept.
+///
+/// ```
+/// # use substreams_solana_program_instructions::normalize::dedup_inner_instructions;
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::{ConfirmedTransaction, InnerInstruction, InnerInstructions, TransactionStatusMeta};
+/// let entry = InnerInstructions {
+///     index: 0,
+///     instructions: vec![InnerInstruction { data: vec![3], ..Default::default() }],
+/// };
+/// let mut trx = ConfirmedTransaction {
+///     meta: Some(TransactionStatusMeta {
+///         inner_instructions: vec![entry.clone(), entry],
+///         ..Default::default()
+///     }),
+///     ..Default::default()
+/// };
+/// assert_eq!(dedup_inner_instructions(&mut trx), 1);
+/// assert_eq!(trx.meta.unwrap().inner_instructions.len(), 1);
+/// ```
+pub fn dedup_inner_instructions(trx: &mut ConfirmedTransaction) -> usize {
+    let meta = match trx.meta.as_mut() {
+        Some(meta) => meta,
+        None => return 0,
+    };
+    let len = meta.inner_instructions.len();
+    let mut kept: Vec<InnerInstructions> = Vec::with_capacity(len);
+    for entry in meta.inner_instructions.drain(..) {
+        if !kept.contains(&entry) {
+            kept.push(entry);
+        }
+    }
+    meta.inner_instructions = kept;
+    len - meta.inner_instructions.len()
+}
+
+/// Applies [dedup_inner_instructions] to every transaction of `block`, returning the number
+/// of entries removed
+pub fn dedup_block_inner_instructions(block: &mut Block) -> usize {
+    block.transactions.iter_mut().map(dedup_inner_instructions).sum()
+}
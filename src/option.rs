@@ -171,7 +171,7 @@ impl<T> COption<T> {
     /// assert_eq!(x.expect("the world is ending"), "value");
     /// ```
     ///
-    /// ```ignore{.should_panic}
+    /// ```ignore
     /// let x: COption<&str> = COption::None;
     /// x.expect("the world is ending"); // panics with `the world is ending`
     /// ```
@@ -203,7 +203,7 @@ impl<T> COption<T> {
     /// assert_eq!(x.unwrap(), "air");
     /// ```
     ///
-    /// ```ignore{.should_panic}
+    /// ```ignore
     /// let x: COption<&str> = COption::None;
     /// assert_eq!(x.unwrap(), "air"); // fails
     /// ```
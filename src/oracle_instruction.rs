@@ -0,0 +1,470 @@
+//! Pyth and Switchboard oracle instruction types
+//!
+//! Pyth prices are pulled: a client posts a price update signed by the Wormhole guardians to
+//! the Pyth receiver program, which stores it in a `PriceUpdateV2` account, either ephemeral or,
+//! through the push oracle program, the canonical price feed account of the feed. Updates carry
+//! the price as a `PriceFeedMessage`, big-endian encoded unlike the rest of the payload.
+//! Switchboard v2 aggregators are pushed: oracles answer the rounds opened on an aggregator
+//! with `aggregator_save_result`, each answer a decimal number. All three programs are Anchor
+//! programs, their instructions identified by an 8 bytes discriminator.
+
+use anyhow::anyhow;
+use {
+    crate::{pubkey::Pubkey, token_instruction_2022::TokenInstruction},
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// Pyth receiver `post_update_atomic` discriminator, hash of `global:post_update_atomic`
+pub const PYTH_POST_UPDATE_ATOMIC_DISCRIMINATOR: [u8; 8] = [49, 172, 84, 192, 175, 180, 52, 234];
+/// Pyth receiver `post_update` discriminator, hash of `global:post_update`
+pub const PYTH_POST_UPDATE_DISCRIMINATOR: [u8; 8] = [133, 95, 207, 175, 11, 79, 118, 44];
+/// Pyth push oracle `update_price_feed` discriminator, hash of `global:update_price_feed`
+pub const PYTH_UPDATE_PRICE_FEED_DISCRIMINATOR: [u8; 8] = [28, 9, 93, 150, 86, 153, 188, 115];
+/// `PriceUpdateV2` account discriminator, hash of `account:PriceUpdateV2`
+pub const PYTH_PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+/// Switchboard `aggregator_open_round` discriminator, hash of `global:aggregator_open_round`
+pub const SWITCHBOARD_AGGREGATOR_OPEN_ROUND_DISCRIMINATOR: [u8; 8] = [239, 69, 229, 179, 156, 246, 118, 191];
+/// Switchboard `aggregator_save_result` discriminator, hash of `global:aggregator_save_result`
+pub const SWITCHBOARD_AGGREGATOR_SAVE_RESULT_DISCRIMINATOR: [u8; 8] = [21, 67, 5, 0, 74, 168, 51, 192];
+
+/// Length of the nodes of the Merkle proofs of Pyth price updates
+const MERKLE_NODE_BYTES: usize = 20;
+
+/// A Pyth price, the price being `price * 10^exponent`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceFeedMessage {
+    /// Identifier of the price feed
+    pub feed_id: [u8; 32],
+    /// Price
+    pub price: i64,
+    /// Confidence interval around the price
+    pub conf: u64,
+    /// Exponent of the price and confidence interval
+    pub exponent: i32,
+    /// Unix timestamp of the price
+    pub publish_time: i64,
+    /// Unix timestamp of the previous price
+    pub prev_publish_time: i64,
+    /// Exponentially-weighted moving average of the price
+    pub ema_price: i64,
+    /// Exponentially-weighted moving average of the confidence interval
+    pub ema_conf: u64,
+}
+
+impl PriceFeedMessage {
+    /// Price as a floating point number, `price * 10^exponent`
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::oracle_instruction::PriceFeedMessage;
+    /// let message = PriceFeedMessage {
+    ///     feed_id: [0; 32],
+    ///     price: 15_012_345_678,
+    ///     conf: 0,
+    ///     exponent: -8,
+    ///     publish_time: 0,
+    ///     prev_publish_time: 0,
+    ///     ema_price: 0,
+    ///     ema_conf: 0,
+    /// };
+    /// assert_eq!(message.price_f64(), 150.12345678);
+    /// ```
+    pub fn price_f64(&self) -> f64 {
+        // Dividing by the exact power of ten rounds once, multiplying by its inverse twice
+        if self.exponent < 0 {
+            self.price as f64 / 10f64.powi(-self.exponent)
+        } else {
+            self.price as f64 * 10f64.powi(self.exponent)
+        }
+    }
+
+    /// Unpacks a message of a price update, big-endian encoded after its type byte. Other
+    /// message types, rejected by the receiver program, fail to unpack.
+    pub fn unpack_message(input: &[u8]) -> Result<Self, Error> {
+        let (&message_type, rest) = input.split_first().ok_or(anyhow!("Unable to unpack Pyth message"))?;
+        if message_type != 0 {
+            return Err(anyhow!("Invalid Pyth message - not a price feed message: {}", message_type));
+        }
+        let (feed_id, rest) = TokenInstruction::unpack_bytes(rest, 32)?;
+        let (price, rest) = unpack_be::<8>(rest)?;
+        let (conf, rest) = unpack_be::<8>(rest)?;
+        let (exponent, rest) = unpack_be::<4>(rest)?;
+        let (publish_time, rest) = unpack_be::<8>(rest)?;
+        let (prev_publish_time, rest) = unpack_be::<8>(rest)?;
+        let (ema_price, rest) = unpack_be::<8>(rest)?;
+        let (ema_conf, _rest) = unpack_be::<8>(rest)?;
+        Ok(Self {
+            feed_id: feed_id.try_into().map_err(|_| anyhow!("Unable to unpack feed id"))?,
+            price: i64::from_be_bytes(price),
+            conf: u64::from_be_bytes(conf),
+            exponent: i32::from_be_bytes(exponent),
+            publish_time: i64::from_be_bytes(publish_time),
+            prev_publish_time: i64::from_be_bytes(prev_publish_time),
+            ema_price: i64::from_be_bytes(ema_price),
+            ema_conf: u64::from_be_bytes(ema_conf),
+        })
+    }
+
+    /// Unpacks a message stored in an account, little-endian encoded without type byte
+    fn unpack_stored(input: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (feed_id, rest) = TokenInstruction::unpack_bytes(input, 32)?;
+        let (price, rest) = TokenInstruction::unpack_u64(rest)?;
+        let (conf, rest) = TokenInstruction::unpack_u64(rest)?;
+        let (exponent, rest) = TokenInstruction::unpack_u32(rest)?;
+        let (publish_time, rest) = TokenInstruction::unpack_u64(rest)?;
+        let (prev_publish_time, rest) = TokenInstruction::unpack_u64(rest)?;
+        let (ema_price, rest) = TokenInstruction::unpack_u64(rest)?;
+        let (ema_conf, rest) = TokenInstruction::unpack_u64(rest)?;
+        let message = Self {
+            feed_id: feed_id.try_into().map_err(|_| anyhow!("Unable to unpack feed id"))?,
+            price: price as i64,
+            conf,
+            exponent: exponent as i32,
+            publish_time: publish_time as i64,
+            prev_publish_time: prev_publish_time as i64,
+            ema_price: ema_price as i64,
+            ema_conf,
+        };
+        Ok((message, rest))
+    }
+}
+
+/// Verification of the Wormhole signatures of a price update
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationLevel {
+    /// Only some of the signatures were verified
+    Partial {
+        /// Number of signatures verified
+        num_signatures: u8,
+    },
+    /// All the signatures needed were verified
+    Full,
+}
+
+/// A Pyth price stored by the receiver program
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceUpdateV2 {
+    /// Account allowed to overwrite the price
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub write_authority: Pubkey,
+    /// Verification of the update
+    pub verification_level: VerificationLevel,
+    /// The price
+    pub price_message: PriceFeedMessage,
+    /// Slot the update was posted at
+    pub posted_slot: u64,
+}
+
+impl PriceUpdateV2 {
+    /// Unpacks the data of a `PriceUpdateV2` account, discriminator included
+    pub fn unpack(data: &[u8]) -> Result<Self, Error> {
+        let (discriminator, rest) = TokenInstruction::unpack_bytes(data, 8).map_err(|_| anyhow!("Invalid Pyth price update"))?;
+        if discriminator != PYTH_PRICE_UPDATE_V2_DISCRIMINATOR {
+            return Err(anyhow!("Invalid Pyth price update - account discriminator: {:?}", discriminator));
+        }
+        let (write_authority, rest) = TokenInstruction::unpack_pubkey(rest)?;
+        let (verification_level, rest) = match rest {
+            [0, num_signatures, rest @ ..] => (
+                VerificationLevel::Partial {
+                    num_signatures: *num_signatures,
+                },
+                rest,
+            ),
+            [1, rest @ ..] => (VerificationLevel::Full, rest),
+            _ => return Err(anyhow!("Unable to unpack verification level")),
+        };
+        let (price_message, rest) = PriceFeedMessage::unpack_stored(rest)?;
+        let (posted_slot, _rest) = TokenInstruction::unpack_u64(rest)?;
+        Ok(Self {
+            write_authority,
+            verification_level,
+            price_message,
+            posted_slot,
+        })
+    }
+}
+
+/// Instructions of the Pyth receiver and push oracle programs.
+///
+/// Only the instructions posting prices are decoded, configuration instructions are rejected
+/// as unknown discriminators. Price messages are decoded, the Merkle proofs and Wormhole
+/// messages attesting them are not.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PythInstruction {
+    /// Post a price update along with the Wormhole message attesting it, verified in the
+    /// same instruction. Sent to the receiver program.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Payer
+    ///   1. `[]` Wormhole guardian set
+    ///   2. `[]` Receiver config
+    ///   3. `[writable]` Treasury
+    ///   4. `[writable, signer]` Price update account
+    ///   5. `[]` System program
+    ///   6. `[signer]` Write authority of the price update account
+    PostUpdateAtomic {
+        /// The price
+        price_feed: PriceFeedMessage,
+        /// Treasury receiving the update fee
+        treasury_id: u8,
+    },
+    /// Post a price update attested by a Wormhole message verified beforehand. Sent to the
+    /// receiver program.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Payer
+    ///   1. `[]` Verified Wormhole message
+    ///   2. `[]` Receiver config
+    ///   3. `[writable]` Treasury
+    ///   4. `[writable, signer]` Price update account
+    ///   5. `[]` System program
+    ///   6. `[signer]` Write authority of the price update account
+    PostUpdate {
+        /// The price
+        price_feed: PriceFeedMessage,
+        /// Treasury receiving the update fee
+        treasury_id: u8,
+    },
+    /// Update the canonical price feed account of a feed, posting the update through the
+    /// receiver program. Sent to the push oracle program.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Payer
+    ///   1. `[]` Receiver program
+    ///   2. `[]` Verified Wormhole message
+    ///   3. `[]` Receiver config
+    ///   4. `[writable]` Treasury
+    ///   5. `[writable]` Price feed account
+    ///   6. `[]` System program
+    UpdatePriceFeed {
+        /// The price
+        price_feed: PriceFeedMessage,
+        /// Treasury receiving the update fee
+        treasury_id: u8,
+        /// Shard of the price feed account
+        shard_id: u16,
+        /// Identifier of the feed of the price feed account
+        feed_id: [u8; 32],
+    },
+}
+impl PythInstruction {
+    /// Unpacks a byte buffer into a [PythInstruction](enum.PythInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, Error> {
+        let (discriminator, rest) =
+            TokenInstruction::unpack_bytes(input, 8).map_err(|_| anyhow!("Invalid Pyth Instruction"))?;
+        let discriminator: [u8; 8] = discriminator.try_into().map_err(|_| anyhow!("Invalid Pyth Instruction"))?;
+        Ok(match discriminator {
+            PYTH_POST_UPDATE_ATOMIC_DISCRIMINATOR => {
+                let (_vaa, rest) = unpack_vec(rest, 1)?;
+                let (price_feed, treasury_id, _rest) = unpack_merkle_price_update(rest)?;
+                Self::PostUpdateAtomic { price_feed, treasury_id }
+            }
+            PYTH_POST_UPDATE_DISCRIMINATOR => {
+                let (price_feed, treasury_id, _rest) = unpack_merkle_price_update(rest)?;
+                Self::PostUpdate { price_feed, treasury_id }
+            }
+            PYTH_UPDATE_PRICE_FEED_DISCRIMINATOR => {
+                let (price_feed, treasury_id, rest) = unpack_merkle_price_update(rest)?;
+                let (shard_id, rest) = TokenInstruction::unpack_u16(rest)?;
+                let (feed_id, _rest) = TokenInstruction::unpack_bytes(rest, 32)?;
+                Self::UpdatePriceFeed {
+                    price_feed,
+                    treasury_id,
+                    shard_id,
+                    feed_id: feed_id.try_into().map_err(|_| anyhow!("Unable to unpack feed id"))?,
+                }
+            }
+            _ => return Err(anyhow!("Invalid Pyth Instruction - unpack didn't match any discriminator: {:?}", discriminator)),
+        })
+    }
+
+    /// The price posted
+    pub fn price_feed(&self) -> &PriceFeedMessage {
+        match self {
+            Self::PostUpdateAtomic { price_feed, .. }
+            | Self::PostUpdate { price_feed, .. }
+            | Self::UpdatePriceFeed { price_feed, .. } => price_feed,
+        }
+    }
+}
+
+/// A Switchboard decimal number, `mantissa * 10^-scale`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwitchboardDecimal {
+    /// Mantissa
+    pub mantissa: i128,
+    /// Number of decimals
+    pub scale: u32,
+}
+
+impl SwitchboardDecimal {
+    /// The number as a floating point number
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::oracle_instruction::SwitchboardDecimal;
+    /// assert_eq!(SwitchboardDecimal { mantissa: 1_234_500, scale: 4 }.to_f64(), 123.45);
+    /// ```
+    pub fn to_f64(&self) -> f64 {
+        self.mantissa as f64 / 10f64.powi(self.scale as i32)
+    }
+
+    fn unpack(input: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (mantissa, rest) = TokenInstruction::unpack_bytes(input, 16)?;
+        let (scale, rest) = TokenInstruction::unpack_u32(rest)?;
+        let mantissa = i128::from_le_bytes(mantissa.try_into().map_err(|_| anyhow!("Unable to unpack mantissa"))?);
+        Ok((Self { mantissa, scale }, rest))
+    }
+}
+
+/// Instructions of the Switchboard v2 program.
+///
+/// Only the instructions running aggregator rounds are decoded, account management
+/// instructions are rejected as unknown discriminators.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwitchboardInstruction {
+    /// Open a round on an aggregator, assigning oracles to answer it
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Aggregator
+    ///   1. `[writable]` Lease
+    ///   2. `[writable]` Oracle queue
+    ///   3. `[]` Queue authority
+    ///   4. `[writable]` Permission
+    ///   5. `[writable]` Lease escrow
+    ///   6. `[]` Program state
+    ///   7. `[writable]` Payout wallet of the caller
+    ///   8. `[]` Token program
+    ///   9. `[]` Queue data buffer
+    ///   10. `[]` Mint
+    AggregatorOpenRound {
+        /// Bump of the program state account
+        state_bump: u8,
+        /// Bump of the lease account
+        lease_bump: u8,
+        /// Bump of the permission account
+        permission_bump: u8,
+        /// Randomness of the oracle assignment
+        jitter: u8,
+    },
+    /// Save the answer of an oracle to the current round of an aggregator
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Aggregator
+    ///   1. `[writable]` Oracle
+    ///   2. `[signer]` Oracle authority
+    ///   3. `[]` Oracle queue
+    ///   4. `[]` Queue authority
+    ///   5. `[writable]` Aggregator permission
+    ///   6. `[]` Oracle permission
+    ///   7. `[writable]` Lease
+    ///   8. `[writable]` Lease escrow
+    ///   9. `[]` Token program
+    ///   10. `[]` Program state
+    ///   11. `[writable]` History buffer
+    ///   12. `[]` Mint
+    AggregatorSaveResult {
+        /// Index of the oracle among the oracles of the round
+        oracle_idx: u32,
+        /// Whether the oracle failed to produce an answer
+        error: bool,
+        /// The answer
+        value: SwitchboardDecimal,
+        /// Checksum of the jobs run by the oracle
+        jobs_checksum: [u8; 32],
+        /// Lowest result of the jobs
+        min_response: SwitchboardDecimal,
+        /// Highest result of the jobs
+        max_response: SwitchboardDecimal,
+    },
+}
+impl SwitchboardInstruction {
+    /// Unpacks a byte buffer into a [SwitchboardInstruction](enum.SwitchboardInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, Error> {
+        let (discriminator, rest) =
+            TokenInstruction::unpack_bytes(input, 8).map_err(|_| anyhow!("Invalid Switchboard Instruction"))?;
+        let discriminator: [u8; 8] = discriminator.try_into().map_err(|_| anyhow!("Invalid Switchboard Instruction"))?;
+        Ok(match discriminator {
+            SWITCHBOARD_AGGREGATOR_OPEN_ROUND_DISCRIMINATOR => match rest {
+                [state_bump, lease_bump, permission_bump, jitter, ..] => Self::AggregatorOpenRound {
+                    state_bump: *state_bump,
+                    lease_bump: *lease_bump,
+                    permission_bump: *permission_bump,
+                    jitter: *jitter,
+                },
+                _ => return Err(anyhow!("Unable to unpack open round parameters")),
+            },
+            SWITCHBOARD_AGGREGATOR_SAVE_RESULT_DISCRIMINATOR => {
+                let (oracle_idx, rest) = TokenInstruction::unpack_u32(rest)?;
+                let (&error, rest) = rest.split_first().ok_or(anyhow!("Unable to unpack error flag"))?;
+                let (value, rest) = SwitchboardDecimal::unpack(rest)?;
+                let (jobs_checksum, rest) = TokenInstruction::unpack_bytes(rest, 32)?;
+                let (min_response, rest) = SwitchboardDecimal::unpack(rest)?;
+                // Followed by the bumps of the accounts
+                let (max_response, _rest) = SwitchboardDecimal::unpack(rest)?;
+                Self::AggregatorSaveResult {
+                    oracle_idx,
+                    error: error != 0,
+                    value,
+                    jobs_checksum: jobs_checksum.try_into().map_err(|_| anyhow!("Unable to unpack jobs checksum"))?,
+                    min_response,
+                    max_response,
+                }
+            }
+            _ => return Err(anyhow!("Invalid Switchboard Instruction - unpack didn't match any discriminator: {:?}", discriminator)),
+        })
+    }
+}
+
+/// Unpacks a `MerklePriceUpdate` followed by the treasury id, returning the price of the
+/// message
+fn unpack_merkle_price_update(input: &[u8]) -> Result<(PriceFeedMessage, u8, &[u8]), Error> {
+    let (message, rest) = unpack_vec(input, 1)?;
+    let (_proof, rest) = unpack_vec(rest, MERKLE_NODE_BYTES)?;
+    let (&treasury_id, rest) = rest.split_first().ok_or(anyhow!("Unable to unpack treasury id"))?;
+    Ok((PriceFeedMessage::unpack_message(message)?, treasury_id, rest))
+}
+
+/// Unpacks a borsh `Vec` of fixed size items as raw bytes, a `u32` length followed by the items
+fn unpack_vec(input: &[u8], item_len: usize) -> Result<(&[u8], &[u8]), Error> {
+    let (len, rest) = TokenInstruction::unpack_u32(input)?;
+    let bytes_len = (len as usize)
+        .checked_mul(item_len)
+        .ok_or(anyhow!("Unable to unpack vec, invalid length: {}", len))?;
+    TokenInstruction::unpack_bytes(rest, bytes_len)
+}
+
+fn unpack_be<const N: usize>(input: &[u8]) -> Result<([u8; N], &[u8]), Error> {
+    let (bytes, rest) = TokenInstruction::unpack_bytes(input, N)?;
+    Ok((bytes.try_into().map_err(|_| anyhow!("Unable to unpack big-endian integer"))?, rest))
+}
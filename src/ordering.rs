@@ -0,0 +1,85 @@
+//! Canonical ordering of event batches
+//!
+//! Extractors emit the events of a block in execution order, see [crate::events]. Batches
+//! gathering several blocks or extractors, for stream joins or sinks, are sorted back into
+//! that order with [sort_events]: by slot, transaction index, instruction index, then inner
+//! instruction index. The sort is stable, so events of a same instruction keep the order of
+//! the batch.
+
+use crate::events::EventContext;
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Position of an event within the chain, ordered like execution
+///
+/// ```
+/// # use substreams_solana_program_instructions::{events::EventContext, ordering::EventPosition};
+/// let top_level = EventContext { transaction_index: 1, instruction_index: 0, ..Default::default() };
+/// let inner = EventContext { inner_instruction_index: Some(0), ..top_level.clone() };
+/// let next_transaction = EventContext { transaction_index: 2, ..Default::default() };
+/// assert!(EventPosition::new(100, &top_level) < EventPosition::new(100, &inner));
+/// assert!(EventPosition::new(100, &inner) < EventPosition::new(100, &next_transaction));
+/// assert!(EventPosition::new(100, &next_transaction) < EventPosition::new(101, &top_level));
+/// ```
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EventPosition {
+    /// Slot of the block
+    pub slot: u64,
+    /// Index of the transaction in its block
+    pub transaction_index: u32,
+    /// Index of the top-level instruction
+    pub instruction_index: u32,
+    /// Index of the inner instruction, `None` for top-level instructions, which come first
+    pub inner_instruction_index: Option<u32>,
+    /// Position among the data logged by the instruction, `None` for events not read from logs
+    pub log_index: Option<u32>,
+}
+
+impl EventPosition {
+    /// Position of the event at `context` in the block at `slot`
+    pub fn new(slot: u64, context: &EventContext) -> Self {
+        Self {
+            slot,
+            transaction_index: context.transaction_index,
+            instruction_index: context.instruction_index,
+            inner_instruction_index: context.inner_instruction_index,
+            log_index: context.log_index,
+        }
+    }
+}
+
+/// Sorts `events` in canonical order, `position` giving the position of each event
+///
+/// ```
+/// # use substreams_solana_program_instructions::{events::EventContext, ordering::*};
+/// // (slot, context) pairs gathered from two blocks, out of order
+/// let mut events = vec![
+///     (101, EventContext { transaction_index: 0, ..Default::default() }),
+///     (100, EventContext { transaction_index: 3, ..Default::default() }),
+///     (100, EventContext { transaction_index: 3, inner_instruction_index: Some(1), ..Default::default() }),
+///     (100, EventContext { transaction_index: 3, inner_instruction_index: Some(0), ..Default::default() }),
+/// ];
+/// sort_events(&mut events, |(slot, context)| EventPosition::new(*slot, context));
+/// assert!(is_sorted(&events, |(slot, context)| EventPosition::new(*slot, context)));
+/// assert_eq!(events[0].1.inner_instruction_index, None);
+/// assert_eq!(events[2].1.inner_instruction_index, Some(1));
+/// assert_eq!(events[3].0, 101);
+/// ```
+pub fn sort_events<T, F: Fn(&T) -> EventPosition>(events: &mut [T], position: F) {
+    events.sort_by_key(|event| position(event));
+}
+
+/// Sorts `events` of a single block in canonical order, `context` giving the context of each
+/// event
+pub fn sort_block_events<T, F: Fn(&T) -> &EventContext>(events: &mut [T], context: F) {
+    events.sort_by(|a, b| context(a).cmp(context(b)));
+}
+
+/// Returns `true` if `events` are in canonical order, `position` giving the position of each
+/// event
+pub fn is_sorted<T, F: Fn(&T) -> EventPosition>(events: &[T], position: F) -> bool {
+    events.windows(2).all(|pair| position(&pair[0]) <= position(&pair[1]))
+}
@@ -0,0 +1,106 @@
+//! Block-level extraction parallelized across transactions with rayon
+//!
+//! Only available with the `rayon` feature on native targets, substreams modules run
+//! single-threaded in WASM. Results are returned in the same order as their sequential
+//! counterparts.
+
+use {
+    crate::{
+        events::{group_member_events, GroupMemberAdded},
+        instructions::{instructions, is_successful, DecodedInstruction},
+        metrics,
+        program_ids::is_token_program,
+        pubkey::Pubkey,
+        snapshot::{decode_account, DecodedAccount},
+        token_instruction_2022::TokenInstruction,
+    },
+    rayon::prelude::*,
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+/// Runs the transaction-level extractor `extract` over every transaction of `block` in
+/// parallel, concatenating the results in transaction order
+///
+/// ```no_run
+/// # use substreams_solana_program_instructions::{events::group_member_events, parallel::par_extract};
+/// # let block = substreams_solana_core::pb::sf::solana::r#type::v1::Block::default();
+/// let members = par_extract(&block, group_member_events);
+/// ```
+pub fn par_extract<'a, T, F, I>(block: &'a Block, extract: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(&'a ConfirmedTransaction, u32) -> I + Sync + Send,
+    I: IntoIterator<Item = T>,
+{
+    block
+        .transactions
+        .par_iter()
+        .enumerate()
+        .flat_map_iter(|(index, trx)| extract(trx, index as u32))
+        .collect()
+}
+
+/// Parallel counterpart of [crate::instructions::token_instructions], decoding the token
+/// program instructions of the successful transactions of `block`
+///
+/// ```
+/// # use substreams_solana_program_instructions::{parallel::par_token_instructions, program_ids::register_token_program, pubkey::Pubkey, token_instruction_2022::TokenInstruction, unified::TokenProgram};
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::*;
+/// let fork = Pubkey::from([7; 32]);
+/// let trx = ConfirmedTransaction {
+///     transaction: Some(Transaction {
+///         message: Some(Message {
+///             account_keys: vec![vec![1; 32], fork.as_ref().to_vec()],
+///             instructions: vec![CompiledInstruction { program_id_index: 1, accounts: vec![0, 0, 0], data: vec![9] }],
+///             ..Default::default()
+///         }),
+///         ..Default::default()
+///     }),
+///     meta: Some(TransactionStatusMeta::default()),
+/// };
+/// let block = Block { transactions: vec![trx; 64], ..Default::default() };
+///
+/// // Registered on this thread, seen by the rayon workers
+/// register_token_program(fork, TokenProgram::Token2022);
+/// let decoded = par_token_instructions(&block);
+/// assert_eq!(decoded.len(), 64);
+/// assert!(decoded.iter().all(|instruction| instruction.decoded == TokenInstruction::CloseAccount));
+/// ```
+pub fn par_token_instructions(block: &Block) -> Vec<DecodedInstruction<'_>> {
+    par_extract(block, |trx, transaction_index| {
+        let successful = is_successful(trx);
+        instructions(trx)
+            .filter(move |instruction| successful && is_token_program(instruction.program_id))
+            .filter_map(move |instruction| {
+                let decoded = TokenInstruction::unpack(instruction.data);
+                metrics::decoded(instruction.data, &decoded);
+                decoded
+                    .ok()
+                    .map(|decoded| DecodedInstruction {
+                        transaction_index,
+                        transaction: trx,
+                        instruction,
+                        decoded,
+                    })
+            })
+    })
+}
+
+/// Parallel counterpart of [crate::events::block_group_member_events]
+pub fn par_block_group_member_events(block: &Block) -> Vec<GroupMemberAdded> {
+    par_extract(block, group_member_events)
+}
+
+/// Parallel counterpart of [crate::snapshot::decode_accounts], decoding the mints and token
+/// accounts of `(address, owner, data)` account records in record order
+pub fn par_decode_accounts<I, O, D>(records: I) -> Vec<DecodedAccount>
+where
+    I: IntoParallelIterator<Item = (Pubkey, O, D)>,
+    O: AsRef<[u8]>,
+    D: AsRef<[u8]>,
+{
+    records
+        .into_par_iter()
+        .filter_map(|(address, owner, data)| decode_account(address, owner.as_ref(), data.as_ref()))
+        .collect()
+}
@@ -0,0 +1,95 @@
+//! Detection of pass-through programs, programs whose instructions never move tokens and
+//! can be skipped by extraction pipelines (memos, no-ops, compute budget, guard assertions)
+
+use {
+    crate::{
+        instructions::instructions,
+        program_ids::{
+            COMPUTE_BUDGET_PROGRAM_ID, LIGHTHOUSE_PROGRAM_ID, MEMO_PROGRAM_ID, MEMO_V1_PROGRAM_ID,
+            NOOP_PROGRAM_ID,
+        },
+    },
+    substreams_solana_core::pb::sf::solana::r#type::v1::ConfirmedTransaction,
+};
+
+/// Kind of pass-through program
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PassThroughKind {
+    /// SPL Memo, v1 or v2
+    Memo,
+    /// SPL Noop, used to log data through CPI
+    Noop,
+    /// Compute Budget, compute unit limit and price
+    ComputeBudget,
+    /// Guard programs asserting on account state (Lighthouse), failing the transaction otherwise
+    Guard,
+}
+
+/// Classifies `program_id`, returning `None` if it's not a known pass-through program
+pub fn classify(program_id: &[u8]) -> Option<PassThroughKind> {
+    if program_id == MEMO_PROGRAM_ID.as_ref() || program_id == MEMO_V1_PROGRAM_ID.as_ref() {
+        Some(PassThroughKind::Memo)
+    } else if program_id == NOOP_PROGRAM_ID.as_ref() {
+        Some(PassThroughKind::Noop)
+    } else if program_id == COMPUTE_BUDGET_PROGRAM_ID.as_ref() {
+        Some(PassThroughKind::ComputeBudget)
+    } else if program_id == LIGHTHOUSE_PROGRAM_ID.as_ref() {
+        Some(PassThroughKind::Guard)
+    } else {
+        None
+    }
+}
+
+/// Returns `true` if `program_id` is a known pass-through program
+pub fn is_pass_through(program_id: &[u8]) -> bool {
+    classify(program_id).is_some()
+}
+
+/// Instruction counts of a transaction, top-level and inner instructions included, broken
+/// down by pass-through kind
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Composition {
+    /// Memo instructions
+    pub memo: usize,
+    /// Noop instructions
+    pub noop: usize,
+    /// Compute budget instructions
+    pub compute_budget: usize,
+    /// Guard assertion instructions
+    pub guard: usize,
+    /// Instructions of any other program
+    pub other: usize,
+}
+
+impl Composition {
+    /// Computes the composition of `trx`
+    pub fn of(trx: &ConfirmedTransaction) -> Self {
+        let mut composition = Self::default();
+        for instruction in instructions(trx) {
+            composition.record(classify(instruction.program_id));
+        }
+
+        composition
+    }
+
+    /// Records one instruction of the given kind, `None` being any other program
+    pub fn record(&mut self, kind: Option<PassThroughKind>) {
+        match kind {
+            Some(PassThroughKind::Memo) => self.memo += 1,
+            Some(PassThroughKind::Noop) => self.noop += 1,
+            Some(PassThroughKind::ComputeBudget) => self.compute_budget += 1,
+            Some(PassThroughKind::Guard) => self.guard += 1,
+            None => self.other += 1,
+        }
+    }
+
+    /// Number of pass-through instructions
+    pub fn pass_through(&self) -> usize {
+        self.memo + self.noop + self.compute_budget + self.guard
+    }
+
+    /// Total number of instructions
+    pub fn total(&self) -> usize {
+        self.pass_through() + self.other
+    }
+}
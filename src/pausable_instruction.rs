@@ -0,0 +1,77 @@
+use anyhow::anyhow;
+use {
+    substreams::errors::Error,
+    crate::{token_instruction_2022::TokenInstruction},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+use crate::pubkey::Pubkey;
+
+/// Pausable extension instructions
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+pub enum PausableInstruction {
+    /// Initialize the pausable extension for the given mint account
+    ///
+    /// Fails if the account has already been initialized, so must be called
+    /// before `InitializeMint`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint account to initialize.
+    Initialize {
+        /// The public key for the account that can pause the mint
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        authority: Pubkey,
+    },
+    /// Pause minting, burning, and transferring for the mint.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint to update.
+    ///   1. `[signer]` The mint's pause authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint to update.
+    ///   1. `[]` The mint's multisignature pause authority.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    Pause,
+    /// Resume minting, burning, and transferring for the mint.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint to update.
+    ///   1. `[signer]` The mint's pause authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint to update.
+    ///   1. `[]` The mint's multisignature pause authority.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    Resume,
+}
+impl PausableInstruction {
+    /// Unpacks a byte buffer into a PausableInstruction
+    pub fn unpack(input: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (&tag, rest) = input.split_first().ok_or(anyhow!("Invalid Pausable Instruction"))?;
+        Ok(match tag {
+            0 => {
+                let (authority, rest) = TokenInstruction::unpack_pubkey(rest)?;
+                (Self::Initialize { authority }, rest)
+            }
+            1 => (Self::Pause, rest),
+            2 => (Self::Resume, rest),
+            _ => return Err(anyhow!("Invalid Pausable Instruction - unpack didn't match any tag value: {}", tag)),
+        })
+    }
+}
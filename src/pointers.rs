@@ -0,0 +1,147 @@
+//! Resolution of the metadata, group and group member pointers of Token 2022 mints
+//!
+//! A pointer extension names the account holding a mint's metadata, group or group member
+//! data: the mint itself, which then holds the data in a second extension, or an external
+//! account, the metadata account of another program for instance. [resolve_pointer] reads a
+//! pointer from the mint's data and tells the two apart, and [is_pointed_account] checks an
+//! account claiming to hold the data against it.
+
+use crate::{
+    account_data::{extension_data, extension_types, optional_nonzero_pubkey},
+    pubkey::Pubkey,
+    token_instruction_2022::ExtensionType,
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// Pointer extensions of a mint
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PointerKind {
+    /// `MetadataPointer`, to the token metadata
+    Metadata,
+    /// `GroupPointer`, to the token group
+    Group,
+    /// `GroupMemberPointer`, to the token group member
+    GroupMember,
+}
+
+impl PointerKind {
+    /// The pointer extension
+    pub fn pointer_extension(self) -> ExtensionType {
+        match self {
+            Self::Metadata => ExtensionType::MetadataPointer,
+            Self::Group => ExtensionType::GroupPointer,
+            Self::GroupMember => ExtensionType::GroupMemberPointer,
+        }
+    }
+
+    /// The extension holding the pointed-to data when the mint points to itself
+    pub fn data_extension(self) -> ExtensionType {
+        match self {
+            Self::Metadata => ExtensionType::TokenMetadata,
+            Self::Group => ExtensionType::TokenGroup,
+            Self::GroupMember => ExtensionType::TokenGroupMember,
+        }
+    }
+}
+
+/// A pointer extension, as stored in mint data
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pointer {
+    /// The authority that can update the address, `None` if none can
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub authority: Option<Pubkey>,
+    /// The account holding the data, `None` if unset
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub address: Option<Pubkey>,
+}
+
+/// Reads the `kind` pointer of Token 2022 mint `data`, `None` if the mint has none
+pub fn pointer(data: &[u8], kind: PointerKind) -> Option<Pointer> {
+    let value = extension_data(data, kind.pointer_extension())?;
+    Some(Pointer {
+        authority: optional_nonzero_pubkey(value, 0),
+        address: optional_nonzero_pubkey(value, 32),
+    })
+}
+
+/// Where the data a pointer names lives
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointerTarget {
+    /// The mint points to itself and holds the data
+    InMint,
+    /// The mint points to itself but does not hold the data yet, the pointer being
+    /// initialized before the mint and the data after it
+    InMintUninitialized,
+    /// The data lives in another account
+    External {
+        /// The account holding the data
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        address: Pubkey,
+    },
+    /// The pointer names no account
+    Unset,
+}
+
+impl PointerTarget {
+    /// Account holding the data, `mint` itself for self-pointing mints, `None` if unset
+    pub fn address(&self, mint: &Pubkey) -> Option<Pubkey> {
+        match self {
+            Self::InMint | Self::InMintUninitialized => Some(*mint),
+            Self::External { address } => Some(*address),
+            Self::Unset => None,
+        }
+    }
+}
+
+/// Resolves the `kind` pointer of `mint` from its `data`, `None` if the mint has none
+///
+/// ```
+/// # use substreams_solana_program_instructions::{pointers::*, pubkey::Pubkey};
+/// let mint = Pubkey::from([1; 32]);
+/// let mut data = vec![0u8; 165];
+/// // Account type, then the MetadataPointer entry: type 18, length 64, no authority, the mint
+/// data.extend_from_slice(&[1, 18, 0, 64, 0]);
+/// data.extend_from_slice(&[0; 32]);
+/// data.extend_from_slice(mint.as_ref());
+/// assert_eq!(resolve_pointer(&mint, &data, PointerKind::Metadata), Some(PointerTarget::InMintUninitialized));
+///
+/// // The TokenMetadata entry, type 19
+/// data.extend_from_slice(&[19, 0, 0, 0]);
+/// assert_eq!(resolve_pointer(&mint, &data, PointerKind::Metadata), Some(PointerTarget::InMint));
+/// assert!(is_pointed_account(&mint, &data, PointerKind::Metadata, &mint));
+/// assert_eq!(resolve_pointer(&mint, &data, PointerKind::Group), None);
+/// ```
+pub fn resolve_pointer(mint: &Pubkey, data: &[u8], kind: PointerKind) -> Option<PointerTarget> {
+    let pointer = pointer(data, kind)?;
+    Some(match pointer.address {
+        Some(address) if address == *mint => {
+            if extension_types(data).contains(&kind.data_extension()) {
+                PointerTarget::InMint
+            } else {
+                PointerTarget::InMintUninitialized
+            }
+        }
+        Some(address) => PointerTarget::External { address },
+        None => PointerTarget::Unset,
+    })
+}
+
+/// Returns `true` if `account` is the account the `kind` pointer of `mint` names, from the
+/// mint's `data`
+pub fn is_pointed_account(mint: &Pubkey, data: &[u8], kind: PointerKind, account: &Pubkey) -> bool {
+    resolve_pointer(mint, data, kind).and_then(|target| target.address(mint)) == Some(*account)
+}
@@ -0,0 +1,303 @@
+//! Compact single-line rendering of decoded instructions, for logs and alerting messages
+//!
+//! Instructions render as their name followed by `key=value` fields, extension instructions
+//! as `Extension.Instruction`. Pubkeys are base58 encoded, unset optional pubkeys render as
+//! `none`.
+//!
+//! Full pubkeys quickly fill substreams log quotas: [set_pubkey_formatter] changes how the
+//! renderings of this module format them, to their [Pubkey::short] form for instance.
+
+use {
+    crate::{
+        confidential_transfer_instruction::ConfidentialTransferInstruction,
+        default_account_state_instruction::DefaultAccountStateInstruction,
+        group_member_pointer_instruction::GroupMemberPointerInstruction,
+        instructions::InstructionRef,
+        interest_bearing_mint::InterestBearingMintInstruction,
+        option::COption,
+        pausable_instruction::PausableInstruction,
+        pubkey::Pubkey,
+        scaled_ui_amount_instruction::ScaledUiAmountInstruction,
+        token_instruction_2022::TokenInstruction,
+        transfer_fee_instruction::TransferFeeInstruction,
+        unified::{TokenProgram, UnifiedTokenInstruction},
+    },
+    std::{
+        fmt::{Debug, Write},
+        sync::RwLock,
+    },
+};
+
+/// Formats a pubkey in pretty renderings, see [set_pubkey_formatter]
+pub type PubkeyFormatter = fn(&Pubkey) -> String;
+
+/// Formatter set through [set_pubkey_formatter], `None` for the full base58 encoding
+static PUBKEY_FORMATTER: RwLock<Option<PubkeyFormatter>> = RwLock::new(None);
+
+/// Sets how pretty renderings format pubkeys, [Pubkey::short] for instance. Renderings made
+/// on any thread use it, so a module logging from rayon workers sets it once, up front.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{pretty::{reset_pubkey_formatter, set_pubkey_formatter}, pubkey::Pubkey, token_instruction_2022::TokenInstruction};
+/// let mut data = vec![35];
+/// data.extend_from_slice(&[1; 32]);
+/// let instruction = TokenInstruction::unpack(&data).unwrap();
+///
+/// set_pubkey_formatter(Pubkey::short);
+/// assert_eq!(instruction.to_pretty_string(), "InitializePermanentDelegate delegate=4vJ9...kLKi");
+///
+/// reset_pubkey_formatter();
+/// assert_eq!(instruction.to_pretty_string(), "InitializePermanentDelegate delegate=4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi");
+/// ```
+pub fn set_pubkey_formatter(formatter: PubkeyFormatter) {
+    *PUBKEY_FORMATTER.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(formatter);
+}
+
+/// Restores the full base58 encoding of pubkeys in pretty renderings
+pub fn reset_pubkey_formatter() {
+    *PUBKEY_FORMATTER.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+/// Formats `pubkey` with the formatter set through [set_pubkey_formatter]
+fn key(pubkey: &Pubkey) -> String {
+    match *PUBKEY_FORMATTER.read().unwrap_or_else(|poisoned| poisoned.into_inner()) {
+        Some(formatter) => formatter(pubkey),
+        None => pubkey.to_string(),
+    }
+}
+
+impl TokenInstruction<'_> {
+    /// Renders the instruction on a single line, e.g. `TransferChecked amount=1500000 decimals=6`
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::token_instruction_2022::TokenInstruction;
+    /// let mut data = vec![12];
+    /// data.extend_from_slice(&1_500_000u64.to_le_bytes());
+    /// data.push(6);
+    /// let instruction = TokenInstruction::unpack(&data).unwrap();
+    /// assert_eq!(instruction.to_pretty_string(), "TransferChecked amount=1500000 decimals=6");
+    /// ```
+    pub fn to_pretty_string(&self) -> String {
+        let mut out = variant_name(self);
+        match self {
+            Self::InitializeMint {
+                decimals,
+                mint_authority,
+                freeze_authority,
+            }
+            | Self::InitializeMint2 {
+                decimals,
+                mint_authority,
+                freeze_authority,
+            } => {
+                field(&mut out, "decimals", decimals);
+                field(&mut out, "mint_authority", key(mint_authority));
+                field(&mut out, "freeze_authority", optional(freeze_authority));
+            }
+            Self::InitializeAccount2 { owner } | Self::InitializeAccount3 { owner } => {
+                field(&mut out, "owner", key(owner));
+            }
+            Self::InitializeMultisig { m } | Self::InitializeMultisig2 { m } => field(&mut out, "m", m),
+            #[allow(deprecated)]
+            Self::Transfer { amount } => field(&mut out, "amount", amount),
+            Self::Approve { amount } | Self::MintTo { amount } | Self::Burn { amount } => {
+                field(&mut out, "amount", amount);
+            }
+            Self::TransferChecked { amount, decimals }
+            | Self::ApproveChecked { amount, decimals }
+            | Self::MintToChecked { amount, decimals }
+            | Self::BurnChecked { amount, decimals } => {
+                field(&mut out, "amount", amount);
+                field(&mut out, "decimals", decimals);
+            }
+            Self::SetAuthority {
+                authority_type,
+                new_authority,
+            } => {
+                field(&mut out, "authority_type", variant_name(authority_type));
+                field(&mut out, "new_authority", optional(new_authority));
+            }
+            Self::AmountToUiAmount { amount } => field(&mut out, "amount", amount),
+            Self::UiAmountToAmount { ui_amount } => field(&mut out, "ui_amount", ui_amount),
+            Self::InitializeMintCloseAuthority { close_authority } => {
+                field(&mut out, "close_authority", optional(close_authority));
+            }
+            Self::InitializePermanentDelegate { delegate } => field(&mut out, "delegate", key(delegate)),
+            Self::TransferFeeExtension(instruction) => {
+                extension(&mut out, instruction);
+                match instruction {
+                    TransferFeeInstruction::InitializeTransferFeeConfig {
+                        transfer_fee_config_authority,
+                        withdraw_withheld_authority,
+                        transfer_fee_basis_points,
+                        maximum_fee,
+                    } => {
+                        field(&mut out, "transfer_fee_config_authority", optional(transfer_fee_config_authority));
+                        field(&mut out, "withdraw_withheld_authority", optional(withdraw_withheld_authority));
+                        field(&mut out, "transfer_fee_basis_points", transfer_fee_basis_points);
+                        field(&mut out, "maximum_fee", maximum_fee);
+                    }
+                    TransferFeeInstruction::TransferCheckedWithFee { amount, decimals, fee } => {
+                        field(&mut out, "amount", amount);
+                        field(&mut out, "decimals", decimals);
+                        field(&mut out, "fee", fee);
+                    }
+                    TransferFeeInstruction::WithdrawWithheldTokensFromAccounts { num_token_accounts } => {
+                        field(&mut out, "num_token_accounts", num_token_accounts);
+                    }
+                    TransferFeeInstruction::SetTransferFee {
+                        transfer_fee_basis_points,
+                        maximum_fee,
+                    } => {
+                        field(&mut out, "transfer_fee_basis_points", transfer_fee_basis_points);
+                        field(&mut out, "maximum_fee", maximum_fee);
+                    }
+                    _ => {}
+                }
+            }
+            Self::DefaultAccountStateExtension(instruction) => {
+                extension(&mut out, instruction);
+                match instruction {
+                    DefaultAccountStateInstruction::Initialize { state }
+                    | DefaultAccountStateInstruction::Update { state } => {
+                        field(&mut out, "state", variant_name(state));
+                    }
+                }
+            }
+            Self::GroupMemberPointerExtension(instruction) => {
+                extension(&mut out, instruction);
+                match instruction {
+                    GroupMemberPointerInstruction::Initialize {
+                        authority,
+                        member_address,
+                    } => {
+                        field(&mut out, "authority", optional(authority));
+                        field(&mut out, "member_address", optional(member_address));
+                    }
+                    GroupMemberPointerInstruction::Update { member_address } => {
+                        field(&mut out, "member_address", optional(member_address));
+                    }
+                }
+            }
+            Self::ConfidentialTransferExtension(instruction) => {
+                extension(&mut out, instruction);
+                match instruction {
+                    ConfidentialTransferInstruction::ConfigureAccount {
+                        maximum_pending_balance_credit_counter,
+                        ..
+                    } => {
+                        field(&mut out, "maximum_pending_balance_credit_counter", maximum_pending_balance_credit_counter);
+                    }
+                    ConfidentialTransferInstruction::Deposit { amount, decimals }
+                    | ConfidentialTransferInstruction::Withdraw { amount, decimals, .. } => {
+                        field(&mut out, "amount", amount);
+                        field(&mut out, "decimals", decimals);
+                    }
+                    _ => {}
+                }
+            }
+            Self::ConfidentialMintBurnExtension(instruction) => extension(&mut out, instruction),
+            Self::ScaledUiAmountExtension(instruction) => {
+                extension(&mut out, instruction);
+                match instruction {
+                    ScaledUiAmountInstruction::Initialize { authority, multiplier } => {
+                        field(&mut out, "authority", optional(authority));
+                        field(&mut out, "multiplier", multiplier);
+                    }
+                    ScaledUiAmountInstruction::UpdateMultiplier {
+                        multiplier,
+                        effective_timestamp,
+                    } => {
+                        field(&mut out, "multiplier", multiplier);
+                        field(&mut out, "effective_timestamp", effective_timestamp);
+                    }
+                }
+            }
+            Self::InterestBearingMintExtension(instruction) => {
+                extension(&mut out, instruction);
+                match instruction {
+                    InterestBearingMintInstruction::Initialize { rate_authority, rate } => {
+                        field(&mut out, "rate_authority", optional(rate_authority));
+                        field(&mut out, "rate", rate);
+                    }
+                    InterestBearingMintInstruction::UpdateRate { rate } => field(&mut out, "rate", rate),
+                }
+            }
+            Self::PausableExtension(instruction) => {
+                extension(&mut out, instruction);
+                if let PausableInstruction::Initialize { authority } = instruction {
+                    field(&mut out, "authority", key(authority));
+                }
+            }
+            _ => {}
+        }
+        out
+    }
+}
+
+impl InstructionRef<'_> {
+    /// Renders the instruction on a single line: token instructions as
+    /// [TokenInstruction::to_pretty_string] followed by their `src`, `mint`, `dst` and `auth`
+    /// accounts, when the instruction has them, other instructions as their program id and
+    /// data length.
+    pub fn to_pretty_string(&self) -> String {
+        let program = match TokenProgram::from_program_id(self.program_id) {
+            Some(program) => program,
+            None => return self.raw_pretty_string(),
+        };
+        let decoded = match TokenInstruction::unpack(self.data) {
+            Ok(decoded) => decoded,
+            Err(_) => return self.raw_pretty_string(),
+        };
+
+        let mut out = decoded.to_pretty_string();
+        let positions = UnifiedTokenInstruction::from_instruction(program, decoded).account_positions();
+        for (name, position) in [
+            ("src", positions.source),
+            ("mint", positions.mint),
+            ("dst", positions.destination),
+            ("auth", positions.authority),
+        ] {
+            if let Some(account) = position.and_then(|position| self.accounts.get_pubkey(position)) {
+                field(&mut out, name, key(&account));
+            }
+        }
+        out
+    }
+
+    fn raw_pretty_string(&self) -> String {
+        let mut out = String::from("Instruction");
+        match Pubkey::try_from(self.program_id) {
+            Ok(program_id) => field(&mut out, "program", key(&program_id)),
+            Err(_) => field(&mut out, "program", "unknown"),
+        }
+        field(&mut out, "data_len", self.data.len());
+        out
+    }
+}
+
+/// Name of an enum variant, as the leading identifier of its `Debug` rendering
+fn variant_name<T: Debug>(value: &T) -> String {
+    let mut name = format!("{:?}", value);
+    let len = name.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(name.len());
+    name.truncate(len);
+    name
+}
+
+/// Appends the name of an extension's instruction to the extension's name
+fn extension<T: Debug>(out: &mut String, instruction: &T) {
+    out.push('.');
+    out.push_str(&variant_name(instruction));
+}
+
+fn field<T: std::fmt::Display>(out: &mut String, key: &str, value: T) {
+    // Writing to a String cannot fail
+    let _ = write!(out, " {}={}", key, value);
+}
+
+fn optional(value: &COption<Pubkey>) -> String {
+    match value {
+        COption::Some(pubkey) => key(pubkey),
+        COption::None => "none".to_string(),
+    }
+}
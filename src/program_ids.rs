@@ -0,0 +1,278 @@
+//! Program ids of the programs decoded by this crate
+
+use {
+    crate::{pubkey::Pubkey, unified::TokenProgram},
+    std::sync::RwLock,
+};
+
+/// SPL Token program id, `TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA`
+pub const TOKEN_PROGRAM_ID: Pubkey = Pubkey([
+    6, 221, 246, 225, 215, 101, 161, 147, 217, 203, 225, 70, 206, 235, 121, 172,
+    28, 180, 133, 237, 95, 91, 55, 145, 58, 140, 245, 133, 126, 255, 0, 169,
+]);
+
+/// SPL Token 2022 program id, `TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb`
+pub const TOKEN_2022_PROGRAM_ID: Pubkey = Pubkey([
+    6, 221, 246, 225, 238, 117, 143, 222, 24, 66, 93, 188, 228, 108, 205, 218,
+    182, 26, 252, 77, 131, 185, 13, 39, 254, 189, 249, 40, 216, 161, 139, 252,
+]);
+
+/// System program id, `11111111111111111111111111111111`
+pub const SYSTEM_PROGRAM_ID: Pubkey = Pubkey([0; 32]);
+
+/// Stake native program id, `Stake11111111111111111111111111111111111111`
+pub const STAKE_PROGRAM_ID: Pubkey = Pubkey([
+    6, 161, 216, 23, 145, 55, 84, 42, 152, 52, 55, 189, 254, 42, 122, 178,
+    85, 127, 83, 92, 138, 120, 114, 43, 104, 164, 157, 192, 0, 0, 0, 0,
+]);
+
+/// Vote native program id, `Vote111111111111111111111111111111111111111`
+pub const VOTE_PROGRAM_ID: Pubkey = Pubkey([
+    7, 97, 72, 29, 53, 116, 116, 187, 124, 77, 118, 36, 235, 211, 189, 179,
+    216, 53, 94, 115, 209, 16, 67, 252, 13, 163, 83, 128, 0, 0, 0, 0,
+]);
+
+/// SPL Name Service program id, `namesLPneVptA9Z5rqUDD9tMTWEJwofgaYwp8cawRkX`
+pub const NAME_SERVICE_PROGRAM_ID: Pubkey = Pubkey([
+    11, 173, 81, 244, 19, 193, 243, 169, 148, 96, 217, 0, 216, 191, 46, 214,
+    146, 126, 202, 52, 215, 183, 132, 43, 248, 16, 169, 115, 8, 45, 30, 220,
+]);
+
+/// Solana Name Service (`.sol` domains) registrar program id, `jCebN34bUfdeUYJT13J1yG16XWQpt5PDx6Mse9GUqhR`
+pub const SNS_REGISTRAR_PROGRAM_ID: Pubkey = Pubkey([
+    10, 207, 144, 136, 108, 148, 104, 166, 24, 48, 41, 246, 133, 100, 197, 101,
+    55, 17, 85, 23, 9, 61, 75, 155, 71, 17, 74, 191, 101, 47, 185, 80,
+]);
+
+/// SPL Memo program (v1) id, `Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo`
+pub const MEMO_V1_PROGRAM_ID: Pubkey = Pubkey([
+    5, 74, 83, 80, 248, 93, 200, 130, 214, 20, 165, 86, 114, 120, 138, 41,
+    109, 223, 30, 171, 171, 208, 166, 6, 120, 136, 73, 50, 244, 238, 246, 160,
+]);
+
+/// SPL Memo program (v2) id, `MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr`
+pub const MEMO_PROGRAM_ID: Pubkey = Pubkey([
+    5, 74, 83, 90, 153, 41, 33, 6, 77, 36, 232, 113, 96, 218, 56, 124,
+    124, 53, 181, 221, 188, 146, 187, 129, 228, 31, 168, 64, 65, 5, 68, 141,
+]);
+
+/// SPL Noop program id, `noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV`
+pub const NOOP_PROGRAM_ID: Pubkey = Pubkey([
+    11, 188, 15, 192, 187, 71, 202, 47, 116, 196, 17, 46, 148, 171, 19, 207,
+    163, 198, 52, 229, 220, 23, 234, 203, 3, 205, 26, 35, 205, 126, 120, 124,
+]);
+
+/// Compute Budget native program id, `ComputeBudget111111111111111111111111111111`
+pub const COMPUTE_BUDGET_PROGRAM_ID: Pubkey = Pubkey([
+    3, 6, 70, 111, 229, 33, 23, 50, 255, 236, 173, 186, 114, 195, 155, 231,
+    188, 140, 229, 187, 197, 247, 18, 107, 44, 67, 155, 58, 64, 0, 0, 0,
+]);
+
+/// Lighthouse assertion program id, `L2TExMFKdjpN9kozasaurPirfHy9P8sbXoAN1qA3S95`
+pub const LIGHTHOUSE_PROGRAM_ID: Pubkey = Pubkey([
+    4, 223, 173, 121, 98, 255, 177, 221, 146, 93, 10, 159, 181, 230, 208, 12,
+    230, 25, 91, 168, 187, 58, 145, 253, 7, 239, 152, 96, 197, 233, 123, 184,
+]);
+
+/// Upgradeable BPF loader program id, `BPFLoaderUpgradeab1e11111111111111111111111`
+pub const BPF_LOADER_UPGRADEABLE_PROGRAM_ID: Pubkey = Pubkey([
+    2, 168, 246, 145, 78, 136, 161, 176, 226, 16, 21, 62, 247, 99, 174, 43,
+    0, 194, 185, 61, 22, 193, 36, 210, 192, 83, 122, 16, 4, 128, 0, 0,
+]);
+
+/// Ed25519 signature verification precompile id, `Ed25519SigVerify111111111111111111111111111`
+pub const ED25519_PROGRAM_ID: Pubkey = Pubkey([
+    3, 125, 70, 214, 124, 147, 251, 190, 18, 249, 66, 143, 131, 141, 64, 255,
+    5, 112, 116, 73, 39, 244, 138, 100, 252, 202, 112, 68, 128, 0, 0, 0,
+]);
+
+/// Secp256k1 signature verification precompile id, `KeccakSecp256k11111111111111111111111111111`
+pub const SECP256K1_PROGRAM_ID: Pubkey = Pubkey([
+    4, 198, 252, 32, 240, 80, 204, 240, 85, 132, 215, 33, 28, 159, 140, 245,
+    158, 193, 71, 133, 187, 22, 106, 30, 40, 48, 232, 18, 32, 0, 0, 0,
+]);
+
+/// Wormhole core bridge program id, `worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth`
+pub const WORMHOLE_CORE_BRIDGE_PROGRAM_ID: Pubkey = Pubkey([
+    14, 10, 88, 154, 65, 165, 95, 189, 102, 197, 42, 71, 95, 45, 146, 166,
+    211, 220, 155, 71, 71, 17, 76, 185, 175, 130, 90, 152, 181, 69, 211, 206,
+]);
+
+/// Wormhole token bridge program id, `wormDTUJ6AWPNvk59vGQbDvGJmqbDTdgWgAqcLBCgUb`
+pub const WORMHOLE_TOKEN_BRIDGE_PROGRAM_ID: Pubkey = Pubkey([
+    14, 10, 88, 158, 100, 136, 20, 122, 148, 220, 250, 89, 43, 144, 253, 212,
+    17, 82, 187, 44, 167, 123, 246, 1, 103, 88, 166, 244, 223, 157, 33, 180,
+]);
+
+/// Streamflow protocol program id, `strmRqUCoQUgGUan5YhzUZa6KqdzwX5L6FpUxfmKg5m`
+pub const STREAMFLOW_PROGRAM_ID: Pubkey = Pubkey([
+    13, 9, 173, 48, 111, 241, 94, 119, 221, 49, 25, 188, 214, 231, 48, 241,
+    93, 5, 29, 124, 9, 122, 122, 29, 0, 234, 65, 14, 238, 72, 231, 32,
+]);
+
+/// Bonfida token vesting program id, `CChTq6PthWU82YZkbveA3WDf7s97BWhBK4Vx9bmsT743`
+pub const BONFIDA_VESTING_PROGRAM_ID: Pubkey = Pubkey([
+    166, 111, 178, 62, 124, 229, 48, 105, 249, 245, 96, 68, 92, 33, 165, 19,
+    193, 203, 169, 211, 213, 188, 89, 190, 67, 214, 117, 197, 47, 92, 175, 184,
+]);
+
+/// Marinade liquid staking program id, `MarBmsSgKXdrN1egZf5sqe1TMai9K1rChYNDJgjq7aD`
+pub const MARINADE_PROGRAM_ID: Pubkey = Pubkey([
+    5, 69, 227, 101, 190, 242, 113, 173, 117, 53, 3, 103, 86, 93, 164, 13,
+    163, 54, 220, 28, 135, 155, 177, 84, 138, 122, 252, 197, 90, 169, 57, 30,
+]);
+
+/// SPL stake pool program id, `SPoo1Ku8WFXoNDMHPsrGSTSG1Y47rzgn41SLUNakuHy`
+pub const STAKE_POOL_PROGRAM_ID: Pubkey = Pubkey([
+    6, 129, 78, 212, 202, 246, 138, 23, 70, 114, 253, 172, 134, 3, 26, 99,
+    232, 78, 161, 94, 250, 29, 68, 183, 34, 147, 246, 219, 219, 0, 22, 80,
+]);
+
+/// Solend lending program id, `So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo`
+pub const SOLEND_PROGRAM_ID: Pubkey = Pubkey([
+    6, 155, 139, 152, 90, 171, 83, 42, 69, 9, 13, 232, 85, 127, 205, 220,
+    190, 108, 183, 239, 199, 58, 10, 101, 176, 111, 146, 3, 93, 183, 62, 236,
+]);
+
+/// Kamino lending program id, `KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD`
+pub const KAMINO_LENDING_PROGRAM_ID: Pubkey = Pubkey([
+    4, 178, 172, 177, 18, 88, 204, 227, 104, 44, 65, 139, 168, 114, 255, 61,
+    249, 17, 2, 113, 47, 21, 175, 18, 182, 190, 105, 179, 67, 91, 0, 8,
+]);
+
+/// Raydium concentrated liquidity (CLMM) program id, `CAMMCzo5YL8w4VFF8KVHrK22GGUsp5VTaW7grrKgrWqK`
+pub const RAYDIUM_CLMM_PROGRAM_ID: Pubkey = Pubkey([
+    165, 213, 202, 158, 4, 207, 93, 181, 144, 183, 20, 186, 47, 227, 44, 177,
+    89, 19, 63, 193, 193, 146, 183, 34, 87, 253, 7, 211, 156, 176, 64, 30,
+]);
+
+/// Raydium constant product (CPMM) program id, `CPMMoo8L3F4NbTegBCKVNunggL7H1ZpdTHKxQB5qKP1C`
+pub const RAYDIUM_CPMM_PROGRAM_ID: Pubkey = Pubkey([
+    169, 42, 90, 139, 79, 41, 89, 82, 132, 37, 80, 170, 147, 253, 91, 149,
+    181, 172, 230, 168, 235, 146, 12, 147, 148, 46, 67, 105, 12, 32, 236, 115,
+]);
+
+/// Meteora DLMM program id, `LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo`
+pub const METEORA_DLMM_PROGRAM_ID: Pubkey = Pubkey([
+    4, 233, 225, 47, 188, 132, 232, 38, 201, 50, 204, 233, 226, 100, 12, 206,
+    21, 89, 12, 28, 98, 115, 176, 146, 87, 8, 186, 59, 133, 32, 176, 188,
+]);
+
+/// Drift v2 program id, `dRiftyHA39MWEi3m9aunc5MzRF1JYuBsbn6VPcn33UH`
+pub const DRIFT_PROGRAM_ID: Pubkey = Pubkey([
+    9, 84, 219, 190, 158, 201, 96, 201, 138, 122, 41, 63, 226, 19, 54, 150,
+    111, 225, 128, 209, 81, 174, 75, 129, 121, 86, 31, 137, 133, 74, 83, 246,
+]);
+
+/// Pyth receiver program id, `rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ`
+///
+/// ```
+/// # use substreams_solana_program_instructions::program_ids::*;
+/// assert_eq!(PYTH_RECEIVER_PROGRAM_ID.to_string(), "rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+/// assert_eq!(SWITCHBOARD_V2_PROGRAM_ID.to_string(), "SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f");
+/// ```
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = Pubkey([
+    12, 183, 250, 187, 82, 247, 166, 72, 187, 91, 49, 125, 154, 1, 139, 144,
+    87, 203, 2, 71, 116, 250, 254, 1, 230, 196, 223, 152, 204, 56, 88, 129,
+]);
+
+/// Switchboard v2 program id, `SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f`
+pub const SWITCHBOARD_V2_PROGRAM_ID: Pubkey = Pubkey([
+    6, 136, 81, 198, 140, 104, 50, 240, 47, 165, 129, 177, 191, 73, 27, 119,
+    202, 65, 119, 107, 162, 185, 136, 181, 166, 250, 186, 142, 227, 162, 236, 144,
+]);
+
+/// Clockwork thread program id, `CLoCKyJ6DXBJqqu2VWx9RLbgnwwR6BMHHuyasVmfMzBh`
+pub const CLOCKWORK_THREAD_PROGRAM_ID: Pubkey = Pubkey([
+    168, 130, 211, 34, 180, 16, 11, 91, 210, 107, 238, 73, 114, 36, 220, 100,
+    86, 253, 186, 23, 29, 123, 105, 187, 8, 124, 106, 153, 176, 24, 159, 112,
+]);
+
+/// Clockwork thread program v1 id, `3XXuUFfweXBwFgFfYaejLvZE4cGZiHgKiGfMtdxNzYmv`
+pub const CLOCKWORK_THREAD_V1_PROGRAM_ID: Pubkey = Pubkey([
+    37, 137, 189, 252, 18, 245, 185, 255, 68, 142, 218, 80, 152, 98, 37, 61,
+    122, 132, 248, 122, 88, 55, 81, 254, 135, 39, 205, 172, 1, 199, 24, 161,
+]);
+
+/// Associated Token Account program id, `ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL`
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey = Pubkey([
+    140, 151, 37, 143, 78, 36, 137, 241, 187, 61, 16, 41, 20, 142, 13, 131,
+    11, 90, 19, 153, 218, 255, 16, 132, 4, 142, 123, 216, 219, 233, 248, 89,
+]);
+
+/// ZK ElGamal proof program id, `ZkE1Gama1Proof11111111111111111111111111111`
+pub const ZK_ELGAMAL_PROOF_PROGRAM_ID: Pubkey = Pubkey([
+    8, 99, 117, 172, 226, 174, 234, 40, 26, 107, 55, 77, 104, 27, 167, 106,
+    83, 204, 246, 56, 192, 116, 85, 147, 108, 5, 208, 101, 64, 0, 0, 0,
+]);
+
+/// Token program deployments registered through [register_token_program]
+static CUSTOM_TOKEN_PROGRAMS: RwLock<Vec<(Pubkey, TokenProgram)>> = RwLock::new(Vec::new());
+
+/// Registers `program_id` as a deployment of `program`, so that extractors decode its
+/// instructions like those of the mainnet program: a devnet or testnet build, a fork of
+/// the token programs sharing their instruction layouts, ...
+///
+/// The registry is shared by all threads of the process, the rayon workers of
+/// [crate::parallel] included: register the deployments once, before any extraction.
+/// Registering a mainnet program id, or an id already registered, has no effect.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{program_ids::*, pubkey::Pubkey, unified::TokenProgram};
+/// let fork = Pubkey::from([7; 32]);
+/// assert!(!is_token_program(fork.as_ref()));
+///
+/// register_token_program(fork, TokenProgram::Token2022);
+/// assert!(is_token_program(fork.as_ref()));
+/// assert!(is_token_2022_program(fork.as_ref()));
+/// assert_eq!(TokenProgram::from_program_id(fork.as_ref()), Some(TokenProgram::Token2022));
+///
+/// clear_token_programs();
+/// assert!(!is_token_program(fork.as_ref()));
+/// ```
+pub fn register_token_program(program_id: Pubkey, program: TokenProgram) {
+    if program_id == TOKEN_PROGRAM_ID || program_id == TOKEN_2022_PROGRAM_ID {
+        return;
+    }
+    let mut programs = CUSTOM_TOKEN_PROGRAMS.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if !programs.iter().any(|(id, _)| *id == program_id) {
+        programs.push((program_id, program));
+    }
+}
+
+/// Removes the deployments registered through [register_token_program]
+pub fn clear_token_programs() {
+    CUSTOM_TOKEN_PROGRAMS
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clear();
+}
+
+/// Token program `program_id` is a deployment of, mainnet ids first then the registered
+/// deployments, `None` for other programs
+pub(crate) fn token_program_of(program_id: &[u8]) -> Option<TokenProgram> {
+    if program_id == TOKEN_PROGRAM_ID.as_ref() {
+        Some(TokenProgram::Token)
+    } else if program_id == TOKEN_2022_PROGRAM_ID.as_ref() {
+        Some(TokenProgram::Token2022)
+    } else {
+        CUSTOM_TOKEN_PROGRAMS
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .find(|(id, _)| id.as_ref() == program_id)
+            .map(|&(_, program)| program)
+    }
+}
+
+/// Returns `true` if `program_id` is either the SPL Token or the SPL Token 2022 program, or
+/// a deployment of one of them registered through [register_token_program].
+pub fn is_token_program(program_id: &[u8]) -> bool {
+    token_program_of(program_id).is_some()
+}
+
+/// Returns `true` if `program_id` is the SPL Token 2022 program, or a deployment of it
+/// registered through [register_token_program].
+pub fn is_token_2022_program(program_id: &[u8]) -> bool {
+    token_program_of(program_id) == Some(TokenProgram::Token2022)
+}
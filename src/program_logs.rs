@@ -0,0 +1,139 @@
+//! Data logged by programs through `sol_log_data`, as Anchor events are
+//!
+//! The runtime records `sol_log_data` calls in the transaction logs as `Program data: ` lines
+//! holding the base64 encoded data. The logs also record every invocation, so each line can
+//! be attributed to the program that logged it and to the position of its instruction, as
+//! long as the logs were not truncated.
+
+use {
+    crate::{instructions::is_successful, pubkey::Pubkey},
+    substreams_solana_core::pb::sf::solana::r#type::v1::ConfirmedTransaction,
+};
+
+/// Prefix of the log lines recording `sol_log_data` calls
+pub const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+/// Log line replacing the logs past the runtime's log size limit
+pub const LOG_TRUNCATED: &str = "Log truncated";
+
+/// Data logged by a program
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgramData {
+    /// The program that logged the data
+    pub program_id: Pubkey,
+    /// Index of the top-level instruction, or of the top-level instruction holding the inner
+    /// instruction that logged the data
+    pub instruction_index: usize,
+    /// Index of the inner instruction that logged the data, `None` for top-level instructions
+    pub inner_instruction_index: Option<usize>,
+    /// Position of the data among the data logged by its instruction
+    pub log_index: usize,
+    /// The logged data, the fields of a single `sol_log_data` call concatenated
+    pub data: Vec<u8>,
+}
+
+/// Extracts the data logged by the programs of a successful transaction, in execution order.
+/// Lines that are not valid base64, or that follow a truncation of the logs, are skipped.
+pub fn program_data(trx: &ConfirmedTransaction) -> Vec<ProgramData> {
+    let meta = match trx.meta.as_ref() {
+        Some(meta) if is_successful(trx) => meta,
+        _ => return vec![],
+    };
+
+    let mut data = vec![];
+    // Programs being executed, innermost last, along with their inner instruction index and
+    // the number of data they logged
+    let mut stack: Vec<(Pubkey, Option<usize>, usize)> = vec![];
+    let mut instruction_index: Option<usize> = None;
+    let mut inner_instruction_count = 0;
+    for line in &meta.log_messages {
+        if line == LOG_TRUNCATED {
+            break;
+        }
+        if let Some(encoded) = line.strip_prefix(PROGRAM_DATA_PREFIX) {
+            if let (Some((program_id, inner_instruction_index, logged)), Some(instruction_index)) =
+                (stack.last_mut(), instruction_index)
+            {
+                // Events of several fields are logged as space separated base64 strings
+                let decoded: Option<Vec<Vec<u8>>> = encoded.split(' ').map(decode_base64).collect();
+                if let Some(fields) = decoded {
+                    data.push(ProgramData {
+                        program_id: *program_id,
+                        instruction_index,
+                        inner_instruction_index: *inner_instruction_index,
+                        log_index: *logged,
+                        data: fields.concat(),
+                    });
+                    *logged += 1;
+                }
+            }
+            continue;
+        }
+
+        let mut words = line.split(' ');
+        let (program_id, status) = match (words.next(), words.next(), words.next()) {
+            (Some("Program"), Some(program_id), Some(status)) => (program_id, status),
+            _ => continue,
+        };
+        if status == "invoke" {
+            let program_id = match bs58::decode(program_id).into_vec().ok().and_then(|key| Pubkey::try_from(key.as_slice()).ok()) {
+                Some(program_id) => program_id,
+                None => continue,
+            };
+            if stack.is_empty() {
+                instruction_index = Some(instruction_index.map_or(0, |index| index + 1));
+                inner_instruction_count = 0;
+                stack.push((program_id, None, 0));
+            } else {
+                stack.push((program_id, Some(inner_instruction_count), 0));
+                inner_instruction_count += 1;
+            }
+        } else if status == "success" || status == "failed:" {
+            stack.pop();
+        }
+    }
+
+    data
+}
+
+/// Decodes standard base64 with padding, as written by the runtime. Returns `None` on
+/// invalid input.
+///
+/// ```
+/// # use substreams_solana_program_instructions::program_logs::decode_base64;
+/// assert_eq!(decode_base64("aGVsbG8="), Some(b"hello".to_vec()));
+/// assert_eq!(decode_base64(""), Some(vec![]));
+/// assert_eq!(decode_base64("a"), None);
+/// ```
+pub fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    fn value(c: u8) -> Option<u32> {
+        Some(match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        } as u32)
+    }
+
+    let bytes = encoded.as_bytes();
+    if bytes.len() % 4 != 0 {
+        return None;
+    }
+    let mut out = Vec::with_capacity(bytes.len() / 4 * 3);
+    let chunks = bytes.len() / 4;
+    for (i, chunk) in bytes.chunks(4).enumerate() {
+        let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+        // Padding only ends the last chunk
+        if padding > 2 || (padding > 0 && i + 1 != chunks) {
+            return None;
+        }
+        let mut word = 0;
+        for c in &chunk[..4 - padding] {
+            word = (word << 6) | value(*c)?;
+        }
+        word <<= 6 * padding as u32;
+        out.extend_from_slice(&word.to_be_bytes()[1..4 - padding]);
+    }
+    Some(out)
+}
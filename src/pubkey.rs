@@ -1,3 +1,5 @@
+use std::{fmt, hint::black_box, str::FromStr, sync::RwLock};
+
 /// Number of bytes in a pubkey
 pub const PUBKEY_BYTES: usize = 32;
 /// maximum length of derived `Pubkey` seed
@@ -9,13 +11,138 @@ pub const PUBKEY_BYTES: usize = 32;
 //
 // const PDA_MARKER: &[u8; 21] = b"ProgramDerivedAddress";
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Pubkey(pub(crate) [u8; 32]);
 
 impl Pubkey{
     pub fn to_bytes(self) -> [u8; 32] {
         self.0
     }
+
+    /// Returns `true` if `bytes` is this pubkey, comparing in place
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::program_ids::TOKEN_PROGRAM_ID;
+    /// let key: Vec<u8> = TOKEN_PROGRAM_ID.to_bytes().to_vec();
+    /// assert!(TOKEN_PROGRAM_ID.eq_bytes(&key));
+    /// assert!(key == TOKEN_PROGRAM_ID);
+    /// assert!(!TOKEN_PROGRAM_ID.eq_bytes(&key[1..]));
+    /// ```
+    #[inline]
+    pub fn eq_bytes(&self, bytes: &[u8]) -> bool {
+        self.0[..] == *bytes
+    }
+
+    /// Returns `true` if this pubkey is one of `pubkeys`
+    #[inline]
+    pub fn matches_any(&self, pubkeys: &[Pubkey]) -> bool {
+        pubkeys.contains(self)
+    }
+
+    /// Short form of the base58 encoding, its first and last 4 characters, for logs
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::program_ids::TOKEN_PROGRAM_ID;
+    /// assert_eq!(TOKEN_PROGRAM_ID.short(), "Toke...Q5DA");
+    /// ```
+    pub fn short(&self) -> String {
+        let encoded = self.to_string();
+        if encoded.len() <= 11 {
+            return encoded;
+        }
+        format!("{}...{}", &encoded[..4], &encoded[encoded.len() - 4..])
+    }
+
+    /// Returns `true` if `other` is this pubkey, comparing every byte whatever the first
+    /// difference, for keys derived from secrets
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::pubkey::Pubkey;
+    /// assert!(Pubkey::from([1; 32]).ct_eq(&Pubkey::from([1; 32])));
+    /// assert!(!Pubkey::from([1; 32]).ct_eq(&Pubkey::from([2; 32])));
+    /// ```
+    pub fn ct_eq(&self, other: &Pubkey) -> bool {
+        self.ct_eq_bytes(&other.0)
+    }
+
+    /// Returns `true` if `bytes` is this pubkey, comparing every byte whatever the first
+    /// difference. Only the length of `bytes`, which is not secret, is checked upfront.
+    pub fn ct_eq_bytes(&self, bytes: &[u8]) -> bool {
+        if bytes.len() != PUBKEY_BYTES {
+            return false;
+        }
+        // Each byte difference goes through `black_box`, so the fold is not turned into an
+        // early-exit comparison
+        let difference = self
+            .0
+            .iter()
+            .zip(bytes)
+            .fold(0u8, |difference, (a, b)| difference | black_box(a ^ b));
+        black_box(difference) == 0
+    }
+
+    /// Base58 encoding with its middle masked, for compliance logs, keeping the characters
+    /// set through [set_redaction]
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::{program_ids::TOKEN_PROGRAM_ID, pubkey::{reset_redaction, set_redaction, Redaction}};
+    /// assert_eq!(TOKEN_PROGRAM_ID.redact(), "Toke****Q5DA");
+    ///
+    /// set_redaction(Redaction { prefix: 2, suffix: 0 });
+    /// assert_eq!(TOKEN_PROGRAM_ID.redact(), "To****");
+    /// reset_redaction();
+    /// ```
+    pub fn redact(&self) -> String {
+        let redaction = *REDACTION.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let encoded = self.to_string();
+        if redaction.prefix + redaction.suffix >= encoded.len() {
+            return encoded;
+        }
+        format!(
+            "{}{}{}",
+            &encoded[..redaction.prefix],
+            REDACTION_MASK,
+            &encoded[encoded.len() - redaction.suffix..]
+        )
+    }
+}
+
+/// Replaces the masked characters of [Pubkey::redact], whatever their number
+const REDACTION_MASK: &str = "****";
+
+/// Characters [Pubkey::redact] keeps at each end of the base58 encoding
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Redaction {
+    /// Number of leading characters kept
+    pub prefix: usize,
+    /// Number of trailing characters kept
+    pub suffix: usize,
+}
+
+impl Default for Redaction {
+    fn default() -> Self {
+        Self { prefix: 4, suffix: 4 }
+    }
+}
+
+/// Redaction set through [set_redaction]
+static REDACTION: RwLock<Redaction> = RwLock::new(Redaction { prefix: 4, suffix: 4 });
+
+/// Sets the characters [Pubkey::redact] keeps, for every thread of the process. Pass
+/// [Pubkey::redact] to [crate::pretty::set_pubkey_formatter] to redact pretty renderings too.
+pub fn set_redaction(redaction: Redaction) {
+    *REDACTION.write().unwrap_or_else(|poisoned| poisoned.into_inner()) = redaction;
+}
+
+/// Restores the default redaction, keeping 4 characters at each end
+pub fn reset_redaction() {
+    set_redaction(Redaction::default());
+}
+
+/// Returns `true` if `bytes` is one of `pubkeys`, without building a [Pubkey] out of `bytes`
+#[inline]
+pub fn bytes_match_any(bytes: &[u8], pubkeys: &[Pubkey]) -> bool {
+    pubkeys.iter().any(|pubkey| pubkey.eq_bytes(bytes))
 }
 
 impl AsRef<[u8]> for Pubkey {
@@ -24,6 +151,34 @@ impl AsRef<[u8]> for Pubkey {
     }
 }
 
+impl PartialEq<[u8]> for Pubkey {
+    #[inline]
+    fn eq(&self, other: &[u8]) -> bool {
+        self.eq_bytes(other)
+    }
+}
+
+impl PartialEq<Pubkey> for [u8] {
+    #[inline]
+    fn eq(&self, other: &Pubkey) -> bool {
+        other.eq_bytes(self)
+    }
+}
+
+impl PartialEq<Vec<u8>> for Pubkey {
+    #[inline]
+    fn eq(&self, other: &Vec<u8>) -> bool {
+        self.eq_bytes(other)
+    }
+}
+
+impl PartialEq<Pubkey> for Vec<u8> {
+    #[inline]
+    fn eq(&self, other: &Pubkey) -> bool {
+        other.eq_bytes(self)
+    }
+}
+
 impl From<[u8; 32]> for Pubkey {
     #[inline]
     fn from(from: [u8; 32]) -> Self {
@@ -49,3 +204,40 @@ impl TryFrom<Vec<u8>> for Pubkey {
     }
 }
 
+
+impl fmt::Display for Pubkey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", bs58::encode(self.0).into_string())
+    }
+}
+
+/// Error returned when a base58 string cannot be parsed into a [Pubkey]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParsePubkeyError {
+    /// String is not valid base58
+    Invalid,
+    /// String decodes to a byte length different than [PUBKEY_BYTES]
+    WrongSize,
+}
+
+impl fmt::Display for ParsePubkeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParsePubkeyError::Invalid => f.write_str("Invalid Base58 string"),
+            ParsePubkeyError::WrongSize => f.write_str("String is the wrong size"),
+        }
+    }
+}
+
+impl std::error::Error for ParsePubkeyError {}
+
+impl FromStr for Pubkey {
+    type Err = ParsePubkeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = bs58::decode(s)
+            .into_vec()
+            .map_err(|_| ParsePubkeyError::Invalid)?;
+        Pubkey::try_from(bytes).map_err(|_| ParsePubkeyError::WrongSize)
+    }
+}
@@ -0,0 +1,641 @@
+//! Raydium concentrated liquidity (CLMM) and constant product (CPMM) instruction types
+//!
+//! Both are Anchor programs, their instructions identified by an 8 bytes discriminator. CLMM
+//! liquidity is held in positions between two ticks, each position tracked by an NFT, while
+//! CPMM liquidity is pooled and represented by LP tokens. The `V2` CLMM instructions add
+//! Token 2022 support and take the vault mints as extra accounts.
+
+use anyhow::anyhow;
+use {
+    crate::token_instruction_2022::TokenInstruction,
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// CLMM `open_position` discriminator, hash of `global:open_position`
+pub const CLMM_OPEN_POSITION_DISCRIMINATOR: [u8; 8] = [135, 128, 47, 77, 15, 152, 240, 49];
+/// CLMM `open_position_v2` discriminator, hash of `global:open_position_v2`
+pub const CLMM_OPEN_POSITION_V2_DISCRIMINATOR: [u8; 8] = [77, 184, 74, 214, 112, 86, 241, 199];
+/// CLMM `open_position_with_token22_nft` discriminator, hash of `global:open_position_with_token22_nft`
+pub const CLMM_OPEN_POSITION_WITH_TOKEN22_NFT_DISCRIMINATOR: [u8; 8] = [77, 255, 174, 82, 125, 29, 201, 46];
+/// CLMM `increase_liquidity` discriminator, hash of `global:increase_liquidity`
+pub const CLMM_INCREASE_LIQUIDITY_DISCRIMINATOR: [u8; 8] = [46, 156, 243, 118, 13, 205, 251, 178];
+/// CLMM `increase_liquidity_v2` discriminator, hash of `global:increase_liquidity_v2`
+pub const CLMM_INCREASE_LIQUIDITY_V2_DISCRIMINATOR: [u8; 8] = [133, 29, 89, 223, 69, 238, 176, 10];
+/// CLMM `decrease_liquidity` discriminator, hash of `global:decrease_liquidity`
+pub const CLMM_DECREASE_LIQUIDITY_DISCRIMINATOR: [u8; 8] = [160, 38, 208, 111, 104, 91, 44, 1];
+/// CLMM `decrease_liquidity_v2` discriminator, hash of `global:decrease_liquidity_v2`
+pub const CLMM_DECREASE_LIQUIDITY_V2_DISCRIMINATOR: [u8; 8] = [58, 127, 188, 62, 79, 82, 196, 96];
+/// CLMM `swap` discriminator, hash of `global:swap`
+pub const CLMM_SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+/// CLMM `swap_v2` discriminator, hash of `global:swap_v2`
+pub const CLMM_SWAP_V2_DISCRIMINATOR: [u8; 8] = [43, 4, 237, 11, 26, 201, 30, 98];
+
+/// CPMM `initialize` discriminator, hash of `global:initialize`
+pub const CPMM_INITIALIZE_DISCRIMINATOR: [u8; 8] = [175, 175, 109, 31, 13, 152, 155, 237];
+/// CPMM `deposit` discriminator, hash of `global:deposit`
+pub const CPMM_DEPOSIT_DISCRIMINATOR: [u8; 8] = [242, 35, 198, 137, 82, 225, 242, 182];
+/// CPMM `withdraw` discriminator, hash of `global:withdraw`
+pub const CPMM_WITHDRAW_DISCRIMINATOR: [u8; 8] = [183, 18, 70, 156, 148, 109, 161, 34];
+/// CPMM `swap_base_input` discriminator, hash of `global:swap_base_input`
+pub const CPMM_SWAP_BASE_INPUT_DISCRIMINATOR: [u8; 8] = [143, 190, 90, 218, 196, 30, 51, 222];
+/// CPMM `swap_base_output` discriminator, hash of `global:swap_base_output`
+pub const CPMM_SWAP_BASE_OUTPUT_DISCRIMINATOR: [u8; 8] = [55, 217, 98, 86, 163, 74, 180, 173];
+
+/// Instructions of the Raydium CLMM program.
+///
+/// Only the instructions opening positions, moving liquidity and swapping are decoded, pool
+/// administration and reward instructions are rejected as unknown discriminators.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RaydiumClmmInstruction {
+    /// Open a position, minting its NFT, and add liquidity to it
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Payer
+    ///   1. `[]` Position NFT owner
+    ///   2. `[writable, signer]` Position NFT mint
+    ///   3. `[writable]` Position NFT account
+    ///   4. `[writable]` Position NFT metadata
+    ///   5. `[writable]` Pool state
+    ///   6. `[writable]` Protocol position
+    ///   7. `[writable]` Lower tick array
+    ///   8. `[writable]` Upper tick array
+    ///   9. `[writable]` Personal position
+    ///   10. `[writable]` Token 0 account of the payer
+    ///   11. `[writable]` Token 1 account of the payer
+    ///   12. `[writable]` Token 0 vault
+    ///   13. `[writable]` Token 1 vault
+    ///   14. `[]` Rent sysvar
+    ///   15. `[]` System program
+    ///   16. `[]` Token program
+    ///   17. `[]` Associated token account program
+    ///   18. `[]` Metadata program
+    OpenPosition {
+        /// Lower tick of the position
+        tick_lower_index: i32,
+        /// Upper tick of the position
+        tick_upper_index: i32,
+        /// Start index of the lower tick array
+        tick_array_lower_start_index: i32,
+        /// Start index of the upper tick array
+        tick_array_upper_start_index: i32,
+        /// Liquidity to add
+        liquidity: u128,
+        /// Maximum amount of token 0 to deposit
+        amount_0_max: u64,
+        /// Maximum amount of token 1 to deposit
+        amount_1_max: u64,
+    },
+    /// Same as [RaydiumClmmInstruction::OpenPosition], supporting Token 2022 vaults
+    ///
+    /// Accounts expected by this instruction, in addition to those of
+    /// [RaydiumClmmInstruction::OpenPosition]:
+    ///
+    ///   19. `[]` Token 2022 program
+    ///   20. `[]` Token 0 mint
+    ///   21. `[]` Token 1 mint
+    OpenPositionV2 {
+        /// Lower tick of the position
+        tick_lower_index: i32,
+        /// Upper tick of the position
+        tick_upper_index: i32,
+        /// Start index of the lower tick array
+        tick_array_lower_start_index: i32,
+        /// Start index of the upper tick array
+        tick_array_upper_start_index: i32,
+        /// Liquidity to add, computed from the maximum amounts when 0
+        liquidity: u128,
+        /// Maximum amount of token 0 to deposit
+        amount_0_max: u64,
+        /// Maximum amount of token 1 to deposit
+        amount_1_max: u64,
+        /// Whether to create the metadata of the position NFT
+        with_metadata: bool,
+        /// Whether the liquidity is computed from `amount_0_max` (`true`) or `amount_1_max`,
+        /// when `liquidity` is 0
+        base_flag: Option<bool>,
+    },
+    /// Same as [RaydiumClmmInstruction::OpenPositionV2], minting the position NFT with the
+    /// Token 2022 program, its metadata held in the mint
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Payer
+    ///   1. `[]` Position NFT owner
+    ///   2. `[writable, signer]` Position NFT mint
+    ///   3. `[writable]` Position NFT account
+    ///   4. `[writable]` Pool state
+    ///   5. `[writable]` Protocol position
+    ///   6. `[writable]` Lower tick array
+    ///   7. `[writable]` Upper tick array
+    ///   8. `[writable]` Personal position
+    ///   9. `[writable]` Token 0 account of the payer
+    ///   10. `[writable]` Token 1 account of the payer
+    ///   11. `[writable]` Token 0 vault
+    ///   12. `[writable]` Token 1 vault
+    ///   13. `[]` Rent sysvar
+    ///   14. `[]` System program
+    ///   15. `[]` Token program
+    ///   16. `[]` Associated token account program
+    ///   17. `[]` Token 2022 program
+    ///   18. `[]` Token 0 mint
+    ///   19. `[]` Token 1 mint
+    OpenPositionWithToken22Nft {
+        /// Lower tick of the position
+        tick_lower_index: i32,
+        /// Upper tick of the position
+        tick_upper_index: i32,
+        /// Start index of the lower tick array
+        tick_array_lower_start_index: i32,
+        /// Start index of the upper tick array
+        tick_array_upper_start_index: i32,
+        /// Liquidity to add, computed from the maximum amounts when 0
+        liquidity: u128,
+        /// Maximum amount of token 0 to deposit
+        amount_0_max: u64,
+        /// Maximum amount of token 1 to deposit
+        amount_1_max: u64,
+        /// Whether to create the metadata of the position NFT
+        with_metadata: bool,
+        /// Whether the liquidity is computed from `amount_0_max` (`true`) or `amount_1_max`,
+        /// when `liquidity` is 0
+        base_flag: Option<bool>,
+    },
+    /// Add liquidity to a position
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` Position NFT owner
+    ///   1. `[]` Position NFT account
+    ///   2. `[writable]` Pool state
+    ///   3. `[writable]` Protocol position
+    ///   4. `[writable]` Personal position
+    ///   5. `[writable]` Lower tick array
+    ///   6. `[writable]` Upper tick array
+    ///   7. `[writable]` Token 0 account of the owner
+    ///   8. `[writable]` Token 1 account of the owner
+    ///   9. `[writable]` Token 0 vault
+    ///   10. `[writable]` Token 1 vault
+    ///   11. `[]` Token program
+    IncreaseLiquidity {
+        /// Liquidity to add
+        liquidity: u128,
+        /// Maximum amount of token 0 to deposit
+        amount_0_max: u64,
+        /// Maximum amount of token 1 to deposit
+        amount_1_max: u64,
+    },
+    /// Same as [RaydiumClmmInstruction::IncreaseLiquidity], supporting Token 2022 vaults
+    ///
+    /// Accounts expected by this instruction, in addition to those of
+    /// [RaydiumClmmInstruction::IncreaseLiquidity]:
+    ///
+    ///   12. `[]` Token 2022 program
+    ///   13. `[]` Token 0 mint
+    ///   14. `[]` Token 1 mint
+    IncreaseLiquidityV2 {
+        /// Liquidity to add, computed from the maximum amounts when 0
+        liquidity: u128,
+        /// Maximum amount of token 0 to deposit
+        amount_0_max: u64,
+        /// Maximum amount of token 1 to deposit
+        amount_1_max: u64,
+        /// Whether the liquidity is computed from `amount_0_max` (`true`) or `amount_1_max`,
+        /// when `liquidity` is 0
+        base_flag: Option<bool>,
+    },
+    /// Remove liquidity from a position, collecting its fees
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` Position NFT owner
+    ///   1. `[]` Position NFT account
+    ///   2. `[writable]` Personal position
+    ///   3. `[writable]` Pool state
+    ///   4. `[writable]` Protocol position
+    ///   5. `[writable]` Token 0 vault
+    ///   6. `[writable]` Token 1 vault
+    ///   7. `[writable]` Lower tick array
+    ///   8. `[writable]` Upper tick array
+    ///   9. `[writable]` Token 0 recipient account
+    ///   10. `[writable]` Token 1 recipient account
+    ///   11. `[]` Token program
+    DecreaseLiquidity {
+        /// Liquidity to remove
+        liquidity: u128,
+        /// Minimum amount of token 0 to receive
+        amount_0_min: u64,
+        /// Minimum amount of token 1 to receive
+        amount_1_min: u64,
+    },
+    /// Same as [RaydiumClmmInstruction::DecreaseLiquidity], supporting Token 2022 vaults
+    ///
+    /// Accounts expected by this instruction, in addition to those of
+    /// [RaydiumClmmInstruction::DecreaseLiquidity]:
+    ///
+    ///   12. `[]` Token 2022 program
+    ///   13. `[]` Memo program
+    ///   14. `[]` Token 0 mint
+    ///   15. `[]` Token 1 mint
+    DecreaseLiquidityV2 {
+        /// Liquidity to remove
+        liquidity: u128,
+        /// Minimum amount of token 0 to receive
+        amount_0_min: u64,
+        /// Minimum amount of token 1 to receive
+        amount_1_min: u64,
+    },
+    /// Swap tokens against the pool
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` Payer
+    ///   1. `[]` AMM config
+    ///   2. `[writable]` Pool state
+    ///   3. `[writable]` Input token account
+    ///   4. `[writable]` Output token account
+    ///   5. `[writable]` Input vault
+    ///   6. `[writable]` Output vault
+    ///   7. `[writable]` Observation state
+    ///   8. `[]` Token program
+    ///   9. `[writable]` Tick array
+    ///   10. ..10+N `[writable]` Additional tick arrays crossed by the swap
+    Swap {
+        /// Amount in when `is_base_input`, amount out otherwise
+        amount: u64,
+        /// Minimum amount out when `is_base_input`, maximum amount in otherwise
+        other_amount_threshold: u64,
+        /// Price limit of the swap, as a Q64.64 square root, 0 for none
+        sqrt_price_limit_x64: u128,
+        /// Whether `amount` is the exact amount in, rather than the exact amount out
+        is_base_input: bool,
+    },
+    /// Same as [RaydiumClmmInstruction::Swap], supporting Token 2022 vaults
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` Payer
+    ///   1. `[]` AMM config
+    ///   2. `[writable]` Pool state
+    ///   3. `[writable]` Input token account
+    ///   4. `[writable]` Output token account
+    ///   5. `[writable]` Input vault
+    ///   6. `[writable]` Output vault
+    ///   7. `[writable]` Observation state
+    ///   8. `[]` Token program
+    ///   9. `[]` Token 2022 program
+    ///   10. `[]` Memo program
+    ///   11. `[]` Input vault mint
+    ///   12. `[]` Output vault mint
+    ///   13. ..13+N `[writable]` Tick arrays crossed by the swap
+    SwapV2 {
+        /// Amount in when `is_base_input`, amount out otherwise
+        amount: u64,
+        /// Minimum amount out when `is_base_input`, maximum amount in otherwise
+        other_amount_threshold: u64,
+        /// Price limit of the swap, as a Q64.64 square root, 0 for none
+        sqrt_price_limit_x64: u128,
+        /// Whether `amount` is the exact amount in, rather than the exact amount out
+        is_base_input: bool,
+    },
+}
+impl RaydiumClmmInstruction {
+    /// Unpacks a byte buffer into a [RaydiumClmmInstruction](enum.RaydiumClmmInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, Error> {
+        let (discriminator, rest) =
+            TokenInstruction::unpack_bytes(input, 8).map_err(|_| anyhow!("Invalid Raydium CLMM Instruction"))?;
+        let discriminator: [u8; 8] = discriminator.try_into().map_err(|_| anyhow!("Invalid Raydium CLMM Instruction"))?;
+        Ok(match discriminator {
+            CLMM_OPEN_POSITION_DISCRIMINATOR => {
+                let (ticks, rest) = unpack_ticks(rest)?;
+                let (liquidity, rest) = TokenInstruction::unpack_u128(rest)?;
+                let (amount_0_max, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (amount_1_max, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::OpenPosition {
+                    tick_lower_index: ticks[0],
+                    tick_upper_index: ticks[1],
+                    tick_array_lower_start_index: ticks[2],
+                    tick_array_upper_start_index: ticks[3],
+                    liquidity,
+                    amount_0_max,
+                    amount_1_max,
+                }
+            }
+            CLMM_OPEN_POSITION_V2_DISCRIMINATOR | CLMM_OPEN_POSITION_WITH_TOKEN22_NFT_DISCRIMINATOR => {
+                let (ticks, rest) = unpack_ticks(rest)?;
+                let (liquidity, rest) = TokenInstruction::unpack_u128(rest)?;
+                let (amount_0_max, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (amount_1_max, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (with_metadata, rest) = unpack_bool(rest)?;
+                let (base_flag, _rest) = unpack_option_bool(rest)?;
+                if discriminator == CLMM_OPEN_POSITION_V2_DISCRIMINATOR {
+                    Self::OpenPositionV2 {
+                        tick_lower_index: ticks[0],
+                        tick_upper_index: ticks[1],
+                        tick_array_lower_start_index: ticks[2],
+                        tick_array_upper_start_index: ticks[3],
+                        liquidity,
+                        amount_0_max,
+                        amount_1_max,
+                        with_metadata,
+                        base_flag,
+                    }
+                } else {
+                    Self::OpenPositionWithToken22Nft {
+                        tick_lower_index: ticks[0],
+                        tick_upper_index: ticks[1],
+                        tick_array_lower_start_index: ticks[2],
+                        tick_array_upper_start_index: ticks[3],
+                        liquidity,
+                        amount_0_max,
+                        amount_1_max,
+                        with_metadata,
+                        base_flag,
+                    }
+                }
+            }
+            CLMM_INCREASE_LIQUIDITY_DISCRIMINATOR => {
+                let (liquidity, rest) = TokenInstruction::unpack_u128(rest)?;
+                let (amount_0_max, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (amount_1_max, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::IncreaseLiquidity {
+                    liquidity,
+                    amount_0_max,
+                    amount_1_max,
+                }
+            }
+            CLMM_INCREASE_LIQUIDITY_V2_DISCRIMINATOR => {
+                let (liquidity, rest) = TokenInstruction::unpack_u128(rest)?;
+                let (amount_0_max, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (amount_1_max, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (base_flag, _rest) = unpack_option_bool(rest)?;
+                Self::IncreaseLiquidityV2 {
+                    liquidity,
+                    amount_0_max,
+                    amount_1_max,
+                    base_flag,
+                }
+            }
+            CLMM_DECREASE_LIQUIDITY_DISCRIMINATOR | CLMM_DECREASE_LIQUIDITY_V2_DISCRIMINATOR => {
+                let (liquidity, rest) = TokenInstruction::unpack_u128(rest)?;
+                let (amount_0_min, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (amount_1_min, _rest) = TokenInstruction::unpack_u64(rest)?;
+                if discriminator == CLMM_DECREASE_LIQUIDITY_DISCRIMINATOR {
+                    Self::DecreaseLiquidity {
+                        liquidity,
+                        amount_0_min,
+                        amount_1_min,
+                    }
+                } else {
+                    Self::DecreaseLiquidityV2 {
+                        liquidity,
+                        amount_0_min,
+                        amount_1_min,
+                    }
+                }
+            }
+            CLMM_SWAP_DISCRIMINATOR | CLMM_SWAP_V2_DISCRIMINATOR => {
+                let (amount, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (other_amount_threshold, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (sqrt_price_limit_x64, rest) = TokenInstruction::unpack_u128(rest)?;
+                let (is_base_input, _rest) = unpack_bool(rest)?;
+                if discriminator == CLMM_SWAP_DISCRIMINATOR {
+                    Self::Swap {
+                        amount,
+                        other_amount_threshold,
+                        sqrt_price_limit_x64,
+                        is_base_input,
+                    }
+                } else {
+                    Self::SwapV2 {
+                        amount,
+                        other_amount_threshold,
+                        sqrt_price_limit_x64,
+                        is_base_input,
+                    }
+                }
+            }
+            _ => return Err(anyhow!("Invalid Raydium CLMM Instruction - unpack didn't match any discriminator: {:?}", discriminator)),
+        })
+    }
+}
+
+/// Instructions of the Raydium CPMM program.
+///
+/// Only the instructions creating pools, moving liquidity and swapping are decoded, pool
+/// administration instructions are rejected as unknown discriminators.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RaydiumCpmmInstruction {
+    /// Create a pool with its initial liquidity
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Creator
+    ///   1. `[]` AMM config
+    ///   2. `[]` Pool authority
+    ///   3. `[writable]` Pool state
+    ///   4. `[]` Token 0 mint
+    ///   5. `[]` Token 1 mint
+    ///   6. `[writable]` LP mint
+    ///   7. `[writable]` Token 0 account of the creator
+    ///   8. `[writable]` Token 1 account of the creator
+    ///   9. `[writable]` LP token account of the creator
+    ///   10. `[writable]` Token 0 vault
+    ///   11. `[writable]` Token 1 vault
+    ///   12. `[writable]` Pool creation fee receiver
+    ///   13. `[writable]` Observation state
+    ///   14. `[]` Token program, of the LP mint
+    ///   15. `[]` Token 0 program
+    ///   16. `[]` Token 1 program
+    ///   17. `[]` Associated token account program
+    ///   18. `[]` System program
+    ///   19. `[]` Rent sysvar
+    Initialize {
+        /// Initial amount of token 0
+        init_amount_0: u64,
+        /// Initial amount of token 1
+        init_amount_1: u64,
+        /// Unix timestamp from which swaps are allowed
+        open_time: u64,
+    },
+    /// Deposit tokens in exchange for LP tokens
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` Owner
+    ///   1. `[]` Pool authority
+    ///   2. `[writable]` Pool state
+    ///   3. `[writable]` LP token account of the owner
+    ///   4. `[writable]` Token 0 account of the owner
+    ///   5. `[writable]` Token 1 account of the owner
+    ///   6. `[writable]` Token 0 vault
+    ///   7. `[writable]` Token 1 vault
+    ///   8. `[]` Token program
+    ///   9. `[]` Token 2022 program
+    ///   10. `[]` Token 0 mint
+    ///   11. `[]` Token 1 mint
+    ///   12. `[writable]` LP mint
+    Deposit {
+        /// Amount of LP tokens to mint
+        lp_token_amount: u64,
+        /// Maximum amount of token 0 to deposit
+        maximum_token_0_amount: u64,
+        /// Maximum amount of token 1 to deposit
+        maximum_token_1_amount: u64,
+    },
+    /// Burn LP tokens in exchange for the tokens they represent
+    ///
+    /// Accounts expected by this instruction, in addition to those of
+    /// [RaydiumCpmmInstruction::Deposit]:
+    ///
+    ///   13. `[]` Memo program
+    Withdraw {
+        /// Amount of LP tokens to burn
+        lp_token_amount: u64,
+        /// Minimum amount of token 0 to receive
+        minimum_token_0_amount: u64,
+        /// Minimum amount of token 1 to receive
+        minimum_token_1_amount: u64,
+    },
+    /// Swap an exact amount in
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` Payer
+    ///   1. `[]` Pool authority
+    ///   2. `[]` AMM config
+    ///   3. `[writable]` Pool state
+    ///   4. `[writable]` Input token account
+    ///   5. `[writable]` Output token account
+    ///   6. `[writable]` Input vault
+    ///   7. `[writable]` Output vault
+    ///   8. `[]` Input token program
+    ///   9. `[]` Output token program
+    ///   10. `[]` Input token mint
+    ///   11. `[]` Output token mint
+    ///   12. `[writable]` Observation state
+    SwapBaseInput {
+        /// Amount in
+        amount_in: u64,
+        /// Minimum amount out
+        minimum_amount_out: u64,
+    },
+    /// Swap for an exact amount out
+    ///
+    /// Accounts expected by this instruction: same as [RaydiumCpmmInstruction::SwapBaseInput]
+    SwapBaseOutput {
+        /// Maximum amount in
+        max_amount_in: u64,
+        /// Amount out
+        amount_out: u64,
+    },
+}
+impl RaydiumCpmmInstruction {
+    /// Unpacks a byte buffer into a [RaydiumCpmmInstruction](enum.RaydiumCpmmInstruction.html).
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::raydium_instruction::{RaydiumCpmmInstruction, CPMM_SWAP_BASE_INPUT_DISCRIMINATOR};
+    /// let mut data = CPMM_SWAP_BASE_INPUT_DISCRIMINATOR.to_vec();
+    /// data.extend_from_slice(&1_000_000u64.to_le_bytes());
+    /// data.extend_from_slice(&990_000u64.to_le_bytes());
+    /// assert_eq!(
+    ///     RaydiumCpmmInstruction::unpack(&data).unwrap(),
+    ///     RaydiumCpmmInstruction::SwapBaseInput { amount_in: 1_000_000, minimum_amount_out: 990_000 },
+    /// );
+    /// ```
+    pub fn unpack(input: &[u8]) -> Result<Self, Error> {
+        let (discriminator, rest) =
+            TokenInstruction::unpack_bytes(input, 8).map_err(|_| anyhow!("Invalid Raydium CPMM Instruction"))?;
+        let discriminator: [u8; 8] = discriminator.try_into().map_err(|_| anyhow!("Invalid Raydium CPMM Instruction"))?;
+        Ok(match discriminator {
+            CPMM_INITIALIZE_DISCRIMINATOR => {
+                let (init_amount_0, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (init_amount_1, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (open_time, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::Initialize {
+                    init_amount_0,
+                    init_amount_1,
+                    open_time,
+                }
+            }
+            CPMM_DEPOSIT_DISCRIMINATOR => {
+                let (lp_token_amount, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (maximum_token_0_amount, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (maximum_token_1_amount, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::Deposit {
+                    lp_token_amount,
+                    maximum_token_0_amount,
+                    maximum_token_1_amount,
+                }
+            }
+            CPMM_WITHDRAW_DISCRIMINATOR => {
+                let (lp_token_amount, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (minimum_token_0_amount, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (minimum_token_1_amount, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::Withdraw {
+                    lp_token_amount,
+                    minimum_token_0_amount,
+                    minimum_token_1_amount,
+                }
+            }
+            CPMM_SWAP_BASE_INPUT_DISCRIMINATOR => {
+                let (amount_in, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (minimum_amount_out, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::SwapBaseInput {
+                    amount_in,
+                    minimum_amount_out,
+                }
+            }
+            CPMM_SWAP_BASE_OUTPUT_DISCRIMINATOR => {
+                let (max_amount_in, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (amount_out, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::SwapBaseOutput {
+                    max_amount_in,
+                    amount_out,
+                }
+            }
+            _ => return Err(anyhow!("Invalid Raydium CPMM Instruction - unpack didn't match any discriminator: {:?}", discriminator)),
+        })
+    }
+}
+
+/// Unpacks the lower and upper ticks of a position followed by the start indexes of their
+/// tick arrays
+fn unpack_ticks(input: &[u8]) -> Result<([i32; 4], &[u8]), Error> {
+    let mut ticks = [0; 4];
+    let mut rest = input;
+    for tick in ticks.iter_mut() {
+        let (value, next) = TokenInstruction::unpack_u32(rest)?;
+        *tick = value as i32;
+        rest = next;
+    }
+    Ok((ticks, rest))
+}
+
+fn unpack_bool(input: &[u8]) -> Result<(bool, &[u8]), Error> {
+    let (&value, rest) = input.split_first().ok_or(anyhow!("Unable to unpack bool"))?;
+    Ok((value != 0, rest))
+}
+
+/// Unpacks a borsh `Option<bool>`, a flag byte followed by the value when set. Older clients
+/// omit it, read as `None`.
+fn unpack_option_bool(input: &[u8]) -> Result<(Option<bool>, &[u8]), Error> {
+    match input.split_first() {
+        None | Some((0, _)) => Ok((None, input.get(1..).unwrap_or_default())),
+        Some((1, rest)) => unpack_bool(rest).map(|(value, rest)| (Some(value), rest)),
+        _ => Err(anyhow!("Unable to unpack optional bool")),
+    }
+}
@@ -0,0 +1,271 @@
+//! Flat records of the extracted events, for sinks writing files or database rows
+//!
+//! Records hold strings and integers only, one column per field, so they map directly to CSV
+//! columns, Parquet fields or table columns. Fields that do not apply to an event kind are
+//! left empty.
+
+use crate::{
+    attribution::{AttributedTokenEvent, CallerAttribution},
+    events::{
+        AccountOwnerChanged, ConfidentialAccountApproved, ConfidentialAccountConfigured, DelegationChange, EventContext,
+        GroupMemberAdded, MetadataFieldUpdated, MintPaused, MintResumed, MultisigInitialized, TokenEvent, TransferEvent,
+    },
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// `event_type` of the records of [TransferEvent]s
+pub const TRANSFER: &str = TransferEvent::KIND;
+/// `event_type` of the records of [GroupMemberAdded] events
+pub const GROUP_MEMBER_ADDED: &str = GroupMemberAdded::KIND;
+/// `event_type` of the records of [MintPaused] events
+pub const MINT_PAUSED: &str = MintPaused::KIND;
+/// `event_type` of the records of [MintResumed] events
+pub const MINT_RESUMED: &str = MintResumed::KIND;
+/// `event_type` of the records of [MetadataFieldUpdated] events
+pub const METADATA_FIELD_UPDATED: &str = MetadataFieldUpdated::KIND;
+/// `event_type` of the records of [AccountOwnerChanged] events
+pub const ACCOUNT_OWNER_CHANGED: &str = AccountOwnerChanged::KIND;
+/// `event_type` of the records of [DelegationChange] events
+pub const DELEGATION_CHANGE: &str = DelegationChange::KIND;
+/// `event_type` of the records of [MultisigInitialized] events
+pub const MULTISIG_INITIALIZED: &str = MultisigInitialized::KIND;
+/// `event_type` of the records of [ConfidentialAccountConfigured] events
+pub const CONFIDENTIAL_ACCOUNT_CONFIGURED: &str = ConfidentialAccountConfigured::KIND;
+/// `event_type` of the records of [ConfidentialAccountApproved] events
+pub const CONFIDENTIAL_ACCOUNT_APPROVED: &str = ConfidentialAccountApproved::KIND;
+
+/// A [TokenEvent] flattened into a single row
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TokenEventRecord {
+    /// Kind of event, see the constants of this module
+    pub event_type: String,
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub event_id: String,
+    /// Signature of the transaction, base58 encoded
+    pub signature: String,
+    /// Index of the transaction in its block
+    pub transaction_index: u32,
+    /// Index of the top-level instruction
+    pub instruction_index: u32,
+    /// Index of the inner instruction, empty for top-level instructions
+    pub inner_instruction_index: Option<u32>,
+    /// The mint, paused or resumed mint, member mint, or account holding updated metadata
+    pub mint: String,
+    /// Source token account of a transfer, token account changing owner or delegate, token
+    /// account configured or approved for confidential transfers, or initialized multisig
+    /// account
+    pub source: String,
+    /// Owner of the source token account of a transfer or delegation, or previous owner of a
+    /// token account
+    pub source_owner: String,
+    /// Destination token account of a transfer, or new delegate of a token account, empty
+    /// for revoked delegations
+    pub destination: String,
+    /// Owner of the destination token account of a transfer, or new owner of a token account
+    pub destination_owner: String,
+    /// Transfer, pause, metadata update or confidential transfer authority
+    pub authority: String,
+    /// Amount transferred or delegated
+    pub amount: Option<u64>,
+    /// Fee withheld from a transfer
+    pub fee: Option<u64>,
+    /// Group mint of a new member
+    pub group_mint: String,
+    /// Account holding the member extension of a new member
+    pub member: String,
+    /// Member number of a new member
+    pub member_number: Option<u64>,
+    /// Updated metadata field: `name`, `symbol`, `uri` or the key of a user field
+    pub metadata_field: String,
+    /// New value of the updated metadata field
+    pub metadata_value: String,
+    /// ElGamal public key of an account configured for confidential transfers, hex encoded
+    pub elgamal_pubkey: String,
+    /// Maximum pending balance credit counter of an account configured for confidential
+    /// transfers
+    pub maximum_pending_balance_credit_counter: Option<u64>,
+    /// Number of signers required by an initialized multisig account, M
+    pub multisig_m: Option<u32>,
+    /// Signers of an initialized multisig account, comma separated
+    pub multisig_signers: String,
+    /// Program of the top-level instruction, empty unless set through
+    /// [TokenEventRecord::with_attribution]
+    pub top_level_program: String,
+    /// Program that invoked the token instruction, empty for top-level instructions or
+    /// unless set through [TokenEventRecord::with_attribution]
+    pub caller_program: String,
+}
+
+impl TokenEventRecord {
+    fn new(event_type: &str, context: EventContext) -> Self {
+        Self {
+            event_type: event_type.to_string(),
+            event_id: context.event_id(event_type),
+            signature: context.signature,
+            transaction_index: context.transaction_index,
+            instruction_index: context.instruction_index,
+            inner_instruction_index: context.inner_instruction_index,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the programs the event was executed on behalf of
+    pub fn with_attribution(mut self, attribution: &CallerAttribution) -> Self {
+        self.top_level_program = attribution.top_level_program.to_string();
+        self.caller_program = attribution.caller_program.map(|caller| caller.to_string()).unwrap_or_default();
+        self
+    }
+}
+
+impl From<TransferEvent> for TokenEventRecord {
+    fn from(event: TransferEvent) -> Self {
+        Self {
+            mint: event.mint.map(|mint| mint.to_string()).unwrap_or_default(),
+            source: event.source.to_string(),
+            source_owner: event.source_owner.map(|owner| owner.to_string()).unwrap_or_default(),
+            destination: event.destination.to_string(),
+            destination_owner: event.destination_owner.map(|owner| owner.to_string()).unwrap_or_default(),
+            authority: event.authority.to_string(),
+            amount: Some(event.amount),
+            fee: event.fee,
+            ..Self::new(TRANSFER, event.context)
+        }
+    }
+}
+
+impl From<GroupMemberAdded> for TokenEventRecord {
+    fn from(event: GroupMemberAdded) -> Self {
+        Self {
+            mint: event.member_mint.to_string(),
+            group_mint: event.group_mint.to_string(),
+            member: event.member.to_string(),
+            member_number: event.member_number,
+            ..Self::new(GROUP_MEMBER_ADDED, event.context)
+        }
+    }
+}
+
+impl From<MintPaused> for TokenEventRecord {
+    fn from(event: MintPaused) -> Self {
+        Self {
+            mint: event.mint.to_string(),
+            authority: event.authority.to_string(),
+            ..Self::new(MINT_PAUSED, event.context)
+        }
+    }
+}
+
+impl From<MintResumed> for TokenEventRecord {
+    fn from(event: MintResumed) -> Self {
+        Self {
+            mint: event.mint.to_string(),
+            authority: event.authority.to_string(),
+            ..Self::new(MINT_RESUMED, event.context)
+        }
+    }
+}
+
+impl From<MetadataFieldUpdated> for TokenEventRecord {
+    fn from(event: MetadataFieldUpdated) -> Self {
+        Self {
+            mint: event.metadata.to_string(),
+            authority: event.update_authority.to_string(),
+            metadata_field: event.field.key().to_string(),
+            metadata_value: event.value,
+            ..Self::new(METADATA_FIELD_UPDATED, event.context)
+        }
+    }
+}
+
+impl From<AccountOwnerChanged> for TokenEventRecord {
+    fn from(event: AccountOwnerChanged) -> Self {
+        Self {
+            mint: event.mint.map(|mint| mint.to_string()).unwrap_or_default(),
+            source: event.account.to_string(),
+            source_owner: event.previous_owner.map(|owner| owner.to_string()).unwrap_or_default(),
+            destination_owner: event.new_owner.to_string(),
+            ..Self::new(ACCOUNT_OWNER_CHANGED, event.context)
+        }
+    }
+}
+
+impl From<DelegationChange> for TokenEventRecord {
+    fn from(event: DelegationChange) -> Self {
+        Self {
+            mint: event.mint.map(|mint| mint.to_string()).unwrap_or_default(),
+            source: event.account.to_string(),
+            source_owner: event.owner.to_string(),
+            destination: event.delegate.map(|delegate| delegate.to_string()).unwrap_or_default(),
+            amount: Some(event.amount),
+            ..Self::new(DELEGATION_CHANGE, event.context)
+        }
+    }
+}
+
+impl From<ConfidentialAccountConfigured> for TokenEventRecord {
+    fn from(event: ConfidentialAccountConfigured) -> Self {
+        Self {
+            mint: event.mint.to_string(),
+            source: event.account.to_string(),
+            elgamal_pubkey: event
+                .elgamal_pubkey
+                .map(|pubkey| pubkey.iter().map(|byte| format!("{:02x}", byte)).collect())
+                .unwrap_or_default(),
+            maximum_pending_balance_credit_counter: Some(event.maximum_pending_balance_credit_counter),
+            ..Self::new(CONFIDENTIAL_ACCOUNT_CONFIGURED, event.context)
+        }
+    }
+}
+
+impl From<ConfidentialAccountApproved> for TokenEventRecord {
+    fn from(event: ConfidentialAccountApproved) -> Self {
+        Self {
+            mint: event.mint.to_string(),
+            source: event.account.to_string(),
+            authority: event.authority.to_string(),
+            ..Self::new(CONFIDENTIAL_ACCOUNT_APPROVED, event.context)
+        }
+    }
+}
+
+impl From<MultisigInitialized> for TokenEventRecord {
+    fn from(event: MultisigInitialized) -> Self {
+        Self {
+            source: event.multisig.to_string(),
+            multisig_m: Some(event.m as u32),
+            multisig_signers: event
+                .signers
+                .iter()
+                .map(|signer| signer.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+            ..Self::new(MULTISIG_INITIALIZED, event.context)
+        }
+    }
+}
+
+impl From<TokenEvent> for TokenEventRecord {
+    fn from(event: TokenEvent) -> Self {
+        match event {
+            TokenEvent::Transfer(event) => event.into(),
+            TokenEvent::GroupMemberAdded(event) => event.into(),
+            TokenEvent::MintPaused(event) => event.into(),
+            TokenEvent::MintResumed(event) => event.into(),
+            TokenEvent::MetadataFieldUpdated(event) => event.into(),
+            TokenEvent::AccountOwnerChanged(event) => event.into(),
+            TokenEvent::DelegationChange(event) => event.into(),
+            TokenEvent::MultisigInitialized(event) => event.into(),
+            TokenEvent::ConfidentialAccountConfigured(event) => event.into(),
+            TokenEvent::ConfidentialAccountApproved(event) => event.into(),
+        }
+    }
+}
+
+impl From<AttributedTokenEvent> for TokenEventRecord {
+    fn from(event: AttributedTokenEvent) -> Self {
+        Self::from(event.event).with_attribution(&event.attribution)
+    }
+}
@@ -0,0 +1,280 @@
+//! Rent-exemption thresholds of token accounts and mints
+//!
+//! Uses the default rent parameters of the Solana clusters, which have never been changed on
+//! mainnet, so the lamports funding a `CreateAccount` can be checked against the data length
+//! the token program expects without reading the rent sysvar. [Rent] takes other parameters,
+//! read from the sysvar of a test validator or a fork for instance.
+
+use anyhow::anyhow;
+use {
+    crate::token_instruction_2022::{ExtensionType, TokenInstruction},
+    std::convert::TryFrom,
+    substreams::errors::Error,
+};
+
+/// Account storage overhead for calculation of base rent, in bytes
+pub const ACCOUNT_STORAGE_OVERHEAD: u64 = 128;
+/// Default rental rate in lamports/byte-year
+pub const DEFAULT_LAMPORTS_PER_BYTE_YEAR: u64 = 1_000_000_000 / 100 * 365 / (1024 * 1024);
+/// Default amount of time (in years) the balance has to include rent for to be exempt
+pub const DEFAULT_EXEMPTION_THRESHOLD: f64 = 2.0;
+
+/// Length of a mint without extensions
+pub const MINT_LEN: usize = 82;
+/// Length of a token account without extensions
+pub const ACCOUNT_LEN: usize = 165;
+/// Length of a multisig account
+pub const MULTISIG_LEN: usize = 355;
+
+/// Length of the base state of extended accounts and mints (a token account's length),
+/// followed by the account type byte
+const BASE_ACCOUNT_AND_TYPE_LEN: usize = ACCOUNT_LEN + 1;
+/// Length of the type and length prefix of each TLV extension entry
+const TLV_HEADER_LEN: usize = 4;
+/// Length of an `ExtensionType`, added to accounts that would otherwise be mistaken for
+/// multisigs
+const EXTENSION_TYPE_LEN: usize = 2;
+
+/// Rent parameters, as held by the rent sysvar
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rent {
+    /// Rental rate in lamports/byte-year
+    pub lamports_per_byte_year: u64,
+    /// Amount of time (in years) the balance has to include rent for to be exempt
+    pub exemption_threshold: f64,
+    /// Percentage of collected rent that is burned
+    pub burn_percent: u8,
+}
+
+impl Default for Rent {
+    fn default() -> Self {
+        Self {
+            lamports_per_byte_year: DEFAULT_LAMPORTS_PER_BYTE_YEAR,
+            exemption_threshold: DEFAULT_EXEMPTION_THRESHOLD,
+            burn_percent: 50,
+        }
+    }
+}
+
+impl Rent {
+    /// Unpacks the data of the rent sysvar
+    pub fn unpack(data: &[u8]) -> Result<Self, Error> {
+        let (lamports_per_byte_year, rest) = TokenInstruction::unpack_u64(data)?;
+        let (exemption_threshold, rest) = TokenInstruction::unpack_f64(rest)?;
+        let (&burn_percent, _rest) = rest
+            .split_first()
+            .ok_or(anyhow!("Unable to unpack rent - missing burn percent"))?;
+        Ok(Self {
+            lamports_per_byte_year,
+            exemption_threshold,
+            burn_percent,
+        })
+    }
+
+    /// Minimum balance, in lamports, for an account holding `data_len` bytes to be rent exempt
+    pub fn minimum_balance(&self, data_len: usize) -> u64 {
+        (((ACCOUNT_STORAGE_OVERHEAD + data_len as u64) * self.lamports_per_byte_year) as f64
+            * self.exemption_threshold) as u64
+    }
+
+    /// Minimum balance, in lamports, for a token account holding `extensions`, each listed
+    /// once, to be rent exempt, so the lamports of the `CreateAccount` funding it can be
+    /// checked. Variable-length extensions, which only mints hold, count for their header.
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::{rent::Rent, token_instruction_2022::ExtensionType};
+    /// let rent = Rent::default();
+    /// assert_eq!(rent.minimum_balance_for(&[]), 2_039_280);
+    /// assert_eq!(rent.minimum_balance_for(&[ExtensionType::ImmutableOwner]), 2_074_080);
+    ///
+    /// let doubled = Rent { exemption_threshold: 4.0, ..rent };
+    /// assert_eq!(doubled.minimum_balance_for(&[]), 4_078_560);
+    /// ```
+    pub fn minimum_balance_for(&self, extensions: &[ExtensionType]) -> u64 {
+        let data_len = account_len(extensions).unwrap_or_else(|| {
+            let fixed: Vec<_> = extensions
+                .iter()
+                .map(|extension_type| match extension_len(*extension_type) {
+                    Some(_) => *extension_type,
+                    None => ExtensionType::Uninitialized,
+                })
+                .collect();
+            account_len(&fixed).unwrap_or(ACCOUNT_LEN)
+        });
+        self.minimum_balance(data_len)
+    }
+}
+
+/// Minimum balance, in lamports, for an account holding `data_len` bytes to be rent exempt,
+/// with the default rent parameters
+///
+/// ```
+/// # use substreams_solana_program_instructions::rent::{minimum_balance, ACCOUNT_LEN, MINT_LEN};
+/// assert_eq!(minimum_balance(ACCOUNT_LEN), 2_039_280);
+/// assert_eq!(minimum_balance(MINT_LEN), 1_461_600);
+/// ```
+pub fn minimum_balance(data_len: usize) -> u64 {
+    Rent::default().minimum_balance(data_len)
+}
+
+/// Returns `true` if `lamports` make an account holding `data_len` bytes rent exempt
+pub fn is_exempt(lamports: u64, data_len: usize) -> bool {
+    lamports >= minimum_balance(data_len)
+}
+
+/// Length of the data of `extension_type`, `None` for variable-length extensions (token
+/// metadata)
+pub fn extension_len(extension_type: ExtensionType) -> Option<usize> {
+    Some(match extension_type {
+        ExtensionType::Uninitialized => 0,
+        ExtensionType::TransferFeeConfig => 108,
+        ExtensionType::TransferFeeAmount => 8,
+        ExtensionType::MintCloseAuthority => 32,
+        ExtensionType::ConfidentialTransferMint => 65,
+        ExtensionType::ConfidentialTransferAccount => 295,
+        ExtensionType::DefaultAccountState => 1,
+        ExtensionType::ImmutableOwner => 0,
+        ExtensionType::MemoTransfer => 1,
+        ExtensionType::NonTransferable => 0,
+        ExtensionType::InterestBearingConfig => 52,
+        ExtensionType::CpiGuard => 1,
+        ExtensionType::PermanentDelegate => 32,
+        ExtensionType::NonTransferableAccount => 0,
+        ExtensionType::TransferHook => 64,
+        ExtensionType::TransferHookAccount => 1,
+        ExtensionType::ConfidentialTransferFeeConfig => 129,
+        ExtensionType::ConfidentialTransferFeeAmount => 64,
+        ExtensionType::MetadataPointer => 64,
+        ExtensionType::TokenMetadata => return None,
+        ExtensionType::GroupPointer => 64,
+        ExtensionType::TokenGroup => 80,
+        ExtensionType::GroupMemberPointer => 64,
+        ExtensionType::TokenGroupMember => 72,
+        ExtensionType::ConfidentialMintBurn => 196,
+        ExtensionType::ScaledUiAmount => 56,
+        ExtensionType::Pausable => 33,
+        ExtensionType::PausableAccount => 0,
+        #[cfg(test)]
+        ExtensionType::VariableLenMintTest => return None,
+        #[cfg(test)]
+        ExtensionType::AccountPaddingTest | ExtensionType::MintPaddingTest => return None,
+    })
+}
+
+/// Length of a mint holding `extension_types`, each listed once, as computed by the token
+/// 2022 program. `None` if one of them is variable-length.
+pub fn mint_len(extension_types: &[ExtensionType]) -> Option<usize> {
+    extended_len(MINT_LEN, extension_types)
+}
+
+/// Length of a token account holding `extension_types`, each listed once, as computed by the
+/// token 2022 program. `None` if one of them is variable-length.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{rent::account_len, token_instruction_2022::ExtensionType};
+/// assert_eq!(account_len(&[]), Some(165));
+/// assert_eq!(account_len(&[ExtensionType::ImmutableOwner]), Some(170));
+/// ```
+pub fn account_len(extension_types: &[ExtensionType]) -> Option<usize> {
+    extended_len(ACCOUNT_LEN, extension_types)
+}
+
+fn extended_len(base_len: usize, extension_types: &[ExtensionType]) -> Option<usize> {
+    if extension_types.is_empty() {
+        return Some(base_len);
+    }
+    let mut len = BASE_ACCOUNT_AND_TYPE_LEN;
+    for extension_type in extension_types {
+        len += TLV_HEADER_LEN + extension_len(*extension_type)?;
+    }
+    if len == MULTISIG_LEN {
+        len += EXTENSION_TYPE_LEN;
+    }
+    Some(len)
+}
+
+/// Outcome of a token 2022 `Reallocate` on a token account
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReallocatePlan {
+    /// Length of the account once reallocated
+    pub new_len: usize,
+    /// Requested extensions the account did not hold yet, in request order
+    pub added_extensions: Vec<ExtensionType>,
+}
+
+impl ReallocatePlan {
+    /// Lamports the payer adds to keep the account rent exempt, growing from `current_len`
+    pub fn additional_rent(&self, current_len: usize) -> u64 {
+        minimum_balance(self.new_len).saturating_sub(minimum_balance(current_len))
+    }
+}
+
+/// Extension entries of the data of a token account or mint: the type and data length of
+/// each TLV entry, in order. Empty for accounts and mints without extensions.
+pub fn extension_entries(data: &[u8]) -> Result<Vec<(ExtensionType, usize)>, Error> {
+    let mut entries = vec![];
+    let mut tlv = data.get(BASE_ACCOUNT_AND_TYPE_LEN..).unwrap_or(&[]);
+    while tlv.len() >= TLV_HEADER_LEN {
+        let (raw_type, rest) = TokenInstruction::unpack_u16(tlv)?;
+        // Zeroed space past the last entry
+        if raw_type == u16::from(ExtensionType::Uninitialized) {
+            break;
+        }
+        let extension_type = ExtensionType::try_from(raw_type)
+            .map_err(|_| anyhow!("Unable to unpack extension entries - invalid extension type: {}", raw_type))?;
+        let (len, rest) = TokenInstruction::unpack_u16(rest)?;
+        let (_value, rest) = TokenInstruction::unpack_bytes(rest, len as usize)?;
+        entries.push((extension_type, len as usize));
+        tlv = rest;
+    }
+    Ok(entries)
+}
+
+/// Computes the outcome of a `Reallocate` requesting `extension_types` on the token account
+/// holding `account_data`, as the token 2022 program does. Without the account's data, it is
+/// assumed to hold no extension yet.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{rent::{reallocate_plan, ACCOUNT_LEN}, token_instruction_2022::ExtensionType};
+/// let plan = reallocate_plan(&[ExtensionType::MemoTransfer], None).unwrap();
+/// assert_eq!(plan.new_len, 171);
+/// assert_eq!(plan.added_extensions, vec![ExtensionType::MemoTransfer]);
+/// assert_eq!(plan.additional_rent(ACCOUNT_LEN), 41_760);
+/// ```
+pub fn reallocate_plan(extension_types: &[ExtensionType], account_data: Option<&[u8]>) -> Result<ReallocatePlan, Error> {
+    let existing = match account_data {
+        Some(data) => extension_entries(data)?,
+        None => vec![],
+    };
+    let mut added_extensions: Vec<ExtensionType> = vec![];
+    for extension_type in extension_types {
+        let held = existing.iter().any(|(existing_type, _)| existing_type == extension_type);
+        if !held && !added_extensions.contains(extension_type) {
+            added_extensions.push(*extension_type);
+        }
+    }
+
+    let current_len = account_data.map_or(ACCOUNT_LEN, |data| data.len());
+    if added_extensions.is_empty() {
+        return Ok(ReallocatePlan {
+            new_len: current_len,
+            added_extensions,
+        });
+    }
+    let mut new_len = BASE_ACCOUNT_AND_TYPE_LEN;
+    for (_, len) in &existing {
+        new_len += TLV_HEADER_LEN + len;
+    }
+    for extension_type in &added_extensions {
+        let len = extension_len(*extension_type)
+            .ok_or(anyhow!("Unable to plan reallocation - variable-length extension: {:?}", extension_type))?;
+        new_len += TLV_HEADER_LEN + len;
+    }
+    if new_len == MULTISIG_LEN {
+        new_len += EXTENSION_TYPE_LEN;
+    }
+    Ok(ReallocatePlan {
+        new_len,
+        added_extensions,
+    })
+}
@@ -0,0 +1,145 @@
+//! Event replay helpers for sinks restarting from a cursor
+//!
+//! A sink restarting from its last cursor reprocesses the block the cursor points to, and
+//! possibly blocks it already wrote when its cursor lagged behind. A [ReplayCursor] records
+//! where the sink stopped, a slot or the last event written, and [replay_events] drops the
+//! events before it, so the restart neither writes events twice nor skips any. Events are
+//! tagged with the [Finality] of their block, from the last final slot the sink is told about
+//! by substreams (its final block height), so reversible writes can be kept apart.
+
+use {
+    crate::{
+        events::{block_token_events, EventContext, TokenEvent},
+        ordering::EventPosition,
+    },
+    substreams_solana_core::pb::sf::solana::r#type::v1::Block,
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Finality of the block of an event
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Finality {
+    /// The block is final, its events are never undone
+    Final,
+    /// The block may still be forked out, undoing its events
+    Reversible,
+}
+
+impl Finality {
+    /// Finality of the block at `slot`, given the last final slot, `None` if unknown
+    pub fn of(slot: u64, final_slot: Option<u64>) -> Self {
+        match final_slot {
+            Some(final_slot) if slot <= final_slot => Self::Final,
+            _ => Self::Reversible,
+        }
+    }
+}
+
+/// Where a sink resumes: the events at or after a slot, or the events following the last one
+/// it wrote
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReplayCursor {
+    /// Slot the sink resumes at
+    pub slot: u64,
+    /// Last event written at `slot`, `None` if none was
+    pub last_event: Option<EventContext>,
+}
+
+impl ReplayCursor {
+    /// Cursor keeping the events at `slot` and after
+    pub fn from_slot(slot: u64) -> Self {
+        Self { slot, last_event: None }
+    }
+
+    /// Cursor keeping the events following `context`, the last event written, at `slot`
+    pub fn after_event(slot: u64, context: &EventContext) -> Self {
+        Self {
+            slot,
+            last_event: Some(context.clone()),
+        }
+    }
+
+    /// Returns `true` if the event at `context`, in the block at `slot`, is replayed. Events
+    /// of a same block are ordered by position, inner instructions following the top-level
+    /// instruction invoking them.
+    pub fn includes(&self, slot: u64, context: &EventContext) -> bool {
+        if slot != self.slot {
+            return slot > self.slot;
+        }
+        match &self.last_event {
+            Some(last_event) => EventPosition::new(slot, context) > EventPosition::new(slot, last_event),
+            None => true,
+        }
+    }
+}
+
+/// An event to replay, along with its block's slot and finality
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplayedEvent<T> {
+    /// Slot of the block of the event
+    pub slot: u64,
+    /// Finality of the block of the event
+    pub finality: Finality,
+    /// The event
+    pub event: T,
+}
+
+/// Keeps the `events` extracted from `block` that `cursor` replays, in their order, tagged
+/// with the finality of `block` given `final_slot`, the last final slot. `context` reads the
+/// position of each event.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{events::EventContext, replay::*};
+/// # let block = substreams_solana_core::pb::sf::solana::r#type::v1::Block { slot: 100, ..Default::default() };
+/// let events: Vec<EventContext> = (0..3)
+///     .map(|instruction_index| EventContext { instruction_index, ..Default::default() })
+///     .collect();
+/// let cursor = ReplayCursor::after_event(100, &events[0]);
+///
+/// let replayed = replay_events(&block, events, &cursor, Some(99), |event| event);
+/// assert_eq!(replayed.len(), 2);
+/// assert_eq!(replayed[0].event.instruction_index, 1);
+/// assert_eq!(replayed[0].finality, Finality::Reversible);
+/// ```
+pub fn replay_events<T, I, F>(
+    block: &Block,
+    events: I,
+    cursor: &ReplayCursor,
+    final_slot: Option<u64>,
+    context: F,
+) -> Vec<ReplayedEvent<T>>
+where
+    I: IntoIterator<Item = T>,
+    F: Fn(&T) -> &EventContext,
+{
+    if block.slot < cursor.slot {
+        return vec![];
+    }
+    let finality = Finality::of(block.slot, final_slot);
+    events
+        .into_iter()
+        .filter(|event| cursor.includes(block.slot, context(event)))
+        .map(|event| ReplayedEvent {
+            slot: block.slot,
+            finality,
+            event,
+        })
+        .collect()
+}
+
+/// Extracts the events of all successful transactions of `block` that `cursor` replays, see
+/// [replay_events]
+pub fn replay_token_events(block: &Block, cursor: &ReplayCursor, final_slot: Option<u64>) -> Vec<ReplayedEvent<TokenEvent>> {
+    if block.slot < cursor.slot {
+        return vec![];
+    }
+    replay_events(block, block_token_events(block), cursor, final_slot, TokenEvent::context)
+}
@@ -0,0 +1,113 @@
+//! Decoding of the return data set by token instructions through `sol_set_return_data`
+
+use anyhow::anyhow;
+use {
+    crate::{
+        events::EventContext, instructions::instructions, program_ids::is_token_program,
+        token_instruction_2022::TokenInstruction,
+    },
+    substreams::errors::Error,
+    substreams_solana_core::pb::sf::solana::r#type::v1::ConfirmedTransaction,
+};
+
+/// Value returned by a token instruction
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReturnValue {
+    /// Little-endian `u64` returned by `UiAmountToAmount` and `GetAccountDataSize`
+    Amount(u64),
+    /// UTF-8 string returned by `AmountToUiAmount`
+    UiAmount(String),
+}
+
+/// A return value along with the instruction that produced it
+#[derive(Clone, Debug, PartialEq)]
+pub struct InstructionReturnValue<'a> {
+    /// Position of the instruction that produced the value
+    pub context: EventContext,
+    /// The instruction that produced the value
+    pub instruction: TokenInstruction<'a>,
+    /// The decoded value
+    pub value: ReturnValue,
+}
+
+/// Decodes `data` as the return data of `instruction`. Returns `Ok(None)` if the instruction
+/// does not set any return data.
+pub fn decode_return_data(instruction: &TokenInstruction, data: &[u8]) -> Result<Option<ReturnValue>, Error> {
+    Ok(match instruction {
+        TokenInstruction::AmountToUiAmount { .. } => {
+            let ui_amount = String::from_utf8(data.to_vec())
+                .map_err(|_| anyhow!("Invalid Return Data - ui amount is not valid UTF-8"))?;
+            Some(ReturnValue::UiAmount(ui_amount))
+        }
+        TokenInstruction::UiAmountToAmount { .. } | TokenInstruction::GetAccountDataSize { .. } => {
+            let (amount, _rest) = TokenInstruction::unpack_u64(data)?;
+            Some(ReturnValue::Amount(amount))
+        }
+        _ => None,
+    })
+}
+
+/// Finds the token instruction that produced the transaction's return data and decodes
+/// the value it returned.
+///
+/// The transaction meta only retains the last return data set during execution, so the
+/// producer is the last instruction (top-level or inner) of the returning program that
+/// sets return data, skipping the instructions whose answer the data does not decode as, an
+/// `AmountToUiAmount` for data that is not UTF-8 for instance. Returns `None` when the
+/// transaction has no return data or when it was not set by a token program.
+/// `transaction_index` is the index of `trx` in its block.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{program_ids::TOKEN_2022_PROGRAM_ID, return_data::{find_return_value, ReturnValue}};
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::{CompiledInstruction, ConfirmedTransaction, Message, ReturnData, Transaction, TransactionStatusMeta};
+/// let token_2022 = TOKEN_2022_PROGRAM_ID.as_ref().to_vec();
+/// let trx = ConfirmedTransaction {
+///     transaction: Some(Transaction {
+///         message: Some(Message {
+///             account_keys: vec![vec![1; 32], token_2022.clone()],
+///             instructions: vec![
+///                 // GetAccountDataSize, then AmountToUiAmount
+///                 CompiledInstruction { program_id_index: 1, accounts: vec![0], data: vec![21] },
+///                 CompiledInstruction { program_id_index: 1, accounts: vec![0], data: vec![23, 1, 0, 0, 0, 0, 0, 0, 0] },
+///             ],
+///             ..Default::default()
+///         }),
+///         ..Default::default()
+///     }),
+///     meta: Some(TransactionStatusMeta {
+///         // Not UTF-8, so not the answer of AmountToUiAmount
+///         return_data: Some(ReturnData { program_id: token_2022, data: vec![0xa5, 0, 0, 0, 0, 0, 0, 0xff] }),
+///         ..Default::default()
+///     }),
+/// };
+///
+/// let found = find_return_value(&trx, 0).unwrap();
+/// assert_eq!(found.context.instruction_index, 0);
+/// assert_eq!(found.value, ReturnValue::Amount(0xff00_0000_0000_00a5));
+/// ```
+pub fn find_return_value(trx: &ConfirmedTransaction, transaction_index: u32) -> Option<InstructionReturnValue<'_>> {
+    let return_data = match trx.meta.as_ref().and_then(|meta| meta.return_data.as_ref()) {
+        Some(return_data) if is_token_program(&return_data.program_id) => return_data,
+        _ => return None,
+    };
+
+    let candidates: Vec<_> = instructions(trx)
+        .filter(|instruction| instruction.program_id == return_data.program_id.as_slice())
+        .collect();
+
+    for candidate in candidates.into_iter().rev() {
+        let instruction = match TokenInstruction::unpack(candidate.data) {
+            Ok(instruction) => instruction,
+            Err(_) => continue,
+        };
+        if let Some(value) = decode_return_data(&instruction, &return_data.data).ok().flatten() {
+            return Some(InstructionReturnValue {
+                context: EventContext::new(trx, transaction_index, &candidate),
+                instruction,
+                value,
+            });
+        }
+    }
+
+    None
+}
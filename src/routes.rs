@@ -0,0 +1,116 @@
+//! Netting of the token transfers of multi-hop routes into sender to receiver flows
+
+use {
+    crate::{events::TransferEvent, pubkey::Pubkey},
+    std::collections::HashMap,
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// A net flow of tokens of a mint between two parties of a transaction
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetFlow {
+    /// The mint, `None` for transfers whose mint could not be resolved
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub mint: Option<Pubkey>,
+    /// The sending party, a token account owner or the token account itself when its owner is unknown
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub from: Pubkey,
+    /// The receiving party, a token account owner or the token account itself when its owner is unknown
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub to: Pubkey,
+    /// Net amount received by `to`
+    pub amount: u64,
+}
+
+/// Collapses the `transfers` of one transaction into net flows per mint between the parties
+/// left with a net balance change.
+///
+/// Parties are token account owners, so temporary accounts (wrapped SOL accounts created and
+/// closed around a swap) merge with their owner, and intermediate hops (router-owned vaults
+/// receiving then forwarding the tokens) net to zero and disappear. Net senders are matched
+/// against net receivers in order of first appearance. Withheld transfer fees leave the
+/// matched flows, the unmatched remainder of the senders being dropped.
+///
+/// Flows are ordered by mint then sender, in order of first appearance.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{events::{EventContext, TransferEvent}, pubkey::Pubkey, routes::{net_route, NetFlow}};
+/// let (user, router, pool) = (Pubkey::from([1; 32]), Pubkey::from([2; 32]), Pubkey::from([3; 32]));
+/// let hop = |source_owner, destination_owner, amount| TransferEvent {
+///     context: EventContext::default(),
+///     mint: None,
+///     source: Pubkey::from([10; 32]),
+///     source_owner: Some(source_owner),
+///     destination: Pubkey::from([11; 32]),
+///     destination_owner: Some(destination_owner),
+///     authority: source_owner,
+///     amount,
+///     fee: None,
+/// };
+/// // The router keeps 1 token and forwards the rest to the pool
+/// let flows = net_route(&[hop(user, router, 100), hop(router, pool, 99)]);
+/// assert_eq!(flows, vec![
+///     NetFlow { mint: None, from: user, to: router, amount: 1 },
+///     NetFlow { mint: None, from: user, to: pool, amount: 99 },
+/// ]);
+/// ```
+pub fn net_route(transfers: &[TransferEvent]) -> Vec<NetFlow> {
+    let mut mints: Vec<Option<Pubkey>> = vec![];
+    let mut balances: HashMap<Option<Pubkey>, Vec<(Pubkey, i128)>> = HashMap::new();
+    for transfer in transfers {
+        if !mints.contains(&transfer.mint) {
+            mints.push(transfer.mint);
+        }
+        let parties = balances.entry(transfer.mint).or_default();
+        let from = transfer.source_owner.unwrap_or(transfer.source);
+        let to = transfer.destination_owner.unwrap_or(transfer.destination);
+        add(parties, from, -(transfer.amount as i128));
+        add(parties, to, transfer.received_amount() as i128);
+    }
+
+    let mut flows = vec![];
+    for mint in mints {
+        let parties = &balances[&mint];
+        let mut senders: Vec<(Pubkey, i128)> = parties
+            .iter()
+            .filter(|(_, net)| *net < 0)
+            .map(|(party, net)| (*party, -net))
+            .collect();
+        let mut receivers: Vec<(Pubkey, i128)> = parties.iter().filter(|(_, net)| *net > 0).copied().collect();
+
+        let (mut s, mut r) = (0, 0);
+        while s < senders.len() && r < receivers.len() {
+            let amount = senders[s].1.min(receivers[r].1);
+            flows.push(NetFlow {
+                mint,
+                from: senders[s].0,
+                to: receivers[r].0,
+                amount: amount as u64,
+            });
+            senders[s].1 -= amount;
+            receivers[r].1 -= amount;
+            if senders[s].1 == 0 {
+                s += 1;
+            }
+            if receivers[r].1 == 0 {
+                r += 1;
+            }
+        }
+    }
+
+    flows
+}
+
+fn add(parties: &mut Vec<(Pubkey, i128)>, party: Pubkey, delta: i128) {
+    match parties.iter_mut().find(|(candidate, _)| *candidate == party) {
+        Some((_, net)) => *net += delta,
+        None => parties.push((party, delta)),
+    }
+}
@@ -0,0 +1,160 @@
+//! Sampled extraction, for canary modules checking decoder health on live traffic
+//!
+//! A [Sampler] processes one transaction out of `every`, so a canary module can run the
+//! decoders of a new release on live blocks for a fraction of the cost of a full rollout.
+//! Results come as [Sampled] values recording how many transactions were processed out of
+//! the block's, from which [Sampled::extrapolate] estimates the counts of a full run.
+//!
+//! Transactions are picked by slot and index, deterministically, so reprocessing a block
+//! samples the same transactions, while consecutive blocks sample different positions.
+
+use {
+    crate::{
+        instructions::{instructions, is_successful},
+        program_ids::is_token_program,
+        token_instruction_2022::TokenInstruction,
+    },
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Selects one transaction out of `every`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sampler {
+    every: u32,
+}
+
+impl Sampler {
+    /// Sampler processing one transaction out of `every`, at least one: `Sampler::new(1)`
+    /// processes them all
+    pub fn new(every: u32) -> Self {
+        Self { every: every.max(1) }
+    }
+
+    /// Number of transactions per sampled transaction
+    pub fn every(&self) -> u32 {
+        self.every
+    }
+
+    /// Returns `true` if the `transaction_index`-th transaction of the block at `slot` is
+    /// sampled
+    pub fn is_sampled(&self, slot: u64, transaction_index: u32) -> bool {
+        slot.wrapping_add(transaction_index as u64) % self.every as u64 == 0
+    }
+
+    /// Runs the transaction-level extractor `extract` over the sampled transactions of
+    /// `block`, concatenating the results in transaction order
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::{events::transfer_events, sampling::Sampler};
+    /// # use substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction};
+    /// let block = Block { slot: 100, transactions: vec![ConfirmedTransaction::default(); 10], ..Default::default() };
+    /// let transfers = Sampler::new(4).extract(&block, transfer_events);
+    /// // Transactions 0, 4 and 8
+    /// assert_eq!((transfers.sampled_transactions, transfers.total_transactions), (3, 10));
+    /// assert_eq!(transfers.extrapolate(6), 20);
+    /// ```
+    pub fn extract<'a, T, F, I>(&self, block: &'a Block, extract: F) -> Sampled<Vec<T>>
+    where
+        F: Fn(&'a ConfirmedTransaction, u32) -> I,
+        I: IntoIterator<Item = T>,
+    {
+        let mut sampled_transactions = 0;
+        let mut value = vec![];
+        for (index, trx) in block.transactions.iter().enumerate() {
+            if !self.is_sampled(block.slot, index as u32) {
+                continue;
+            }
+            sampled_transactions += 1;
+            value.extend(extract(trx, index as u32));
+        }
+        Sampled {
+            value,
+            sampled_transactions,
+            total_transactions: block.transactions.len() as u32,
+        }
+    }
+
+    /// Decodes the token instructions of the sampled successful transactions of `block`,
+    /// counting those that fail to decode
+    pub fn decode_health(&self, block: &Block) -> Sampled<DecodeCounts> {
+        let counts = self.extract(block, |trx, _| {
+            let successful = is_successful(trx);
+            instructions(trx)
+                .filter(move |instruction| successful && is_token_program(instruction.program_id))
+                .map(|instruction| TokenInstruction::unpack(instruction.data).is_ok())
+        });
+        Sampled {
+            value: DecodeCounts {
+                instructions: counts.value.len() as u64,
+                decode_failures: counts.value.iter().filter(|decoded| !**decoded).count() as u64,
+            },
+            sampled_transactions: counts.sampled_transactions,
+            total_transactions: counts.total_transactions,
+        }
+    }
+}
+
+/// A result computed over the sampled transactions of a block
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sampled<T> {
+    /// The result over the sampled transactions
+    pub value: T,
+    /// Number of transactions sampled
+    pub sampled_transactions: u32,
+    /// Number of transactions of the block, failed transactions included
+    pub total_transactions: u32,
+}
+
+impl<T> Sampled<T> {
+    /// Ratio of the block's transactions to the sampled ones, 0 if none was sampled
+    pub fn scale(&self) -> f64 {
+        if self.sampled_transactions == 0 {
+            return 0.0;
+        }
+        self.total_transactions as f64 / self.sampled_transactions as f64
+    }
+
+    /// Estimates the value of `count`, counted over the sampled transactions, over the whole
+    /// block, rounded to the nearest integer
+    pub fn extrapolate(&self, count: u64) -> u64 {
+        (count as f64 * self.scale()).round() as u64
+    }
+}
+
+/// Token instruction decoding counts
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DecodeCounts {
+    /// Number of token instructions
+    pub instructions: u64,
+    /// Number of token instructions that failed to decode
+    pub decode_failures: u64,
+}
+
+impl DecodeCounts {
+    /// Share of the instructions that failed to decode, between 0 and 1
+    pub fn failure_rate(&self) -> f64 {
+        if self.instructions == 0 {
+            return 0.0;
+        }
+        self.decode_failures as f64 / self.instructions as f64
+    }
+}
+
+impl Sampled<DecodeCounts> {
+    /// Estimated number of token instructions of the block
+    pub fn extrapolated_instructions(&self) -> u64 {
+        self.extrapolate(self.value.instructions)
+    }
+
+    /// Estimated number of token instructions of the block failing to decode
+    pub fn extrapolated_decode_failures(&self) -> u64 {
+        self.extrapolate(self.value.decode_failures)
+    }
+}
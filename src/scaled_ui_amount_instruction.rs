@@ -0,0 +1,193 @@
+use anyhow::anyhow;
+use {
+    crate::{option::COption, pubkey::Pubkey, token_instruction_2022::TokenInstruction},
+    std::fmt,
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    crate::serialization::coption_fromstr,
+    serde::{Deserialize, Serialize},
+};
+
+/// Serialized length of a multiplier, a little-endian `f64`
+const MULTIPLIER_BYTES: usize = 8;
+
+/// Multiplier of the scaled UI amount extension
+///
+/// The multiplier is an `f64` on chain. It is kept as the bits read from the instruction
+/// data, so decoding, comparing and re-encoding it never goes through float arithmetic:
+/// every bit pattern, negative zero and NaN payloads included, round-trips unchanged and
+/// compares equal only to itself. With `serde-traits`, it serializes as these bits, a `u64`,
+/// JSON having no representation of NaN and infinite values.
+///
+/// ```
+/// # use substreams_solana_program_instructions::scaled_ui_amount_instruction::Multiplier;
+/// let data = 1.5f64.to_le_bytes();
+/// let (multiplier, rest) = Multiplier::unpack(&data).unwrap();
+/// assert_eq!(multiplier.value(), 1.5);
+/// assert_eq!(multiplier.to_le_bytes(), data);
+/// assert!(rest.is_empty());
+///
+/// // A NaN with a payload, and negative zero
+/// for bits in [0x7ff8_0000_dead_beef_u64, 0x8000_0000_0000_0000] {
+///     let (multiplier, _) = Multiplier::unpack(&bits.to_le_bytes()).unwrap();
+///     assert_eq!(multiplier.to_bits(), bits);
+///     assert_eq!(multiplier, Multiplier::from_bits(bits));
+/// }
+/// assert_ne!(Multiplier::from(0.0), Multiplier::from(-0.0));
+///
+/// # #[cfg(feature = "canonical-json")]
+/// # {
+/// let nan = Multiplier::from_bits(0x7ff8_0000_dead_beef);
+/// let json = serde_json::to_string(&nan).unwrap();
+/// assert_eq!(serde_json::from_str::<Multiplier>(&json).unwrap(), nan);
+/// # }
+/// ```
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(transparent))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Multiplier(u64);
+
+impl Multiplier {
+    /// Multiplier with the bits `bits`
+    pub fn from_bits(bits: u64) -> Self {
+        Self(bits)
+    }
+
+    /// Bits of the multiplier, as stored on chain
+    pub fn to_bits(self) -> u64 {
+        self.0
+    }
+
+    /// Value of the multiplier
+    pub fn value(self) -> f64 {
+        f64::from_bits(self.0)
+    }
+
+    /// Multiplier encoded as stored on chain
+    pub fn from_le_bytes(bytes: [u8; MULTIPLIER_BYTES]) -> Self {
+        Self(u64::from_le_bytes(bytes))
+    }
+
+    /// Encodes the multiplier as stored on chain
+    pub fn to_le_bytes(self) -> [u8; MULTIPLIER_BYTES] {
+        self.0.to_le_bytes()
+    }
+
+    /// Unpacks a multiplier, returning it along with the remaining bytes
+    pub fn unpack(input: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (bits, rest) = TokenInstruction::unpack_u64(input).map_err(|_| anyhow!("Unable to unpack multiplier"))?;
+        Ok((Self(bits), rest))
+    }
+}
+
+impl From<f64> for Multiplier {
+    fn from(value: f64) -> Self {
+        Self(value.to_bits())
+    }
+}
+
+impl From<Multiplier> for f64 {
+    fn from(multiplier: Multiplier) -> Self {
+        multiplier.value()
+    }
+}
+
+impl fmt::Display for Multiplier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+/// Scaled UI amount extension instructions
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(u8)]
+pub enum ScaledUiAmountInstruction {
+    /// Initialize a new mint with scaled UI amounts.
+    ///
+    /// Fails if the mint has already been initialized, so must be called before
+    /// `InitializeMint`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint to initialize.
+    Initialize {
+        /// The authority that can update the multiplier
+        #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+        authority: COption<Pubkey>,
+        /// The initial multiplier
+        multiplier: Multiplier,
+    },
+    /// Update the multiplier. Only supported for mints that include the
+    /// `ScaledUiAmountConfig` extension.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[signer]` The multiplier authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[]` The mint's multisignature multiplier authority.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    UpdateMultiplier {
+        /// The new multiplier
+        multiplier: Multiplier,
+        /// Timestamp at which the new multiplier will take effect
+        effective_timestamp: i64,
+    },
+}
+impl ScaledUiAmountInstruction {
+    /// Unpacks a byte buffer into a ScaledUiAmountInstruction
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::{scaled_ui_amount_instruction::*, token_instruction_2022::TokenInstruction};
+    /// let mut data = vec![43, 1];
+    /// data.extend_from_slice(&2.0f64.to_le_bytes());
+    /// data.extend_from_slice(&1_700_000_000i64.to_le_bytes());
+    /// assert_eq!(
+    ///     TokenInstruction::unpack(&data).unwrap(),
+    ///     TokenInstruction::ScaledUiAmountExtension(ScaledUiAmountInstruction::UpdateMultiplier {
+    ///         multiplier: Multiplier::from(2.0),
+    ///         effective_timestamp: 1_700_000_000,
+    ///     })
+    /// );
+    /// ```
+    pub fn unpack(input: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (&tag, rest) = input
+            .split_first()
+            .ok_or(anyhow!("Invalid Scaled UI Amount Instruction"))?;
+        Ok(match tag {
+            0 => {
+                let (authority, rest) = TokenInstruction::unpack_optional_nonzero_pubkey(rest)?;
+                let (multiplier, rest) = Multiplier::unpack(rest)?;
+                (Self::Initialize { authority, multiplier }, rest)
+            }
+            1 => {
+                let (multiplier, rest) = Multiplier::unpack(rest)?;
+                let (effective_timestamp, rest) = TokenInstruction::unpack_i64(rest)?;
+                (
+                    Self::UpdateMultiplier {
+                        multiplier,
+                        effective_timestamp,
+                    },
+                    rest,
+                )
+            }
+            _ => {
+                return Err(anyhow!(
+                    "Invalid Scaled UI Amount Instruction - unpack didn't match any tag value: {}",
+                    tag
+                ))
+            }
+        })
+    }
+}
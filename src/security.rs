@@ -0,0 +1,224 @@
+//! Heuristics flagging wallet drainer patterns in successful transactions
+//!
+//! Drainers trick a wallet into signing a transaction that hands its token accounts, or
+//! the right to move their tokens, to an address the wallet does not control, often emptying
+//! many of its accounts at once. Each pattern alone is common in legitimate transactions:
+//! [DrainerDetector] only reports an owner when the transaction combines several of them.
+//! Findings are leads for wallet security monitoring, not proofs.
+
+use {
+    crate::{
+        accounts,
+        events::{account_owner_events, delegation_events, transfer_events, EventContext},
+        pubkey::Pubkey,
+    },
+    std::collections::HashSet,
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// A pattern contributing to a [SuspiciousActivity]
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SuspicionReason {
+    /// A token account handed over, through `SetAuthority`, to an owner that did not sign
+    /// the transaction
+    OwnershipToNonSigner {
+        /// The token account
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        account: Pubkey,
+        /// The new owner
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        new_owner: Pubkey,
+    },
+    /// A token account delegated to an address that did not sign the transaction
+    DelegationToNonSigner {
+        /// The token account
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        account: Pubkey,
+        /// The delegate
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        delegate: Pubkey,
+        /// Amount delegated
+        amount: u64,
+    },
+    /// Many transfers out of the owner's accounts to accounts of other owners
+    BulkTransfers {
+        /// Number of transfers
+        transfers: u32,
+        /// Number of distinct mints transferred
+        mints: u32,
+    },
+}
+
+/// An owner whose token accounts a transaction handled like a drainer would
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SuspiciousActivity {
+    /// Position of the first instruction contributing to the finding
+    pub context: EventContext,
+    /// The owner of the token accounts, the presumed victim
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub owner: Pubkey,
+    /// The patterns found, in execution order, bulk transfers last
+    pub reasons: Vec<SuspicionReason>,
+}
+
+impl SuspiciousActivity {
+    /// Kind of the event, part of its [SuspiciousActivity::event_id]
+    pub const KIND: &'static str = "suspicious_activity";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+}
+
+/// Runs the drainer heuristics over transactions. An owner is reported when its accounts
+/// are handed over or delegated to non-signers, and its tokens moved out in bulk, in the
+/// same transaction; thresholds are set through the `with_` methods.
+#[derive(Clone, Debug)]
+pub struct DrainerDetector {
+    bulk_transfer_threshold: u32,
+    min_reasons: usize,
+}
+
+impl Default for DrainerDetector {
+    fn default() -> Self {
+        Self {
+            bulk_transfer_threshold: 3,
+            min_reasons: 2,
+        }
+    }
+}
+
+impl DrainerDetector {
+    /// Sets the number of transfers out of an owner's accounts making bulk transfers, 3 by
+    /// default
+    pub fn with_bulk_transfer_threshold(mut self, transfers: u32) -> Self {
+        self.bulk_transfer_threshold = transfers;
+        self
+    }
+
+    /// Sets the number of reasons needed to report an owner, 2 by default. With 1, any
+    /// handover or delegation to a non-signer is reported.
+    pub fn with_min_reasons(mut self, reasons: usize) -> Self {
+        self.min_reasons = reasons;
+        self
+    }
+
+    /// Checks the successful transactions of `block`, in transaction order
+    pub fn detect_block(&self, block: &Block) -> Vec<SuspiciousActivity> {
+        block
+            .transactions
+            .iter()
+            .enumerate()
+            .flat_map(|(index, trx)| self.detect(trx, index as u32))
+            .collect()
+    }
+
+    /// Checks a successful transaction, the `transaction_index`-th of its block, reporting
+    /// owners in order of first suspicious instruction. Failed transactions yield no finding.
+    pub fn detect(&self, trx: &ConfirmedTransaction, transaction_index: u32) -> Vec<SuspiciousActivity> {
+        let signers = signers(trx);
+        let mut activities: Vec<SuspiciousActivity> = vec![];
+        let mut add = |owner: Pubkey, context: EventContext, reason: SuspicionReason| {
+            match activities.iter_mut().find(|activity| activity.owner == owner) {
+                Some(activity) => {
+                    if context < activity.context {
+                        activity.context = context;
+                    }
+                    activity.reasons.push(reason);
+                }
+                None => activities.push(SuspiciousActivity {
+                    context,
+                    owner,
+                    reasons: vec![reason],
+                }),
+            }
+        };
+
+        let mut handovers: Vec<(EventContext, Pubkey, SuspicionReason)> = vec![];
+        for event in account_owner_events(trx, transaction_index) {
+            if signers.contains(&event.new_owner) {
+                continue;
+            }
+            if let Some(owner) = event.previous_owner {
+                handovers.push((
+                    event.context,
+                    owner,
+                    SuspicionReason::OwnershipToNonSigner {
+                        account: event.account,
+                        new_owner: event.new_owner,
+                    },
+                ));
+            }
+        }
+        for event in delegation_events(trx, transaction_index) {
+            let delegate = match event.delegate {
+                Some(delegate) if !signers.contains(&delegate) => delegate,
+                _ => continue,
+            };
+            handovers.push((
+                event.context,
+                event.owner,
+                SuspicionReason::DelegationToNonSigner {
+                    account: event.account,
+                    delegate,
+                    amount: event.amount,
+                },
+            ));
+        }
+        handovers.sort_by(|a, b| a.0.cmp(&b.0));
+        for (context, owner, reason) in handovers {
+            add(owner, context, reason);
+        }
+
+        // Transfers out of each owner's accounts to other owners
+        let mut outflows: Vec<(Pubkey, EventContext, u32, HashSet<Option<Pubkey>>)> = vec![];
+        for transfer in transfer_events(trx, transaction_index) {
+            let owner = match transfer.source_owner {
+                Some(owner) if transfer.destination_owner != Some(owner) => owner,
+                _ => continue,
+            };
+            match outflows.iter_mut().find(|(o, ..)| *o == owner) {
+                Some((_, _, transfers, mints)) => {
+                    *transfers += 1;
+                    mints.insert(transfer.mint);
+                }
+                None => outflows.push((owner, transfer.context, 1, [transfer.mint].into_iter().collect())),
+            }
+        }
+        for (owner, context, transfers, mints) in outflows {
+            if transfers >= self.bulk_transfer_threshold {
+                add(
+                    owner,
+                    context,
+                    SuspicionReason::BulkTransfers {
+                        transfers,
+                        mints: mints.len() as u32,
+                    },
+                );
+            }
+        }
+
+        activities.retain(|activity| activity.reasons.len() >= self.min_reasons);
+        activities.sort_by(|a, b| a.context.cmp(&b.context));
+        activities
+    }
+}
+
+/// Accounts that signed `trx`
+fn signers(trx: &ConfirmedTransaction) -> HashSet<Pubkey> {
+    accounts::signers(trx).into_iter().collect()
+}
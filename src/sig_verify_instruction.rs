@@ -0,0 +1,228 @@
+//! Ed25519 and Secp256k1 signature verification precompile instruction types
+//!
+//! Precompile instructions have no tag, their data starts with the number of signatures to
+//! verify followed by one offsets entry per signature. The offsets locate the signature, public
+//! key and message within the data of an instruction of the same transaction, usually the
+//! precompile instruction itself.
+
+use anyhow::anyhow;
+use {
+    crate::token_instruction_2022::TokenInstruction,
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Serialized length of [Ed25519SignatureOffsets]
+pub const ED25519_SIGNATURE_OFFSETS_LEN: usize = 14;
+/// Offset of the first [Ed25519SignatureOffsets], after the count and a padding byte
+pub const ED25519_SIGNATURE_OFFSETS_START: usize = 2;
+/// Serialized length of an Ed25519 signature
+pub const ED25519_SIGNATURE_LEN: usize = 64;
+/// Serialized length of an Ed25519 public key
+pub const ED25519_PUBKEY_LEN: usize = 32;
+/// Instruction index referring to the Ed25519 instruction itself
+pub const ED25519_CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Serialized length of [Secp256k1SignatureOffsets]
+pub const SECP256K1_SIGNATURE_OFFSETS_LEN: usize = 11;
+/// Offset of the first [Secp256k1SignatureOffsets], after the count
+pub const SECP256K1_SIGNATURE_OFFSETS_START: usize = 1;
+/// Serialized length of a Secp256k1 signature, without its recovery id
+pub const SECP256K1_SIGNATURE_LEN: usize = 64;
+/// Serialized length of an Ethereum address, the hashed Secp256k1 public key
+pub const ETH_ADDRESS_LEN: usize = 20;
+
+/// Location of the material of one signature verified by the Ed25519 program
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ed25519SignatureOffsets {
+    /// Offset to the ed25519 signature of 64 bytes
+    pub signature_offset: u16,
+    /// Instruction index holding the signature, [ED25519_CURRENT_INSTRUCTION] for this one
+    pub signature_instruction_index: u16,
+    /// Offset to the public key of 32 bytes
+    pub public_key_offset: u16,
+    /// Instruction index holding the public key, [ED25519_CURRENT_INSTRUCTION] for this one
+    pub public_key_instruction_index: u16,
+    /// Offset to the start of the message data
+    pub message_data_offset: u16,
+    /// Size of the message data
+    pub message_data_size: u16,
+    /// Instruction index holding the message, [ED25519_CURRENT_INSTRUCTION] for this one
+    pub message_instruction_index: u16,
+}
+
+/// Signature, public key and message verified by the Ed25519 program
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ed25519Signature<'a> {
+    /// The signature, 64 bytes
+    pub signature: &'a [u8],
+    /// The signer's public key, 32 bytes
+    pub public_key: &'a [u8],
+    /// The signed message
+    pub message: &'a [u8],
+}
+
+impl Ed25519SignatureOffsets {
+    /// Resolves the signed material, `current` being the data of the Ed25519 instruction and
+    /// `instructions` the data of the transaction's top-level instructions. `None` if an
+    /// offset is out of bounds.
+    pub fn resolve<'a>(&self, current: &'a [u8], instructions: &[&'a [u8]]) -> Option<Ed25519Signature<'a>> {
+        let data = |index: u16| {
+            if index == ED25519_CURRENT_INSTRUCTION {
+                Some(current)
+            } else {
+                instructions.get(index as usize).copied()
+            }
+        };
+        Some(Ed25519Signature {
+            signature: slice(data(self.signature_instruction_index)?, self.signature_offset, ED25519_SIGNATURE_LEN)?,
+            public_key: slice(data(self.public_key_instruction_index)?, self.public_key_offset, ED25519_PUBKEY_LEN)?,
+            message: slice(
+                data(self.message_instruction_index)?,
+                self.message_data_offset,
+                self.message_data_size as usize,
+            )?,
+        })
+    }
+}
+
+/// Instruction of the Ed25519 signature verification program
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ed25519Instruction {
+    /// Offsets of the verified signatures
+    pub offsets: Vec<Ed25519SignatureOffsets>,
+}
+
+impl Ed25519Instruction {
+    /// Unpacks a byte buffer into an [Ed25519Instruction]
+    pub fn unpack(input: &[u8]) -> Result<Self, Error> {
+        let (&count, _) = input.split_first().ok_or(anyhow!("Invalid Ed25519 Instruction"))?;
+        let mut rest = input.get(ED25519_SIGNATURE_OFFSETS_START..).unwrap_or(&[]);
+        let mut offsets = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let (signature_offset, r) = TokenInstruction::unpack_u16(rest)?;
+            let (signature_instruction_index, r) = TokenInstruction::unpack_u16(r)?;
+            let (public_key_offset, r) = TokenInstruction::unpack_u16(r)?;
+            let (public_key_instruction_index, r) = TokenInstruction::unpack_u16(r)?;
+            let (message_data_offset, r) = TokenInstruction::unpack_u16(r)?;
+            let (message_data_size, r) = TokenInstruction::unpack_u16(r)?;
+            let (message_instruction_index, r) = TokenInstruction::unpack_u16(r)?;
+            offsets.push(Ed25519SignatureOffsets {
+                signature_offset,
+                signature_instruction_index,
+                public_key_offset,
+                public_key_instruction_index,
+                message_data_offset,
+                message_data_size,
+                message_instruction_index,
+            });
+            rest = r;
+        }
+        Ok(Self { offsets })
+    }
+}
+
+/// Location of the material of one signature verified by the Secp256k1 program
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Secp256k1SignatureOffsets {
+    /// Offset to the 64-byte signature plus 1-byte recovery id
+    pub signature_offset: u16,
+    /// Top-level instruction index holding the signature
+    pub signature_instruction_index: u8,
+    /// Offset to the 20-byte Ethereum address
+    pub eth_address_offset: u16,
+    /// Top-level instruction index holding the Ethereum address
+    pub eth_address_instruction_index: u8,
+    /// Offset to the start of the message data
+    pub message_data_offset: u16,
+    /// Size of the message data
+    pub message_data_size: u16,
+    /// Top-level instruction index holding the message
+    pub message_instruction_index: u8,
+}
+
+/// Signature, signer address and message verified by the Secp256k1 program
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Secp256k1Signature<'a> {
+    /// The signature, 64 bytes
+    pub signature: &'a [u8],
+    /// The signature's recovery id
+    pub recovery_id: u8,
+    /// The signer's Ethereum address, 20 bytes
+    pub eth_address: &'a [u8],
+    /// The signed message
+    pub message: &'a [u8],
+}
+
+impl Secp256k1SignatureOffsets {
+    /// Resolves the signed material, `instructions` being the data of the transaction's
+    /// top-level instructions. `None` if an offset is out of bounds.
+    pub fn resolve<'a>(&self, instructions: &[&'a [u8]]) -> Option<Secp256k1Signature<'a>> {
+        let data = |index: u8| instructions.get(index as usize).copied();
+        let signature = slice(
+            data(self.signature_instruction_index)?,
+            self.signature_offset,
+            SECP256K1_SIGNATURE_LEN + 1,
+        )?;
+        Some(Secp256k1Signature {
+            signature: &signature[..SECP256K1_SIGNATURE_LEN],
+            recovery_id: signature[SECP256K1_SIGNATURE_LEN],
+            eth_address: slice(data(self.eth_address_instruction_index)?, self.eth_address_offset, ETH_ADDRESS_LEN)?,
+            message: slice(
+                data(self.message_instruction_index)?,
+                self.message_data_offset,
+                self.message_data_size as usize,
+            )?,
+        })
+    }
+}
+
+/// Instruction of the Secp256k1 signature verification program
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Secp256k1Instruction {
+    /// Offsets of the verified signatures
+    pub offsets: Vec<Secp256k1SignatureOffsets>,
+}
+
+impl Secp256k1Instruction {
+    /// Unpacks a byte buffer into a [Secp256k1Instruction]
+    pub fn unpack(input: &[u8]) -> Result<Self, Error> {
+        let (&count, mut rest) = input.split_first().ok_or(anyhow!("Invalid Secp256k1 Instruction"))?;
+        let mut offsets = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let (signature_offset, r) = TokenInstruction::unpack_u16(rest)?;
+            let (&signature_instruction_index, r) = r.split_first().ok_or(anyhow!("Unable to unpack u8"))?;
+            let (eth_address_offset, r) = TokenInstruction::unpack_u16(r)?;
+            let (&eth_address_instruction_index, r) = r.split_first().ok_or(anyhow!("Unable to unpack u8"))?;
+            let (message_data_offset, r) = TokenInstruction::unpack_u16(r)?;
+            let (message_data_size, r) = TokenInstruction::unpack_u16(r)?;
+            let (&message_instruction_index, r) = r.split_first().ok_or(anyhow!("Unable to unpack u8"))?;
+            offsets.push(Secp256k1SignatureOffsets {
+                signature_offset,
+                signature_instruction_index,
+                eth_address_offset,
+                eth_address_instruction_index,
+                message_data_offset,
+                message_data_size,
+                message_instruction_index,
+            });
+            rest = r;
+        }
+        Ok(Self { offsets })
+    }
+}
+
+fn slice(data: &[u8], offset: u16, len: usize) -> Option<&[u8]> {
+    let start = offset as usize;
+    data.get(start..start.checked_add(len)?)
+}
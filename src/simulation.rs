@@ -0,0 +1,160 @@
+//! Adapters for `simulateTransaction` results, to decode simulated transactions like
+//! confirmed ones
+//!
+//! Simulations report the inner instructions of a transaction in the RPC's compiled form:
+//! base58 encoded data, `u8` program id indices and stack heights possibly left out. A
+//! [SimulationResult] holds them along with the logs and compute units of the simulation, and
+//! [SimulationResult::into_transaction] turns it, along with the simulated transaction, into a
+//! [ConfirmedTransaction] every extractor of the crate accepts, for pre-trade analysis tools
+//! sharing the decoders of the indexing path.
+//!
+//! Simulations report neither token balances nor the addresses loaded from address lookup
+//! tables: events resolving mints or owners from the balances leave them unset, and the
+//! addresses a versioned transaction loads must be provided by the caller.
+
+use anyhow::anyhow;
+use {
+    substreams::errors::Error,
+    substreams_solana_core::pb::sf::solana::r#type::v1::{
+        ConfirmedTransaction, InnerInstruction, InnerInstructions, Transaction, TransactionError,
+        TransactionStatusMeta,
+    },
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// An inner instruction, as reported by `simulateTransaction`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimulatedInstruction {
+    /// Index of the program id in the transaction's keys
+    pub program_id_index: u8,
+    /// Indices of the accounts in the transaction's keys
+    pub accounts: Vec<u8>,
+    /// Instruction data, base58 encoded
+    pub data: String,
+    /// Invocation stack height, `None` when not reported
+    #[cfg_attr(feature = "serde-traits", serde(default))]
+    pub stack_height: Option<u32>,
+}
+
+/// Inner instructions of a top-level instruction, as reported by `simulateTransaction`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimulatedInnerInstructions {
+    /// Index of the top-level instruction
+    pub index: u8,
+    /// The inner instructions, in execution order
+    pub instructions: Vec<SimulatedInstruction>,
+}
+
+impl SimulatedInstruction {
+    /// Converts the instruction to the firehose shape, decoding its data
+    pub fn to_inner_instruction(&self) -> Result<InnerInstruction, Error> {
+        let data = bs58::decode(&self.data)
+            .into_vec()
+            .map_err(|e| anyhow!("invalid simulated instruction data {}: {}", self.data, e))?;
+        Ok(InnerInstruction {
+            program_id_index: self.program_id_index as u32,
+            accounts: self.accounts.clone(),
+            data,
+            stack_height: self.stack_height,
+        })
+    }
+}
+
+impl SimulatedInnerInstructions {
+    /// Converts the inner instructions to the firehose shape, decoding their data
+    pub fn to_inner_instructions(&self) -> Result<InnerInstructions, Error> {
+        Ok(InnerInstructions {
+            index: self.index as u32,
+            instructions: self
+                .instructions
+                .iter()
+                .map(SimulatedInstruction::to_inner_instruction)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// Outcome of a `simulateTransaction` call
+///
+/// ```
+/// # use substreams_solana_program_instructions::{instructions::instructions, simulation::*, token_instruction_2022::TokenInstruction};
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::{CompiledInstruction, Message, Transaction};
+/// let transaction = Transaction {
+///     message: Some(Message {
+///         account_keys: vec![vec![1; 32], vec![2; 32], vec![3; 32]],
+///         instructions: vec![CompiledInstruction { program_id_index: 1, ..Default::default() }],
+///         ..Default::default()
+///     }),
+///     ..Default::default()
+/// };
+/// let result = SimulationResult {
+///     units_consumed: Some(4_321),
+///     inner_instructions: vec![SimulatedInnerInstructions {
+///         index: 0,
+///         instructions: vec![SimulatedInstruction {
+///             program_id_index: 2,
+///             accounts: vec![0],
+///             // CloseAccount
+///             data: "A".to_string(),
+///             stack_height: None,
+///         }],
+///     }],
+///     ..Default::default()
+/// };
+///
+/// let trx = result.into_transaction(transaction).unwrap();
+/// let inner: Vec<_> = instructions(&trx).filter(|instruction| !instruction.is_root()).collect();
+/// assert_eq!(inner[0].program_id, &[3; 32]);
+/// assert_eq!(TokenInstruction::unpack(inner[0].data).unwrap(), TokenInstruction::CloseAccount);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SimulationResult {
+    /// Whether the simulation failed, its `err` being set
+    pub failed: bool,
+    /// Log messages of the simulation
+    pub logs: Vec<String>,
+    /// Compute units consumed by the simulation
+    pub units_consumed: Option<u64>,
+    /// Inner instructions of the simulation, requested with `innerInstructions`
+    pub inner_instructions: Vec<SimulatedInnerInstructions>,
+    /// Writable addresses loaded from address lookup tables by the simulated transaction,
+    /// resolved by the caller
+    pub loaded_writable_addresses: Vec<Vec<u8>>,
+    /// Readonly addresses loaded from address lookup tables by the simulated transaction,
+    /// resolved by the caller
+    pub loaded_readonly_addresses: Vec<Vec<u8>>,
+}
+
+impl SimulationResult {
+    /// Builds the confirmed transaction equivalent to the simulation of `transaction`.
+    /// Failed simulations are given an empty error, their error not being carried over.
+    pub fn into_transaction(self, transaction: Transaction) -> Result<ConfirmedTransaction, Error> {
+        let inner_instructions = self
+            .inner_instructions
+            .iter()
+            .map(SimulatedInnerInstructions::to_inner_instructions)
+            .collect::<Result<_, _>>()?;
+        Ok(ConfirmedTransaction {
+            transaction: Some(transaction),
+            meta: Some(TransactionStatusMeta {
+                err: if self.failed {
+                    Some(TransactionError::default())
+                } else {
+                    None
+                },
+                inner_instructions,
+                log_messages: self.logs,
+                compute_units_consumed: self.units_consumed,
+                loaded_writable_addresses: self.loaded_writable_addresses,
+                loaded_readonly_addresses: self.loaded_readonly_addresses,
+                ..Default::default()
+            }),
+        })
+    }
+}
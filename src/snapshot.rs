@@ -0,0 +1,150 @@
+//! Bulk decoding of token program accounts, for bootstrapping stores from snapshots
+//!
+//! Snapshot exports list every account of the chain as `(address, owner, data)` records.
+//! [decode_accounts] lazily turns them into the mints and token accounts they hold, along with
+//! their Token 2022 extensions, skipping the accounts of other programs, multisigs and
+//! uninitialized accounts. With the `rayon` feature, [crate::parallel::par_decode_accounts]
+//! decodes them in parallel.
+
+use crate::{
+    account_data::{extension_types, is_mint_data, Mint, TokenAccount, ACCOUNT_TYPE_OFFSET},
+    account_state::AccountState,
+    program_ids::is_token_program,
+    pubkey::Pubkey,
+    rent::MULTISIG_LEN,
+    token_instruction_2022::ExtensionType,
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// Account type byte of Token 2022 token accounts with extensions
+const TOKEN_ACCOUNT_TYPE: u8 = 2;
+
+/// A mint or token account decoded from its account data
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodedAccount {
+    /// An initialized mint
+    Mint {
+        /// Address of the mint
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        address: Pubkey,
+        /// The mint
+        mint: Mint,
+        /// Types of the mint's extensions, in storage order
+        extensions: Vec<ExtensionType>,
+    },
+    /// An initialized token account
+    TokenAccount {
+        /// Address of the token account
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        address: Pubkey,
+        /// The token account
+        account: TokenAccount,
+        /// Types of the account's extensions, in storage order
+        extensions: Vec<ExtensionType>,
+    },
+}
+
+impl DecodedAccount {
+    /// Address of the account
+    pub fn address(&self) -> &Pubkey {
+        match self {
+            Self::Mint { address, .. } | Self::TokenAccount { address, .. } => address,
+        }
+    }
+
+    /// Types of the account's extensions, in storage order
+    pub fn extensions(&self) -> &[ExtensionType] {
+        match self {
+            Self::Mint { extensions, .. } | Self::TokenAccount { extensions, .. } => extensions,
+        }
+    }
+}
+
+/// Returns `true` if token program account `data` holds a token account: token accounts
+/// without extensions have their exact length, Token 2022 ones with extensions are tagged
+/// with their account type. Multisigs are told apart by their length, the byte at the
+/// account type offset being part of their signers.
+fn is_token_account_data(data: &[u8]) -> bool {
+    if data.len() == MULTISIG_LEN {
+        return false;
+    }
+    data.len() == TokenAccount::LEN
+        || (data.len() > ACCOUNT_TYPE_OFFSET && data[ACCOUNT_TYPE_OFFSET] == TOKEN_ACCOUNT_TYPE)
+}
+
+/// Decodes the account at `address`, owned by the program `owner`. Returns `None` for
+/// accounts of other programs, multisigs, uninitialized and malformed accounts.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{program_ids::TOKEN_PROGRAM_ID, pubkey::Pubkey, rent::MULTISIG_LEN, snapshot::decode_account};
+/// // An initialized multisig whose 6th signer has 2 at the account type offset
+/// let mut multisig = vec![1; MULTISIG_LEN];
+/// multisig[165] = 2;
+/// assert_eq!(decode_account(Pubkey::from([1; 32]), TOKEN_PROGRAM_ID.as_ref(), &multisig), None);
+/// ```
+pub fn decode_account(address: Pubkey, owner: &[u8], data: &[u8]) -> Option<DecodedAccount> {
+    if !is_token_program(owner) {
+        return None;
+    }
+    if is_mint_data(data) {
+        let mint = Mint::unpack(data).ok().filter(|mint| mint.is_initialized)?;
+        Some(DecodedAccount::Mint {
+            address,
+            mint,
+            extensions: extension_types(data),
+        })
+    } else if is_token_account_data(data) {
+        let account = TokenAccount::unpack(data)
+            .ok()
+            .filter(|account| account.state != AccountState::Uninitialized)?;
+        Some(DecodedAccount::TokenAccount {
+            address,
+            account,
+            extensions: extension_types(data),
+        })
+    } else {
+        None
+    }
+}
+
+/// Decodes the mints and token accounts of `records`, `(address, owner, data)` account
+/// records, lazily and in record order, see [decode_account]
+///
+/// ```
+/// # use substreams_solana_program_instructions::{program_ids::TOKEN_PROGRAM_ID, pubkey::Pubkey, snapshot::{decode_accounts, DecodedAccount}};
+/// let mut mint = vec![0u8; 82];
+/// mint[44] = 6;
+/// mint[45] = 1;
+/// let mut account = vec![0u8; 165];
+/// account[108] = 1;
+/// let records = vec![
+///     (Pubkey::from([1; 32]), TOKEN_PROGRAM_ID, mint),
+///     (Pubkey::from([2; 32]), TOKEN_PROGRAM_ID, account),
+///     // An account of another program
+///     (Pubkey::from([3; 32]), Pubkey::from([0; 32]), vec![0u8; 165]),
+/// ];
+/// let decoded: Vec<_> = decode_accounts(records).collect();
+/// assert_eq!(decoded.len(), 2);
+/// assert!(matches!(&decoded[0], DecodedAccount::Mint { mint, .. } if mint.decimals == 6));
+/// assert_eq!(decoded[1].address(), &Pubkey::from([2; 32]));
+/// ```
+pub fn decode_accounts<I, O, D>(records: I) -> impl Iterator<Item = DecodedAccount>
+where
+    I: IntoIterator<Item = (Pubkey, O, D)>,
+    O: AsRef<[u8]>,
+    D: AsRef<[u8]>,
+{
+    records
+        .into_iter()
+        .filter_map(|(address, owner, data)| decode_account(address, owner.as_ref(), data.as_ref()))
+}
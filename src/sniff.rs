@@ -0,0 +1,175 @@
+//! Guessing of the instruction encoding of unknown programs from the first bytes of their
+//! payloads, for exploratory decoding
+//!
+//! Solana programs mostly identify their instructions in one of three ways:
+//!
+//! * native programs (System, Stake, Vote, loaders) serialize an enum with bincode, a `u32`
+//!   little-endian tag followed by the fields;
+//! * SPL programs (Token, Associated Token Account, ...) use a single byte tag;
+//! * Anchor programs, and the SPL interfaces modeled after them, prefix the payload with an
+//!   8 bytes discriminator, the first bytes of the hash of the instruction's name.
+//!
+//! The guesses are heuristics: short payloads in particular fit several encodings.
+
+use crate::{
+    token_group_instruction::{
+        INITIALIZE_GROUP_DISCRIMINATOR, INITIALIZE_MEMBER_DISCRIMINATOR, UPDATE_GROUP_AUTHORITY_DISCRIMINATOR,
+        UPDATE_GROUP_MAX_SIZE_DISCRIMINATOR,
+    },
+    token_metadata_instruction::{
+        EMIT_DISCRIMINATOR, INITIALIZE_DISCRIMINATOR, REMOVE_KEY_DISCRIMINATOR, UPDATE_AUTHORITY_DISCRIMINATOR,
+        UPDATE_FIELD_DISCRIMINATOR,
+    },
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Discriminators known to this crate
+const KNOWN_DISCRIMINATORS: [[u8; 8]; 9] = [
+    INITIALIZE_GROUP_DISCRIMINATOR,
+    UPDATE_GROUP_MAX_SIZE_DISCRIMINATOR,
+    UPDATE_GROUP_AUTHORITY_DISCRIMINATOR,
+    INITIALIZE_MEMBER_DISCRIMINATOR,
+    INITIALIZE_DISCRIMINATOR,
+    UPDATE_FIELD_DISCRIMINATOR,
+    REMOVE_KEY_DISCRIMINATOR,
+    UPDATE_AUTHORITY_DISCRIMINATOR,
+    EMIT_DISCRIMINATOR,
+];
+
+/// Highest tag expected from a bincode or single byte tagged instruction enum
+const MAX_LIKELY_TAG: u32 = 64;
+
+/// Confidence in a guess
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Confidence {
+    /// The payload fits the encoding, but fits others as well
+    Low,
+    /// The payload fits the encoding better than the others
+    Medium,
+    /// The payload starts with an identifier known to use the encoding
+    High,
+}
+
+/// Guessed encoding of an instruction payload
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InstructionKindGuess {
+    /// Bincode serialized enum of a native program
+    Bincode {
+        /// The `u32` tag
+        tag: u32,
+        /// Confidence in the guess
+        confidence: Confidence,
+    },
+    /// Single byte tagged instruction of an SPL program
+    Tag {
+        /// The tag
+        tag: u8,
+        /// Confidence in the guess
+        confidence: Confidence,
+    },
+    /// 8 bytes discriminator of an Anchor program or SPL interface
+    Discriminator {
+        /// The discriminator
+        discriminator: [u8; 8],
+        /// Confidence in the guess
+        confidence: Confidence,
+    },
+    /// Empty payload, as sent to programs with a single instruction
+    Empty,
+    /// No encoding fits
+    Unknown,
+}
+
+impl InstructionKindGuess {
+    /// Confidence in the guess, `None` for [InstructionKindGuess::Empty] and
+    /// [InstructionKindGuess::Unknown]
+    pub fn confidence(&self) -> Option<Confidence> {
+        match *self {
+            Self::Bincode { confidence, .. } | Self::Tag { confidence, .. } | Self::Discriminator { confidence, .. } => {
+                Some(confidence)
+            }
+            Self::Empty | Self::Unknown => None,
+        }
+    }
+}
+
+/// Guesses the encoding of the instruction payload `data` from its first bytes
+///
+/// ```
+/// # use substreams_solana_program_instructions::sniff::{sniff, Confidence, InstructionKindGuess};
+/// // System program transfer of 1 SOL
+/// let mut transfer = vec![2, 0, 0, 0];
+/// transfer.extend_from_slice(&1_000_000_000u64.to_le_bytes());
+/// assert_eq!(sniff(&transfer), InstructionKindGuess::Bincode { tag: 2, confidence: Confidence::Medium });
+///
+/// // Anchor discriminator followed by an argument
+/// let data = [248, 198, 158, 145, 225, 117, 135, 200, 1, 0, 0, 0];
+/// assert!(matches!(sniff(&data), InstructionKindGuess::Discriminator { confidence: Confidence::Medium, .. }));
+///
+/// // Token program SyncNative
+/// assert_eq!(sniff(&[17]), InstructionKindGuess::Tag { tag: 17, confidence: Confidence::Medium });
+/// ```
+pub fn sniff(data: &[u8]) -> InstructionKindGuess {
+    if data.is_empty() {
+        return InstructionKindGuess::Empty;
+    }
+
+    if let Some(discriminator) = data.get(..8).and_then(|prefix| <[u8; 8]>::try_from(prefix).ok()) {
+        if KNOWN_DISCRIMINATORS.contains(&discriminator) {
+            return InstructionKindGuess::Discriminator {
+                discriminator,
+                confidence: Confidence::High,
+            };
+        }
+        if looks_hashed(&discriminator) {
+            return InstructionKindGuess::Discriminator {
+                discriminator,
+                confidence: Confidence::Medium,
+            };
+        }
+    }
+
+    if let Some(prefix) = data.get(..4) {
+        let tag = u32::from_le_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]);
+        // Also fits a single byte tag followed by zeroed bytes, hence at most medium confidence
+        if tag < MAX_LIKELY_TAG {
+            let confidence = if data.len() == 4 || (data.len() - 4) % 8 == 0 {
+                Confidence::Medium
+            } else {
+                Confidence::Low
+            };
+            return InstructionKindGuess::Bincode { tag, confidence };
+        }
+    }
+
+    if (data[0] as u32) < MAX_LIKELY_TAG {
+        // Payloads too short for the other encodings, or a tag followed by `u64` arguments
+        let confidence = if data.len() < 4 || (data.len() - 1) % 8 == 0 {
+            Confidence::Medium
+        } else {
+            Confidence::Low
+        };
+        return InstructionKindGuess::Tag { tag: data[0], confidence };
+    }
+
+    InstructionKindGuess::Unknown
+}
+
+/// Returns `true` if `bytes` look like the output of a hash rather than small integers: few
+/// zero bytes and mostly distinct values
+fn looks_hashed(bytes: &[u8; 8]) -> bool {
+    let zeros = bytes.iter().filter(|byte| **byte == 0).count();
+    let mut distinct = bytes.to_vec();
+    distinct.sort_unstable();
+    distinct.dedup();
+    zeros <= 1 && distinct.len() >= 6
+}
@@ -0,0 +1,420 @@
+//! Stake program instruction types
+//!
+//! Stake accounts are delegated to a vote account, activate and deactivate over epoch
+//! boundaries, and are split, merged and withdrawn from through the stake program. See
+//! [crate::stake_lifecycle] for the events they go through.
+
+use anyhow::anyhow;
+use {
+    crate::{pubkey::Pubkey, token_instruction_2022::TokenInstruction},
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// Authority of a stake account
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StakeAuthorize {
+    /// Authority delegating, deactivating, splitting and merging the stake
+    Staker,
+    /// Authority withdrawing the account's lamports
+    Withdrawer,
+}
+
+impl StakeAuthorize {
+    fn unpack(input: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (tag, rest) = TokenInstruction::unpack_u32(input)?;
+        let authorize = match tag {
+            0 => Self::Staker,
+            1 => Self::Withdrawer,
+            _ => return Err(anyhow!("Invalid Stake Authorize: {}", tag)),
+        };
+        Ok((authorize, rest))
+    }
+}
+
+/// Lockup of a stake account, preventing withdrawals before a date and an epoch unless the
+/// custodian signs
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Lockup {
+    /// Unix timestamp at which the lockup ends, 0 for none
+    pub unix_timestamp: i64,
+    /// Epoch at which the lockup ends, 0 for none
+    pub epoch: u64,
+    /// The custodian, who can withdraw or change the lockup while in force
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub custodian: Pubkey,
+}
+
+/// Instructions of the stake program, bincode encoded with a `u32` tag.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StakeInstruction<'a> {
+    /// Initializes a stake account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Uninitialized stake account
+    ///   1. `[]` Rent sysvar
+    Initialize {
+        /// The stake authority
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        staker: Pubkey,
+        /// The withdraw authority
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        withdrawer: Pubkey,
+        /// The lockup
+        lockup: Lockup,
+    },
+    /// Authorizes a key to manage the stake or withdraw
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Stake account
+    ///   1. `[]` Clock sysvar
+    ///   2. `[signer]` Stake or withdraw authority
+    ///   3. Optional: `[signer]` Lockup custodian, when changing the withdraw authority
+    ///      during the lockup
+    Authorize {
+        /// The new authority
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        authority: Pubkey,
+        /// The authority changed
+        authorize: StakeAuthorize,
+    },
+    /// Delegates the stake to a vote account, activating it from the next epoch
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Initialized stake account
+    ///   1. `[]` Vote account
+    ///   2. `[]` Clock sysvar
+    ///   3. `[]` Stake history sysvar
+    ///   4. `[]` Unused, formerly the stake config account
+    ///   5. `[signer]` Stake authority
+    DelegateStake,
+    /// Splits lamports off a stake account into another, uninitialized, stake account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Stake account to split
+    ///   1. `[writable]` Uninitialized stake account receiving the lamports
+    ///   2. `[signer]` Stake authority
+    Split {
+        /// Number of lamports to split off
+        lamports: u64,
+    },
+    /// Withdraws lamports from an undelegated or fully deactivated stake account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Stake account
+    ///   1. `[writable]` Recipient account
+    ///   2. `[]` Clock sysvar
+    ///   3. `[]` Stake history sysvar
+    ///   4. `[signer]` Withdraw authority
+    ///   5. Optional: `[signer]` Lockup custodian, when withdrawing during the lockup
+    Withdraw {
+        /// Number of lamports to withdraw
+        lamports: u64,
+    },
+    /// Deactivates the stake, from the next epoch
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Delegated stake account
+    ///   1. `[]` Clock sysvar
+    ///   2. `[signer]` Stake authority
+    Deactivate,
+    /// Sets the fields of the lockup that are set, the others left unchanged
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Initialized stake account
+    ///   1. `[signer]` Lockup custodian during the lockup, withdraw authority after it
+    SetLockup {
+        /// New lockup end timestamp
+        unix_timestamp: Option<i64>,
+        /// New lockup end epoch
+        epoch: Option<u64>,
+        /// New custodian
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+        custodian: Option<Pubkey>,
+    },
+    /// Merges a stake account into another, closing it
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Destination stake account
+    ///   1. `[writable]` Source stake account, closed
+    ///   2. `[]` Clock sysvar
+    ///   3. `[]` Stake history sysvar
+    ///   4. `[signer]` Stake authority
+    Merge,
+    /// Authorizes a key to manage the stake or withdraw, the current authority being derived
+    /// from a base key and a seed
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Stake account
+    ///   1. `[signer]` Base key of the current stake or withdraw authority
+    ///   2. `[]` Clock sysvar
+    ///   3. Optional: `[signer]` Lockup custodian, when changing the withdraw authority
+    ///      during the lockup
+    AuthorizeWithSeed {
+        /// The new authority
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        authority: Pubkey,
+        /// The authority changed
+        authorize: StakeAuthorize,
+        /// Seed deriving the current authority from the base key
+        authority_seed: &'a str,
+        /// Owner deriving the current authority from the base key
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        authority_owner: Pubkey,
+    },
+    /// Initializes a stake account without lockup, the withdraw authority signing
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Uninitialized stake account
+    ///   1. `[]` Rent sysvar
+    ///   2. `[]` Stake authority
+    ///   3. `[signer]` Withdraw authority
+    InitializeChecked,
+    /// Authorizes a key to manage the stake or withdraw, the new authority signing as well
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Stake account
+    ///   1. `[]` Clock sysvar
+    ///   2. `[signer]` Stake or withdraw authority
+    ///   3. `[signer]` New stake or withdraw authority
+    ///   4. Optional: `[signer]` Lockup custodian, when changing the withdraw authority
+    ///      during the lockup
+    AuthorizeChecked {
+        /// The authority changed
+        authorize: StakeAuthorize,
+    },
+    /// Authorizes a key to manage the stake or withdraw, the current authority being derived
+    /// from a base key and a seed and the new authority signing as well
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Stake account
+    ///   1. `[signer]` Base key of the current stake or withdraw authority
+    ///   2. `[]` Clock sysvar
+    ///   3. `[signer]` New stake or withdraw authority
+    ///   4. Optional: `[signer]` Lockup custodian, when changing the withdraw authority
+    ///      during the lockup
+    AuthorizeCheckedWithSeed {
+        /// The authority changed
+        authorize: StakeAuthorize,
+        /// Seed deriving the current authority from the base key
+        authority_seed: &'a str,
+        /// Owner deriving the current authority from the base key
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        authority_owner: Pubkey,
+    },
+    /// Sets the fields of the lockup that are set, the new custodian signing
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Initialized stake account
+    ///   1. `[signer]` Lockup custodian during the lockup, withdraw authority after it
+    ///   2. Optional: `[signer]` New lockup custodian
+    SetLockupChecked {
+        /// New lockup end timestamp
+        unix_timestamp: Option<i64>,
+        /// New lockup end epoch
+        epoch: Option<u64>,
+    },
+    /// Returns the minimum delegation through the return data
+    GetMinimumDelegation,
+    /// Deactivates the stake delegated to a vote account that has not voted for the last
+    /// epochs
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Delegated stake account
+    ///   1. `[]` Delinquent vote account
+    ///   2. `[]` Reference vote account, which voted in each of the last epochs
+    DeactivateDelinquent,
+    /// Redelegates the stake to another vote account, deprecated
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Delegated stake account
+    ///   1. `[writable]` Uninitialized stake account receiving the redelegated stake
+    ///   2. `[]` Vote account
+    ///   3. `[]` Unused, formerly the stake config account
+    ///   4. `[signer]` Stake authority
+    Redelegate,
+    /// Moves active stake between two stake accounts delegated to the same vote account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Source stake account
+    ///   1. `[writable]` Destination stake account
+    ///   2. `[signer]` Stake authority
+    MoveStake {
+        /// Number of lamports to move
+        lamports: u64,
+    },
+    /// Moves lamports that are not part of the stake between two stake accounts
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Source stake account
+    ///   1. `[writable]` Destination stake account
+    ///   2. `[signer]` Stake authority
+    MoveLamports {
+        /// Number of lamports to move
+        lamports: u64,
+    },
+}
+
+impl<'a> StakeInstruction<'a> {
+    /// Unpacks a byte buffer into a [StakeInstruction]
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::stake_instruction::StakeInstruction;
+    /// assert_eq!(StakeInstruction::unpack(&[2, 0, 0, 0]).unwrap(), StakeInstruction::DelegateStake);
+    /// let set_lockup = [&[6, 0, 0, 0, 0, 1][..], &10u64.to_le_bytes(), &[0]].concat();
+    /// assert_eq!(
+    ///     StakeInstruction::unpack(&set_lockup).unwrap(),
+    ///     StakeInstruction::SetLockup { unix_timestamp: None, epoch: Some(10), custodian: None },
+    /// );
+    /// let seed = b"stake";
+    /// let authorize_with_seed =
+    ///     [&[11, 0, 0, 0, 1, 0, 0, 0][..], &(seed.len() as u64).to_le_bytes(), seed, &[7; 32]].concat();
+    /// assert!(matches!(
+    ///     StakeInstruction::unpack(&authorize_with_seed).unwrap(),
+    ///     StakeInstruction::AuthorizeCheckedWithSeed { authority_seed: "stake", .. },
+    /// ));
+    /// ```
+    pub fn unpack(input: &'a [u8]) -> Result<Self, Error> {
+        let (tag, rest) = TokenInstruction::unpack_u32(input).map_err(|_| anyhow!("Invalid Stake Instruction"))?;
+        Ok(match tag {
+            0 => {
+                let (staker, rest) = TokenInstruction::unpack_pubkey(rest)?;
+                let (withdrawer, rest) = TokenInstruction::unpack_pubkey(rest)?;
+                let (unix_timestamp, rest) = TokenInstruction::unpack_i64(rest)?;
+                let (epoch, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (custodian, _rest) = TokenInstruction::unpack_pubkey(rest)?;
+                Self::Initialize {
+                    staker,
+                    withdrawer,
+                    lockup: Lockup {
+                        unix_timestamp,
+                        epoch,
+                        custodian,
+                    },
+                }
+            }
+            1 => {
+                let (authority, rest) = TokenInstruction::unpack_pubkey(rest)?;
+                let (authorize, _rest) = StakeAuthorize::unpack(rest)?;
+                Self::Authorize { authority, authorize }
+            }
+            2 => Self::DelegateStake,
+            3 => {
+                let (lamports, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::Split { lamports }
+            }
+            4 => {
+                let (lamports, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::Withdraw { lamports }
+            }
+            5 => Self::Deactivate,
+            6 => {
+                let (unix_timestamp, rest) = unpack_option(rest, TokenInstruction::unpack_i64)?;
+                let (epoch, rest) = unpack_option(rest, TokenInstruction::unpack_u64)?;
+                let (custodian, _rest) = unpack_option(rest, TokenInstruction::unpack_pubkey)?;
+                Self::SetLockup {
+                    unix_timestamp,
+                    epoch,
+                    custodian,
+                }
+            }
+            7 => Self::Merge,
+            8 => {
+                let (authority, rest) = TokenInstruction::unpack_pubkey(rest)?;
+                let (authorize, rest) = StakeAuthorize::unpack(rest)?;
+                let (authority_seed, rest) = unpack_seed(rest)?;
+                let (authority_owner, _rest) = TokenInstruction::unpack_pubkey(rest)?;
+                Self::AuthorizeWithSeed {
+                    authority,
+                    authorize,
+                    authority_seed,
+                    authority_owner,
+                }
+            }
+            9 => Self::InitializeChecked,
+            10 => {
+                let (authorize, _rest) = StakeAuthorize::unpack(rest)?;
+                Self::AuthorizeChecked { authorize }
+            }
+            11 => {
+                let (authorize, rest) = StakeAuthorize::unpack(rest)?;
+                let (authority_seed, rest) = unpack_seed(rest)?;
+                let (authority_owner, _rest) = TokenInstruction::unpack_pubkey(rest)?;
+                Self::AuthorizeCheckedWithSeed {
+                    authorize,
+                    authority_seed,
+                    authority_owner,
+                }
+            }
+            12 => {
+                let (unix_timestamp, rest) = unpack_option(rest, TokenInstruction::unpack_i64)?;
+                let (epoch, _rest) = unpack_option(rest, TokenInstruction::unpack_u64)?;
+                Self::SetLockupChecked { unix_timestamp, epoch }
+            }
+            13 => Self::GetMinimumDelegation,
+            14 => Self::DeactivateDelinquent,
+            15 => Self::Redelegate,
+            16 => {
+                let (lamports, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::MoveStake { lamports }
+            }
+            17 => {
+                let (lamports, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::MoveLamports { lamports }
+            }
+            _ => return Err(anyhow!("Invalid Stake Instruction - unpack didn't match any tag value: {}", tag)),
+        })
+    }
+}
+
+/// Unpacks a bincode string, a `u64` length followed by UTF-8 bytes
+fn unpack_seed(input: &[u8]) -> Result<(&str, &[u8]), Error> {
+    let (len, rest) = TokenInstruction::unpack_u64(input)?;
+    let len = usize::try_from(len).map_err(|_| anyhow!("Unable to unpack seed of length {}", len))?;
+    let (bytes, rest) = TokenInstruction::unpack_bytes(rest, len)?;
+    let seed = std::str::from_utf8(bytes).map_err(|_| anyhow!("Unable to unpack seed, invalid UTF-8"))?;
+    Ok((seed, rest))
+}
+
+/// Unpacks a bincode option, a `u8` tag followed by the value when set
+fn unpack_option<T, F>(input: &[u8], unpack: F) -> Result<(Option<T>, &[u8]), Error>
+where
+    F: Fn(&[u8]) -> Result<(T, &[u8]), Error>,
+{
+    match input.split_first() {
+        Some((0, rest)) => Ok((None, rest)),
+        Some((1, rest)) => unpack(rest).map(|(value, rest)| (Some(value), rest)),
+        _ => Err(anyhow!("Unable to unpack option")),
+    }
+}
@@ -0,0 +1,454 @@
+//! Lifecycle of stake accounts: delegation, deactivation, rewards, withdrawals and merges
+//!
+//! Stake program instructions and the staking rewards of a block are combined into typed
+//! [StakeLifecycleEvent]s, each concerning a single stake account, see
+//! [StakeLifecycleEvent::stake_account]. Store modules fold them per account into a
+//! [StakeLifecycle].
+//!
+//! Rewards are credited at the start of each epoch, in its first blocks, for the epoch
+//! before: [StakeRewarded::epoch] is the epoch rewarded, not the epoch of the block.
+
+use {
+    crate::{
+        epoch::{EpochContext, EpochSchedule},
+        events::EventContext,
+        instructions::{instructions, is_successful, InstructionRef},
+        metrics,
+        program_ids::STAKE_PROGRAM_ID,
+        pubkey::Pubkey,
+        stake_instruction::StakeInstruction,
+    },
+    std::str::FromStr,
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction, RewardType},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// A stake account was delegated to a vote account through `DelegateStake`, the stake
+/// activating from the next epoch
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StakeActivated {
+    /// Position of the `DelegateStake` instruction
+    pub context: EventContext,
+    /// Epoch of the block of the instruction, the activation epoch of the stake
+    pub epoch: EpochContext,
+    /// The stake account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub stake_account: Pubkey,
+    /// The vote account the stake is delegated to
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub vote_account: Pubkey,
+    /// The stake authority
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub authority: Pubkey,
+}
+
+impl StakeActivated {
+    /// Kind of the event, part of its [StakeActivated::event_id]
+    pub const KIND: &'static str = "stake_activated";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+}
+
+/// A stake account was deactivated through `Deactivate` or `DeactivateDelinquent`, the
+/// stake cooling down from the next epoch
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StakeDeactivated {
+    /// Position of the deactivating instruction
+    pub context: EventContext,
+    /// Epoch of the block of the instruction, the deactivation epoch of the stake
+    pub epoch: EpochContext,
+    /// The stake account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub stake_account: Pubkey,
+    /// The stake authority, `None` for `DeactivateDelinquent`, which anyone can invoke
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub authority: Option<Pubkey>,
+}
+
+impl StakeDeactivated {
+    /// Kind of the event, part of its [StakeDeactivated::event_id]
+    pub const KIND: &'static str = "stake_deactivated";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+
+    /// Returns `true` if the stake was deactivated for the delinquency of its validator
+    pub fn is_delinquent(&self) -> bool {
+        self.authority.is_none()
+    }
+}
+
+/// Lamports were withdrawn from a stake account through `Withdraw`
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StakeWithdrawn {
+    /// Position of the `Withdraw` instruction
+    pub context: EventContext,
+    /// Epoch of the block of the instruction
+    pub epoch: EpochContext,
+    /// The stake account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub stake_account: Pubkey,
+    /// The recipient account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub recipient: Pubkey,
+    /// Lamports withdrawn
+    pub lamports: u64,
+    /// The withdraw authority
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub authority: Pubkey,
+}
+
+impl StakeWithdrawn {
+    /// Kind of the event, part of its [StakeWithdrawn::event_id]
+    pub const KIND: &'static str = "stake_withdrawn";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+}
+
+/// A stake account was merged into another through `Merge`, closing it
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StakeMerged {
+    /// Position of the `Merge` instruction
+    pub context: EventContext,
+    /// Epoch of the block of the instruction
+    pub epoch: EpochContext,
+    /// The stake account merged and closed
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub source: Pubkey,
+    /// The stake account receiving the merged stake
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub destination: Pubkey,
+    /// The stake authority
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub authority: Pubkey,
+}
+
+impl StakeMerged {
+    /// Kind of the event, part of its [StakeMerged::event_id]
+    pub const KIND: &'static str = "stake_merged";
+
+    /// Deterministic identifier of the event, see [EventContext::event_id]
+    pub fn event_id(&self) -> String {
+        self.context.event_id(Self::KIND)
+    }
+}
+
+/// A stake account was credited its staking rewards for an epoch, as recorded in the rewards
+/// of a block
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StakeRewarded {
+    /// Slot of the block crediting the rewards
+    pub slot: u64,
+    /// The epoch rewarded, the one before the epoch of the block
+    pub epoch: u64,
+    /// The stake account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub stake_account: Pubkey,
+    /// Lamports credited
+    pub lamports: i64,
+    /// Balance of the stake account after the rewards
+    pub post_balance: u64,
+    /// Commission of the validator, in percent, `None` if not recorded
+    pub commission: Option<u8>,
+}
+
+impl StakeRewarded {
+    /// Kind of the event
+    pub const KIND: &'static str = "stake_rewarded";
+}
+
+/// An event of the lifecycle of a stake account
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum StakeLifecycleEvent {
+    /// The stake was delegated
+    Activated(StakeActivated),
+    /// The stake was deactivated
+    Deactivated(StakeDeactivated),
+    /// The stake account was credited rewards
+    Rewarded(StakeRewarded),
+    /// Lamports were withdrawn
+    Withdrawn(StakeWithdrawn),
+    /// The stake account was merged into another
+    Merged(StakeMerged),
+}
+
+impl StakeLifecycleEvent {
+    /// Kind of the event, the `KIND` of its variant's event
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Activated(_) => StakeActivated::KIND,
+            Self::Deactivated(_) => StakeDeactivated::KIND,
+            Self::Rewarded(_) => StakeRewarded::KIND,
+            Self::Withdrawn(_) => StakeWithdrawn::KIND,
+            Self::Merged(_) => StakeMerged::KIND,
+        }
+    }
+
+    /// The stake account whose lifecycle the event is part of, the source account for merges
+    pub fn stake_account(&self) -> &Pubkey {
+        match self {
+            Self::Activated(event) => &event.stake_account,
+            Self::Deactivated(event) => &event.stake_account,
+            Self::Rewarded(event) => &event.stake_account,
+            Self::Withdrawn(event) => &event.stake_account,
+            Self::Merged(event) => &event.source,
+        }
+    }
+}
+
+/// Extracts the stake lifecycle events of `block`: the staking rewards it credits first,
+/// see [stake_reward_events], then the events of its successful transactions in execution
+/// order, see [stake_lifecycle_events]. The epoch of the block is derived from its slot with
+/// `schedule`.
+pub fn block_stake_lifecycle_events(block: &Block, schedule: &EpochSchedule) -> Vec<StakeLifecycleEvent> {
+    let epoch = schedule.context(block.slot);
+    let mut events: Vec<StakeLifecycleEvent> = stake_reward_events(block, schedule)
+        .into_iter()
+        .map(StakeLifecycleEvent::Rewarded)
+        .collect();
+    events.extend(
+        block
+            .transactions
+            .iter()
+            .enumerate()
+            .flat_map(|(index, trx)| stake_lifecycle_events(trx, index as u32, epoch)),
+    );
+    events
+}
+
+/// Extracts the staking rewards credited by `block`, in the order of its rewards
+pub fn stake_reward_events(block: &Block, schedule: &EpochSchedule) -> Vec<StakeRewarded> {
+    let epoch = schedule.epoch(block.slot).saturating_sub(1);
+    let events: Vec<StakeRewarded> = block
+        .rewards
+        .iter()
+        .filter(|reward| reward.reward_type == RewardType::Staking as i32)
+        .filter_map(|reward| {
+            Some(StakeRewarded {
+                slot: block.slot,
+                epoch,
+                stake_account: Pubkey::from_str(&reward.pubkey).ok()?,
+                lamports: reward.lamports,
+                post_balance: reward.post_balance,
+                commission: reward.commission.parse().ok(),
+            })
+        })
+        .collect();
+    metrics::events_emitted(StakeRewarded::KIND, events.len());
+    events
+}
+
+/// Extracts the stake lifecycle events of a successful transaction, the
+/// `transaction_index`-th of its block, in execution order. `epoch` is the epoch of the
+/// block.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{epoch::EpochSchedule, program_ids::STAKE_PROGRAM_ID, pubkey::Pubkey, stake_lifecycle::*};
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::{CompiledInstruction, ConfirmedTransaction, Message, Transaction, TransactionStatusMeta};
+/// let key = |byte: u8| Pubkey::from([byte; 32]).as_ref().to_vec();
+/// let trx = ConfirmedTransaction {
+///     transaction: Some(Transaction {
+///         message: Some(Message {
+///             // Stake account, vote account, sysvars, stake authority, stake program
+///             account_keys: vec![key(1), key(2), key(3), key(4), key(5), STAKE_PROGRAM_ID.as_ref().to_vec()],
+///             instructions: vec![
+///                 CompiledInstruction { program_id_index: 5, accounts: vec![0, 1, 3, 3, 3, 4], data: vec![2, 0, 0, 0] },
+///                 CompiledInstruction { program_id_index: 5, accounts: vec![0, 3, 4], data: vec![5, 0, 0, 0] },
+///             ],
+///             ..Default::default()
+///         }),
+///         ..Default::default()
+///     }),
+///     meta: Some(TransactionStatusMeta::default()),
+/// };
+///
+/// let mut lifecycle = StakeLifecycle::new(Pubkey::from([1; 32]));
+/// for event in stake_lifecycle_events(&trx, 0, EpochSchedule::default().context(300_000_000)) {
+///     lifecycle.apply(&event);
+/// }
+/// assert_eq!(lifecycle.vote_account, Some(Pubkey::from([2; 32])));
+/// assert_eq!((lifecycle.activation_epoch, lifecycle.deactivation_epoch), (Some(694), Some(694)));
+/// assert_eq!(lifecycle.status(), StakeStatus::Deactivated);
+/// ```
+pub fn stake_lifecycle_events(
+    trx: &ConfirmedTransaction,
+    transaction_index: u32,
+    epoch: EpochContext,
+) -> Vec<StakeLifecycleEvent> {
+    if !is_successful(trx) {
+        return vec![];
+    }
+
+    let events: Vec<StakeLifecycleEvent> = instructions(trx)
+        .filter(|instruction| instruction.program_id == STAKE_PROGRAM_ID.as_ref())
+        .filter_map(|instruction| lifecycle_event(trx, transaction_index, epoch, &instruction))
+        .collect();
+
+    for kind in [
+        StakeActivated::KIND,
+        StakeDeactivated::KIND,
+        StakeWithdrawn::KIND,
+        StakeMerged::KIND,
+    ] {
+        metrics::events_emitted(kind, events.iter().filter(|event| event.kind() == kind).count());
+    }
+    events
+}
+
+/// Lifecycle event of the stake program `instruction`, `None` for other instructions
+fn lifecycle_event(
+    trx: &ConfirmedTransaction,
+    transaction_index: u32,
+    epoch: EpochContext,
+    instruction: &InstructionRef,
+) -> Option<StakeLifecycleEvent> {
+    let accounts = &instruction.accounts;
+    let context = EventContext::new(trx, transaction_index, instruction);
+    Some(match StakeInstruction::unpack(instruction.data).ok()? {
+        StakeInstruction::DelegateStake => StakeLifecycleEvent::Activated(StakeActivated {
+            context,
+            epoch,
+            stake_account: accounts.get_pubkey(0)?,
+            vote_account: accounts.get_pubkey(1)?,
+            authority: accounts.get_pubkey(5)?,
+        }),
+        StakeInstruction::Deactivate => StakeLifecycleEvent::Deactivated(StakeDeactivated {
+            context,
+            epoch,
+            stake_account: accounts.get_pubkey(0)?,
+            authority: Some(accounts.get_pubkey(2)?),
+        }),
+        StakeInstruction::DeactivateDelinquent => StakeLifecycleEvent::Deactivated(StakeDeactivated {
+            context,
+            epoch,
+            stake_account: accounts.get_pubkey(0)?,
+            authority: None,
+        }),
+        StakeInstruction::Withdraw { lamports } => StakeLifecycleEvent::Withdrawn(StakeWithdrawn {
+            context,
+            epoch,
+            stake_account: accounts.get_pubkey(0)?,
+            recipient: accounts.get_pubkey(1)?,
+            lamports,
+            authority: accounts.get_pubkey(4)?,
+        }),
+        StakeInstruction::Merge => StakeLifecycleEvent::Merged(StakeMerged {
+            context,
+            epoch,
+            source: accounts.get_pubkey(1)?,
+            destination: accounts.get_pubkey(0)?,
+            authority: accounts.get_pubkey(4)?,
+        }),
+        _ => return None,
+    })
+}
+
+/// Stage of the lifecycle of a stake account
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StakeStatus {
+    /// Never seen delegated
+    Undelegated,
+    /// Delegated, activating or active
+    Delegated,
+    /// Deactivated, cooling down or inactive
+    Deactivated,
+    /// Merged into another stake account, closed
+    Merged,
+}
+
+/// Lifecycle of a stake account, folded from its [StakeLifecycleEvent]s
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct StakeLifecycle {
+    /// The stake account
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub stake_account: Pubkey,
+    /// Vote account of the last delegation
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub vote_account: Option<Pubkey>,
+    /// Epoch of the last delegation
+    pub activation_epoch: Option<u64>,
+    /// Epoch of the deactivation following the last delegation
+    pub deactivation_epoch: Option<u64>,
+    /// Staking rewards credited, in lamports
+    pub rewards: i64,
+    /// Lamports withdrawn
+    pub withdrawn: u64,
+    /// Stake account the account was merged into
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+    pub merged_into: Option<Pubkey>,
+}
+
+impl StakeLifecycle {
+    /// Lifecycle of `stake_account`, before any event
+    pub fn new(stake_account: Pubkey) -> Self {
+        Self {
+            stake_account,
+            vote_account: None,
+            activation_epoch: None,
+            deactivation_epoch: None,
+            rewards: 0,
+            withdrawn: 0,
+            merged_into: None,
+        }
+    }
+
+    /// Applies `event`, ignored if it concerns another stake account
+    pub fn apply(&mut self, event: &StakeLifecycleEvent) {
+        if *event.stake_account() != self.stake_account {
+            return;
+        }
+        match event {
+            StakeLifecycleEvent::Activated(event) => {
+                self.vote_account = Some(event.vote_account);
+                self.activation_epoch = Some(event.epoch.epoch);
+                self.deactivation_epoch = None;
+            }
+            StakeLifecycleEvent::Deactivated(event) => self.deactivation_epoch = Some(event.epoch.epoch),
+            StakeLifecycleEvent::Rewarded(event) => self.rewards += event.lamports,
+            StakeLifecycleEvent::Withdrawn(event) => self.withdrawn += event.lamports,
+            StakeLifecycleEvent::Merged(event) => self.merged_into = Some(event.destination),
+        }
+    }
+
+    /// Current stage of the lifecycle
+    pub fn status(&self) -> StakeStatus {
+        if self.merged_into.is_some() {
+            StakeStatus::Merged
+        } else if self.deactivation_epoch.is_some() {
+            StakeStatus::Deactivated
+        } else if self.activation_epoch.is_some() {
+            StakeStatus::Delegated
+        } else {
+            StakeStatus::Undelegated
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! SPL stake pool instruction types
+//!
+//! Most liquid staking tokens besides mSOL, JitoSOL first, are minted by pools of the SPL
+//! stake pool program: every pool is an account of the same program, identified by the first
+//! account of its instructions, see [stake_pool] and [is_jito_stake_pool].
+
+use anyhow::anyhow;
+use {
+    crate::{accounts::Accounts, token_instruction_2022::TokenInstruction},
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+use crate::pubkey::Pubkey;
+
+/// Jito stake pool, `Jito4APyf642JPZPx3hGc6WWJ8zPKtRbRs4P815Awbb`
+pub const JITO_STAKE_POOL: Pubkey = Pubkey([
+    4, 138, 62, 8, 195, 180, 149, 190, 23, 244, 84, 39, 216, 155, 236, 91,
+    128, 199, 226, 105, 92, 24, 100, 215, 103, 67, 219, 57, 190, 211, 70, 214,
+]);
+
+/// JitoSOL mint, the pool mint of the Jito stake pool, `J1toso1uCk3RLmjorhTtrVwY9HJ7X8V9yYac6Y7kGCPn`
+pub const JITOSOL_MINT: Pubkey = Pubkey([
+    252, 209, 65, 233, 131, 44, 175, 16, 173, 145, 116, 149, 202, 15, 39, 27,
+    91, 41, 60, 212, 112, 39, 234, 115, 112, 7, 237, 64, 235, 57, 160, 189,
+]);
+
+/// Instructions of the SPL stake pool program.
+///
+/// Only the instructions of users depositing in or withdrawing from a pool, and the pool
+/// balance update, are decoded. Pool management instructions are rejected as unknown tags.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StakePoolInstruction {
+    /// Updates the total pool balance from the validator list and the reserve, and mints the
+    /// epoch fees to the manager
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Stake pool
+    ///   1. `[]` Stake pool withdraw authority
+    ///   2. `[writable]` Validator list
+    ///   3. `[]` Reserve stake account
+    ///   4. `[writable]` Manager fee account
+    ///   5. `[writable]` Pool mint
+    ///   6. `[]` Token program
+    UpdateStakePoolBalance,
+    /// Deposits a stake account in the pool, minting pool tokens
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Stake pool
+    ///   1. `[writable]` Validator list
+    ///   2. `[s]/[]` Stake pool deposit authority
+    ///   3. `[]` Stake pool withdraw authority
+    ///   4. `[writable]` Stake account to deposit
+    ///   5. `[writable]` Validator stake account receiving the stake
+    ///   6. `[writable]` Reserve stake account, to withdraw rent exempt reserve
+    ///   7. `[writable]` Pool token account receiving the pool tokens
+    ///   8. `[writable]` Manager fee account
+    ///   9. `[writable]` Referrer pool token account
+    ///   10. `[writable]` Pool mint
+    ///   11. `[]` Clock sysvar
+    ///   12. `[]` Stake history sysvar
+    ///   13. `[]` Token program
+    ///   14. `[]` Stake program
+    DepositStake {
+        /// Minimum pool tokens to receive, `None` for the variant without slippage check
+        minimum_pool_tokens_out: Option<u64>,
+    },
+    /// Burns pool tokens, withdrawing a stake account split from a validator stake account or
+    /// the reserve
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Stake pool
+    ///   1. `[writable]` Validator list
+    ///   2. `[]` Stake pool withdraw authority
+    ///   3. `[writable]` Validator or reserve stake account to split
+    ///   4. `[writable]` Uninitialized stake account receiving the withdrawal
+    ///   5. `[]` User account to set as the new withdraw authority
+    ///   6. `[s]` User transfer authority, for the pool token account
+    ///   7. `[writable]` Pool token account burning the pool tokens
+    ///   8. `[writable]` Manager fee account
+    ///   9. `[writable]` Pool mint
+    ///   10. `[]` Clock sysvar
+    ///   11. `[]` Token program
+    ///   12. `[]` Stake program
+    WithdrawStake {
+        /// Pool tokens burnt
+        pool_tokens_in: u64,
+        /// Minimum lamports to receive, `None` for the variant without slippage check
+        minimum_lamports_out: Option<u64>,
+    },
+    /// Deposits SOL in the pool reserve, minting pool tokens
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Stake pool
+    ///   1. `[]` Stake pool withdraw authority
+    ///   2. `[writable]` Reserve stake account
+    ///   3. `[writable, signer]` Account sending the SOL
+    ///   4. `[writable]` Pool token account receiving the pool tokens
+    ///   5. `[writable]` Manager fee account
+    ///   6. `[writable]` Referrer pool token account
+    ///   7. `[writable]` Pool mint
+    ///   8. `[]` System program
+    ///   9. `[]` Token program
+    ///   10. `[signer]` (Optional) Stake pool SOL deposit authority
+    DepositSol {
+        /// Lamports deposited
+        lamports_in: u64,
+        /// Minimum pool tokens to receive, `None` for the variant without slippage check
+        minimum_pool_tokens_out: Option<u64>,
+    },
+    /// Burns pool tokens, withdrawing SOL from the pool reserve
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Stake pool
+    ///   1. `[]` Stake pool withdraw authority
+    ///   2. `[signer]` User transfer authority, for the pool token account
+    ///   3. `[writable]` Pool token account burning the pool tokens
+    ///   4. `[writable]` Reserve stake account
+    ///   5. `[writable]` Account receiving the SOL
+    ///   6. `[writable]` Manager fee account
+    ///   7. `[writable]` Pool mint
+    ///   8. `[]` Clock sysvar
+    ///   9. `[]` Stake history sysvar
+    ///   10. `[]` Stake program
+    ///   11. `[]` Token program
+    ///   12. `[signer]` (Optional) Stake pool SOL withdraw authority
+    WithdrawSol {
+        /// Pool tokens burnt
+        pool_tokens_in: u64,
+        /// Minimum lamports to receive, `None` for the variant without slippage check
+        minimum_lamports_out: Option<u64>,
+    },
+}
+impl StakePoolInstruction {
+    /// Unpacks a byte buffer into a [StakePoolInstruction](enum.StakePoolInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, Error> {
+        let (&tag, rest) = input.split_first().ok_or(anyhow!("Invalid Stake Pool Instruction"))?;
+        Ok(match tag {
+            7 => Self::UpdateStakePoolBalance,
+            9 => Self::DepositStake {
+                minimum_pool_tokens_out: None,
+            },
+            10 => {
+                let (pool_tokens_in, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::WithdrawStake {
+                    pool_tokens_in,
+                    minimum_lamports_out: None,
+                }
+            }
+            14 => {
+                let (lamports_in, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::DepositSol {
+                    lamports_in,
+                    minimum_pool_tokens_out: None,
+                }
+            }
+            16 => {
+                let (pool_tokens_in, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::WithdrawSol {
+                    pool_tokens_in,
+                    minimum_lamports_out: None,
+                }
+            }
+            23 => {
+                let (minimum_pool_tokens_out, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::DepositStake {
+                    minimum_pool_tokens_out: Some(minimum_pool_tokens_out),
+                }
+            }
+            24..=26 => {
+                let (amount_in, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (minimum_out, _rest) = TokenInstruction::unpack_u64(rest)?;
+                match tag {
+                    24 => Self::WithdrawStake {
+                        pool_tokens_in: amount_in,
+                        minimum_lamports_out: Some(minimum_out),
+                    },
+                    25 => Self::DepositSol {
+                        lamports_in: amount_in,
+                        minimum_pool_tokens_out: Some(minimum_out),
+                    },
+                    26 => Self::WithdrawSol {
+                        pool_tokens_in: amount_in,
+                        minimum_lamports_out: Some(minimum_out),
+                    },
+                    _ => unreachable!(),
+                }
+            }
+            _ => return Err(anyhow!("Invalid Stake Pool Instruction - unpack didn't match any tag value: {}", tag)),
+        })
+    }
+}
+
+/// Stake pool of a stake pool instruction, its first account
+pub fn stake_pool(accounts: &Accounts) -> Option<Pubkey> {
+    accounts.get_pubkey(0)
+}
+
+/// Returns `true` if the stake pool instruction with `accounts` targets the Jito stake pool
+pub fn is_jito_stake_pool(accounts: &Accounts) -> bool {
+    stake_pool(accounts) == Some(JITO_STAKE_POOL)
+}
@@ -0,0 +1,166 @@
+//! Token instruction decoding statistics, for decoder health dashboards
+//!
+//! Only available with the `stats` feature. [StatsCollector] counts the token program
+//! instructions of a block per program and tag, along with their data lengths and how many
+//! failed to decode. The result is the [InstructionStats] protobuf message, defined in
+//! `proto/stats.proto` for the `protobuf` section of substreams manifests, so an
+//! observability module can output it as is:
+//!
+//! ```no_run
+//! # use substreams_solana_program_instructions::stats::{block_stats, InstructionStats};
+//! # use substreams_solana_core::pb::sf::solana::r#type::v1::Block;
+//! fn map_decoder_stats(block: Block) -> Result<InstructionStats, substreams::errors::Error> {
+//!     Ok(block_stats(&block))
+//! }
+//! ```
+
+use {
+    crate::{
+        instructions::{instructions, InstructionRef},
+        program_ids::is_token_program,
+        pubkey::Pubkey,
+        token_instruction_2022::TokenInstruction,
+    },
+    std::collections::BTreeMap,
+    substreams_solana_core::pb::sf::solana::r#type::v1::Block,
+};
+
+/// Token instruction decoding statistics of a block
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct InstructionStats {
+    /// Slot of the block
+    #[prost(uint64, tag = "1")]
+    pub slot: u64,
+    /// One entry per program and tag seen in the block, ordered by program then tag
+    #[prost(message, repeated, tag = "2")]
+    pub tags: Vec<TagStats>,
+}
+
+/// Decoding statistics of the instructions of a token program sharing a tag
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct TagStats {
+    /// The token program, base58 encoded
+    #[prost(string, tag = "1")]
+    pub program_id: String,
+    /// First byte of the instruction data
+    #[prost(uint32, tag = "2")]
+    pub tag: u32,
+    /// Number of instructions
+    #[prost(uint64, tag = "3")]
+    pub count: u64,
+    /// Number of instructions that failed to decode
+    #[prost(uint64, tag = "4")]
+    pub decode_failures: u64,
+    /// Sum of the data lengths, tag included
+    #[prost(uint64, tag = "5")]
+    pub total_bytes: u64,
+    /// Shortest data length
+    #[prost(uint64, tag = "6")]
+    pub min_bytes: u64,
+    /// Longest data length
+    #[prost(uint64, tag = "7")]
+    pub max_bytes: u64,
+}
+
+impl TagStats {
+    /// Share of the instructions that failed to decode, between 0 and 1
+    pub fn failure_rate(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        self.decode_failures as f64 / self.count as f64
+    }
+
+    /// Average data length
+    pub fn average_bytes(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        self.total_bytes as f64 / self.count as f64
+    }
+}
+
+/// Accumulates the decoding statistics of token instructions
+///
+/// ```
+/// # use substreams_solana_program_instructions::{accounts::Accounts, instructions::InstructionRef, program_ids::TOKEN_PROGRAM_ID, stats::StatsCollector};
+/// let mut collector = StatsCollector::new(300_000_000);
+/// for data in [&[9][..], &[3, 1, 2]] {
+///     collector.record(&InstructionRef {
+///         program_id: TOKEN_PROGRAM_ID.as_ref(),
+///         accounts: Accounts::Pubkeys(&[]),
+///         data,
+///         instruction_index: 0,
+///         inner_instruction_index: None,
+///         stack_height: None,
+///         revision: Default::default(),
+///     });
+/// }
+/// let stats = collector.finish();
+/// assert_eq!(stats.tags.len(), 2);
+/// // CloseAccount
+/// assert_eq!(stats.tags[1].tag, 9);
+/// assert_eq!(stats.tags[1].decode_failures, 0);
+/// // Transfer, too short
+/// assert_eq!(stats.tags[0].failure_rate(), 1.0);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct StatsCollector {
+    slot: u64,
+    tags: BTreeMap<(Pubkey, u8), TagStats>,
+}
+
+impl StatsCollector {
+    /// Collector of the statistics of the block at `slot`
+    pub fn new(slot: u64) -> Self {
+        Self {
+            slot,
+            tags: BTreeMap::new(),
+        }
+    }
+
+    /// Records `instruction`, ignored if it is not a token program instruction or has no data
+    pub fn record(&mut self, instruction: &InstructionRef) {
+        if !is_token_program(instruction.program_id) {
+            return;
+        }
+        let (program_id, &tag) = match (Pubkey::try_from(instruction.program_id), instruction.data.first()) {
+            (Ok(program_id), Some(tag)) => (program_id, tag),
+            _ => return,
+        };
+        let len = instruction.data.len() as u64;
+        let stats = self.tags.entry((program_id, tag)).or_insert_with(|| TagStats {
+            program_id: program_id.to_string(),
+            tag: tag as u32,
+            min_bytes: len,
+            ..Default::default()
+        });
+        stats.count += 1;
+        stats.total_bytes += len;
+        stats.min_bytes = stats.min_bytes.min(len);
+        stats.max_bytes = stats.max_bytes.max(len);
+        if TokenInstruction::unpack(instruction.data).is_err() {
+            stats.decode_failures += 1;
+        }
+    }
+
+    /// The statistics recorded so far
+    pub fn finish(self) -> InstructionStats {
+        InstructionStats {
+            slot: self.slot,
+            tags: self.tags.into_values().collect(),
+        }
+    }
+}
+
+/// Decoding statistics of the token instructions of `block`. Failed transactions are
+/// included, their instructions being decoded all the same.
+pub fn block_stats(block: &Block) -> InstructionStats {
+    let mut collector = StatsCollector::new(block.slot);
+    for trx in &block.transactions {
+        for instruction in instructions(trx) {
+            collector.record(&instruction);
+        }
+    }
+    collector.finish()
+}
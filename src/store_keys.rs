@@ -0,0 +1,59 @@
+//! Canonical keys for substreams stores
+//!
+//! Modules composed together read each other's stores, keys built here are meant to be
+//! shared across them: addresses and signatures are base58 encoded, segments are joined
+//! with [DELIMITER] and start with a fixed prefix, for example `balance:<owner>:<mint>`.
+
+/// Delimiter between the segments of a key
+pub const DELIMITER: char = ':';
+/// Prefix of the keys built by [balance_key]
+pub const BALANCE_PREFIX: &str = "balance";
+/// Prefix of the keys built by [mint_key]
+pub const MINT_PREFIX: &str = "mint";
+/// Prefix of the keys built by [account_key]
+pub const ACCOUNT_PREFIX: &str = "account";
+/// Prefix of the keys built by [transfer_key]
+pub const TRANSFER_PREFIX: &str = "transfer";
+
+/// Key of the balance of `owner` for `mint`, `balance:<owner>:<mint>`
+pub fn balance_key<O: AsRef<[u8]>, M: AsRef<[u8]>>(owner: O, mint: M) -> String {
+    join(BALANCE_PREFIX, &[&encode(owner), &encode(mint)])
+}
+
+/// Key of a mint, `mint:<mint>`
+pub fn mint_key<M: AsRef<[u8]>>(mint: M) -> String {
+    join(MINT_PREFIX, &[&encode(mint)])
+}
+
+/// Key of a token account, `account:<account>`
+pub fn account_key<A: AsRef<[u8]>>(account: A) -> String {
+    join(ACCOUNT_PREFIX, &[&encode(account)])
+}
+
+/// Key of the transfer at `index` within the transaction with signature `signature`,
+/// `transfer:<signature>:<index>`
+pub fn transfer_key<S: AsRef<[u8]>>(signature: S, index: u32) -> String {
+    join(TRANSFER_PREFIX, &[&encode(signature), &index.to_string()])
+}
+
+/// Returns the prefix of all keys of `kind` for the given first segment, `<kind>:<first>:`,
+/// usable with prefix-based store deletions, e.g. all balances of an owner
+pub fn key_prefix<F: AsRef<[u8]>>(kind: &str, first: F) -> String {
+    let mut prefix = join(kind, &[&encode(first)]);
+    prefix.push(DELIMITER);
+    prefix
+}
+
+fn encode<T: AsRef<[u8]>>(value: T) -> String {
+    bs58::encode(value.as_ref()).into_string()
+}
+
+fn join(prefix: &str, segments: &[&str]) -> String {
+    let mut key = String::with_capacity(prefix.len() + segments.iter().map(|s| s.len() + 1).sum::<usize>());
+    key.push_str(prefix);
+    for segment in segments {
+        key.push(DELIMITER);
+        key.push_str(segment);
+    }
+    key
+}
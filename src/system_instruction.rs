@@ -0,0 +1,292 @@
+//! System program instruction types
+
+use anyhow::anyhow;
+use {
+    crate::{pubkey::Pubkey, token_instruction_2022::TokenInstruction},
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// Instructions supported by the System program, bincode encoded with a `u32` tag.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum SystemInstruction<'a> {
+    /// Create a new account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Funding account
+    ///   1. `[writable, signer]` New account
+    CreateAccount {
+        /// Number of lamports to transfer to the new account
+        lamports: u64,
+        /// Number of bytes of memory to allocate
+        space: u64,
+        /// Address of program that will own the new account
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        owner: Pubkey,
+    },
+    /// Assign account to a program
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Assigned account public key
+    Assign {
+        /// Owner program account
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        owner: Pubkey,
+    },
+    /// Transfer lamports
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Funding account
+    ///   1. `[writable]` Recipient account
+    Transfer {
+        /// Number of lamports to transfer
+        lamports: u64,
+    },
+    /// Create a new account at an address derived from a base pubkey and a seed
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Funding account
+    ///   1. `[writable]` Created account
+    ///   2. `[signer]` (optional) Base account; the account matching the base Pubkey below must be
+    ///      provided as a signer, but may be the same as the funding account
+    CreateAccountWithSeed {
+        /// Base public key
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        base: Pubkey,
+        /// String of ASCII chars, no longer than `Pubkey::MAX_SEED_LEN`
+        seed: &'a str,
+        /// Number of lamports to transfer to the new account
+        lamports: u64,
+        /// Number of bytes of memory to allocate
+        space: u64,
+        /// Owner program account address
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        owner: Pubkey,
+    },
+    /// Consumes a stored nonce, replacing it with a successor
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Nonce account
+    ///   1. `[]` RecentBlockhashes sysvar
+    ///   2. `[signer]` Nonce authority
+    AdvanceNonceAccount,
+    /// Withdraw funds from a nonce account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Nonce account
+    ///   1. `[writable]` Recipient account
+    ///   2. `[]` RecentBlockhashes sysvar
+    ///   3. `[]` Rent sysvar
+    ///   4. `[signer]` Nonce authority
+    WithdrawNonceAccount {
+        /// Number of lamports to withdraw
+        lamports: u64,
+    },
+    /// Drive state of Uninitialized nonce account to Initialized, setting the nonce value
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Nonce account
+    ///   1. `[]` RecentBlockhashes sysvar
+    ///   2. `[]` Rent sysvar
+    InitializeNonceAccount {
+        /// Entity authorized to execute nonce instruction on the account
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        authority: Pubkey,
+    },
+    /// Change the entity authorized to execute nonce instructions on the account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Nonce account
+    ///   1. `[signer]` Nonce authority
+    AuthorizeNonceAccount {
+        /// The new nonce authority
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        authority: Pubkey,
+    },
+    /// Allocate space in a (possibly new) account without funding
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` New account
+    Allocate {
+        /// Number of bytes of memory to allocate
+        space: u64,
+    },
+    /// Allocate space for and assign an account at an address derived from a base public key
+    /// and a seed
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Allocated account
+    ///   1. `[signer]` Base account
+    AllocateWithSeed {
+        /// Base public key
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        base: Pubkey,
+        /// String of ASCII chars, no longer than `Pubkey::MAX_SEED_LEN`
+        seed: &'a str,
+        /// Number of bytes of memory to allocate
+        space: u64,
+        /// Owner program account
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        owner: Pubkey,
+    },
+    /// Assign account to a program based on a seed
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Assigned account
+    ///   1. `[signer]` Base account
+    AssignWithSeed {
+        /// Base public key
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        base: Pubkey,
+        /// String of ASCII chars, no longer than `Pubkey::MAX_SEED_LEN`
+        seed: &'a str,
+        /// Owner program account
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        owner: Pubkey,
+    },
+    /// Transfer lamports from a derived address
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Funding account
+    ///   1. `[signer]` Base for funding account
+    ///   2. `[writable]` Recipient account
+    TransferWithSeed {
+        /// Amount to transfer
+        lamports: u64,
+        /// Seed to use to derive the funding account address
+        from_seed: &'a str,
+        /// Owner to use to derive the funding account address
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        from_owner: Pubkey,
+    },
+    /// One-time idempotent upgrade of legacy nonce versions in order to bump them out of chain
+    /// blockhash domain.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Nonce account
+    UpgradeNonceAccount,
+}
+
+impl<'a> SystemInstruction<'a> {
+    /// Unpacks a byte buffer into a [SystemInstruction]
+    pub fn unpack(input: &'a [u8]) -> Result<Self, Error> {
+        let (tag, rest) = TokenInstruction::unpack_u32(input).map_err(|_| anyhow!("Invalid System Instruction"))?;
+        Ok(match tag {
+            0 => {
+                let (lamports, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (space, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (owner, _rest) = TokenInstruction::unpack_pubkey(rest)?;
+                Self::CreateAccount { lamports, space, owner }
+            }
+            1 => {
+                let (owner, _rest) = TokenInstruction::unpack_pubkey(rest)?;
+                Self::Assign { owner }
+            }
+            2 => {
+                let (lamports, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::Transfer { lamports }
+            }
+            3 => {
+                let (base, rest) = TokenInstruction::unpack_pubkey(rest)?;
+                let (seed, rest) = unpack_seed(rest)?;
+                let (lamports, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (space, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (owner, _rest) = TokenInstruction::unpack_pubkey(rest)?;
+                Self::CreateAccountWithSeed {
+                    base,
+                    seed,
+                    lamports,
+                    space,
+                    owner,
+                }
+            }
+            4 => Self::AdvanceNonceAccount,
+            5 => {
+                let (lamports, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::WithdrawNonceAccount { lamports }
+            }
+            6 => {
+                let (authority, _rest) = TokenInstruction::unpack_pubkey(rest)?;
+                Self::InitializeNonceAccount { authority }
+            }
+            7 => {
+                let (authority, _rest) = TokenInstruction::unpack_pubkey(rest)?;
+                Self::AuthorizeNonceAccount { authority }
+            }
+            8 => {
+                let (space, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::Allocate { space }
+            }
+            9 => {
+                let (base, rest) = TokenInstruction::unpack_pubkey(rest)?;
+                let (seed, rest) = unpack_seed(rest)?;
+                let (space, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (owner, _rest) = TokenInstruction::unpack_pubkey(rest)?;
+                Self::AllocateWithSeed { base, seed, space, owner }
+            }
+            10 => {
+                let (base, rest) = TokenInstruction::unpack_pubkey(rest)?;
+                let (seed, rest) = unpack_seed(rest)?;
+                let (owner, _rest) = TokenInstruction::unpack_pubkey(rest)?;
+                Self::AssignWithSeed { base, seed, owner }
+            }
+            11 => {
+                let (lamports, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (from_seed, rest) = unpack_seed(rest)?;
+                let (from_owner, _rest) = TokenInstruction::unpack_pubkey(rest)?;
+                Self::TransferWithSeed {
+                    lamports,
+                    from_seed,
+                    from_owner,
+                }
+            }
+            12 => Self::UpgradeNonceAccount,
+            _ => return Err(anyhow!("Invalid System Instruction - unpack didn't match any tag value: {}", tag)),
+        })
+    }
+
+    /// Lamports moved by the instruction along with the positions of the funding and
+    /// recipient accounts, `None` for instructions moving no lamport
+    pub fn lamport_transfer(&self) -> Option<(u64, usize, usize)> {
+        match *self {
+            Self::CreateAccount { lamports, .. }
+            | Self::CreateAccountWithSeed { lamports, .. }
+            | Self::Transfer { lamports }
+            | Self::WithdrawNonceAccount { lamports } => Some((lamports, 0, 1)),
+            Self::TransferWithSeed { lamports, .. } => Some((lamports, 0, 2)),
+            _ => None,
+        }
+    }
+}
+
+/// Unpacks a bincode string, a `u64` length followed by UTF-8 bytes
+fn unpack_seed(input: &[u8]) -> Result<(&str, &[u8]), Error> {
+    let (len, rest) = TokenInstruction::unpack_u64(input)?;
+    let len = usize::try_from(len).map_err(|_| anyhow!("Unable to unpack seed of length {}", len))?;
+    let (bytes, rest) = TokenInstruction::unpack_bytes(rest, len)?;
+    let seed = std::str::from_utf8(bytes).map_err(|_| anyhow!("Unable to unpack seed, invalid UTF-8"))?;
+    Ok((seed, rest))
+}
@@ -0,0 +1,119 @@
+//! Token group interface instruction types
+//!
+//! The token group interface is implemented by the token 2022 program, which keeps the group
+//! and member configurations in the group and member mints themselves. Its instructions are
+//! identified by an 8 bytes discriminator instead of the token program's 1 byte tag.
+
+use anyhow::anyhow;
+use {
+    substreams::errors::Error,
+    crate::{token_instruction_2022::TokenInstruction},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    crate::serialization::coption_fromstr,
+    serde::{Deserialize, Serialize},
+};
+use crate::option::COption;
+use crate::pubkey::Pubkey;
+
+/// Discriminator length of token group instructions
+pub const DISCRIMINATOR_BYTES: usize = 8;
+/// `InitializeGroup` discriminator, hash of `spl_token_group_interface:initialize_token_group`
+pub const INITIALIZE_GROUP_DISCRIMINATOR: [u8; 8] = [121, 113, 108, 39, 54, 51, 0, 4];
+/// `UpdateGroupMaxSize` discriminator, hash of `spl_token_group_interface:update_group_max_size`
+pub const UPDATE_GROUP_MAX_SIZE_DISCRIMINATOR: [u8; 8] = [108, 37, 171, 143, 248, 30, 18, 110];
+/// `UpdateGroupAuthority` discriminator, hash of `spl_token_group_interface:update_authority`
+pub const UPDATE_GROUP_AUTHORITY_DISCRIMINATOR: [u8; 8] = [161, 105, 88, 1, 237, 221, 216, 203];
+/// `InitializeMember` discriminator, hash of `spl_token_group_interface:initialize_member`
+pub const INITIALIZE_MEMBER_DISCRIMINATOR: [u8; 8] = [152, 32, 222, 176, 223, 237, 116, 134];
+
+/// Instructions supported by the token group interface.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TokenGroupInstruction {
+    /// Initialize a new `Group`
+    ///
+    /// Assumes one has already initialized a mint for the group.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]`  Group
+    ///   1. `[]`   Mint
+    ///   2. `[s]`  Mint authority
+    InitializeGroup {
+        /// Update authority for the group
+        #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+        update_authority: COption<Pubkey>,
+        /// The maximum number of group members
+        max_size: u64,
+    },
+    /// Update the max size of a `Group`
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]`  Group
+    ///   1. `[s]`  Update authority
+    UpdateGroupMaxSize {
+        /// New max size for the group
+        max_size: u64,
+    },
+    /// Update the authority of a `Group`
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]`  Group
+    ///   1. `[s]`  Current update authority
+    UpdateGroupAuthority {
+        /// New authority for the group, or unset if `None`
+        #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+        new_authority: COption<Pubkey>,
+    },
+    /// Initialize a new `Member` of a `Group`
+    ///
+    /// Assumes the `Group` has already been initialized,
+    /// as well as the mint for the member.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]`  Member
+    ///   1. `[]`   Member mint
+    ///   2. `[s]`  Member mint authority
+    ///   3. `[w]`  Group
+    ///   4. `[s]`  Group update authority
+    InitializeMember,
+}
+impl TokenGroupInstruction {
+    /// Unpacks a byte buffer into a [TokenGroupInstruction](enum.TokenGroupInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, Error> {
+        if input.len() < DISCRIMINATOR_BYTES {
+            return Err(anyhow!("Invalid Token Group Instruction"));
+        }
+        let (discriminator, rest) = input.split_at(DISCRIMINATOR_BYTES);
+        Ok(match discriminator {
+            d if d == INITIALIZE_GROUP_DISCRIMINATOR => {
+                let (update_authority, rest) = TokenInstruction::unpack_optional_nonzero_pubkey(rest)?;
+                let (max_size, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::InitializeGroup {
+                    update_authority,
+                    max_size,
+                }
+            }
+            d if d == UPDATE_GROUP_MAX_SIZE_DISCRIMINATOR => {
+                let (max_size, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::UpdateGroupMaxSize { max_size }
+            }
+            d if d == UPDATE_GROUP_AUTHORITY_DISCRIMINATOR => {
+                let (new_authority, _rest) = TokenInstruction::unpack_optional_nonzero_pubkey(rest)?;
+                Self::UpdateGroupAuthority { new_authority }
+            }
+            d if d == INITIALIZE_MEMBER_DISCRIMINATOR => Self::InitializeMember,
+            _ => return Err(anyhow!("Invalid Token Group Instruction - unpack didn't match any discriminator: {:?}", discriminator)),
+        })
+    }
+}
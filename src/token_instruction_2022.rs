@@ -6,11 +6,23 @@ use anyhow::anyhow;
 use {
     substreams::{errors::Error},
     num_enum::{IntoPrimitive, TryFromPrimitive},
-    crate::{transfer_fee_instruction::TransferFeeInstruction},
+    crate::{
+        accounts::TransactionKeys,
+        confidential_mint_burn_instruction::ConfidentialMintBurnInstruction,
+        confidential_transfer_instruction::ConfidentialTransferInstruction,
+        default_account_state_instruction::DefaultAccountStateInstruction,
+        group_member_pointer_instruction::GroupMemberPointerInstruction,
+        interest_bearing_mint::InterestBearingMintInstruction,
+        pausable_instruction::PausableInstruction,
+        program_ids::is_token_program,
+        scaled_ui_amount_instruction::ScaledUiAmountInstruction,
+        transfer_fee_instruction::TransferFeeInstruction,
+    },
     std::{
         convert::{TryFrom, TryInto},
         mem::size_of,
     },
+    substreams_solana_core::pb::sf::solana::r#type::v1::{CompiledInstruction, InnerInstruction},
 };
 
 #[cfg(feature = "serde-traits")]
@@ -19,6 +31,7 @@ use {
     serde::{Deserialize, Serialize},
     serde_with::{As, DisplayFromStr},
 };
+use crate::amount::{amount, decimals, Amount, AmountDecimals};
 use crate::option::COption;
 use crate::pubkey::{Pubkey, PUBKEY_BYTES};
 
@@ -28,8 +41,169 @@ pub const MIN_SIGNERS: usize = 1;
 pub const MAX_SIGNERS: usize = 11;
 /// Serialized length of a u16, for unpacking
 const U16_BYTES: usize = 2;
+/// Serialized length of a u32, for unpacking
+const U32_BYTES: usize = 4;
 /// Serialized length of a u64, for unpacking
 const U64_BYTES: usize = 8;
+/// Serialized length of a u128, for unpacking
+const U128_BYTES: usize = 16;
+
+/// Decoding metadata of a token instruction tag, see [TAG_REGISTRY]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TagInfo {
+    /// The tag, first byte of the instruction data
+    pub tag: u8,
+    /// Name of the [TokenInstruction] variant decoded from the tag
+    pub name: &'static str,
+    /// Minimum length of the payload following the tag byte. Extension prefixes whose
+    /// sub-instructions are not decoded only require the tag.
+    pub min_payload_len: usize,
+    /// Minimum number of accounts of the instruction, with a single authority
+    pub min_accounts: usize,
+    /// Section of the CHANGELOG in which the crate started decoding the tag
+    pub added_in: &'static str,
+}
+
+/// Declares the tags decoded by [TokenInstruction::unpack], generating [TAG_REGISTRY] and
+/// [TokenInstruction::tag]. Tags must be listed contiguously from 0.
+macro_rules! tag_registry {
+    ($($tag:literal => $variant:ident { payload: $len:literal, accounts: $accounts:literal, since: $since:literal },)*) => {
+        /// Decoding metadata of every tag decoded by [TokenInstruction::unpack], indexed by tag.
+        /// Everything describing a tag, from [MIN_PAYLOAD_LENS] to [TokenInstruction::name],
+        /// is derived from this table.
+        ///
+        /// ```
+        /// # use substreams_solana_program_instructions::token_instruction_2022::TAG_REGISTRY;
+        /// let transfer_checked = &TAG_REGISTRY[12];
+        /// assert_eq!(transfer_checked.name, "TransferChecked");
+        /// assert_eq!(transfer_checked.min_payload_len, 9);
+        /// assert_eq!(transfer_checked.min_accounts, 4);
+        /// ```
+        pub const TAG_REGISTRY: [TagInfo; [$(stringify!($tag)),*].len()] = [
+            $(TagInfo {
+                tag: $tag,
+                name: stringify!($variant),
+                min_payload_len: $len,
+                min_accounts: $accounts,
+                added_in: $since,
+            },)*
+        ];
+
+        impl TokenInstruction<'_> {
+            /// Tag of the instruction, first byte of its data
+            pub fn tag(&self) -> u8 {
+                match self {
+                    $(Self::$variant { .. } => $tag,)*
+                }
+            }
+        }
+    };
+}
+
+tag_registry! {
+    0 => InitializeMint { payload: 34, accounts: 2, since: "v0.1.0" },
+    1 => InitializeAccount { payload: 0, accounts: 4, since: "v0.1.0" },
+    2 => InitializeMultisig { payload: 1, accounts: 3, since: "v0.1.0" },
+    3 => Transfer { payload: 8, accounts: 3, since: "v0.1.0" },
+    4 => Approve { payload: 8, accounts: 3, since: "v0.1.0" },
+    5 => Revoke { payload: 0, accounts: 2, since: "v0.1.0" },
+    6 => SetAuthority { payload: 2, accounts: 2, since: "v0.1.0" },
+    7 => MintTo { payload: 8, accounts: 3, since: "v0.1.0" },
+    8 => Burn { payload: 8, accounts: 3, since: "v0.1.0" },
+    9 => CloseAccount { payload: 0, accounts: 3, since: "v0.1.0" },
+    10 => FreezeAccount { payload: 0, accounts: 3, since: "v0.1.0" },
+    11 => ThawAccount { payload: 0, accounts: 3, since: "v0.1.0" },
+    12 => TransferChecked { payload: 9, accounts: 4, since: "v0.1.0" },
+    13 => ApproveChecked { payload: 9, accounts: 4, since: "v0.1.0" },
+    14 => MintToChecked { payload: 9, accounts: 3, since: "v0.1.0" },
+    15 => BurnChecked { payload: 9, accounts: 3, since: "v0.1.0" },
+    16 => InitializeAccount2 { payload: 32, accounts: 3, since: "v0.1.0" },
+    17 => SyncNative { payload: 0, accounts: 1, since: "v0.1.0" },
+    18 => InitializeAccount3 { payload: 32, accounts: 2, since: "v0.1.0" },
+    19 => InitializeMultisig2 { payload: 1, accounts: 2, since: "v0.1.0" },
+    20 => InitializeMint2 { payload: 34, accounts: 1, since: "v0.1.0" },
+    21 => GetAccountDataSize { payload: 0, accounts: 1, since: "v0.1.0" },
+    22 => InitializeImmutableOwner { payload: 0, accounts: 1, since: "v0.1.0" },
+    23 => AmountToUiAmount { payload: 8, accounts: 1, since: "v0.1.0" },
+    24 => UiAmountToAmount { payload: 0, accounts: 1, since: "v0.1.0" },
+    25 => InitializeMintCloseAuthority { payload: 1, accounts: 1, since: "v0.1.0" },
+    26 => TransferFeeExtension { payload: 1, accounts: 1, since: "v0.1.0" },
+    27 => ConfidentialTransferExtension { payload: 1, accounts: 1, since: "v0.1.0" },
+    28 => DefaultAccountStateExtension { payload: 1, accounts: 1, since: "v0.1.0" },
+    29 => Reallocate { payload: 0, accounts: 4, since: "v0.1.0" },
+    30 => MemoTransferExtension { payload: 0, accounts: 2, since: "v0.1.0" },
+    31 => CreateNativeMint { payload: 0, accounts: 3, since: "v0.1.0" },
+    32 => InitializeNonTransferableMint { payload: 0, accounts: 1, since: "v0.1.0" },
+    33 => InterestBearingMintExtension { payload: 1, accounts: 1, since: "v0.1.0" },
+    34 => CpiGuardExtension { payload: 0, accounts: 2, since: "v0.1.0" },
+    35 => InitializePermanentDelegate { payload: 32, accounts: 1, since: "v0.1.0" },
+    36 => TransferHookExtension { payload: 0, accounts: 1, since: "v0.1.0" },
+    37 => ConfidentialTransferFeeExtension { payload: 0, accounts: 1, since: "v0.1.0" },
+    38 => WithdrawExcessLamports { payload: 0, accounts: 3, since: "v0.1.0" },
+    39 => MetadataPointerExtension { payload: 0, accounts: 1, since: "v0.1.0" },
+    40 => GroupPointerExtension { payload: 0, accounts: 1, since: "Unreleased" },
+    41 => GroupMemberPointerExtension { payload: 1, accounts: 1, since: "Unreleased" },
+    42 => ConfidentialMintBurnExtension { payload: 1, accounts: 1, since: "Unreleased" },
+    43 => ScaledUiAmountExtension { payload: 1, accounts: 1, since: "Unreleased" },
+    44 => PausableExtension { payload: 1, accounts: 1, since: "Unreleased" },
+}
+
+/// Number of tags decoded by [TokenInstruction::unpack]
+const TAG_COUNT: usize = TAG_REGISTRY.len();
+
+// Tags are listed contiguously, so that the registry is indexed by tag
+const _: () = {
+    let mut i = 0;
+    while i < TAG_COUNT {
+        assert!(TAG_REGISTRY[i].tag as usize == i);
+        i += 1;
+    }
+};
+
+/// Minimum length of the payload following the tag byte, indexed by instruction tag, see
+/// [TagInfo::min_payload_len]
+pub const MIN_PAYLOAD_LENS: [usize; TAG_COUNT] = {
+    let mut lens = [0; TAG_COUNT];
+    let mut i = 0;
+    while i < TAG_COUNT {
+        lens[i] = TAG_REGISTRY[i].min_payload_len;
+        i += 1;
+    }
+    lens
+};
+
+/// Tags decoded by [TokenInstruction::unpack], in increasing order
+const SUPPORTED_TAGS: [u8; TAG_COUNT] = {
+    let mut tags = [0; TAG_COUNT];
+    let mut i = 0;
+    while i < TAG_COUNT {
+        tags[i] = TAG_REGISTRY[i].tag;
+        i += 1;
+    }
+    tags
+};
+
+/// Decoding metadata of `tag`, `None` if [TokenInstruction::unpack] does not decode it
+pub fn tag_info(tag: u8) -> Option<&'static TagInfo> {
+    TAG_REGISTRY.get(tag as usize)
+}
+
+/// Tags decoded by [TokenInstruction::unpack], in increasing order. Instructions with any
+/// other tag fail to decode, see [crate::coverage] to report them.
+///
+/// ```
+/// # use substreams_solana_program_instructions::token_instruction_2022::supported_tags;
+/// assert!(supported_tags().contains(&12));
+/// assert_eq!(supported_tags().last(), Some(&44));
+/// ```
+pub const fn supported_tags() -> &'static [u8] {
+    &SUPPORTED_TAGS
+}
+
+/// Returns `true` if [TokenInstruction::unpack] decodes instructions with `tag`
+pub fn is_supported_tag(tag: u8) -> bool {
+    SUPPORTED_TAGS.binary_search(&tag).is_ok()
+}
 
 /// Instructions supported by the token program.
 #[repr(C)]
@@ -58,7 +232,7 @@ pub enum TokenInstruction<'a> {
     ///
     InitializeMint {
         /// Number of base 10 digits to the right of the decimal place.
-        decimals: u8,
+        decimals: AmountDecimals,
         /// The authority/multisignature to mint tokens.
         #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
         mint_authority: Pubkey,
@@ -138,7 +312,7 @@ pub enum TokenInstruction<'a> {
     )]
     Transfer {
         /// The amount of tokens to transfer.
-        amount: u64,
+        amount: Amount,
     },
     /// Approves a delegate.  A delegate is given the authority over tokens on
     /// behalf of the source account's owner.
@@ -157,7 +331,7 @@ pub enum TokenInstruction<'a> {
     ///   3. ..3+M `[signer]` M signer accounts
     Approve {
         /// The amount of tokens the delegate is approved for.
-        amount: u64,
+        amount: Amount,
     },
     /// Revokes the delegate's authority.
     ///
@@ -208,7 +382,7 @@ pub enum TokenInstruction<'a> {
     ///   3. ..3+M `[signer]` M signer accounts.
     MintTo {
         /// The amount of new tokens to mint.
-        amount: u64,
+        amount: Amount,
     },
     /// Burns tokens by removing them from an account.  `Burn` does not support
     /// accounts associated with the native mint, use `CloseAccount` instead.
@@ -227,7 +401,7 @@ pub enum TokenInstruction<'a> {
     ///   3. ..3+M `[signer]` M signer accounts.
     Burn {
         /// The amount of tokens to burn.
-        amount: u64,
+        amount: Amount,
     },
     /// Close an account by transferring all its SOL to the destination account.
     /// Non-native accounts may only be closed if its token amount is zero.
@@ -322,9 +496,9 @@ pub enum TokenInstruction<'a> {
     ///   4. ..4+M `[signer]` M signer accounts.
     TransferChecked {
         /// The amount of tokens to transfer.
-        amount: u64,
+        amount: Amount,
         /// Expected number of base 10 digits to the right of the decimal place.
-        decimals: u8,
+        decimals: AmountDecimals,
     },
     /// Approves a delegate.  A delegate is given the authority over tokens on
     /// behalf of the source account's owner.
@@ -349,9 +523,9 @@ pub enum TokenInstruction<'a> {
     ///   4. ..4+M `[signer]` M signer accounts
     ApproveChecked {
         /// The amount of tokens the delegate is approved for.
-        amount: u64,
+        amount: Amount,
         /// Expected number of base 10 digits to the right of the decimal place.
-        decimals: u8,
+        decimals: AmountDecimals,
     },
     /// Mints new tokens to an account.  The native mint does not support
     /// minting.
@@ -374,9 +548,9 @@ pub enum TokenInstruction<'a> {
     ///   3. ..3+M `[signer]` M signer accounts.
     MintToChecked {
         /// The amount of new tokens to mint.
-        amount: u64,
+        amount: Amount,
         /// Expected number of base 10 digits to the right of the decimal place.
-        decimals: u8,
+        decimals: AmountDecimals,
     },
     /// Burns tokens by removing them from an account.  `BurnChecked` does not
     /// support accounts associated with the native mint, use `CloseAccount`
@@ -400,9 +574,9 @@ pub enum TokenInstruction<'a> {
     ///   3. ..3+M `[signer]` M signer accounts.
     BurnChecked {
         /// The amount of tokens to burn.
-        amount: u64,
+        amount: Amount,
         /// Expected number of base 10 digits to the right of the decimal place.
-        decimals: u8,
+        decimals: AmountDecimals,
     },
     /// Like InitializeAccount, but the owner pubkey is passed via instruction data
     /// rather than the accounts list. This variant may be preferable when using
@@ -460,7 +634,7 @@ pub enum TokenInstruction<'a> {
     ///
     InitializeMint2 {
         /// Number of base 10 digits to the right of the decimal place.
-        decimals: u8,
+        decimals: AmountDecimals,
         /// The authority/multisignature to mint tokens.
         #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
         mint_authority: Pubkey,
@@ -506,7 +680,7 @@ pub enum TokenInstruction<'a> {
     ///   0. `[]` The mint to calculate for
     AmountToUiAmount {
         /// The amount of tokens to convert.
-        amount: u64,
+        amount: Amount,
     },
     /// Convert a UiAmount of tokens to a little-endian `u64` raw Amount, using the given mint.
     ///
@@ -544,14 +718,14 @@ pub enum TokenInstruction<'a> {
     TransferFeeExtension(TransferFeeInstruction),
     /// The common instruction prefix for Confidential Transfer extension instructions.
     ///
-    /// See `extension::confidential_transfer::instruction::ConfidentialTransferInstruction` for
-    /// further details about the extended instructions that share this instruction prefix
-    ConfidentialTransferExtension,
+    /// See `ConfidentialTransferInstruction` for further details about the extended
+    /// instructions that share this instruction prefix
+    ConfidentialTransferExtension(ConfidentialTransferInstruction<'a>),
     /// The common instruction prefix for Default Account State extension instructions.
     ///
-    /// See `extension::default_account_state::instruction::DefaultAccountStateInstruction` for
-    /// further details about the extended instructions that share this instruction prefix
-    DefaultAccountStateExtension,
+    /// See `DefaultAccountStateInstruction` for further details about the extended instructions
+    /// that share this instruction prefix
+    DefaultAccountStateExtension(DefaultAccountStateInstruction),
     /// Check to see if a token account is large enough for a list of ExtensionTypes, and if not,
     /// use reallocation to increase the data size.
     ///
@@ -607,9 +781,9 @@ pub enum TokenInstruction<'a> {
     InitializeNonTransferableMint,
     /// The common instruction prefix for Interest Bearing extension instructions.
     ///
-    /// See `extension::interest_bearing_mint::instruction::InterestBearingMintInstruction` for
-    /// further details about the extended instructions that share this instruction prefix
-    InterestBearingMintExtension,
+    /// See `InterestBearingMintInstruction` for further details about the extended
+    /// instructions that share this instruction prefix
+    InterestBearingMintExtension(InterestBearingMintInstruction),
     /// The common instruction prefix for CPI Guard account extension instructions.
     ///
     /// See `extension::cpi_guard::instruction::CpiGuardInstruction` for
@@ -662,148 +836,253 @@ pub enum TokenInstruction<'a> {
     /// for further details about the extended instructions that share this instruction
     /// prefix
     MetadataPointerExtension,
+    /// The common instruction prefix for group pointer extension instructions.
+    ///
+    /// See `extension::group_pointer::instruction::GroupPointerInstruction`
+    /// for further details about the extended instructions that share this instruction
+    /// prefix
+    GroupPointerExtension,
+    /// The common instruction prefix for group member pointer extension instructions.
+    ///
+    /// See `GroupMemberPointerInstruction` for further details about the extended
+    /// instructions that share this instruction prefix
+    GroupMemberPointerExtension(GroupMemberPointerInstruction),
+    /// Instruction prefix for instructions to the confidential-mint-burn extension
+    ///
+    /// See `ConfidentialMintBurnInstruction` for further details about the extended
+    /// instructions that share this instruction prefix
+    #[cfg_attr(feature = "serde-traits", serde(borrow))]
+    ConfidentialMintBurnExtension(ConfidentialMintBurnInstruction<'a>),
+    /// Instruction prefix for instructions to the scaled ui amount extension
+    ///
+    /// See `ScaledUiAmountInstruction` for further details about the extended instructions
+    /// that share this instruction prefix
+    ScaledUiAmountExtension(ScaledUiAmountInstruction),
+    /// Instruction prefix for instructions to the pausable extension
+    ///
+    /// See `PausableInstruction` for further details about the extended instructions
+    /// that share this instruction prefix
+    PausableExtension(PausableInstruction),
 }
 impl<'a> TokenInstruction<'a> {
     /// Unpacks a byte buffer into a [TokenInstruction](enum.TokenInstruction.html).
+    /// Bytes following the instruction's payload are ignored, as the token programs do.
     pub fn unpack(input: &'a [u8]) -> Result<Self, Error> {
+        Self::unpack_with_rest(input).map(|(instruction, _rest)| instruction)
+    }
+
+    /// Unpacks a byte buffer into a [TokenInstruction](enum.TokenInstruction.html), failing
+    /// if bytes follow the instruction's payload. See [TokenInstruction::unpack_with_rest].
+    pub fn unpack_strict(input: &'a [u8]) -> Result<Self, Error> {
+        let (instruction, rest) = Self::unpack_with_rest(input)?;
+        if !rest.is_empty() {
+            return Err(anyhow!("Invalid Instruction - {} trailing bytes after payload", rest.len()));
+        }
+        Ok(instruction)
+    }
+
+    /// Unpacks a byte buffer into a [TokenInstruction](enum.TokenInstruction.html), along with
+    /// the bytes following the instruction's payload. Payloads of extension instructions this
+    /// crate does not decode further are consumed whole.
+    pub fn unpack_with_rest(input: &'a [u8]) -> Result<(Self, &'a [u8]), Error> {
+        Self::validate_len(input)?;
 
         let (&tag, rest) = input.split_first().ok_or(anyhow!("Invalid Instruction"))?;
         Ok(match tag {
             0 => {
-                let (&decimals, rest) = rest.split_first().ok_or(anyhow!("Invalid Instruction - 0"))?;
+                let (&raw_decimals, rest) = rest.split_first().ok_or(anyhow!("Invalid Instruction - 0"))?;
                 let (mint_authority, rest) = Self::unpack_pubkey(rest)?;
-                let (freeze_authority, _rest) = Self::unpack_pubkey_option(rest)?;
-                Self::InitializeMint {
+                let (freeze_authority, rest) = Self::unpack_pubkey_option(rest)?;
+                let instruction = Self::InitializeMint {
                     mint_authority,
                     freeze_authority,
-                    decimals,
-                }
+                    decimals: decimals(raw_decimals),
+                };
+                (instruction, rest)
             }
-            1 => Self::InitializeAccount,
+            1 => (Self::InitializeAccount, rest),
             2 => {
-                let &m = rest.first().ok_or(anyhow!("Invalid Instruction - 2"))?;
-                Self::InitializeMultisig { m }
+                let (&m, rest) = rest.split_first().ok_or(anyhow!("Invalid Instruction - 2"))?;
+                (Self::InitializeMultisig { m }, rest)
             }
             3 | 4 | 7 | 8 => {
-                let amount = rest
-                    .get(..U64_BYTES)
-                    .and_then(|slice| slice.try_into().ok())
-                    .map(u64::from_le_bytes)
-                    .ok_or(anyhow!("Invalid Instruction - 3 | 4 | 7 | 8"))?;
-                match tag {
+                let (raw_amount, rest) =
+                    Self::unpack_u64(rest).map_err(|_| anyhow!("Invalid Instruction - 3 | 4 | 7 | 8"))?;
+                let amount = amount(raw_amount);
+                let instruction = match tag {
                     #[allow(deprecated)]
                     3 => Self::Transfer { amount },
                     4 => Self::Approve { amount },
                     7 => Self::MintTo { amount },
                     8 => Self::Burn { amount },
                     _ => unreachable!(),
-                }
+                };
+                (instruction, rest)
             }
-            5 => Self::Revoke,
+            5 => (Self::Revoke, rest),
             6 => {
                 let (authority_type, rest) = rest
                     .split_first()
                     .ok_or_else(|| anyhow!("Invalid Instruction - 6"))
                     .and_then(|(&t, rest)| Ok((AuthorityType::from(t)?, rest)))?;
-                let (new_authority, _rest) = Self::unpack_pubkey_option(rest)?;
-
-                Self::SetAuthority {
+                let (new_authority, rest) = Self::unpack_pubkey_option(rest)?;
+                let instruction = Self::SetAuthority {
                     authority_type,
                     new_authority,
-                }
-            }
-            9 => Self::CloseAccount,
-            10 => Self::FreezeAccount,
-            11 => Self::ThawAccount,
-            12 => {
-                let (amount, decimals, _rest) = Self::unpack_amount_decimals(rest)?;
-                Self::TransferChecked { amount, decimals }
-            }
-            13 => {
-                let (amount, decimals, _rest) = Self::unpack_amount_decimals(rest)?;
-                Self::ApproveChecked { amount, decimals }
-            }
-            14 => {
-                let (amount, decimals, _rest) = Self::unpack_amount_decimals(rest)?;
-                Self::MintToChecked { amount, decimals }
+                };
+                (instruction, rest)
             }
-            15 => {
-                let (amount, decimals, _rest) = Self::unpack_amount_decimals(rest)?;
-                Self::BurnChecked { amount, decimals }
+            9 => (Self::CloseAccount, rest),
+            10 => (Self::FreezeAccount, rest),
+            11 => (Self::ThawAccount, rest),
+            12..=15 => {
+                let (raw_amount, raw_decimals, rest) = Self::unpack_amount_decimals(rest)?;
+                let (amount, decimals) = (amount(raw_amount), decimals(raw_decimals));
+                let instruction = match tag {
+                    12 => Self::TransferChecked { amount, decimals },
+                    13 => Self::ApproveChecked { amount, decimals },
+                    14 => Self::MintToChecked { amount, decimals },
+                    15 => Self::BurnChecked { amount, decimals },
+                    _ => unreachable!(),
+                };
+                (instruction, rest)
             }
             16 => {
-                let (owner, _rest) = Self::unpack_pubkey(rest)?;
-                Self::InitializeAccount2 { owner }
+                let (owner, rest) = Self::unpack_pubkey(rest)?;
+                (Self::InitializeAccount2 { owner }, rest)
             }
-            17 => Self::SyncNative,
+            17 => (Self::SyncNative, rest),
             18 => {
-                let (owner, _rest) = Self::unpack_pubkey(rest)?;
-                Self::InitializeAccount3 { owner }
+                let (owner, rest) = Self::unpack_pubkey(rest)?;
+                (Self::InitializeAccount3 { owner }, rest)
             }
             19 => {
-                let &m = rest.first().ok_or(anyhow!("Invalid Instruction - 19"))?;
-                Self::InitializeMultisig2 { m }
+                let (&m, rest) = rest.split_first().ok_or(anyhow!("Invalid Instruction - 19"))?;
+                (Self::InitializeMultisig2 { m }, rest)
             }
             20 => {
-                let (&decimals, rest) = rest.split_first().ok_or(anyhow!("Invalid Instruction - 20"))?;
+                let (&raw_decimals, rest) = rest.split_first().ok_or(anyhow!("Invalid Instruction - 20"))?;
                 let (mint_authority, rest) = Self::unpack_pubkey(rest)?;
-                let (freeze_authority, _rest) = Self::unpack_pubkey_option(rest)?;
-                Self::InitializeMint2 {
+                let (freeze_authority, rest) = Self::unpack_pubkey_option(rest)?;
+                let instruction = Self::InitializeMint2 {
                     mint_authority,
                     freeze_authority,
-                    decimals,
-                }
+                    decimals: decimals(raw_decimals),
+                };
+                (instruction, rest)
             }
             21 => {
                 let mut extension_types = vec![];
                 for chunk in rest.chunks(size_of::<ExtensionType>()) {
                     extension_types.push(chunk.try_into()?);
                 }
-                Self::GetAccountDataSize { extension_types }
+                (Self::GetAccountDataSize { extension_types }, &[][..])
             }
-            22 => Self::InitializeImmutableOwner,
+            22 => (Self::InitializeImmutableOwner, rest),
             23 => {
-                let (amount, _rest) = Self::unpack_u64(rest)?;
-                Self::AmountToUiAmount { amount }
+                let (raw_amount, rest) = Self::unpack_u64(rest)?;
+                (Self::AmountToUiAmount { amount: amount(raw_amount) }, rest)
             }
             24 => {
                 let ui_amount = std::str::from_utf8(rest).map_err(|_| anyhow!("Invalid Instruction - 24"))?;
-                Self::UiAmountToAmount { ui_amount }
+                (Self::UiAmountToAmount { ui_amount }, &[][..])
             }
             25 => {
-                let (close_authority, _rest) = Self::unpack_pubkey_option(rest)?;
-                Self::InitializeMintCloseAuthority { close_authority }
+                let (close_authority, rest) = Self::unpack_pubkey_option(rest)?;
+                (Self::InitializeMintCloseAuthority { close_authority }, rest)
             }
             26 => {
-                let (instruction, _rest) = TransferFeeInstruction::unpack(rest)?;
-                Self::TransferFeeExtension(instruction)
+                let (instruction, rest) = TransferFeeInstruction::unpack(rest)?;
+                (Self::TransferFeeExtension(instruction), rest)
+            }
+            27 => {
+                let (instruction, rest) = ConfidentialTransferInstruction::unpack(rest)?;
+                (Self::ConfidentialTransferExtension(instruction), rest)
+            }
+            28 => {
+                let (instruction, rest) = DefaultAccountStateInstruction::unpack(rest)?;
+                (Self::DefaultAccountStateExtension(instruction), rest)
             }
-            27 => Self::ConfidentialTransferExtension,
-            28 => Self::DefaultAccountStateExtension,
             29 => {
                 let mut extension_types = vec![];
                 for chunk in rest.chunks(size_of::<ExtensionType>()) {
                     extension_types.push(chunk.try_into()?);
                 }
-                Self::Reallocate { extension_types }
+                (Self::Reallocate { extension_types }, &[][..])
+            }
+            30 => (Self::MemoTransferExtension, &[][..]),
+            31 => (Self::CreateNativeMint, rest),
+            32 => (Self::InitializeNonTransferableMint, rest),
+            33 => {
+                let (instruction, rest) = InterestBearingMintInstruction::unpack(rest)?;
+                (Self::InterestBearingMintExtension(instruction), rest)
             }
-            30 => Self::MemoTransferExtension,
-            31 => Self::CreateNativeMint,
-            32 => Self::InitializeNonTransferableMint,
-            33 => Self::InterestBearingMintExtension,
-            34 => Self::CpiGuardExtension,
+            34 => (Self::CpiGuardExtension, &[][..]),
             35 => {
-                let (delegate, _rest) = Self::unpack_pubkey(rest)?;
-                Self::InitializePermanentDelegate { delegate }
+                let (delegate, rest) = Self::unpack_pubkey(rest)?;
+                (Self::InitializePermanentDelegate { delegate }, rest)
+            }
+            36 => (Self::TransferHookExtension, &[][..]),
+            37 => (Self::ConfidentialTransferFeeExtension, &[][..]),
+            38 => (Self::WithdrawExcessLamports, rest),
+            39 => (Self::MetadataPointerExtension, &[][..]),
+            40 => (Self::GroupPointerExtension, &[][..]),
+            41 => {
+                let (instruction, rest) = GroupMemberPointerInstruction::unpack(rest)?;
+                (Self::GroupMemberPointerExtension(instruction), rest)
+            }
+            42 => {
+                let (instruction, rest) = ConfidentialMintBurnInstruction::unpack(rest)?;
+                (Self::ConfidentialMintBurnExtension(instruction), rest)
+            }
+            43 => {
+                let (instruction, rest) = ScaledUiAmountInstruction::unpack(rest)?;
+                (Self::ScaledUiAmountExtension(instruction), rest)
+            }
+            44 => {
+                let (instruction, rest) = PausableInstruction::unpack(rest)?;
+                (Self::PausableExtension(instruction), rest)
             }
-            36 => Self::TransferHookExtension,
-            37 => Self::ConfidentialTransferFeeExtension,
-            38 => Self::WithdrawExcessLamports,
-            39 => Self::MetadataPointerExtension,
             _ => return Err(anyhow!("Invalid Instruction - unpack didn't match any tag value: {}", tag)),
         })
     }
 
+    /// Checks that `input` holds a known tag followed by at least the minimum payload length
+    /// of that tag, see [MIN_PAYLOAD_LENS]. Cheap enough to pre-filter instructions before
+    /// decoding them.
+    pub fn validate_len(input: &[u8]) -> Result<(), Error> {
+        let (&tag, rest) = input.split_first().ok_or(anyhow!("Invalid Instruction"))?;
+        let min_len = *MIN_PAYLOAD_LENS
+            .get(tag as usize)
+            .ok_or(anyhow!("Invalid Instruction - unpack didn't match any tag value: {}", tag))?;
+        if rest.len() < min_len {
+            return Err(anyhow!(
+                "Invalid Instruction - {}: expected at least {} bytes, got {}",
+                tag,
+                min_len,
+                rest.len()
+            ));
+        }
+        Ok(())
+    }
+
+    /// Name of the instruction, its variant name, see [TagInfo::name]
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::token_instruction_2022::TokenInstruction;
+    /// let instruction = TokenInstruction::unpack(&[9]).unwrap();
+    /// assert_eq!(instruction.tag(), 9);
+    /// assert_eq!(instruction.name(), "CloseAccount");
+    /// assert_eq!(instruction.expected_accounts(), 3);
+    /// ```
+    pub fn name(&self) -> &'static str {
+        TAG_REGISTRY[self.tag() as usize].name
+    }
+
+    /// Minimum number of accounts of the instruction, see [TagInfo::min_accounts]
+    pub fn expected_accounts(&self) -> usize {
+        TAG_REGISTRY[self.tag() as usize].min_accounts
+    }
 
     pub(crate) fn unpack_pubkey(input: &[u8]) -> Result<(Pubkey, &[u8]), Error> {
         let pk = input
@@ -827,6 +1106,28 @@ impl<'a> TokenInstruction<'a> {
     }
 
 
+    /// Unpacks an `OptionalNonZeroPubkey`, an all-zero pubkey meaning `None`
+    pub(crate) fn unpack_optional_nonzero_pubkey(input: &[u8]) -> Result<(COption<Pubkey>, &[u8]), Error> {
+        let (pk, rest) = Self::unpack_pubkey(input)?;
+        if pk.0 == [0; PUBKEY_BYTES] {
+            Ok((COption::None, rest))
+        } else {
+            Ok((COption::Some(pk), rest))
+        }
+    }
+
+    pub(crate) fn unpack_i8(input: &[u8]) -> Result<(i8, &[u8]), Error> {
+        let (&value, rest) = input.split_first().ok_or(anyhow!("Unable to unpack i8"))?;
+        Ok((value as i8, rest))
+    }
+
+    pub(crate) fn unpack_bytes(input: &[u8], len: usize) -> Result<(&[u8], &[u8]), Error> {
+        let bytes = input
+            .get(..len)
+            .ok_or(anyhow!("Unable to unpack {} bytes", len))?;
+        Ok((bytes, &input[len..]))
+    }
+
     pub(crate) fn unpack_u16(input: &[u8]) -> Result<(u16, &[u8]), Error> {
         let value = input
             .get(..U16_BYTES)
@@ -836,6 +1137,15 @@ impl<'a> TokenInstruction<'a> {
         Ok((value, &input[U16_BYTES..]))
     }
 
+    pub(crate) fn unpack_u32(input: &[u8]) -> Result<(u32, &[u8]), Error> {
+        let value = input
+            .get(..U32_BYTES)
+            .and_then(|slice| slice.try_into().ok())
+            .map(u32::from_le_bytes)
+            .ok_or(anyhow!("Unable to unpack u32"))?;
+        Ok((value, &input[U32_BYTES..]))
+    }
+
     pub(crate) fn unpack_u64(input: &[u8]) -> Result<(u64, &[u8]), Error> {
         let value = input
             .get(..U64_BYTES)
@@ -845,6 +1155,57 @@ impl<'a> TokenInstruction<'a> {
         Ok((value, &input[U64_BYTES..]))
     }
 
+    /// Unpacks a little-endian `i16`, the type of the interest-bearing mint rates, returning
+    /// it along with the remaining bytes
+    pub fn unpack_i16(input: &[u8]) -> Result<(i16, &[u8]), Error> {
+        let value = input
+            .get(..U16_BYTES)
+            .and_then(|slice| slice.try_into().ok())
+            .map(i16::from_le_bytes)
+            .ok_or(anyhow!("Unable to unpack i16"))?;
+        Ok((value, &input[U16_BYTES..]))
+    }
+
+    /// Unpacks a little-endian `i64`, the type of unix timestamps, returning it along with
+    /// the remaining bytes
+    pub fn unpack_i64(input: &[u8]) -> Result<(i64, &[u8]), Error> {
+        let value = input
+            .get(..U64_BYTES)
+            .and_then(|slice| slice.try_into().ok())
+            .map(i64::from_le_bytes)
+            .ok_or(anyhow!("Unable to unpack i64"))?;
+        Ok((value, &input[U64_BYTES..]))
+    }
+
+    /// Unpacks a little-endian `u128`, returning it along with the remaining bytes
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::token_instruction_2022::TokenInstruction;
+    /// let mut input = (1u128 << 64).to_le_bytes().to_vec();
+    /// input.push(7);
+    /// assert_eq!(TokenInstruction::unpack_u128(&input).unwrap(), (1 << 64, &[7][..]));
+    /// assert!(TokenInstruction::unpack_u128(&input[..15]).is_err());
+    /// ```
+    pub fn unpack_u128(input: &[u8]) -> Result<(u128, &[u8]), Error> {
+        let value = input
+            .get(..U128_BYTES)
+            .and_then(|slice| slice.try_into().ok())
+            .map(u128::from_le_bytes)
+            .ok_or(anyhow!("Unable to unpack u128"))?;
+        Ok((value, &input[U128_BYTES..]))
+    }
+
+    /// Unpacks a little-endian `f64`, the type of the scaled UI amount multipliers,
+    /// returning it along with the remaining bytes
+    pub fn unpack_f64(input: &[u8]) -> Result<(f64, &[u8]), Error> {
+        let value = input
+            .get(..U64_BYTES)
+            .and_then(|slice| slice.try_into().ok())
+            .map(f64::from_le_bytes)
+            .ok_or(anyhow!("Unable to unpack f64"))?;
+        Ok((value, &input[U64_BYTES..]))
+    }
+
     pub(crate) fn unpack_amount_decimals(input: &[u8]) -> Result<(u64, u8, &[u8]), Error> {
         let (amount, rest) = Self::unpack_u64(input)?;
         let (&decimals, rest) = rest.split_first().ok_or(anyhow!("Unable to unpack amount decimals"))?;
@@ -852,6 +1213,50 @@ impl<'a> TokenInstruction<'a> {
     }
 }
 
+/// Decodes a firehose top-level instruction, its program id resolved through the keys of its
+/// transaction. Fails if the program is not a token program.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{accounts::TransactionKeys, program_ids::TOKEN_PROGRAM_ID, token_instruction_2022::TokenInstruction};
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::CompiledInstruction;
+/// # use std::convert::TryFrom;
+/// let account_keys = vec![vec![1; 32], TOKEN_PROGRAM_ID.as_ref().to_vec()];
+/// let keys = TransactionKeys { account_keys: &account_keys, loaded_writable_addresses: &[], loaded_readonly_addresses: &[], header: None };
+///
+/// let close = CompiledInstruction { program_id_index: 1, accounts: vec![0, 0, 0], data: vec![9] };
+/// assert_eq!(TokenInstruction::try_from((&close, &keys)).unwrap(), TokenInstruction::CloseAccount);
+/// let other_program = CompiledInstruction { program_id_index: 0, ..close };
+/// assert!(TokenInstruction::try_from((&other_program, &keys)).is_err());
+/// ```
+impl<'a> TryFrom<(&'a CompiledInstruction, &TransactionKeys<'_>)> for TokenInstruction<'a> {
+    type Error = Error;
+
+    fn try_from((instruction, keys): (&'a CompiledInstruction, &TransactionKeys<'_>)) -> Result<Self, Self::Error> {
+        unpack_invoked(instruction.program_id_index as usize, keys, &instruction.data)
+    }
+}
+
+/// Decodes a firehose inner instruction, its program id resolved through the keys of its
+/// transaction. Fails if the program is not a token program.
+impl<'a> TryFrom<(&'a InnerInstruction, &TransactionKeys<'_>)> for TokenInstruction<'a> {
+    type Error = Error;
+
+    fn try_from((instruction, keys): (&'a InnerInstruction, &TransactionKeys<'_>)) -> Result<Self, Self::Error> {
+        unpack_invoked(instruction.program_id_index as usize, keys, &instruction.data)
+    }
+}
+
+/// Unpacks `data`, passed to the program at `program_id_index` of `keys`
+fn unpack_invoked<'a>(program_id_index: usize, keys: &TransactionKeys<'_>, data: &'a [u8]) -> Result<TokenInstruction<'a>, Error> {
+    let program_id = keys
+        .get(program_id_index)
+        .ok_or(anyhow!("Invalid program id index: {} ({} keys)", program_id_index, keys.len()))?;
+    if !is_token_program(program_id) {
+        return Err(anyhow!("Not a token program instruction: {}", bs58::encode(program_id).into_string()));
+    }
+    TokenInstruction::unpack(data)
+}
+
 /// Specifies the authority type for SetAuthority instructions
 #[repr(u8)]
 #[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
@@ -885,6 +1290,14 @@ pub enum AuthorityType {
     ConfidentialTransferFeeConfig,
     /// Authority to set the metadata address
     MetadataPointer,
+    /// Authority to set the group address
+    GroupPointer,
+    /// Authority to set the group member address
+    GroupMemberPointer,
+    /// Authority to set the UI amount scale
+    ScaledUiAmount,
+    /// Authority to pause or resume minting / transferring / burning
+    Pause,
 }
 
 impl AuthorityType {
@@ -903,6 +1316,10 @@ impl AuthorityType {
             10 => Ok(AuthorityType::TransferHookProgramId),
             11 => Ok(AuthorityType::ConfidentialTransferFeeConfig),
             12 => Ok(AuthorityType::MetadataPointer),
+            13 => Ok(AuthorityType::GroupPointer),
+            14 => Ok(AuthorityType::GroupMemberPointer),
+            15 => Ok(AuthorityType::ScaledUiAmount),
+            16 => Ok(AuthorityType::Pause),
             _ => Err(anyhow!("Invalid Instruction - Invalid AuthorityType with index {}", index)),
         }
     }
@@ -915,7 +1332,7 @@ impl AuthorityType {
 #[repr(u16)]
 #[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
-#[derive(Clone, Copy, Debug, PartialEq, TryFromPrimitive, IntoPrimitive)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, TryFromPrimitive, IntoPrimitive)]
 pub enum ExtensionType {
     /// Used as padding if the account size would otherwise be 355, same as a multisig
     Uninitialized,
@@ -957,6 +1374,24 @@ pub enum ExtensionType {
     MetadataPointer,
     /// Mint contains token-metadata
     TokenMetadata,
+    /// Mint contains a pointer to another account (or the same account) that holds group
+    /// configurations
+    GroupPointer,
+    /// Mint contains token group configurations
+    TokenGroup,
+    /// Mint contains a pointer to another account (or the same account) that holds group
+    /// member configurations
+    GroupMemberPointer,
+    /// Mint contains token group member configurations
+    TokenGroupMember,
+    /// Mint allowing the minting and burning of confidential tokens
+    ConfidentialMintBurn,
+    /// Tokens whose UI amount is scaled by a given amount
+    ScaledUiAmount,
+    /// Tokens where minting / burning / transferring can be paused
+    Pausable,
+    /// Indicates that the account belongs to a pausable mint
+    PausableAccount,
     /// Test variable-length mint extension
     #[cfg(test)]
     VariableLenMintTest = u16::MAX - 2,
@@ -0,0 +1,304 @@
+//! Token metadata interface instruction types
+//!
+//! The token metadata interface is implemented by the token 2022 program, which keeps the
+//! metadata in the mint itself. Like the token group interface, its instructions are
+//! identified by an 8 bytes discriminator, and their strings are borsh encoded: a `u32`
+//! length followed by UTF-8 bytes. [TokenMetadata] decodes the metadata held by a mint.
+
+use anyhow::anyhow;
+use {
+    substreams::errors::Error,
+    crate::{token_instruction_2022::TokenInstruction},
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    crate::serialization::coption_fromstr,
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+use crate::option::COption;
+use crate::pubkey::Pubkey;
+use crate::token_group_instruction::DISCRIMINATOR_BYTES;
+use crate::{account_data::extension_data, token_instruction_2022::ExtensionType};
+
+/// `Initialize` discriminator, hash of `spl_token_metadata_interface:initialize_account`
+pub const INITIALIZE_DISCRIMINATOR: [u8; 8] = [210, 225, 30, 162, 88, 184, 77, 141];
+/// `UpdateField` discriminator, hash of `spl_token_metadata_interface:updating_field`
+pub const UPDATE_FIELD_DISCRIMINATOR: [u8; 8] = [221, 233, 49, 45, 181, 202, 220, 200];
+/// `RemoveKey` discriminator, hash of `spl_token_metadata_interface:remove_key_ix`
+pub const REMOVE_KEY_DISCRIMINATOR: [u8; 8] = [234, 18, 32, 56, 89, 141, 37, 181];
+/// `UpdateAuthority` discriminator, hash of `spl_token_metadata_interface:update_the_authority`
+pub const UPDATE_AUTHORITY_DISCRIMINATOR: [u8; 8] = [215, 228, 166, 228, 84, 100, 86, 123];
+/// `Emit` discriminator, hash of `spl_token_metadata_interface:emitter`
+pub const EMIT_DISCRIMINATOR: [u8; 8] = [250, 166, 180, 250, 13, 12, 184, 70];
+
+/// Field of the token metadata
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Field<'a> {
+    /// The name field, corresponding to `TokenMetadata.name`
+    Name,
+    /// The symbol field, corresponding to `TokenMetadata.symbol`
+    Symbol,
+    /// The uri field, corresponding to `TokenMetadata.uri`
+    Uri,
+    /// A user field, whose key is given by the associated string
+    Key(&'a str),
+}
+
+impl Field<'_> {
+    /// Name of the field: `name`, `symbol`, `uri` or the key of a user field
+    pub fn key(&self) -> &str {
+        match self {
+            Self::Name => "name",
+            Self::Symbol => "symbol",
+            Self::Uri => "uri",
+            Self::Key(key) => key,
+        }
+    }
+}
+
+/// Instructions supported by the token metadata interface.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all = "camelCase", rename_all_fields = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TokenMetadataInstruction<'a> {
+    /// Initializes a TLV entry with the basic token-metadata fields.
+    ///
+    /// Assumes that the provided mint is an SPL token mint, that the metadata
+    /// account is allocated and assigned to the program, and that the metadata
+    /// account has enough lamports to cover the rent-exempt reserve.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]` Metadata
+    ///   1. `[]` Update authority
+    ///   2. `[]` Mint
+    ///   3. `[s]` Mint authority
+    Initialize {
+        /// Longer name of the token
+        name: &'a str,
+        /// Shortened symbol of the token
+        symbol: &'a str,
+        /// URI pointing to more metadata (image, video, etc.)
+        uri: &'a str,
+    },
+    /// Updates a field in a token-metadata account.
+    ///
+    /// The field can be one of the required fields (name, symbol, URI), or a
+    /// totally new field denoted by a "key" string.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]` Metadata account
+    ///   1. `[s]` Update authority
+    UpdateField {
+        /// Field to update in the metadata
+        #[cfg_attr(feature = "serde-traits", serde(borrow))]
+        field: Field<'a>,
+        /// Value to write for the field
+        value: &'a str,
+    },
+    /// Removes a key-value pair in a token-metadata account.
+    ///
+    /// This only applies to additional fields, and not the base name / symbol /
+    /// URI fields.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]` Metadata account
+    ///   1. `[s]` Update authority
+    RemoveKey {
+        /// If the idempotent flag is set to true, then the instruction will not
+        /// error if the key does not exist
+        idempotent: bool,
+        /// Key to remove in the additional metadata portion
+        key: &'a str,
+    },
+    /// Updates the token-metadata authority
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]` Metadata account
+    ///   1. `[s]` Current update authority
+    UpdateAuthority {
+        /// New authority for the token metadata, or unset if `None`
+        #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+        new_authority: COption<Pubkey>,
+    },
+    /// Emits the token-metadata as return data
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Metadata account
+    Emit {
+        /// Start of range of data to emit
+        start: Option<u64>,
+        /// End of range of data to emit
+        end: Option<u64>,
+    },
+}
+impl<'a> TokenMetadataInstruction<'a> {
+    /// Unpacks a byte buffer into a [TokenMetadataInstruction](enum.TokenMetadataInstruction.html).
+    pub fn unpack(input: &'a [u8]) -> Result<Self, Error> {
+        if input.len() < DISCRIMINATOR_BYTES {
+            return Err(anyhow!("Invalid Token Metadata Instruction"));
+        }
+        let (discriminator, rest) = input.split_at(DISCRIMINATOR_BYTES);
+        Ok(match discriminator {
+            d if d == INITIALIZE_DISCRIMINATOR => {
+                let (name, rest) = unpack_str(rest)?;
+                let (symbol, rest) = unpack_str(rest)?;
+                let (uri, _rest) = unpack_str(rest)?;
+                Self::Initialize { name, symbol, uri }
+            }
+            d if d == UPDATE_FIELD_DISCRIMINATOR => {
+                let (&tag, rest) = rest.split_first().ok_or(anyhow!("Unable to unpack metadata field"))?;
+                let (field, rest) = match tag {
+                    0 => (Field::Name, rest),
+                    1 => (Field::Symbol, rest),
+                    2 => (Field::Uri, rest),
+                    3 => {
+                        let (key, rest) = unpack_str(rest)?;
+                        (Field::Key(key), rest)
+                    }
+                    _ => return Err(anyhow!("Invalid metadata field: {}", tag)),
+                };
+                let (value, _rest) = unpack_str(rest)?;
+                Self::UpdateField { field, value }
+            }
+            d if d == REMOVE_KEY_DISCRIMINATOR => {
+                let (&idempotent, rest) = rest.split_first().ok_or(anyhow!("Unable to unpack idempotent flag"))?;
+                let (key, _rest) = unpack_str(rest)?;
+                Self::RemoveKey {
+                    idempotent: idempotent != 0,
+                    key,
+                }
+            }
+            d if d == UPDATE_AUTHORITY_DISCRIMINATOR => {
+                let (new_authority, _rest) = TokenInstruction::unpack_optional_nonzero_pubkey(rest)?;
+                Self::UpdateAuthority { new_authority }
+            }
+            d if d == EMIT_DISCRIMINATOR => {
+                let (start, rest) = unpack_option_u64(rest)?;
+                let (end, _rest) = unpack_option_u64(rest)?;
+                Self::Emit { start, end }
+            }
+            _ => return Err(anyhow!("Invalid Token Metadata Instruction - unpack didn't match any discriminator: {:?}", discriminator)),
+        })
+    }
+}
+
+/// Token metadata held by a Token 2022 mint, the state of its `TokenMetadata` extension
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenMetadata {
+    /// The authority that can sign to update the metadata
+    #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+    pub update_authority: COption<Pubkey>,
+    /// The associated mint, used to counter spoofing to be sure that metadata belongs to a
+    /// particular mint
+    #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+    pub mint: Pubkey,
+    /// The longer name of the token
+    pub name: String,
+    /// The shortened symbol for the token
+    pub symbol: String,
+    /// The URI pointing to richer metadata
+    pub uri: String,
+    /// Any additional metadata about the token as key-value pairs, in insertion order
+    pub additional: Vec<(String, String)>,
+}
+
+impl TokenMetadata {
+    /// Unpacks the value of a `TokenMetadata` extension entry
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::{pubkey::Pubkey, token_metadata_instruction::TokenMetadata};
+    /// let string = |value: &str| [&(value.len() as u32).to_le_bytes()[..], value.as_bytes()].concat();
+    /// let mut value = vec![0; 32];
+    /// value.extend_from_slice(&[1; 32]);
+    /// for field in ["Token", "TOK", "https://example.com/token.json"] {
+    ///     value.extend_from_slice(&string(field));
+    /// }
+    /// value.extend_from_slice(&1u32.to_le_bytes());
+    /// value.extend_from_slice(&string("website"));
+    /// value.extend_from_slice(&string("example.com"));
+    ///
+    /// let metadata = TokenMetadata::unpack(&value).unwrap();
+    /// assert!(metadata.update_authority.is_none());
+    /// assert_eq!(metadata.mint, Pubkey::from([1; 32]));
+    /// assert_eq!((metadata.name.as_str(), metadata.symbol.as_str()), ("Token", "TOK"));
+    /// assert_eq!(metadata.get("website"), Some("example.com"));
+    /// ```
+    pub fn unpack(input: &[u8]) -> Result<Self, Error> {
+        let (update_authority, rest) = TokenInstruction::unpack_optional_nonzero_pubkey(input)?;
+        let (mint, rest) = TokenInstruction::unpack_pubkey(rest)?;
+        let (name, rest) = unpack_str(rest)?;
+        let (symbol, rest) = unpack_str(rest)?;
+        let (uri, rest) = unpack_str(rest)?;
+        let (count, mut rest) = TokenInstruction::unpack_u32(rest)?;
+        let mut additional = vec![];
+        for _ in 0..count {
+            let (key, tail) = unpack_str(rest)?;
+            let (value, tail) = unpack_str(tail)?;
+            additional.push((key.to_string(), value.to_string()));
+            rest = tail;
+        }
+        Ok(Self {
+            update_authority,
+            mint,
+            name: name.to_string(),
+            symbol: symbol.to_string(),
+            uri: uri.to_string(),
+            additional,
+        })
+    }
+
+    /// Unpacks the token metadata held by Token 2022 mint `data`, `None` if the mint holds
+    /// none, its metadata pointing elsewhere for instance
+    pub fn from_mint_data(data: &[u8]) -> Result<Option<Self>, Error> {
+        extension_data(data, ExtensionType::TokenMetadata).map(Self::unpack).transpose()
+    }
+
+    /// Value of `field`, a user field's being its first value
+    pub fn field(&self, field: &Field) -> Option<&str> {
+        match field {
+            Field::Name => Some(&self.name),
+            Field::Symbol => Some(&self.symbol),
+            Field::Uri => Some(&self.uri),
+            Field::Key(key) => self.get(key),
+        }
+    }
+
+    /// Value of the additional metadata `key`
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.additional
+            .iter()
+            .find(|(additional_key, _)| additional_key == key)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Unpacks a borsh string, a `u32` length followed by UTF-8 bytes
+fn unpack_str(input: &[u8]) -> Result<(&str, &[u8]), Error> {
+    let (len, rest) = TokenInstruction::unpack_u32(input)?;
+    let (bytes, rest) = TokenInstruction::unpack_bytes(rest, len as usize)?;
+    let value = std::str::from_utf8(bytes).map_err(|_| anyhow!("Unable to unpack string, invalid UTF-8"))?;
+    Ok((value, rest))
+}
+
+/// Unpacks a borsh `Option<u64>`, a flag byte followed by the value when set
+fn unpack_option_u64(input: &[u8]) -> Result<(Option<u64>, &[u8]), Error> {
+    match input.split_first() {
+        Some((0, rest)) => Ok((None, rest)),
+        Some((1, rest)) => TokenInstruction::unpack_u64(rest).map(|(value, rest)| (Some(value), rest)),
+        _ => Err(anyhow!("Unable to unpack optional u64")),
+    }
+}
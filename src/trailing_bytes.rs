@@ -0,0 +1,83 @@
+//! Statistics on bytes trailing token instruction payloads
+//!
+//! The token programs ignore bytes following an instruction's payload, so a well-formed
+//! instruction can carry extra data, typically through CPIs of programs building payloads
+//! by hand, sometimes to smuggle data. Use [TokenInstruction::unpack_strict] to reject such
+//! instructions, and [TrailingBytesStats] to measure how often they occur.
+
+use {
+    crate::{
+        instructions::block_instructions, program_ids::is_token_program, token_instruction_2022::TokenInstruction,
+    },
+    std::collections::BTreeMap,
+    substreams_solana_core::pb::sf::solana::r#type::v1::Block,
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Counters of token instructions carrying trailing bytes
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrailingBytesStats {
+    /// Instructions decoded
+    pub decoded: u64,
+    /// Instructions decoded with trailing bytes
+    pub with_trailing_bytes: u64,
+    /// Total number of trailing bytes
+    pub trailing_bytes: u64,
+    /// Instructions decoded with trailing bytes, by instruction tag
+    pub by_tag: BTreeMap<u8, u64>,
+}
+
+impl TrailingBytesStats {
+    /// Counts the token instruction `data`, returning the number of bytes trailing its payload,
+    /// `None` if it does not decode
+    pub fn observe(&mut self, data: &[u8]) -> Option<usize> {
+        let (_instruction, rest) = TokenInstruction::unpack_with_rest(data).ok()?;
+        self.decoded += 1;
+        if !rest.is_empty() {
+            self.with_trailing_bytes += 1;
+            self.trailing_bytes += rest.len() as u64;
+            *self.by_tag.entry(data[0]).or_default() += 1;
+        }
+        Some(rest.len())
+    }
+
+    /// Counts the token instructions of the successful transactions of `block`
+    pub fn observe_block(&mut self, block: &Block) {
+        for item in block_instructions(block) {
+            if is_token_program(item.instruction.program_id) {
+                self.observe(item.instruction.data);
+            }
+        }
+    }
+
+    /// Adds the counters of `other`, to aggregate the statistics of several blocks
+    pub fn merge(&mut self, other: &TrailingBytesStats) {
+        self.decoded += other.decoded;
+        self.with_trailing_bytes += other.with_trailing_bytes;
+        self.trailing_bytes += other.trailing_bytes;
+        for (tag, count) in &other.by_tag {
+            *self.by_tag.entry(*tag).or_default() += count;
+        }
+    }
+
+    /// Share of the decoded instructions carrying trailing bytes, `0` if none was decoded
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::trailing_bytes::TrailingBytesStats;
+    /// let mut stats = TrailingBytesStats::default();
+    /// assert_eq!(stats.observe(&[17]), Some(0));
+    /// assert_eq!(stats.observe(&[17, 42, 42]), Some(2));
+    /// assert_eq!(stats.ratio(), 0.5);
+    /// assert_eq!(stats.by_tag.get(&17), Some(&1));
+    /// ```
+    pub fn ratio(&self) -> f64 {
+        if self.decoded == 0 {
+            return 0.;
+        }
+        self.with_trailing_bytes as f64 / self.decoded as f64
+    }
+}
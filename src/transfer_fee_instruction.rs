@@ -9,6 +9,7 @@ use {
     crate::serialization::coption_fromstr,
     serde::{Deserialize, Serialize},
 };
+use crate::amount::{amount, decimals, Amount, AmountDecimals};
 use crate::option::COption;
 use crate::pubkey::Pubkey;
 
@@ -44,7 +45,7 @@ pub enum TransferFeeInstruction {
         /// transfer amount
         transfer_fee_basis_points: u16,
         /// Maximum fee assessed on transfers
-        maximum_fee: u64,
+        maximum_fee: Amount,
     },
     /// Transfer, providing expected mint information and fees
     ///
@@ -64,12 +65,12 @@ pub enum TransferFeeInstruction {
     ///   4. ..4+M `[signer]` M signer accounts.
     TransferCheckedWithFee {
         /// The amount of tokens to transfer.
-        amount: u64,
+        amount: Amount,
         /// Expected number of base 10 digits to the right of the decimal place.
-        decimals: u8,
+        decimals: AmountDecimals,
         /// Expected fee assessed on this transfer, calculated off-chain based on
         /// the transfer_fee_basis_points and maximum_fee of the mint.
-        fee: u64,
+        fee: Amount,
     },
     /// Transfer all withheld tokens in the mint to an account. Signed by the mint's
     /// withdraw withheld tokens authority.
@@ -105,7 +106,7 @@ pub enum TransferFeeInstruction {
     ///   1. `[writable]` The destination account.
     ///   2. `[]` The mint's multisig `withdraw_withheld_authority`.
     ///   3. ..3+M `[signer]` M signer accounts.
-    ///   3+M+1. ..3+M+N `[writable]` The source accounts to withdraw from.
+    ///      3+M+1. ..3+M+N `[writable]` The source accounts to withdraw from.
     WithdrawWithheldTokensFromAccounts {
         /// Number of token accounts harvested
         num_token_accounts: u8,
@@ -139,7 +140,7 @@ pub enum TransferFeeInstruction {
         /// transfer amount
         transfer_fee_basis_points: u16,
         /// Maximum fee assessed on transfers
-        maximum_fee: u64,
+        maximum_fee: Amount,
     },
 }
 impl TransferFeeInstruction {
@@ -158,17 +159,17 @@ impl TransferFeeInstruction {
                     transfer_fee_config_authority,
                     withdraw_withheld_authority,
                     transfer_fee_basis_points,
-                    maximum_fee,
+                    maximum_fee: amount(maximum_fee),
                 };
                 (instruction, rest)
             }
             1 => {
-                let (amount, decimals, rest) = TokenInstruction::unpack_amount_decimals(rest)?;
+                let (raw_amount, raw_decimals, rest) = TokenInstruction::unpack_amount_decimals(rest)?;
                 let (fee, rest) = TokenInstruction::unpack_u64(rest)?;
                 let instruction = Self::TransferCheckedWithFee {
-                    amount,
-                    decimals,
-                    fee,
+                    amount: amount(raw_amount),
+                    decimals: decimals(raw_decimals),
+                    fee: amount(fee),
                 };
                 (instruction, rest)
             }
@@ -184,7 +185,7 @@ impl TransferFeeInstruction {
                 let (maximum_fee, rest) = TokenInstruction::unpack_u64(rest)?;
                 let instruction = Self::SetTransferFee {
                     transfer_fee_basis_points,
-                    maximum_fee,
+                    maximum_fee: amount(maximum_fee),
                 };
                 (instruction, rest)
             }
@@ -0,0 +1,182 @@
+//! Compact per-transaction summaries, for base modules shared by many pipelines
+//!
+//! Only available with the `tx-summary` feature. [tx_summary] reads, in a single pass over a
+//! transaction, what most downstream modules need before looking at its instructions: its
+//! signature, fee payer, fee, compute units, error and the programs it invoked. The result is
+//! the [TxSummary] protobuf message, defined in `proto/tx_summary.proto`, so a cheap map
+//! module can output the summaries of a block for other modules to depend on:
+//!
+//! ```no_run
+//! # use substreams_solana_program_instructions::tx_summary::{block_tx_summaries, TxSummaries};
+//! # use substreams_solana_core::pb::sf::solana::r#type::v1::Block;
+//! fn map_tx_summaries(block: Block) -> Result<TxSummaries, substreams::errors::Error> {
+//!     Ok(block_tx_summaries(&block))
+//! }
+//! ```
+
+use {
+    crate::{events::signature, instructions::instructions, pubkey::Pubkey},
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+/// Variant index of `TransactionError::InstructionError`
+const INSTRUCTION_ERROR: u32 = 8;
+/// Variant index of `InstructionError::Custom`
+const CUSTOM_ERROR: u32 = 24;
+
+/// Summaries of the transactions of a block
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct TxSummaries {
+    /// Slot of the block
+    #[prost(uint64, tag = "1")]
+    pub slot: u64,
+    /// One entry per transaction, failed ones included, in block order
+    #[prost(message, repeated, tag = "2")]
+    pub transactions: Vec<TxSummary>,
+}
+
+/// Summary of a transaction
+#[derive(Clone, PartialEq, Eq, prost::Message)]
+pub struct TxSummary {
+    /// Index of the transaction in its block
+    #[prost(uint32, tag = "1")]
+    pub index: u32,
+    /// First signature, base58 encoded
+    #[prost(string, tag = "2")]
+    pub signature: String,
+    /// First account of the message, base58 encoded
+    #[prost(string, tag = "3")]
+    pub fee_payer: String,
+    /// Fee paid, in lamports
+    #[prost(uint64, tag = "4")]
+    pub fee: u64,
+    /// Compute units consumed, `None` for blocks predating their recording
+    #[prost(uint64, optional, tag = "5")]
+    pub compute_units: Option<u64>,
+    /// Whether the transaction succeeded
+    #[prost(bool, tag = "6")]
+    pub success: bool,
+    /// Variant index of the `TransactionError` of a failed transaction
+    #[prost(uint32, optional, tag = "7")]
+    pub error_code: Option<u32>,
+    /// Index of the failing instruction, for `InstructionError` failures
+    #[prost(uint32, optional, tag = "8")]
+    pub error_instruction_index: Option<u32>,
+    /// Variant index of the `InstructionError`, for `InstructionError` failures
+    #[prost(uint32, optional, tag = "9")]
+    pub instruction_error_code: Option<u32>,
+    /// Error code returned by the program, for `Custom` instruction errors
+    #[prost(uint32, optional, tag = "10")]
+    pub custom_error_code: Option<u32>,
+    /// Distinct programs invoked by top-level and inner instructions, base58 encoded, in the
+    /// order of their first invocation. Program ids out of the transaction's keys are left out.
+    #[prost(string, repeated, tag = "11")]
+    pub programs_invoked: Vec<String>,
+}
+
+/// Summarizes `trx`, the `transaction_index`-th transaction of its block. Failed
+/// transactions are summarized as well, along with their error.
+///
+/// ```
+/// # use substreams_solana_program_instructions::tx_summary::tx_summary;
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::{CompiledInstruction, ConfirmedTransaction, Message, Transaction, TransactionError, TransactionStatusMeta};
+/// let trx = ConfirmedTransaction {
+///     transaction: Some(Transaction {
+///         message: Some(Message {
+///             account_keys: vec![vec![1; 32], vec![2; 32]],
+///             // The second program id is out of the keys
+///             instructions: vec![
+///                 CompiledInstruction { program_id_index: 1, ..Default::default() },
+///                 CompiledInstruction { program_id_index: 7, ..Default::default() },
+///             ],
+///             ..Default::default()
+///         }),
+///         ..Default::default()
+///     }),
+///     meta: Some(TransactionStatusMeta {
+///         fee: 5000,
+///         // InstructionError(0, Custom(6001))
+///         err: Some(TransactionError { err: vec![8, 0, 0, 0, 0, 24, 0, 0, 0, 0x71, 0x17, 0, 0] }),
+///         ..Default::default()
+///     }),
+/// };
+///
+/// let summary = tx_summary(&trx, 3);
+/// assert!(!summary.success);
+/// assert_eq!(summary.fee, 5000);
+/// assert_eq!(summary.error_instruction_index, Some(0));
+/// assert_eq!(summary.custom_error_code, Some(6001));
+/// assert_eq!(summary.programs_invoked, vec!["8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR".to_string()]);
+/// ```
+pub fn tx_summary(trx: &ConfirmedTransaction, transaction_index: u32) -> TxSummary {
+    let mut summary = TxSummary {
+        index: transaction_index,
+        signature: signature(trx),
+        ..Default::default()
+    };
+    if let Some(fee_payer) = trx
+        .transaction
+        .as_ref()
+        .and_then(|t| t.message.as_ref())
+        .and_then(|m| m.account_keys.first())
+    {
+        summary.fee_payer = bs58::encode(fee_payer).into_string();
+    }
+    if let Some(meta) = trx.meta.as_ref() {
+        summary.fee = meta.fee;
+        summary.compute_units = meta.compute_units_consumed;
+        match meta.err.as_ref() {
+            Some(err) => read_error(&err.err, &mut summary),
+            None => summary.success = true,
+        }
+    }
+
+    let mut programs: Vec<&[u8]> = vec![];
+    for instruction in instructions(trx) {
+        // Program ids out of the keys resolve to an empty id
+        if !instruction.program_id.is_empty() && !programs.contains(&instruction.program_id) {
+            programs.push(instruction.program_id);
+        }
+    }
+    summary.programs_invoked = programs
+        .into_iter()
+        .map(|program_id| match Pubkey::try_from(program_id) {
+            Ok(program_id) => program_id.to_string(),
+            Err(_) => bs58::encode(program_id).into_string(),
+        })
+        .collect();
+    summary
+}
+
+/// Summaries of all transactions of `block`, failed ones included
+pub fn block_tx_summaries(block: &Block) -> TxSummaries {
+    TxSummaries {
+        slot: block.slot,
+        transactions: block
+            .transactions
+            .iter()
+            .enumerate()
+            .map(|(index, trx)| tx_summary(trx, index as u32))
+            .collect(),
+    }
+}
+
+/// Reads the error codes of the bincode encoded `TransactionError` in `err`: the `u32`
+/// variant index, followed for `InstructionError` by the `u8` instruction index and the
+/// `u32` variant index of the `InstructionError`, itself followed by the `u32` code of
+/// `Custom` errors
+fn read_error(err: &[u8], summary: &mut TxSummary) {
+    let read_u32 = |offset: usize| {
+        err.get(offset..offset + 4)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+    summary.error_code = read_u32(0);
+    if summary.error_code != Some(INSTRUCTION_ERROR) {
+        return;
+    }
+    summary.error_instruction_index = err.get(4).map(|&index| index as u32);
+    summary.instruction_error_code = read_u32(5);
+    if summary.instruction_error_code == Some(CUSTOM_ERROR) {
+        summary.custom_error_code = read_u32(9);
+    }
+}
@@ -0,0 +1,347 @@
+//! Token instructions normalized across the SPL Token and SPL Token 2022 programs
+//!
+//! Both programs share their instruction layouts, but expose several flavors of the same
+//! operation (`Transfer`, `TransferChecked`, `TransferCheckedWithFee`, ...). The unified
+//! instruction folds them into one variant per operation, keeping the optional parts.
+
+use anyhow::anyhow;
+use {
+    crate::{
+        amount::{Amount, AmountDecimals},
+        option::COption,
+        program_ids::{token_program_of, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID},
+        pubkey::Pubkey,
+        token_instruction_2022::{AuthorityType, TokenInstruction},
+        transfer_fee_instruction::TransferFeeInstruction,
+    },
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    crate::serialization::coption_fromstr,
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// The token program executing an instruction
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TokenProgram {
+    /// SPL Token
+    Token,
+    /// SPL Token 2022
+    Token2022,
+}
+
+impl TokenProgram {
+    /// Returns the token program of `program_id`, registered deployments included (see
+    /// [crate::program_ids::register_token_program]), `None` for other programs
+    pub fn from_program_id(program_id: &[u8]) -> Option<Self> {
+        token_program_of(program_id)
+    }
+
+    /// Mainnet program id of the token program
+    pub fn program_id(self) -> Pubkey {
+        match self {
+            Self::Token => TOKEN_PROGRAM_ID,
+            Self::Token2022 => TOKEN_2022_PROGRAM_ID,
+        }
+    }
+}
+
+/// A token instruction of either token program, equivalent variants normalized into one.
+///
+/// Account positions differ between the flavors of an operation (the checked flavors insert
+/// the mint), use [UnifiedTokenInstruction::account_positions] to locate them.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum UnifiedTokenInstruction<'a> {
+    /// `InitializeMint` and `InitializeMint2`
+    InitializeMint {
+        /// Program executing the instruction
+        program: TokenProgram,
+        /// Number of base 10 digits to the right of the decimal place.
+        decimals: AmountDecimals,
+        /// The authority/multisignature to mint tokens.
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        mint_authority: Pubkey,
+        /// The freeze authority/multisignature of the mint.
+        #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+        freeze_authority: COption<Pubkey>,
+    },
+    /// `InitializeAccount`, `InitializeAccount2` and `InitializeAccount3`
+    InitializeAccount {
+        /// Program executing the instruction
+        program: TokenProgram,
+        /// The new account's owner, `None` for `InitializeAccount` which takes it as an account
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<Option<DisplayFromStr>>"))]
+        owner: Option<Pubkey>,
+    },
+    /// `InitializeMultisig` and `InitializeMultisig2`
+    InitializeMultisig {
+        /// Program executing the instruction
+        program: TokenProgram,
+        /// The number of signers (M) required to validate this multisignature account.
+        m: u8,
+    },
+    /// `Transfer`, `TransferChecked` and `TransferCheckedWithFee`
+    Transfer {
+        /// Program executing the instruction
+        program: TokenProgram,
+        /// The amount of tokens to transfer.
+        amount: Amount,
+        /// Expected number of base 10 digits to the right of the decimal place, checked
+        /// flavors only
+        decimals: Option<AmountDecimals>,
+        /// Expected fee assessed on this transfer, `TransferCheckedWithFee` only
+        fee: Option<Amount>,
+    },
+    /// `Approve` and `ApproveChecked`
+    Approve {
+        /// Program executing the instruction
+        program: TokenProgram,
+        /// The amount of tokens the delegate is approved for.
+        amount: Amount,
+        /// Expected number of base 10 digits to the right of the decimal place, checked
+        /// flavor only
+        decimals: Option<AmountDecimals>,
+    },
+    /// `Revoke`
+    Revoke {
+        /// Program executing the instruction
+        program: TokenProgram,
+    },
+    /// `SetAuthority`
+    SetAuthority {
+        /// Program executing the instruction
+        program: TokenProgram,
+        /// The type of authority to update.
+        authority_type: AuthorityType,
+        /// The new authority
+        #[cfg_attr(feature = "serde-traits", serde(with = "coption_fromstr"))]
+        new_authority: COption<Pubkey>,
+    },
+    /// `MintTo` and `MintToChecked`
+    MintTo {
+        /// Program executing the instruction
+        program: TokenProgram,
+        /// The amount of new tokens to mint.
+        amount: Amount,
+        /// Expected number of base 10 digits to the right of the decimal place, checked
+        /// flavor only
+        decimals: Option<AmountDecimals>,
+    },
+    /// `Burn` and `BurnChecked`
+    Burn {
+        /// Program executing the instruction
+        program: TokenProgram,
+        /// The amount of tokens to burn.
+        amount: Amount,
+        /// Expected number of base 10 digits to the right of the decimal place, checked
+        /// flavor only
+        decimals: Option<AmountDecimals>,
+    },
+    /// `CloseAccount`
+    CloseAccount {
+        /// Program executing the instruction
+        program: TokenProgram,
+    },
+    /// `FreezeAccount`
+    FreezeAccount {
+        /// Program executing the instruction
+        program: TokenProgram,
+    },
+    /// `ThawAccount`
+    ThawAccount {
+        /// Program executing the instruction
+        program: TokenProgram,
+    },
+    /// `SyncNative`
+    SyncNative {
+        /// Program executing the instruction
+        program: TokenProgram,
+    },
+    /// Any other instruction, as decoded
+    Other {
+        /// Program executing the instruction
+        program: TokenProgram,
+        /// The decoded instruction
+        #[cfg_attr(feature = "serde-traits", serde(borrow))]
+        instruction: TokenInstruction<'a>,
+    },
+}
+
+/// Positions of the accounts common to the flavors of an operation, in the instruction's
+/// account list
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AccountPositions {
+    /// The source, burned, approved or initialized token account
+    pub source: Option<usize>,
+    /// The mint
+    pub mint: Option<usize>,
+    /// The destination token account, or the delegate of an approval
+    pub destination: Option<usize>,
+    /// The signing owner or authority
+    pub authority: Option<usize>,
+}
+
+impl<'a> UnifiedTokenInstruction<'a> {
+    /// Decodes the instruction `data` executed by `program_id`, failing if `program_id` is
+    /// not a token program
+    pub fn unpack(program_id: &[u8], data: &'a [u8]) -> Result<Self, Error> {
+        let program = TokenProgram::from_program_id(program_id).ok_or(anyhow!("Not a token program instruction"))?;
+        Ok(Self::from_instruction(program, TokenInstruction::unpack(data)?))
+    }
+
+    /// Normalizes `instruction`, executed by `program`
+    pub fn from_instruction(program: TokenProgram, instruction: TokenInstruction<'a>) -> Self {
+        match instruction {
+            TokenInstruction::InitializeMint {
+                decimals,
+                mint_authority,
+                freeze_authority,
+            }
+            | TokenInstruction::InitializeMint2 {
+                decimals,
+                mint_authority,
+                freeze_authority,
+            } => Self::InitializeMint {
+                program,
+                decimals,
+                mint_authority,
+                freeze_authority,
+            },
+            TokenInstruction::InitializeAccount => Self::InitializeAccount { program, owner: None },
+            TokenInstruction::InitializeAccount2 { owner } | TokenInstruction::InitializeAccount3 { owner } => {
+                Self::InitializeAccount {
+                    program,
+                    owner: Some(owner),
+                }
+            }
+            TokenInstruction::InitializeMultisig { m } | TokenInstruction::InitializeMultisig2 { m } => {
+                Self::InitializeMultisig { program, m }
+            }
+            #[allow(deprecated)]
+            TokenInstruction::Transfer { amount } => Self::Transfer {
+                program,
+                amount,
+                decimals: None,
+                fee: None,
+            },
+            TokenInstruction::TransferChecked { amount, decimals } => Self::Transfer {
+                program,
+                amount,
+                decimals: Some(decimals),
+                fee: None,
+            },
+            TokenInstruction::TransferFeeExtension(TransferFeeInstruction::TransferCheckedWithFee {
+                amount,
+                decimals,
+                fee,
+            }) => Self::Transfer {
+                program,
+                amount,
+                decimals: Some(decimals),
+                fee: Some(fee),
+            },
+            TokenInstruction::Approve { amount } => Self::Approve {
+                program,
+                amount,
+                decimals: None,
+            },
+            TokenInstruction::ApproveChecked { amount, decimals } => Self::Approve {
+                program,
+                amount,
+                decimals: Some(decimals),
+            },
+            TokenInstruction::Revoke => Self::Revoke { program },
+            TokenInstruction::SetAuthority {
+                authority_type,
+                new_authority,
+            } => Self::SetAuthority {
+                program,
+                authority_type,
+                new_authority,
+            },
+            TokenInstruction::MintTo { amount } => Self::MintTo {
+                program,
+                amount,
+                decimals: None,
+            },
+            TokenInstruction::MintToChecked { amount, decimals } => Self::MintTo {
+                program,
+                amount,
+                decimals: Some(decimals),
+            },
+            TokenInstruction::Burn { amount } => Self::Burn {
+                program,
+                amount,
+                decimals: None,
+            },
+            TokenInstruction::BurnChecked { amount, decimals } => Self::Burn {
+                program,
+                amount,
+                decimals: Some(decimals),
+            },
+            TokenInstruction::CloseAccount => Self::CloseAccount { program },
+            TokenInstruction::FreezeAccount => Self::FreezeAccount { program },
+            TokenInstruction::ThawAccount => Self::ThawAccount { program },
+            TokenInstruction::SyncNative => Self::SyncNative { program },
+            instruction => Self::Other { program, instruction },
+        }
+    }
+
+    /// Program executing the instruction
+    pub fn program(&self) -> TokenProgram {
+        match self {
+            Self::InitializeMint { program, .. }
+            | Self::InitializeAccount { program, .. }
+            | Self::InitializeMultisig { program, .. }
+            | Self::Transfer { program, .. }
+            | Self::Approve { program, .. }
+            | Self::Revoke { program }
+            | Self::SetAuthority { program, .. }
+            | Self::MintTo { program, .. }
+            | Self::Burn { program, .. }
+            | Self::CloseAccount { program }
+            | Self::FreezeAccount { program }
+            | Self::ThawAccount { program }
+            | Self::SyncNative { program }
+            | Self::Other { program, .. } => *program,
+        }
+    }
+
+    /// Positions of the accounts of the instruction, as laid out by its original flavor: the
+    /// checked flavors, carrying `decimals`, insert the mint after the source. Empty for
+    /// [UnifiedTokenInstruction::Other].
+    pub fn account_positions(&self) -> AccountPositions {
+        let (source, mint, destination, authority) = match self {
+            Self::InitializeMint { .. } => (None, Some(0), None, None),
+            Self::InitializeAccount { owner: None, .. } => (Some(0), Some(1), None, Some(2)),
+            Self::InitializeAccount { .. } => (Some(0), Some(1), None, None),
+            Self::Transfer { decimals: None, .. } | Self::Approve { decimals: None, .. } => {
+                (Some(0), None, Some(1), Some(2))
+            }
+            Self::Transfer { .. } | Self::Approve { .. } => (Some(0), Some(1), Some(2), Some(3)),
+            Self::MintTo { .. } => (None, Some(0), Some(1), Some(2)),
+            Self::Burn { .. } => (Some(0), Some(1), None, Some(2)),
+            Self::CloseAccount { .. } => (Some(0), None, Some(1), Some(2)),
+            Self::FreezeAccount { .. } | Self::ThawAccount { .. } => (Some(0), Some(1), None, Some(2)),
+            Self::Revoke { .. } | Self::SetAuthority { .. } => (Some(0), None, None, Some(1)),
+            Self::SyncNative { .. } => (Some(0), None, None, None),
+            Self::InitializeMultisig { .. } | Self::Other { .. } => (None, None, None, None),
+        };
+        AccountPositions {
+            source,
+            mint,
+            destination,
+            authority,
+        }
+    }
+}
@@ -0,0 +1,414 @@
+//! Streamflow and Bonfida token vesting instruction types
+//!
+//! Both programs lock tokens in an escrow account released over time to a recipient.
+//! Streamflow streams unlock linearly, an amount per period after an optional cliff, and may
+//! be topped up or cancelled. Bonfida vesting contracts unlock on a list of fixed schedules.
+
+use anyhow::anyhow;
+use {
+    crate::token_instruction_2022::TokenInstruction,
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+use crate::pubkey::Pubkey;
+
+/// `create` discriminator, hash of `global:create`
+pub const STREAMFLOW_CREATE_DISCRIMINATOR: [u8; 8] = [24, 30, 200, 40, 5, 28, 7, 119];
+/// `withdraw` discriminator, hash of `global:withdraw`
+pub const STREAMFLOW_WITHDRAW_DISCRIMINATOR: [u8; 8] = [183, 18, 70, 156, 148, 109, 161, 34];
+/// `cancel` discriminator, hash of `global:cancel`
+pub const STREAMFLOW_CANCEL_DISCRIMINATOR: [u8; 8] = [232, 219, 223, 41, 219, 236, 220, 190];
+/// `topup` discriminator, hash of `global:topup`
+pub const STREAMFLOW_TOPUP_DISCRIMINATOR: [u8; 8] = [126, 42, 49, 78, 225, 151, 99, 77];
+/// `transfer_recipient` discriminator, hash of `global:transfer_recipient`
+pub const STREAMFLOW_TRANSFER_RECIPIENT_DISCRIMINATOR: [u8; 8] = [235, 246, 224, 64, 105, 166, 20, 138];
+/// `pause` discriminator, hash of `global:pause`
+pub const STREAMFLOW_PAUSE_DISCRIMINATOR: [u8; 8] = [211, 22, 221, 251, 74, 121, 193, 47];
+/// `unpause` discriminator, hash of `global:unpause`
+pub const STREAMFLOW_UNPAUSE_DISCRIMINATOR: [u8; 8] = [169, 144, 4, 38, 10, 141, 188, 255];
+
+/// Length of a Streamflow stream name
+pub const STREAM_NAME_BYTES: usize = 64;
+/// Length of the seeds deriving a Bonfida vesting account
+pub const VESTING_SEEDS_BYTES: usize = 32;
+/// Serialized length of a Bonfida vesting [Schedule]
+pub const SCHEDULE_BYTES: usize = 16;
+
+/// Instructions of the Streamflow protocol program.
+///
+/// Only the instructions creating and moving tokens of streams are decoded, other
+/// instructions are rejected as unknown discriminators.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StreamflowInstruction<'a> {
+    /// Create a stream, moving the deposited amount to its escrow account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Sender
+    ///   1. `[writable]` Sender token account
+    ///   2. `[writable]` Recipient
+    ///   3. `[writable, signer]` Stream metadata account
+    ///   4. `[writable]` Escrow token account
+    ///   5. `[writable]` Recipient token account
+    ///   6. `[writable]` Streamflow treasury
+    ///   7. `[writable]` Streamflow treasury token account
+    ///   8. `[writable]` Withdrawor
+    ///   9. `[writable]` Partner
+    ///   10. `[writable]` Partner token account
+    ///   11. `[]` Mint
+    ///   12. `[]` Fee oracle
+    ///   13. `[]` Rent sysvar
+    ///   14. `[]` Streamflow program
+    ///   15. `[]` Token program
+    ///   16. `[]` Associated token account program
+    ///   17. `[]` System program
+    Create {
+        /// Unix timestamp at which the stream starts
+        start_time: u64,
+        /// Amount deposited in the stream, fees excluded
+        net_amount_deposited: u64,
+        /// Seconds between two releases
+        period: u64,
+        /// Amount released every period
+        amount_per_period: u64,
+        /// Unix timestamp of the cliff
+        cliff: u64,
+        /// Amount released at the cliff
+        cliff_amount: u64,
+        /// Whether the sender can cancel the stream
+        cancelable_by_sender: bool,
+        /// Whether the recipient can cancel the stream
+        cancelable_by_recipient: bool,
+        /// Whether released amounts are withdrawn automatically
+        automatic_withdrawal: bool,
+        /// Whether the sender can transfer the stream to another recipient
+        transferable_by_sender: bool,
+        /// Whether the recipient can transfer the stream to another recipient
+        transferable_by_recipient: bool,
+        /// Whether the stream can be topped up
+        can_topup: bool,
+        /// Name of the stream, UTF-8 padded with zeroes, see [StreamflowInstruction::stream_name]
+        stream_name: &'a [u8],
+        /// Seconds between two automatic withdrawals
+        withdraw_frequency: u64,
+        /// Whether the stream can be paused, `None` for clients predating the option
+        pausable: Option<bool>,
+        /// Whether the release rate can be updated, `None` for clients predating the option
+        can_update_rate: Option<bool>,
+    },
+    /// Withdraw released tokens to the recipient
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` Authority, the recipient or the withdrawor
+    ///   1. `[writable]` Recipient
+    ///   2. `[writable]` Recipient token account
+    ///   3. `[writable]` Stream metadata account
+    ///   4. `[writable]` Escrow token account
+    ///   5. `[writable]` Streamflow treasury
+    ///   6. `[writable]` Streamflow treasury token account
+    ///   7. `[writable]` Partner
+    ///   8. `[writable]` Partner token account
+    ///   9. `[]` Mint
+    ///   10. `[]` Token program
+    Withdraw {
+        /// Amount to withdraw, `u64::MAX` to withdraw everything released
+        amount: u64,
+    },
+    /// Cancel a stream, releasing the unlocked amount to the recipient and returning the
+    /// remainder to the sender
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` Authority, the sender or the recipient
+    ///   1. `[writable]` Sender
+    ///   2. `[writable]` Sender token account
+    ///   3. `[writable]` Recipient
+    ///   4. `[writable]` Recipient token account
+    ///   5. `[writable]` Stream metadata account
+    ///   6. `[writable]` Escrow token account
+    ///   7. `[writable]` Streamflow treasury
+    ///   8. `[writable]` Streamflow treasury token account
+    ///   9. `[writable]` Partner
+    ///   10. `[writable]` Partner token account
+    ///   11. `[]` Mint
+    ///   12. `[]` Token program
+    Cancel,
+    /// Deposit more tokens in a stream
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Sender
+    ///   1. `[writable]` Sender token account
+    ///   2. `[writable]` Stream metadata account
+    ///   3. `[writable]` Escrow token account
+    ///   4. `[writable]` Streamflow treasury
+    ///   5. `[writable]` Streamflow treasury token account
+    ///   6. `[writable]` Withdrawor
+    ///   7. `[writable]` Partner
+    ///   8. `[writable]` Partner token account
+    ///   9. `[]` Mint
+    ///   10. `[]` Token program
+    ///   11. `[]` System program
+    Topup {
+        /// Amount deposited, fees excluded
+        amount: u64,
+    },
+    /// Transfer a stream to a new recipient
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable, signer]` Authority
+    ///   1. `[writable]` New recipient
+    ///   2. `[writable]` New recipient token account
+    ///   3. `[writable]` Stream metadata account
+    ///   4. `[]` Mint
+    ///   5. `[]` Rent sysvar
+    ///   6. `[]` Token program
+    ///   7. `[]` Associated token account program
+    ///   8. `[]` System program
+    TransferRecipient,
+    /// Pause a stream, stopping its releases
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` Sender
+    ///   1. `[writable]` Stream metadata account
+    Pause,
+    /// Resume a paused stream
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[signer]` Sender
+    ///   1. `[writable]` Stream metadata account
+    Unpause,
+}
+impl<'a> StreamflowInstruction<'a> {
+    /// Unpacks a byte buffer into a [StreamflowInstruction](enum.StreamflowInstruction.html).
+    pub fn unpack(input: &'a [u8]) -> Result<Self, Error> {
+        let (discriminator, rest) = TokenInstruction::unpack_bytes(input, 8)
+            .map_err(|_| anyhow!("Invalid Streamflow Instruction"))?;
+        Ok(match discriminator {
+            d if d == STREAMFLOW_CREATE_DISCRIMINATOR => {
+                let (start_time, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (net_amount_deposited, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (period, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (amount_per_period, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (cliff, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (cliff_amount, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (flags, rest) = TokenInstruction::unpack_bytes(rest, 6)?;
+                let (stream_name, rest) = TokenInstruction::unpack_bytes(rest, STREAM_NAME_BYTES)?;
+                let (withdraw_frequency, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (pausable, rest) = unpack_trailing_option_bool(rest)?;
+                let (can_update_rate, _rest) = unpack_trailing_option_bool(rest)?;
+                Self::Create {
+                    start_time,
+                    net_amount_deposited,
+                    period,
+                    amount_per_period,
+                    cliff,
+                    cliff_amount,
+                    cancelable_by_sender: flags[0] != 0,
+                    cancelable_by_recipient: flags[1] != 0,
+                    automatic_withdrawal: flags[2] != 0,
+                    transferable_by_sender: flags[3] != 0,
+                    transferable_by_recipient: flags[4] != 0,
+                    can_topup: flags[5] != 0,
+                    stream_name,
+                    withdraw_frequency,
+                    pausable,
+                    can_update_rate,
+                }
+            }
+            d if d == STREAMFLOW_WITHDRAW_DISCRIMINATOR => {
+                let (amount, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::Withdraw { amount }
+            }
+            d if d == STREAMFLOW_CANCEL_DISCRIMINATOR => Self::Cancel,
+            d if d == STREAMFLOW_TOPUP_DISCRIMINATOR => {
+                let (amount, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::Topup { amount }
+            }
+            d if d == STREAMFLOW_TRANSFER_RECIPIENT_DISCRIMINATOR => Self::TransferRecipient,
+            d if d == STREAMFLOW_PAUSE_DISCRIMINATOR => Self::Pause,
+            d if d == STREAMFLOW_UNPAUSE_DISCRIMINATOR => Self::Unpause,
+            _ => return Err(anyhow!("Invalid Streamflow Instruction - unpack didn't match any discriminator: {:?}", discriminator)),
+        })
+    }
+
+    /// Name of a created stream, without its zero padding. `None` for other instructions or
+    /// names that are not valid UTF-8.
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::vesting_instruction::{StreamflowInstruction, STREAMFLOW_CREATE_DISCRIMINATOR};
+    /// let mut data = STREAMFLOW_CREATE_DISCRIMINATOR.to_vec();
+    /// data.extend_from_slice(&[0; 6 * 8 + 6]);
+    /// let mut name = [0u8; 64];
+    /// name[..7].copy_from_slice(b"Payroll");
+    /// data.extend_from_slice(&name);
+    /// data.extend_from_slice(&0u64.to_le_bytes());
+    /// let instruction = StreamflowInstruction::unpack(&data).unwrap();
+    /// assert_eq!(instruction.stream_name(), Some("Payroll"));
+    /// ```
+    pub fn stream_name(&self) -> Option<&'a str> {
+        match self {
+            Self::Create { stream_name, .. } => {
+                let len = stream_name.iter().position(|byte| *byte == 0).unwrap_or(stream_name.len());
+                std::str::from_utf8(&stream_name[..len]).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A release of a Bonfida vesting contract
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Schedule {
+    /// Unix timestamp from which the amount can be unlocked
+    pub release_time: u64,
+    /// Amount released
+    pub amount: u64,
+}
+
+/// Instructions of the Bonfida token vesting program.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum BonfidaVestingInstruction<'a> {
+    /// Initialize a new empty vesting contract account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` System program
+    ///   1. `[]` Rent sysvar
+    ///   2. `[writable, signer]` Fee payer
+    ///   3. `[writable]` Vesting account, derived from the seeds
+    Init {
+        /// Seeds of the vesting account
+        seeds: &'a [u8],
+        /// Number of schedules the vesting account is sized for
+        number_of_schedules: u32,
+    },
+    /// Create a vesting contract, moving the vested tokens to the vesting token account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Token program
+    ///   1. `[writable]` Vesting account
+    ///   2. `[writable]` Vesting token account
+    ///   3. `[signer]` Source token account owner
+    ///   4. `[writable]` Source token account
+    Create {
+        /// Seeds of the vesting account
+        seeds: &'a [u8],
+        /// Mint of the vested tokens
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        mint_address: Pubkey,
+        /// Token account receiving the unlocked tokens
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        destination_token_address: Pubkey,
+        /// Releases of the contract
+        schedules: Vec<Schedule>,
+    },
+    /// Unlock the released amounts of a vesting contract to its destination
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Token program
+    ///   1. `[]` Clock sysvar
+    ///   2. `[writable]` Vesting account
+    ///   3. `[writable]` Vesting token account
+    ///   4. `[writable]` Destination token account
+    Unlock {
+        /// Seeds of the vesting account
+        seeds: &'a [u8],
+    },
+    /// Change the destination of a vesting contract
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Vesting account
+    ///   1. `[]` Current destination token account
+    ///   2. `[signer]` Current destination token account owner
+    ///   3. `[]` New destination token account
+    ChangeDestination {
+        /// Seeds of the vesting account
+        seeds: &'a [u8],
+    },
+}
+impl<'a> BonfidaVestingInstruction<'a> {
+    /// Unpacks a byte buffer into a [BonfidaVestingInstruction](enum.BonfidaVestingInstruction.html).
+    pub fn unpack(input: &'a [u8]) -> Result<Self, Error> {
+        let (&tag, rest) = input.split_first().ok_or(anyhow!("Invalid Bonfida Vesting Instruction"))?;
+        let (seeds, rest) = TokenInstruction::unpack_bytes(rest, VESTING_SEEDS_BYTES)?;
+        Ok(match tag {
+            0 => {
+                let (number_of_schedules, _rest) = TokenInstruction::unpack_u32(rest)?;
+                Self::Init {
+                    seeds,
+                    number_of_schedules,
+                }
+            }
+            1 => {
+                let (mint_address, rest) = TokenInstruction::unpack_pubkey(rest)?;
+                let (destination_token_address, rest) = TokenInstruction::unpack_pubkey(rest)?;
+                // Schedules fill the rest of the payload, without length prefix
+                let schedules = rest
+                    .chunks_exact(SCHEDULE_BYTES)
+                    .map(|schedule| {
+                        let (release_time, amount) = schedule.split_at(8);
+                        Schedule {
+                            release_time: u64::from_le_bytes(release_time.try_into().unwrap()),
+                            amount: u64::from_le_bytes(amount.try_into().unwrap()),
+                        }
+                    })
+                    .collect();
+                Self::Create {
+                    seeds,
+                    mint_address,
+                    destination_token_address,
+                    schedules,
+                }
+            }
+            2 => Self::Unlock { seeds },
+            3 => Self::ChangeDestination { seeds },
+            _ => return Err(anyhow!("Invalid Bonfida Vesting Instruction - unpack didn't match any tag value: {}", tag)),
+        })
+    }
+
+    /// Total amount vested by a created contract, `None` for other instructions or if the sum
+    /// overflows
+    pub fn total_amount(&self) -> Option<u64> {
+        match self {
+            Self::Create { schedules, .. } => schedules
+                .iter()
+                .try_fold(0u64, |total, schedule| total.checked_add(schedule.amount)),
+            _ => None,
+        }
+    }
+}
+
+/// Unpacks a borsh `Option<bool>` that clients may omit, `None` when absent
+fn unpack_trailing_option_bool(input: &[u8]) -> Result<(Option<bool>, &[u8]), Error> {
+    match input {
+        [] => Ok((None, input)),
+        [0, rest @ ..] => Ok((None, rest)),
+        [1, value, rest @ ..] => Ok((Some(*value != 0), rest)),
+        _ => Err(anyhow!("Unable to unpack optional bool")),
+    }
+}
@@ -0,0 +1,231 @@
+//! Visitor over the token instructions of transactions
+//!
+//! Modules handling a few token operations otherwise repeat the same decoding loop and
+//! `match` over [UnifiedTokenInstruction]. Implementing [TokenInstructionVisitor] only for
+//! the operations of interest and running [walk_transaction] or [walk_block] dispatches each
+//! decoded instruction to its handler, every handler defaulting to doing nothing.
+
+use {
+    crate::{
+        amount::{Amount, AmountDecimals},
+        instructions::{instructions, is_successful, InstructionRef},
+        metrics,
+        option::COption,
+        pubkey::Pubkey,
+        token_instruction_2022::{AuthorityType, TokenInstruction},
+        unified::{TokenProgram, UnifiedTokenInstruction},
+    },
+    substreams::errors::Error,
+    substreams_solana_core::pb::sf::solana::r#type::v1::{Block, ConfirmedTransaction},
+};
+
+/// Handlers of the token instructions visited by [walk_transaction], one per operation of
+/// [UnifiedTokenInstruction]. Each handler receives the visited instruction, to resolve its
+/// accounts, along with its decoded fields.
+///
+/// ```
+/// # use substreams_solana_program_instructions::{amount::{Amount, AmountDecimals}, instructions::InstructionRef, unified::TokenProgram, visitor::{walk_block, TokenInstructionVisitor}};
+/// # use substreams_solana_core::pb::sf::solana::r#type::v1::Block;
+/// /// Sums the amounts transferred
+/// #[derive(Default)]
+/// struct Volume(u128);
+///
+/// impl TokenInstructionVisitor for Volume {
+///     fn visit_transfer(
+///         &mut self,
+///         _instruction: &InstructionRef,
+///         _program: TokenProgram,
+///         amount: Amount,
+///         _decimals: Option<AmountDecimals>,
+///         _fee: Option<Amount>,
+///     ) {
+///         self.0 += u64::from(amount) as u128;
+///     }
+/// }
+///
+/// # let block = Block::default();
+/// let mut volume = Volume::default();
+/// walk_block(&block, &mut volume);
+/// assert_eq!(volume.0, 0);
+/// ```
+#[allow(unused_variables)]
+pub trait TokenInstructionVisitor {
+    /// Called for each successful transaction walked, before its instructions
+    fn visit_transaction(&mut self, trx: &ConfirmedTransaction, transaction_index: u32) {}
+
+    /// `InitializeMint` and `InitializeMint2`
+    fn visit_initialize_mint(
+        &mut self,
+        instruction: &InstructionRef,
+        program: TokenProgram,
+        decimals: AmountDecimals,
+        mint_authority: &Pubkey,
+        freeze_authority: &COption<Pubkey>,
+    ) {
+    }
+
+    /// `InitializeAccount`, `InitializeAccount2` and `InitializeAccount3`, `owner` being
+    /// `None` for `InitializeAccount` which takes it as an account
+    fn visit_initialize_account(&mut self, instruction: &InstructionRef, program: TokenProgram, owner: Option<&Pubkey>) {}
+
+    /// `InitializeMultisig` and `InitializeMultisig2`
+    fn visit_initialize_multisig(&mut self, instruction: &InstructionRef, program: TokenProgram, m: u8) {}
+
+    /// `Transfer`, `TransferChecked` and `TransferCheckedWithFee`
+    fn visit_transfer(
+        &mut self,
+        instruction: &InstructionRef,
+        program: TokenProgram,
+        amount: Amount,
+        decimals: Option<AmountDecimals>,
+        fee: Option<Amount>,
+    ) {
+    }
+
+    /// `Approve` and `ApproveChecked`
+    fn visit_approve(
+        &mut self,
+        instruction: &InstructionRef,
+        program: TokenProgram,
+        amount: Amount,
+        decimals: Option<AmountDecimals>,
+    ) {
+    }
+
+    /// `Revoke`
+    fn visit_revoke(&mut self, instruction: &InstructionRef, program: TokenProgram) {}
+
+    /// `SetAuthority`
+    fn visit_set_authority(
+        &mut self,
+        instruction: &InstructionRef,
+        program: TokenProgram,
+        authority_type: AuthorityType,
+        new_authority: &COption<Pubkey>,
+    ) {
+    }
+
+    /// `MintTo` and `MintToChecked`
+    fn visit_mint_to(
+        &mut self,
+        instruction: &InstructionRef,
+        program: TokenProgram,
+        amount: Amount,
+        decimals: Option<AmountDecimals>,
+    ) {
+    }
+
+    /// `Burn` and `BurnChecked`
+    fn visit_burn(
+        &mut self,
+        instruction: &InstructionRef,
+        program: TokenProgram,
+        amount: Amount,
+        decimals: Option<AmountDecimals>,
+    ) {
+    }
+
+    /// `CloseAccount`
+    fn visit_close_account(&mut self, instruction: &InstructionRef, program: TokenProgram) {}
+
+    /// `FreezeAccount`
+    fn visit_freeze_account(&mut self, instruction: &InstructionRef, program: TokenProgram) {}
+
+    /// `ThawAccount`
+    fn visit_thaw_account(&mut self, instruction: &InstructionRef, program: TokenProgram) {}
+
+    /// `SyncNative`
+    fn visit_sync_native(&mut self, instruction: &InstructionRef, program: TokenProgram) {}
+
+    /// Any other instruction, extension instructions among them, as decoded
+    fn visit_other(&mut self, instruction: &InstructionRef, program: TokenProgram, decoded: &TokenInstruction) {}
+
+    /// A token program instruction whose data failed to decode
+    fn visit_decode_error(&mut self, instruction: &InstructionRef, program: TokenProgram, error: &Error) {}
+}
+
+/// Dispatches the token instructions of `trx`, the `transaction_index`-th transaction of its
+/// block, to `visitor` in execution order. Failed transactions are not visited.
+pub fn walk_transaction<V: TokenInstructionVisitor + ?Sized>(
+    trx: &ConfirmedTransaction,
+    transaction_index: u32,
+    visitor: &mut V,
+) {
+    if !is_successful(trx) {
+        return;
+    }
+    visitor.visit_transaction(trx, transaction_index);
+    for instruction in instructions(trx) {
+        walk_instruction(&instruction, visitor);
+    }
+}
+
+/// Dispatches the token instructions of the successful transactions of `block` to `visitor`,
+/// in execution order
+pub fn walk_block<V: TokenInstructionVisitor + ?Sized>(block: &Block, visitor: &mut V) {
+    for (index, trx) in block.transactions.iter().enumerate() {
+        walk_transaction(trx, index as u32, visitor);
+    }
+}
+
+/// Dispatches `instruction` to `visitor`, ignored if it is not a token program instruction
+pub fn walk_instruction<V: TokenInstructionVisitor + ?Sized>(instruction: &InstructionRef, visitor: &mut V) {
+    let program = match TokenProgram::from_program_id(instruction.program_id) {
+        Some(program) => program,
+        None => return,
+    };
+    let decoded = TokenInstruction::unpack(instruction.data);
+    metrics::decoded(instruction.data, &decoded);
+    let decoded = match decoded {
+        Ok(decoded) => decoded,
+        Err(error) => return visitor.visit_decode_error(instruction, program, &error),
+    };
+    match UnifiedTokenInstruction::from_instruction(program, decoded) {
+        UnifiedTokenInstruction::InitializeMint {
+            program,
+            decimals,
+            mint_authority,
+            freeze_authority,
+        } => visitor.visit_initialize_mint(instruction, program, decimals, &mint_authority, &freeze_authority),
+        UnifiedTokenInstruction::InitializeAccount { program, owner } => {
+            visitor.visit_initialize_account(instruction, program, owner.as_ref())
+        }
+        UnifiedTokenInstruction::InitializeMultisig { program, m } => {
+            visitor.visit_initialize_multisig(instruction, program, m)
+        }
+        UnifiedTokenInstruction::Transfer {
+            program,
+            amount,
+            decimals,
+            fee,
+        } => visitor.visit_transfer(instruction, program, amount, decimals, fee),
+        UnifiedTokenInstruction::Approve {
+            program,
+            amount,
+            decimals,
+        } => visitor.visit_approve(instruction, program, amount, decimals),
+        UnifiedTokenInstruction::Revoke { program } => visitor.visit_revoke(instruction, program),
+        UnifiedTokenInstruction::SetAuthority {
+            program,
+            authority_type,
+            new_authority,
+        } => visitor.visit_set_authority(instruction, program, authority_type, &new_authority),
+        UnifiedTokenInstruction::MintTo {
+            program,
+            amount,
+            decimals,
+        } => visitor.visit_mint_to(instruction, program, amount, decimals),
+        UnifiedTokenInstruction::Burn {
+            program,
+            amount,
+            decimals,
+        } => visitor.visit_burn(instruction, program, amount, decimals),
+        UnifiedTokenInstruction::CloseAccount { program } => visitor.visit_close_account(instruction, program),
+        UnifiedTokenInstruction::FreezeAccount { program } => visitor.visit_freeze_account(instruction, program),
+        UnifiedTokenInstruction::ThawAccount { program } => visitor.visit_thaw_account(instruction, program),
+        UnifiedTokenInstruction::SyncNative { program } => visitor.visit_sync_native(instruction, program),
+        UnifiedTokenInstruction::Other { program, instruction: decoded } => {
+            visitor.visit_other(instruction, program, &decoded)
+        }
+    }
+}
@@ -0,0 +1,260 @@
+//! Vote program instruction types
+//!
+//! Validators manage their vote account, its authorities and commission, through the vote
+//! program, which also receives their consensus votes. Consensus votes make up most of the
+//! program's traffic but carry no account management data: they decode to
+//! [VoteInstruction::Vote] without their payload.
+
+use anyhow::anyhow;
+use {
+    crate::{pubkey::Pubkey, token_instruction_2022::TokenInstruction},
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use {
+    serde::{Deserialize, Serialize},
+    serde_with::{As, DisplayFromStr},
+};
+
+/// Authority of a vote account
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VoteAuthorize {
+    /// Authority signing the votes
+    Voter,
+    /// Authority withdrawing the account's lamports and updating its commission
+    Withdrawer,
+}
+
+impl VoteAuthorize {
+    fn unpack(input: &[u8]) -> Result<(Self, &[u8]), Error> {
+        let (tag, rest) = TokenInstruction::unpack_u32(input)?;
+        let authorize = match tag {
+            0 => Self::Voter,
+            1 => Self::Withdrawer,
+            _ => return Err(anyhow!("Invalid Vote Authorize: {}", tag)),
+        };
+        Ok((authorize, rest))
+    }
+}
+
+/// Instructions of the vote program, bincode encoded with a `u32` tag.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VoteInstruction<'a> {
+    /// Initializes a vote account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Uninitialized vote account
+    ///   1. `[]` Rent sysvar
+    ///   2. `[]` Clock sysvar
+    ///   3. `[signer]` New validator identity (node pubkey)
+    InitializeAccount {
+        /// The validator identity
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        node_pubkey: Pubkey,
+        /// The authority signing the votes
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        authorized_voter: Pubkey,
+        /// The authority withdrawing lamports and updating the commission
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        authorized_withdrawer: Pubkey,
+        /// Share of the inflation rewards kept by the validator, in percent
+        commission: u8,
+    },
+    /// Authorizes a key to sign votes or withdraw
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Vote account
+    ///   1. `[]` Clock sysvar
+    ///   2. `[signer]` Vote or withdraw authority
+    Authorize {
+        /// The new authority
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        authority: Pubkey,
+        /// The authority changed
+        authorize: VoteAuthorize,
+    },
+    /// A consensus vote, its payload not decoded: `Vote`, `VoteSwitch`, `UpdateVoteState`,
+    /// `CompactUpdateVoteState`, `TowerSync` or one of their switch variants
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Vote account
+    ///   1. `[signer]` Vote authority, or the slot hashes sysvar, clock sysvar and vote
+    ///      authority for `Vote` and `VoteSwitch`
+    Vote,
+    /// Withdraws lamports from a vote account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Vote account
+    ///   1. `[writable]` Recipient account
+    ///   2. `[signer]` Withdraw authority
+    Withdraw {
+        /// Number of lamports to withdraw
+        lamports: u64,
+    },
+    /// Updates the validator identity of a vote account
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Vote account
+    ///   1. `[signer]` New validator identity (node pubkey)
+    ///   2. `[signer]` Withdraw authority
+    UpdateValidatorIdentity,
+    /// Updates the commission of a vote account, increases being accepted in the first half
+    /// of an epoch only
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Vote account
+    ///   1. `[signer]` Withdraw authority
+    UpdateCommission {
+        /// The new commission, in percent
+        commission: u8,
+    },
+    /// Authorizes a key to sign votes or withdraw, the new authority signing as well
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Vote account
+    ///   1. `[]` Clock sysvar
+    ///   2. `[signer]` Vote or withdraw authority
+    ///   3. `[signer]` New vote or withdraw authority
+    AuthorizeChecked {
+        /// The authority changed
+        authorize: VoteAuthorize,
+    },
+    /// Authorizes a key to sign votes or withdraw, the current authority being derived from a
+    /// base key and a seed
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Vote account
+    ///   1. `[]` Clock sysvar
+    ///   2. `[signer]` Base key of the current vote or withdraw authority
+    AuthorizeWithSeed {
+        /// The authority changed
+        authorize: VoteAuthorize,
+        /// Owner deriving the current authority from the base key
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        current_authority_owner: Pubkey,
+        /// Seed deriving the current authority from the base key
+        current_authority_seed: &'a str,
+        /// The new authority
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        authority: Pubkey,
+    },
+    /// Authorizes a key to sign votes or withdraw, the current authority being derived from a
+    /// base key and a seed and the new authority signing as well
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Vote account
+    ///   1. `[]` Clock sysvar
+    ///   2. `[signer]` Base key of the current vote or withdraw authority
+    ///   3. `[signer]` New vote or withdraw authority
+    AuthorizeCheckedWithSeed {
+        /// The authority changed
+        authorize: VoteAuthorize,
+        /// Owner deriving the current authority from the base key
+        #[cfg_attr(feature = "serde-traits", serde(with = "As::<DisplayFromStr>"))]
+        current_authority_owner: Pubkey,
+        /// Seed deriving the current authority from the base key
+        current_authority_seed: &'a str,
+    },
+}
+
+impl<'a> VoteInstruction<'a> {
+    /// Unpacks a byte buffer into a [VoteInstruction]
+    ///
+    /// ```
+    /// # use substreams_solana_program_instructions::vote_instruction::VoteInstruction;
+    /// assert_eq!(VoteInstruction::unpack(&[5, 0, 0, 0, 10]).unwrap(), VoteInstruction::UpdateCommission { commission: 10 });
+    /// assert_eq!(VoteInstruction::unpack(&[14, 0, 0, 0, 1, 2, 3]).unwrap(), VoteInstruction::Vote);
+    /// let seed = b"vote";
+    /// let authorize_with_seed =
+    ///     [&[11, 0, 0, 0, 1, 0, 0, 0][..], &[7; 32], &(seed.len() as u64).to_le_bytes(), seed].concat();
+    /// assert!(matches!(
+    ///     VoteInstruction::unpack(&authorize_with_seed).unwrap(),
+    ///     VoteInstruction::AuthorizeCheckedWithSeed { current_authority_seed: "vote", .. },
+    /// ));
+    /// ```
+    pub fn unpack(input: &'a [u8]) -> Result<Self, Error> {
+        let (tag, rest) = TokenInstruction::unpack_u32(input).map_err(|_| anyhow!("Invalid Vote Instruction"))?;
+        Ok(match tag {
+            0 => {
+                let (node_pubkey, rest) = TokenInstruction::unpack_pubkey(rest)?;
+                let (authorized_voter, rest) = TokenInstruction::unpack_pubkey(rest)?;
+                let (authorized_withdrawer, rest) = TokenInstruction::unpack_pubkey(rest)?;
+                let (&commission, _rest) = rest.split_first().ok_or(anyhow!("Unable to unpack commission"))?;
+                Self::InitializeAccount {
+                    node_pubkey,
+                    authorized_voter,
+                    authorized_withdrawer,
+                    commission,
+                }
+            }
+            1 => {
+                let (authority, rest) = TokenInstruction::unpack_pubkey(rest)?;
+                let (authorize, _rest) = VoteAuthorize::unpack(rest)?;
+                Self::Authorize { authority, authorize }
+            }
+            2 | 6 | 8 | 9 | 12..=15 => Self::Vote,
+            3 => {
+                let (lamports, _rest) = TokenInstruction::unpack_u64(rest)?;
+                Self::Withdraw { lamports }
+            }
+            4 => Self::UpdateValidatorIdentity,
+            5 => {
+                let (&commission, _rest) = rest.split_first().ok_or(anyhow!("Unable to unpack commission"))?;
+                Self::UpdateCommission { commission }
+            }
+            7 => {
+                let (authorize, _rest) = VoteAuthorize::unpack(rest)?;
+                Self::AuthorizeChecked { authorize }
+            }
+            10 => {
+                let (authorize, rest) = VoteAuthorize::unpack(rest)?;
+                let (current_authority_owner, rest) = TokenInstruction::unpack_pubkey(rest)?;
+                let (current_authority_seed, rest) = unpack_seed(rest)?;
+                let (authority, _rest) = TokenInstruction::unpack_pubkey(rest)?;
+                Self::AuthorizeWithSeed {
+                    authorize,
+                    current_authority_owner,
+                    current_authority_seed,
+                    authority,
+                }
+            }
+            11 => {
+                let (authorize, rest) = VoteAuthorize::unpack(rest)?;
+                let (current_authority_owner, rest) = TokenInstruction::unpack_pubkey(rest)?;
+                let (current_authority_seed, _rest) = unpack_seed(rest)?;
+                Self::AuthorizeCheckedWithSeed {
+                    authorize,
+                    current_authority_owner,
+                    current_authority_seed,
+                }
+            }
+            _ => return Err(anyhow!("Invalid Vote Instruction - unpack didn't match any tag value: {}", tag)),
+        })
+    }
+}
+
+/// Unpacks a bincode string, a `u64` length followed by UTF-8 bytes
+fn unpack_seed(input: &[u8]) -> Result<(&str, &[u8]), Error> {
+    let (len, rest) = TokenInstruction::unpack_u64(input)?;
+    let len = usize::try_from(len).map_err(|_| anyhow!("Unable to unpack seed of length {}", len))?;
+    let (bytes, rest) = TokenInstruction::unpack_bytes(rest, len)?;
+    let seed = std::str::from_utf8(bytes).map_err(|_| anyhow!("Unable to unpack seed, invalid UTF-8"))?;
+    Ok((seed, rest))
+}
@@ -0,0 +1,171 @@
+//! Rolling aggregations over windows of slots
+//!
+//! A [RollingSum] is fixed-size and allocation-free once created, so it fits WASM modules
+//! keeping it in memory or serializing it (`serde-traits`) into stores between blocks. A
+//! [MintVolume] allocates a window per mint seen, see [MintVolume::prune].
+
+use {
+    crate::{amount::U128Sum, pubkey::Pubkey},
+    std::collections::HashMap,
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Sum of the values added over the last `len` slots, kept in a ring buffer of one bucket
+/// per slot
+///
+/// ```
+/// # use substreams_solana_program_instructions::window::RollingSum;
+/// let mut window = RollingSum::new(3);
+/// window.add(100, 5);
+/// window.add(101, 7);
+/// assert_eq!(window.sum(102).get(), 12);
+/// // Slot 100 leaves the window at slot 103
+/// assert_eq!(window.sum(103).get(), 7);
+/// // Values added late for a slot already out of the window are ignored
+/// window.add(103, 1);
+/// window.add(100, 5);
+/// assert_eq!(window.sum(103).get(), 8);
+/// ```
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub struct RollingSum {
+    /// Slot and sum of each bucket, at index `slot % len`
+    buckets: Vec<(u64, U128Sum)>,
+}
+
+impl RollingSum {
+    /// Window over the last `len` slots, at least one
+    pub fn new(len: usize) -> Self {
+        Self {
+            buckets: vec![(0, U128Sum::default()); len.max(1)],
+        }
+    }
+
+    /// Number of slots covered by the window
+    pub fn len(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// Always `false`, a window covers at least one slot
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+
+    /// Adds `value` at `slot`, replacing the bucket of the slot `len` slots earlier. Values
+    /// for a slot older than the one its bucket holds are ignored, being out of the window.
+    pub fn add(&mut self, slot: u64, value: u128) {
+        let len = self.buckets.len() as u64;
+        let bucket = &mut self.buckets[(slot % len) as usize];
+        if slot < bucket.0 {
+            return;
+        }
+        if slot > bucket.0 {
+            *bucket = (slot, U128Sum::default());
+        }
+        bucket.1 = bucket.1.saturating_add(value);
+    }
+
+    /// Sum of the values added over the `len` slots ending at `current_slot` included
+    pub fn sum(&self, current_slot: u64) -> U128Sum {
+        let oldest = current_slot.saturating_sub(self.buckets.len() as u64 - 1);
+        self.buckets
+            .iter()
+            .filter(|(slot, _)| *slot >= oldest && *slot <= current_slot)
+            .map(|(_, value)| *value)
+            .sum()
+    }
+}
+
+/// Per-mint volume over the last `len` slots, one [RollingSum] per mint
+#[derive(Clone, Debug, PartialEq)]
+pub struct MintVolume {
+    len: usize,
+    windows: HashMap<Pubkey, RollingSum>,
+}
+
+impl MintVolume {
+    /// Volumes over the last `len` slots
+    pub fn new(len: usize) -> Self {
+        Self {
+            len,
+            windows: HashMap::new(),
+        }
+    }
+
+    /// Adds `amount` of `mint` traded at `slot`
+    pub fn add(&mut self, slot: u64, mint: Pubkey, amount: u64) {
+        let len = self.len;
+        self.windows
+            .entry(mint)
+            .or_insert_with(|| RollingSum::new(len))
+            .add(slot, amount as u128);
+    }
+
+    /// Volume of `mint` over the window ending at `current_slot`
+    pub fn volume(&self, mint: &Pubkey, current_slot: u64) -> U128Sum {
+        self.windows.get(mint).map(|window| window.sum(current_slot)).unwrap_or_default()
+    }
+
+    /// Window of `mint`, e.g. to persist it
+    pub fn window(&self, mint: &Pubkey) -> Option<&RollingSum> {
+        self.windows.get(mint)
+    }
+
+    /// Restores a persisted window of `mint`
+    pub fn insert_window(&mut self, mint: Pubkey, window: RollingSum) {
+        self.windows.insert(mint, window);
+    }
+
+    /// Drops the mints without volume in the window ending at `current_slot`
+    pub fn prune(&mut self, current_slot: u64) {
+        self.windows.retain(|_, window| window.sum(current_slot).get() > 0);
+    }
+}
+
+/// Exponential moving average, e.g. of priority fees
+///
+/// ```
+/// # use substreams_solana_program_instructions::window::Ema;
+/// let mut ema = Ema::with_period(3);
+/// ema.update(10.);
+/// ema.update(20.);
+/// assert_eq!(ema.value(), Some(15.));
+/// ```
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ema {
+    alpha: f64,
+    value: Option<f64>,
+}
+
+impl Ema {
+    /// Average weighting each new sample by `alpha`, clamped to `[0, 1]`
+    pub fn new(alpha: f64) -> Self {
+        Self {
+            alpha: alpha.clamp(0., 1.),
+            value: None,
+        }
+    }
+
+    /// Average over about `period` samples, `alpha = 2 / (period + 1)`
+    pub fn with_period(period: u32) -> Self {
+        Self::new(2. / (period as f64 + 1.))
+    }
+
+    /// Adds a sample, the first one initializing the average
+    pub fn update(&mut self, sample: f64) -> f64 {
+        let value = match self.value {
+            Some(value) => value + self.alpha * (sample - value),
+            None => sample,
+        };
+        self.value = Some(value);
+        value
+    }
+
+    /// Current average, `None` before the first sample
+    pub fn value(&self) -> Option<f64> {
+        self.value
+    }
+}
@@ -0,0 +1,128 @@
+//! Detection of the wire format of token instruction payloads
+//!
+//! The token programs encode optional pubkeys (`COption<Pubkey>`) compactly in instructions: a
+//! `0` byte for `None`, a `1` byte followed by the key for `Some`. The instruction layouts of
+//! the `@solana/spl-token` JavaScript library instead encode them with a fixed width, as account
+//! state does: a `u8` option flag always followed by a `publicKey` field, zeroed for `None`, the
+//! `freezeAuthorityOption` of `initializeMint` and the `newAuthorityOption` of `setAuthority`
+//! for instance. The programs always read the compact form, so a fixed-width `None` followed by
+//! more fields is read shifted, which a single parser cannot tell apart from what the client
+//! meant.
+//!
+//! A payload is only ambiguous by its length: a compact `None` followed by 32 zero bytes, which
+//! the programs ignore, reads as a fixed-width `None` too. Detection is thus gated on a slot
+//! cutoff set by the caller, the last slot at which the clients it indexes sent fixed-width
+//! payloads: later payloads are always read compact.
+//!
+//! [detect_wire_format] identifies the format of a payload and [unpack_with_wire_format]
+//! decodes it with the matching parser. Use [TokenInstruction::unpack] to get the programs'
+//! own reading of the payload.
+
+use anyhow::anyhow;
+use {
+    crate::{
+        amount::amount,
+        option::COption,
+        pubkey::{Pubkey, PUBKEY_BYTES},
+        token_instruction_2022::TokenInstruction,
+        transfer_fee_instruction::TransferFeeInstruction,
+    },
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Length of a fixed-width optional pubkey: flag byte and key
+const FIXED_OPTION_LEN: usize = 1 + PUBKEY_BYTES;
+
+/// Wire format of a token instruction payload
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WireFormat {
+    /// Optional pubkeys encoded compactly, as read by the token programs
+    Compact,
+    /// Optional pubkeys encoded with a fixed width, `None` followed by 32 zero bytes
+    FixedWidthOptions,
+}
+
+/// Identifies the wire format of the token instruction `data`, executed at `slot`.
+/// Payloads executed after `legacy_until_slot`, without optional pubkeys, or whose optional
+/// pubkeys are all `Some`, are [WireFormat::Compact].
+///
+/// ```
+/// # use substreams_solana_program_instructions::wire_format::{detect_wire_format, WireFormat};
+/// let legacy_until_slot = 150_000_000;
+/// // SetAuthority clearing the close authority
+/// assert_eq!(detect_wire_format(&[6, 3, 0], 100_000_000, legacy_until_slot), WireFormat::Compact);
+///
+/// // Fixed-width None, or compact None followed by 32 ignored zero bytes
+/// let mut ambiguous = vec![6, 3, 0];
+/// ambiguous.extend_from_slice(&[0; 32]);
+/// assert_eq!(detect_wire_format(&ambiguous, 100_000_000, legacy_until_slot), WireFormat::FixedWidthOptions);
+/// assert_eq!(detect_wire_format(&ambiguous, 200_000_000, legacy_until_slot), WireFormat::Compact);
+/// ```
+pub fn detect_wire_format(data: &[u8], slot: u64, legacy_until_slot: u64) -> WireFormat {
+    if slot > legacy_until_slot {
+        return WireFormat::Compact;
+    }
+    // Offsets of the optional pubkeys and payload length, both in fixed-width form
+    let (offsets, len): (&[usize], usize) = match data {
+        // InitializeMint and InitializeMint2: decimals, mint authority, freeze authority
+        [0, ..] | [20, ..] => (&[34], 34 + FIXED_OPTION_LEN),
+        // SetAuthority: authority type, new authority
+        [6, ..] => (&[2], 2 + FIXED_OPTION_LEN),
+        // InitializeMintCloseAuthority: close authority
+        [25, ..] => (&[1], 1 + FIXED_OPTION_LEN),
+        // InitializeTransferFeeConfig: two authorities, basis points and maximum fee
+        [26, 0, ..] => (&[2, 2 + FIXED_OPTION_LEN], 2 + 2 * FIXED_OPTION_LEN + 10),
+        _ => return WireFormat::Compact,
+    };
+    let fixed_none = |offset: usize| {
+        data.get(offset..offset + FIXED_OPTION_LEN)
+            .map_or(false, |option| option.iter().all(|byte| *byte == 0))
+    };
+    if data.len() == len && offsets.iter().copied().any(fixed_none) {
+        WireFormat::FixedWidthOptions
+    } else {
+        WireFormat::Compact
+    }
+}
+
+/// Decodes the token instruction `data`, executed at `slot`, with the parser of its
+/// [WireFormat], detected as by [detect_wire_format]
+pub fn unpack_with_wire_format(
+    data: &[u8],
+    slot: u64,
+    legacy_until_slot: u64,
+) -> Result<(TokenInstruction<'_>, WireFormat), Error> {
+    let format = detect_wire_format(data, slot, legacy_until_slot);
+    let instruction = match (format, data) {
+        (WireFormat::FixedWidthOptions, [26, 0, rest @ ..]) => {
+            let (transfer_fee_config_authority, rest) = unpack_fixed_width_option(rest)?;
+            let (withdraw_withheld_authority, rest) = unpack_fixed_width_option(rest)?;
+            let (transfer_fee_basis_points, rest) = TokenInstruction::unpack_u16(rest)?;
+            let (maximum_fee, _rest) = TokenInstruction::unpack_u64(rest)?;
+            TokenInstruction::TransferFeeExtension(TransferFeeInstruction::InitializeTransferFeeConfig {
+                transfer_fee_config_authority,
+                withdraw_withheld_authority,
+                transfer_fee_basis_points,
+                maximum_fee: amount(maximum_fee),
+            })
+        }
+        // Fixed-width options in last position only add trailing bytes, ignored by the parser
+        _ => TokenInstruction::unpack(data)?,
+    };
+    Ok((instruction, format))
+}
+
+fn unpack_fixed_width_option(input: &[u8]) -> Result<(COption<Pubkey>, &[u8]), Error> {
+    let (option, rest) = TokenInstruction::unpack_bytes(input, FIXED_OPTION_LEN)?;
+    let option = match option.split_first() {
+        Some((0, _)) => COption::None,
+        Some((1, key)) => COption::Some(Pubkey::try_from(key).map_err(|_| anyhow!("Unable to unpack pubkey"))?),
+        _ => return Err(anyhow!("Invalid optional pubkey flag")),
+    };
+    Ok((option, rest))
+}
@@ -0,0 +1,316 @@
+//! Wormhole core bridge instruction types and token bridge VAA payloads
+//!
+//! Core bridge instructions are borsh encoded (little-endian integers) while VAA payloads
+//! follow the cross-chain wire format (big-endian integers).
+
+use anyhow::anyhow;
+use {
+    crate::token_instruction_2022::TokenInstruction,
+    substreams::errors::Error,
+};
+
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
+
+/// Length of an emitter, token or recipient address in the wormhole wire format
+pub const WORMHOLE_ADDRESS_LEN: usize = 32;
+/// Length of a guardian's Ethereum address
+pub const GUARDIAN_ADDRESS_LEN: usize = 20;
+/// Number of signature slots of `VerifySignatures`
+pub const MAX_LEN_GUARDIAN_KEYS: usize = 19;
+/// Wormhole chain id of Solana
+pub const CHAIN_ID_SOLANA: u16 = 1;
+
+/// Instructions supported by the wormhole core bridge program.
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum CoreBridgeInstruction<'a> {
+    /// Initializes the bridge with its first guardian set
+    Initialize {
+        /// Period for how long a guardian set is valid after it has been replaced, in seconds
+        guardian_set_expiration_time: u32,
+        /// Amount of lamports that needs to be paid to the protocol to post a message
+        fee: u64,
+        /// Ethereum addresses of the initial guardians, 20 bytes each
+        #[cfg_attr(feature = "serde-traits", serde(borrow))]
+        initial_guardians: Vec<&'a [u8]>,
+    },
+    /// Posts a message for the guardians to observe and sign
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` Bridge config
+    ///   1. `[writable, signer]` Message account
+    ///   2. `[signer]` Emitter
+    ///   3. `[writable]` Emitter's sequence tracker
+    ///   4. `[writable, signer]` Payer
+    ///   5. `[writable]` Fee collector
+    ///   6. `[]` Clock sysvar
+    ///   7. `[]` Rent sysvar
+    ///   8. `[]` System program
+    PostMessage {
+        /// Unique nonce for this message
+        nonce: u32,
+        /// Message payload
+        payload: &'a [u8],
+        /// Commitment level required before the guardians sign the message
+        consistency_level: u8,
+    },
+    /// Posts a VAA whose guardian signatures were verified by `VerifySignatures`
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Guardian set
+    ///   1. `[]` Bridge config
+    ///   2. `[]` Signature set
+    ///   3. `[writable]` Posted VAA account
+    ///   4. `[writable, signer]` Payer
+    ///   5. `[]` Clock sysvar
+    ///   6. `[]` Rent sysvar
+    ///   7. `[]` System program
+    PostVaa {
+        /// VAA version
+        version: u8,
+        /// Index of the guardian set that signed the VAA
+        guardian_set_index: u32,
+        /// Timestamp of the observed message
+        timestamp: u32,
+        /// Nonce of the observed message
+        nonce: u32,
+        /// Wormhole chain id of the emitter
+        emitter_chain: u16,
+        /// Emitter address, 32 bytes
+        emitter_address: &'a [u8],
+        /// Sequence of the message within the emitter's messages
+        sequence: u64,
+        /// Commitment level of the observed message
+        consistency_level: u8,
+        /// Message payload
+        payload: &'a [u8],
+    },
+    /// Sets the fee paid to post a message
+    SetFees,
+    /// Transfers the collected fees
+    TransferFees,
+    /// Upgrades the bridge program through a governance VAA
+    UpgradeContract,
+    /// Replaces the guardian set through a governance VAA
+    UpgradeGuardianSet,
+    /// Verifies guardian signatures through the Secp256k1 precompile instruction preceding
+    /// this one, recording them in a signature set
+    VerifySignatures {
+        /// Index of each guardian in the Secp256k1 instruction's signatures, -1 when absent
+        signers: [i8; MAX_LEN_GUARDIAN_KEYS],
+    },
+    /// Same as `PostMessage`, reusing the message account
+    PostMessageUnreliable {
+        /// Unique nonce for this message
+        nonce: u32,
+        /// Message payload
+        payload: &'a [u8],
+        /// Commitment level required before the guardians sign the message
+        consistency_level: u8,
+    },
+}
+
+impl<'a> CoreBridgeInstruction<'a> {
+    /// Unpacks a byte buffer into a [CoreBridgeInstruction](enum.CoreBridgeInstruction.html).
+    pub fn unpack(input: &'a [u8]) -> Result<Self, Error> {
+        let (&tag, rest) = input.split_first().ok_or(anyhow!("Invalid Core Bridge Instruction"))?;
+        Ok(match tag {
+            0 => {
+                let (guardian_set_expiration_time, rest) = TokenInstruction::unpack_u32(rest)?;
+                let (fee, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (count, mut rest) = TokenInstruction::unpack_u32(rest)?;
+                let mut initial_guardians = Vec::with_capacity(count.min(MAX_LEN_GUARDIAN_KEYS as u32) as usize);
+                for _ in 0..count {
+                    let (guardian, r) = TokenInstruction::unpack_bytes(rest, GUARDIAN_ADDRESS_LEN)?;
+                    initial_guardians.push(guardian);
+                    rest = r;
+                }
+                Self::Initialize {
+                    guardian_set_expiration_time,
+                    fee,
+                    initial_guardians,
+                }
+            }
+            1 | 8 => {
+                let (nonce, rest) = TokenInstruction::unpack_u32(rest)?;
+                let (payload, rest) = unpack_vec(rest)?;
+                let (&consistency_level, _rest) = rest.split_first().ok_or(anyhow!("Unable to unpack u8"))?;
+                if tag == 1 {
+                    Self::PostMessage { nonce, payload, consistency_level }
+                } else {
+                    Self::PostMessageUnreliable { nonce, payload, consistency_level }
+                }
+            }
+            2 => {
+                let (&version, rest) = rest.split_first().ok_or(anyhow!("Unable to unpack u8"))?;
+                let (guardian_set_index, rest) = TokenInstruction::unpack_u32(rest)?;
+                let (timestamp, rest) = TokenInstruction::unpack_u32(rest)?;
+                let (nonce, rest) = TokenInstruction::unpack_u32(rest)?;
+                let (emitter_chain, rest) = TokenInstruction::unpack_u16(rest)?;
+                let (emitter_address, rest) = TokenInstruction::unpack_bytes(rest, WORMHOLE_ADDRESS_LEN)?;
+                let (sequence, rest) = TokenInstruction::unpack_u64(rest)?;
+                let (&consistency_level, rest) = rest.split_first().ok_or(anyhow!("Unable to unpack u8"))?;
+                let (payload, _rest) = unpack_vec(rest)?;
+                Self::PostVaa {
+                    version,
+                    guardian_set_index,
+                    timestamp,
+                    nonce,
+                    emitter_chain,
+                    emitter_address,
+                    sequence,
+                    consistency_level,
+                    payload,
+                }
+            }
+            3 => Self::SetFees,
+            4 => Self::TransferFees,
+            5 => Self::UpgradeContract,
+            6 => Self::UpgradeGuardianSet,
+            7 => {
+                let (bytes, _rest) = TokenInstruction::unpack_bytes(rest, MAX_LEN_GUARDIAN_KEYS)?;
+                let mut signers = [0; MAX_LEN_GUARDIAN_KEYS];
+                for (signer, byte) in signers.iter_mut().zip(bytes) {
+                    *signer = *byte as i8;
+                }
+                Self::VerifySignatures { signers }
+            }
+            _ => return Err(anyhow!("Invalid Core Bridge Instruction - unpack didn't match any tag value: {}", tag)),
+        })
+    }
+}
+
+/// Payloads of the messages of the wormhole token bridge
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-traits",
+    serde(rename_all_fields = "camelCase", rename_all = "camelCase")
+)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenBridgePayload<'a> {
+    /// Tokens locked or burned on the emitter chain, to be released on `to_chain`
+    Transfer {
+        /// Amount transferred, a big-endian u256 truncated to 8 decimals
+        amount: &'a [u8],
+        /// Address of the token on its native chain
+        token_address: &'a [u8],
+        /// Wormhole chain id of the token's native chain
+        token_chain: u16,
+        /// Recipient address
+        to: &'a [u8],
+        /// Wormhole chain id of the recipient
+        to_chain: u16,
+        /// Relayer fee, a big-endian u256 truncated to 8 decimals
+        fee: &'a [u8],
+    },
+    /// Token metadata attestation
+    AssetMeta {
+        /// Address of the token on its native chain
+        token_address: &'a [u8],
+        /// Wormhole chain id of the token's native chain
+        token_chain: u16,
+        /// Decimals of the token on its native chain
+        decimals: u8,
+        /// Symbol, 32 bytes right padded with zeros
+        symbol: &'a [u8],
+        /// Name, 32 bytes right padded with zeros
+        name: &'a [u8],
+    },
+    /// Transfer carrying an arbitrary payload for the recipient contract
+    TransferWithPayload {
+        /// Amount transferred, a big-endian u256 truncated to 8 decimals
+        amount: &'a [u8],
+        /// Address of the token on its native chain
+        token_address: &'a [u8],
+        /// Wormhole chain id of the token's native chain
+        token_chain: u16,
+        /// Recipient address
+        to: &'a [u8],
+        /// Wormhole chain id of the recipient
+        to_chain: u16,
+        /// Sender address
+        from_address: &'a [u8],
+        /// Payload for the recipient
+        payload: &'a [u8],
+    },
+}
+
+impl<'a> TokenBridgePayload<'a> {
+    /// Unpacks the payload of a token bridge message
+    pub fn unpack(input: &'a [u8]) -> Result<Self, Error> {
+        let (&payload_id, rest) = input.split_first().ok_or(anyhow!("Invalid Token Bridge Payload"))?;
+        Ok(match payload_id {
+            1 | 3 => {
+                let (amount, rest) = TokenInstruction::unpack_bytes(rest, 32)?;
+                let (token_address, rest) = TokenInstruction::unpack_bytes(rest, WORMHOLE_ADDRESS_LEN)?;
+                let (token_chain, rest) = unpack_u16_be(rest)?;
+                let (to, rest) = TokenInstruction::unpack_bytes(rest, WORMHOLE_ADDRESS_LEN)?;
+                let (to_chain, rest) = unpack_u16_be(rest)?;
+                if payload_id == 1 {
+                    let (fee, _rest) = TokenInstruction::unpack_bytes(rest, 32)?;
+                    Self::Transfer {
+                        amount,
+                        token_address,
+                        token_chain,
+                        to,
+                        to_chain,
+                        fee,
+                    }
+                } else {
+                    let (from_address, payload) = TokenInstruction::unpack_bytes(rest, WORMHOLE_ADDRESS_LEN)?;
+                    Self::TransferWithPayload {
+                        amount,
+                        token_address,
+                        token_chain,
+                        to,
+                        to_chain,
+                        from_address,
+                        payload,
+                    }
+                }
+            }
+            2 => {
+                let (token_address, rest) = TokenInstruction::unpack_bytes(rest, WORMHOLE_ADDRESS_LEN)?;
+                let (token_chain, rest) = unpack_u16_be(rest)?;
+                let (&decimals, rest) = rest.split_first().ok_or(anyhow!("Unable to unpack u8"))?;
+                let (symbol, rest) = TokenInstruction::unpack_bytes(rest, 32)?;
+                let (name, _rest) = TokenInstruction::unpack_bytes(rest, 32)?;
+                Self::AssetMeta {
+                    token_address,
+                    token_chain,
+                    decimals,
+                    symbol,
+                    name,
+                }
+            }
+            _ => return Err(anyhow!("Invalid Token Bridge Payload - unpack didn't match any payload id: {}", payload_id)),
+        })
+    }
+}
+
+/// Converts a big-endian u256 amount to `u64`, `None` if it does not fit
+pub fn u256_to_u64(amount: &[u8]) -> Option<u64> {
+    let split = amount.len().checked_sub(8)?;
+    if amount[..split].iter().any(|byte| *byte != 0) {
+        return None;
+    }
+    Some(u64::from_be_bytes(amount[split..].try_into().ok()?))
+}
+
+/// Unpacks a borsh `Vec<u8>`, a little-endian `u32` length followed by the bytes
+fn unpack_vec(input: &[u8]) -> Result<(&[u8], &[u8]), Error> {
+    let (len, rest) = TokenInstruction::unpack_u32(input)?;
+    TokenInstruction::unpack_bytes(rest, len as usize)
+}
+
+fn unpack_u16_be(input: &[u8]) -> Result<(u16, &[u8]), Error> {
+    let (bytes, rest) = TokenInstruction::unpack_bytes(input, 2)?;
+    Ok((u16::from_be_bytes([bytes[0], bytes[1]]), rest))
+}